@@ -0,0 +1,102 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::summary_budget::SummaryBudget;
+use anyhow::{Error as E, Result};
+use candle_core::{Device, Tensor, D};
+use candle_transformers::models::quantized_t5::{Config, T5ForConditionalGeneration, VarBuilder};
+use crate::model_store::{model_files, SUMMARIZER_MODEL};
+use std::sync::Mutex;
+use tokenizers::Tokenizer;
+
+/// T5 was trained on 512-token inputs; longer documents are summarized from their start.
+const MAX_INPUT_TOKENS: usize = 512;
+/// Rough token count of one summary sentence, for turning a sentence budget into tokens.
+const TOKENS_PER_SENTENCE: usize = 32;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Writes summaries in its own words with a small local seq2seq model, instead of
+/// picking sentences out of the document. Optional: callers fall back to the
+/// extractive summary when the model isn't loaded or fails.
+pub struct AbstractiveSummarizer {
+    // Decoding keeps a key/value cache in the model, so one summary runs at a time
+    model: Mutex<T5ForConditionalGeneration>,
+    tokenizer: Tokenizer,
+    config: Config,
+    device: Device,
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl AbstractiveSummarizer {
+    /// Downloads the model on first use, unless `offline` is set, and loads it.
+    pub async fn load(offline: bool) -> Result<Self> {
+        let device = Device::Cpu;
+
+        let files = model_files(&SUMMARIZER_MODEL, offline).await?;
+        let [config_filename, tokenizer_filename, weights_filename] = <[_; 3]>::try_from(files)
+            .map_err(|_| E::msg("Unexpected summarization model files"))?;
+
+        let mut config: Config = serde_json::from_str(&std::fs::read_to_string(config_filename)?)?;
+        config.use_cache = true;
+        let tokenizer = Tokenizer::from_file(tokenizer_filename).map_err(E::msg)?;
+        let vb = VarBuilder::from_gguf(&weights_filename, &device)?;
+        let model = T5ForConditionalGeneration::load(vb, &config)?;
+
+        println!("AbstractiveSummarizer model loaded successfully");
+        Ok(Self {
+            model: Mutex::new(model),
+            tokenizer,
+            config,
+            device,
+        })
+    }
+
+    /// Summarizes text in about as many sentences as the budget allows for its length.
+    /// Decoding is greedy, so the same document always gets the same summary.
+    pub fn summarize(&self, text: &str, budget: SummaryBudget, document_sentences: usize) -> Result<String> {
+        let max_new_tokens = budget.sentence_count(document_sentences) * TOKENS_PER_SENTENCE;
+
+        // 1. Tokenize the instruction and document, keeping the end-of-sequence token.
+        let encoding = self.tokenizer
+            .encode(format!("summarize: {}", text), true)
+            .map_err(E::msg)?;
+        let mut input_ids = encoding.get_ids().to_vec();
+        if input_ids.len() > MAX_INPUT_TOKENS {
+            input_ids.truncate(MAX_INPUT_TOKENS - 1);
+            input_ids.push(self.config.eos_token_id as u32);
+        }
+        let input = Tensor::new(input_ids.as_slice(), &self.device)?.unsqueeze(0)?;
+
+        // 2. Encode once, then decode one token at a time, feeding only the newest
+        //    token once the cache holds the earlier ones.
+        let mut model = self.model.lock().unwrap();
+        model.clear_kv_cache();
+        let encoder_output = model.encode(&input)?;
+        let start_token = self.config.decoder_start_token_id.unwrap_or(self.config.pad_token_id) as u32;
+        let mut output_ids = vec![start_token];
+        while output_ids.len() <= max_new_tokens {
+            let decoder_input = if output_ids.len() == 1 {
+                Tensor::new(output_ids.as_slice(), &self.device)?.unsqueeze(0)?
+            } else {
+                Tensor::new(&[*output_ids.last().unwrap()], &self.device)?.unsqueeze(0)?
+            };
+            let logits = model.decode(&decoder_input, &encoder_output)?.squeeze(0)?;
+            let next_token = logits.argmax(D::Minus1)?.to_scalar::<u32>()?;
+            if next_token as usize == self.config.eos_token_id {
+                break;
+            }
+            output_ids.push(next_token);
+        }
+        model.clear_kv_cache();
+
+        // 3. Turn the generated tokens back into text.
+        let summary = self.tokenizer.decode(&output_ids[1..], true).map_err(E::msg)?;
+        Ok(summary.trim().to_string())
+    }
+}
@@ -0,0 +1,127 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::scopes::Scope;
+use std::path::PathBuf;
+
+/// Reserved scope name that targets the project of the app that was frontmost
+/// when the launcher opened, e.g. `scope:current-project`.
+pub const CURRENT_PROJECT_SCOPE: &str = "current-project";
+
+// ===================================================================
+//  PUBLIC STRUCT
+// ===================================================================
+
+/// What the user was working in right before opening the launcher.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FrontmostContext {
+    pub app_name: String,
+    /// The open project folder, when the app is one we know how to inspect.
+    pub project_folder: Option<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Asks System Events for the name of the frontmost application.
+#[cfg(target_os = "macos")]
+fn frontmost_app_name() -> Option<String> {
+    let output = std::process::Command::new("osascript")
+        .args([
+            "-e",
+            "tell application \"System Events\" to get name of first application process whose frontmost is true",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn frontmost_app_name() -> Option<String> {
+    None
+}
+
+/// Maps a VS Code-family app name to the folder holding its per-user state.
+fn vscode_config_folder(app_name: &str) -> Option<&'static str> {
+    match app_name {
+        "Code" | "Visual Studio Code" | "Electron" => Some("Code"),
+        "Code - Insiders" | "Visual Studio Code - Insiders" => Some("Code - Insiders"),
+        "Cursor" => Some("Cursor"),
+        "VSCodium" => Some("VSCodium"),
+        _ => None,
+    }
+}
+
+/// Decodes the `%XX` escapes in a `file://` URI path.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Some(byte) = input.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Reads the folder of the most recently active VS Code window from its global state file.
+fn vscode_workspace_folder(config_folder: &str) -> Option<String> {
+    let storage_path: PathBuf = dirs::config_dir()?
+        .join(config_folder)
+        .join("User")
+        .join("globalStorage")
+        .join("storage.json");
+    let contents = std::fs::read_to_string(storage_path).ok()?;
+    let state: serde_json::Value = serde_json::from_str(&contents).ok()?;
+
+    let uri = state
+        .pointer("/windowsState/lastActiveWindow/folder")
+        .and_then(|folder| folder.as_str())?;
+    // Remote workspaces (ssh, containers) have no local folder to scope to
+    let path = uri.strip_prefix("file://")?;
+    let path = percent_decode(path);
+
+    // Windows URIs look like file:///c%3A/Users/..., so drop the slash before the drive letter
+    if cfg!(target_os = "windows") {
+        return Some(path.trim_start_matches('/').to_string());
+    }
+    Some(path)
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Detects the frontmost app and, for editors we understand, its open project.
+/// Must run before the launcher takes focus. Only macOS is supported for now.
+pub fn detect_frontmost_context() -> Option<FrontmostContext> {
+    let app_name = frontmost_app_name()?;
+    let project_folder = vscode_config_folder(&app_name).and_then(vscode_workspace_folder);
+    Some(FrontmostContext { app_name, project_folder })
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl FrontmostContext {
+    /// The temporary scope for "search within current project", if a project was detected.
+    pub fn scope(&self) -> Option<Scope> {
+        self.project_folder.as_ref().map(|folder| Scope {
+            folders: vec![folder.clone()],
+            source_types: Vec::new(),
+        })
+    }
+}
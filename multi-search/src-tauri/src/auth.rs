@@ -0,0 +1,344 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::state_store::now_secs;
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::distributions::{Alphanumeric, DistString};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// Keychain service name the tokens are stored under, one entry per connector.
+const KEYRING_SERVICE: &str = "multi-search";
+/// How long the browser sign-in may take before the flow is abandoned.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Tokens are refreshed this long before they expire, so a request never carries a stale one.
+const REFRESH_MARGIN_SECS: u64 = 60;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// The OAuth app a connector signs in with. Registered by the user, since a desktop app
+/// can't keep a client secret; installed-app flows don't treat the secret as one anyway.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+}
+
+/// Whether a connector can sign in and whether it has.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthStatus {
+    pub connector: String,
+    /// True when the connector supports OAuth and a client is configured for it.
+    pub configured: bool,
+    pub signed_in: bool,
+    /// Unix timestamp (seconds) the access token expires at, if the provider says.
+    pub expires_at: Option<u64>,
+}
+
+/// The provider endpoints and scopes of one connector.
+struct OAuthEndpoints {
+    auth_url: &'static str,
+    token_url: &'static str,
+    /// Name of the scope parameter; Slack asks for user scopes separately.
+    scope_param: &'static str,
+    scopes: &'static [&'static str],
+    extra_params: &'static [(&'static str, &'static str)],
+}
+
+/// Tokens as kept in the OS keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_at: Option<u64>,
+}
+
+/// A token endpoint response. Slack nests user tokens under `authed_user`.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    error: Option<String>,
+    authed_user: Option<Box<TokenResponse>>,
+}
+
+/// The provider refused to issue a token, e.g. because a refresh token was revoked.
+#[derive(Debug)]
+struct SignInRejected(String);
+
+impl std::fmt::Display for SignInRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Sign-in was rejected: {}", self.0)
+    }
+}
+
+impl std::error::Error for SignInRejected {}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the OAuth endpoints of connectors that sign in through the browser.
+fn endpoints(connector: &str) -> Option<OAuthEndpoints> {
+    match connector {
+        "gmail" => Some(OAuthEndpoints {
+            auth_url: "https://accounts.google.com/o/oauth2/v2/auth",
+            token_url: "https://oauth2.googleapis.com/token",
+            scope_param: "scope",
+            scopes: &["https://www.googleapis.com/auth/gmail.readonly"],
+            // Without these Google only returns a refresh token on the very first consent
+            extra_params: &[("access_type", "offline"), ("prompt", "consent")],
+        }),
+        "slack" => Some(OAuthEndpoints {
+            auth_url: "https://slack.com/oauth/v2/authorize",
+            token_url: "https://slack.com/api/oauth.v2.access",
+            scope_param: "user_scope",
+            scopes: &[
+                "channels:history", "channels:read", "groups:history", "groups:read",
+                "im:history", "im:read", "mpim:history", "mpim:read", "users:read",
+            ],
+            extra_params: &[],
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the S256 PKCE challenge for a verifier.
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+fn keyring_entry(connector: &str) -> Result<keyring::Entry> {
+    Ok(keyring::Entry::new(KEYRING_SERVICE, connector)?)
+}
+
+fn load_token(connector: &str) -> Result<Option<StoredToken>> {
+    match keyring_entry(connector)?.get_password() {
+        Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn store_token(connector: &str, token: &StoredToken) -> Result<()> {
+    keyring_entry(connector)?.set_password(&serde_json::to_string(token)?)?;
+    Ok(())
+}
+
+/// Posts a form to the token endpoint and turns the response into a stored token.
+/// `previous_refresh` is kept when a refresh response doesn't rotate the refresh token.
+async fn request_token(token_url: &str, form: &[(&str, &str)], previous_refresh: Option<String>) -> Result<StoredToken> {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?;
+    let response: TokenResponse = client.post(token_url).form(form).send().await?.json().await?;
+    if let Some(error) = response.error {
+        return Err(SignInRejected(error).into());
+    }
+    let response = match response.access_token {
+        Some(_) => response,
+        None => *response.authed_user.ok_or_else(|| anyhow::anyhow!("The provider returned no access token"))?,
+    };
+    Ok(StoredToken {
+        access_token: response.access_token.ok_or_else(|| anyhow::anyhow!("The provider returned no access token"))?,
+        refresh_token: response.refresh_token.or(previous_refresh),
+        expires_at: response.expires_in.map(|secs| now_secs() + secs),
+    })
+}
+
+/// Reads the outcome of a sign-in from a request to the loopback listener: the code, the
+/// provider's error, or None for requests that aren't the redirect at all, such as the
+/// browser asking for a favicon or reloading the page without its parameters.
+fn callback_result(target: &str, expected_state: &str) -> Option<Result<String>> {
+    let url = Url::parse(&format!("http://127.0.0.1{}", target)).ok()?;
+    if url.path() != "/callback" {
+        return None;
+    }
+    let param = |name: &str| url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned());
+    match (param("state"), param("code"), param("error")) {
+        (_, _, Some(error)) => Some(Err(anyhow::anyhow!("Sign-in was cancelled: {}", error))),
+        (Some(state), Some(code), None) if state == expected_state => Some(Ok(code)),
+        (Some(_), Some(_), None) => Some(Err(anyhow::anyhow!("The sign-in response did not match this request"))),
+        _ => None,
+    }
+}
+
+/// Waits for the browser to be redirected back to the loopback listener and returns the
+/// authorization code, answering the browser with a page the user can close. Other
+/// requests are ignored, so the sign-in keeps waiting until it completes or times out.
+async fn receive_code(listener: &TcpListener, expected_state: &str) -> Result<String> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut request_line = String::new();
+        if BufReader::new(&mut stream).read_line(&mut request_line).await.is_err() {
+            continue;
+        }
+        let Some(target) = request_line.split_whitespace().nth(1) else { continue };
+        let Some(result) = callback_result(target, expected_state) else { continue };
+        let message = match &result {
+            Ok(_) => "Signed in. You can close this tab and return to multi-search.",
+            Err(_) => "Sign-in failed. You can close this tab and try again from multi-search.",
+        };
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            message.len(),
+            message
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        return result;
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Reports whether a connector is set up for OAuth and signed in.
+pub fn auth_status(connector: &str, client: Option<&OAuthClient>) -> AuthStatus {
+    let token = load_token(connector).unwrap_or_else(|e| {
+        eprintln!("Warning: Could not read the {} token from the keychain: {}", connector, e);
+        None
+    });
+    AuthStatus {
+        connector: connector.to_string(),
+        configured: endpoints(connector).is_some() && client.is_some_and(|client| !client.client_id.is_empty()),
+        signed_in: token.is_some(),
+        expires_at: token.and_then(|token| token.expires_at),
+    }
+}
+
+/// Runs the browser OAuth flow with PKCE: `open_browser` is handed the provider's sign-in
+/// page, the redirect comes back to a one-off listener on 127.0.0.1, and the resulting
+/// tokens are stored in the OS keychain.
+pub async fn authorize(
+    connector: &str,
+    client: &OAuthClient,
+    open_browser: impl FnOnce(&str) -> Result<()>,
+) -> Result<AuthStatus> {
+    let endpoints = endpoints(connector)
+        .ok_or_else(|| anyhow::anyhow!("{} does not sign in with OAuth", connector))?;
+    if client.client_id.is_empty() {
+        return Err(anyhow::anyhow!("Add an OAuth client ID for {} first", connector));
+    }
+
+    // 1. Listen for the redirect on a free local port.
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+    let redirect_uri = format!("http://127.0.0.1:{}/callback", listener.local_addr()?.port());
+
+    // 2. Send the user to the provider's consent page.
+    let state = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let verifier = Alphanumeric.sample_string(&mut rand::thread_rng(), 64);
+    let challenge = pkce_challenge(&verifier);
+    let scopes = endpoints.scopes.join(" ");
+    let mut params = vec![
+        ("response_type", "code"),
+        ("client_id", client.client_id.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("state", state.as_str()),
+        ("code_challenge", challenge.as_str()),
+        ("code_challenge_method", "S256"),
+        (endpoints.scope_param, scopes.as_str()),
+    ];
+    params.extend_from_slice(endpoints.extra_params);
+    open_browser(Url::parse_with_params(endpoints.auth_url, &params)?.as_str())?;
+
+    // 3. Wait for the authorization code.
+    let code = tokio::time::timeout(AUTH_TIMEOUT, receive_code(&listener, &state))
+        .await
+        .map_err(|_| anyhow::anyhow!("Sign-in timed out"))??;
+
+    // 4. Exchange it for tokens and keep them in the keychain.
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("client_id", client.client_id.as_str()),
+        ("code_verifier", verifier.as_str()),
+    ];
+    if let Some(secret) = &client.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let token = request_token(endpoints.token_url, &form, None).await?;
+    store_token(connector, &token)?;
+    Ok(auth_status(connector, Some(client)))
+}
+
+/// Returns a valid access token for a connector, refreshing it first if it is about to
+/// expire. Connectors call this before every sync rather than caching the token.
+pub async fn access_token(connector: &str, client: &OAuthClient) -> Result<String> {
+    let token = load_token(connector)?
+        .ok_or_else(|| anyhow::anyhow!("Sign in to {} first", connector))?;
+    let expiring = token.expires_at.is_some_and(|expires_at| expires_at <= now_secs() + REFRESH_MARGIN_SECS);
+    if !expiring {
+        return Ok(token.access_token);
+    }
+
+    let refresh_token = token.refresh_token
+        .ok_or_else(|| anyhow::anyhow!("The {} sign-in has expired; sign in again", connector))?;
+    let endpoints = endpoints(connector)
+        .ok_or_else(|| anyhow::anyhow!("{} does not sign in with OAuth", connector))?;
+    let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token.as_str()),
+        ("client_id", client.client_id.as_str()),
+    ];
+    if let Some(secret) = &client.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let refreshed = request_token(endpoints.token_url, &form, Some(refresh_token.clone())).await?;
+    store_token(connector, &refreshed)?;
+    Ok(refreshed.access_token)
+}
+
+/// Returns true when a connector was signed in but the sign-in can no longer be renewed:
+/// the token expired without a refresh token, or the provider rejected the refresh.
+/// Refreshes the token as a side effect. Network failures are errors, not expiry.
+pub async fn sign_in_expired(connector: &str, client: &OAuthClient) -> Result<bool> {
+    let Some(token) = load_token(connector)? else { return Ok(false) };
+    match access_token(connector, client).await {
+        Ok(_) => Ok(false),
+        // Without a refresh token the only way to fail is an expired access token
+        Err(_) if token.refresh_token.is_none() => Ok(true),
+        Err(e) if e.is::<SignInRejected>() => Ok(true),
+        Err(e) => Err(e),
+    }
+}
+
+/// Forgets a connector's tokens.
+pub fn sign_out(connector: &str) -> Result<()> {
+    match keyring_entry(connector)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_challenge() {
+        // Base64url of the SHA-256 digest, without padding
+        let challenge = pkce_challenge("multi-search-verifier");
+        assert_eq!(challenge, "szXa3E8Gn4hOUqLnmgaOXfEjPOfk6NmYfrqHXDPiFOQ");
+        assert_eq!(challenge.len(), 43);
+    }
+
+    #[test]
+    fn test_callback_result() {
+        assert_eq!(callback_result("/callback?state=abc&code=xyz", "abc").unwrap().unwrap(), "xyz");
+        assert!(callback_result("/callback?error=access_denied&state=abc", "abc").unwrap().is_err());
+        assert!(callback_result("/callback?state=old&code=xyz", "abc").unwrap().is_err());
+        // Favicons and reloads without parameters keep the sign-in waiting
+        assert!(callback_result("/favicon.ico", "abc").is_none());
+        assert!(callback_result("/callback", "abc").is_none());
+    }
+}
@@ -0,0 +1,61 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use serde::{Deserialize, Serialize};
+
+/// First Windows 11 build, which introduced Mica.
+pub const MICA_MIN_BUILD: u32 = 22000;
+/// Windows 11 22H2, where Acrylic is drawn by the system backdrop. Before it, Acrylic
+/// lags badly while the window is dragged or resized.
+pub const SMOOTH_ACRYLIC_MIN_BUILD: u32 = 22621;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// The translucent material behind the launcher on Windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowBackdrop {
+    /// The best material the Windows build supports: Mica on Windows 11, blur before it.
+    #[default]
+    Auto,
+    /// Tinted by the desktop wallpaper, and cheap to draw.
+    Mica,
+    /// Frosted glass showing the windows behind the launcher.
+    Acrylic,
+    /// The legacy blur, the only one available on Windows 10.
+    Blur,
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Picks the material to apply on a Windows build: the requested one where it is
+/// supported and smooth, falling back to Mica and then the legacy blur.
+pub fn resolve_backdrop(requested: WindowBackdrop, build: u32) -> WindowBackdrop {
+    match requested {
+        WindowBackdrop::Acrylic if build >= SMOOTH_ACRYLIC_MIN_BUILD => WindowBackdrop::Acrylic,
+        WindowBackdrop::Auto | WindowBackdrop::Mica | WindowBackdrop::Acrylic if build >= MICA_MIN_BUILD => WindowBackdrop::Mica,
+        _ => WindowBackdrop::Blur,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_backdrop() {
+        let windows_10 = 19045;
+        let windows_11_21h2 = 22000;
+        let windows_11_23h2 = 22631;
+        assert_eq!(resolve_backdrop(WindowBackdrop::Auto, windows_10), WindowBackdrop::Blur);
+        assert_eq!(resolve_backdrop(WindowBackdrop::Auto, windows_11_23h2), WindowBackdrop::Mica);
+        assert_eq!(resolve_backdrop(WindowBackdrop::Mica, windows_10), WindowBackdrop::Blur);
+        assert_eq!(resolve_backdrop(WindowBackdrop::Acrylic, windows_11_21h2), WindowBackdrop::Mica);
+        assert_eq!(resolve_backdrop(WindowBackdrop::Acrylic, windows_11_23h2), WindowBackdrop::Acrylic);
+        assert_eq!(resolve_backdrop(WindowBackdrop::Blur, windows_11_23h2), WindowBackdrop::Blur);
+    }
+}
@@ -0,0 +1,97 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Backlogs at least this large are throttled once their most useful files are in.
+pub const LARGE_BACKLOG_FILES: usize = 10_000;
+/// Files of a large backlog indexed at full speed before throttling starts.
+pub const BACKLOG_PRIORITY_FILES: usize = 2_000;
+/// Pause between files in the long tail of a large backlog, so filling it in over hours
+/// leaves the machine usable.
+pub const BACKLOG_TAIL_DELAY: Duration = Duration::from_millis(100);
+
+/// Age in days at which a timestamp's recency score halves.
+const RECENCY_HALF_LIFE_DAYS: f64 = 14.0;
+/// Weight of how often a file was opened, per doubling of the count.
+const FREQUENCY_WEIGHT: f64 = 0.5;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A file waiting to be indexed, with what is known about its use.
+#[derive(Debug, Clone)]
+pub struct BacklogFile {
+    pub path: PathBuf,
+    /// Unix seconds of the last modification.
+    pub modified: u64,
+    /// Unix seconds of the last time the user opened it, `None` if never seen opened.
+    pub last_opened: Option<u64>,
+    pub open_count: u64,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Scores how recent a timestamp is: 1.0 now, halving every half-life.
+fn recency(timestamp: u64, now: u64) -> f64 {
+    let age_days = now.saturating_sub(timestamp) as f64 / 86_400.0;
+    0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS)
+}
+
+/// Scores how soon a file should be indexed: recently modified or opened and frequently
+/// opened files score highest.
+fn priority(file: &BacklogFile, now: u64) -> f64 {
+    recency(file.modified, now)
+        + file.last_opened.map_or(0.0, |opened| recency(opened, now))
+        + FREQUENCY_WEIGHT * (1.0 + file.open_count as f64).log2()
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Orders a backlog so the files the user is most likely to search for come first.
+/// Ties keep path order, so the order is stable between runs.
+pub fn prioritize(mut files: Vec<BacklogFile>, now: u64) -> Vec<PathBuf> {
+    files.sort_by(|a, b| {
+        priority(b, now).partial_cmp(&priority(a, now)).unwrap()
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    files.into_iter().map(|file| file.path).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prioritize() {
+        let now = 1_700_000_000;
+        let day = 86_400;
+        let file = |path: &str, modified_days_ago: u64, opened_days_ago: Option<u64>, open_count: u64| BacklogFile {
+            path: PathBuf::from(path),
+            modified: now - modified_days_ago * day,
+            last_opened: opened_days_ago.map(|days| now - days * day),
+            open_count,
+        };
+        let order = prioritize(vec![
+            file("/archive/2015.pdf", 3000, None, 0),
+            file("/notes/today.md", 0, None, 0),
+            file("/reports/q3.xlsx", 400, Some(1), 25),
+            file("/notes/last-month.md", 30, None, 0),
+            file("/archive/2016.pdf", 3000, None, 0),
+        ], now);
+        let order: Vec<&str> = order.iter().map(|path| path.to_str().unwrap()).collect();
+        assert_eq!(order, vec![
+            "/reports/q3.xlsx",
+            "/notes/today.md",
+            "/notes/last-month.md",
+            "/archive/2015.pdf",
+            "/archive/2016.pdf",
+        ]);
+    }
+}
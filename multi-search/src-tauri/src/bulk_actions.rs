@@ -0,0 +1,167 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::fs_paths::long_path;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Most files "open all" will launch at once, so a stray click on a large result set
+/// doesn't open hundreds of windows.
+pub const OPEN_ALL_LIMIT: usize = 20;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// An action applied to every document in a result set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BulkAction {
+    /// Adds a tag, shown on the documents' results.
+    Tag { tag: String },
+    /// Hides the documents, with the usual undo window.
+    Exclude,
+    /// Opens each document in its default app.
+    OpenAll,
+    /// Copies the paths to the clipboard, one per line.
+    CopyPaths,
+    /// Moves local files into a folder and re-indexes them there.
+    MoveTo { folder: PathBuf },
+}
+
+/// Emitted as `bulk-action-progress` after each document.
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// A document the action could not be applied to.
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// The outcome of a bulk action.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BulkActionReport {
+    pub succeeded: usize,
+    pub failed: Vec<BulkFailure>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Claims a path in `folder` for `file_name` by creating an empty file there, adding
+/// " (2)", " (3)" and so on before the extension as Finder does when the name is taken.
+/// Creating it reserves the name, so a file that appears meanwhile is never overwritten.
+fn reserve_destination(folder: &Path, file_name: &str) -> Result<PathBuf> {
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (file_name, String::new()),
+    };
+    let candidates = std::iter::once(folder.join(file_name))
+        .chain((2..).map(|n| folder.join(format!("{} ({}){}", stem, n, extension))));
+    for candidate in candidates {
+        match OpenOptions::new().write(true).create_new(true).open(long_path(&candidate)) {
+            Ok(_) => return Ok(candidate),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(anyhow::anyhow!("Could not create {}: {}", candidate.display(), e)),
+        }
+    }
+    unreachable!("the candidate names never run out")
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl BulkActionReport {
+    /// Counts one document's result.
+    pub fn record(&mut self, path: &str, result: Result<()>) {
+        match result {
+            Ok(()) => self.succeeded += 1,
+            Err(e) => self.failed.push(BulkFailure { path: path.to_string(), error: e.to_string() }),
+        }
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns true for documents identified by a URL, e.g. `https://` or `gmail://`, which
+/// open through the URL handler rather than as files.
+pub fn is_url(path: &str) -> bool {
+    path.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Moves a file into a folder without overwriting anything there, and returns where it
+/// ended up. Falls back to copying only when the folder is on another volume.
+pub fn move_file(path: &Path, folder: &Path) -> Result<PathBuf> {
+    if !long_path(folder).is_dir() {
+        return Err(anyhow::anyhow!("{} is not a folder", folder.display()));
+    }
+    let file_name = path.file_name()
+        .ok_or_else(|| anyhow::anyhow!("{} has no file name", path.display()))?
+        .to_string_lossy();
+    // The rename replaces the empty file holding the name
+    let destination = reserve_destination(folder, &file_name)?;
+    let moved = match std::fs::rename(long_path(path), long_path(&destination)) {
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => std::fs::copy(long_path(path), long_path(&destination))
+            .and_then(|_| std::fs::remove_file(long_path(path))),
+        result => result,
+    };
+    if let Err(e) = moved {
+        // The source stays where it was, so take the reservation or partial copy back
+        let _ = std::fs::remove_file(long_path(&destination));
+        return Err(anyhow::anyhow!("Could not move {}: {}", path.display(), e));
+    }
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_file_keeps_existing_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let target = dir.join("archive");
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(target.join("notes.md"), "old").unwrap();
+        std::fs::write(dir.join("notes.md"), "new").unwrap();
+
+        let moved = move_file(&dir.join("notes.md"), &target).unwrap();
+        assert_eq!(moved, target.join("notes (2).md"));
+        assert_eq!(std::fs::read_to_string(&moved).unwrap(), "new");
+        assert_eq!(std::fs::read_to_string(target.join("notes.md")).unwrap(), "old");
+        assert!(!dir.join("notes.md").exists());
+
+        assert!(move_file(&moved, &dir.join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url("https://readwise.io/open/1"));
+        assert!(is_url("gmail://msg/18c2"));
+        assert!(!is_url("/Users/me/notes.md"));
+        assert!(!is_url(r"C:\Users\me\notes.md"));
+    }
+
+    #[test]
+    fn test_bulk_action_from_json() {
+        let action: BulkAction = serde_json::from_str(r#"{"action":"move_to","folder":"/tmp/archive"}"#).unwrap();
+        assert_eq!(action, BulkAction::MoveTo { folder: PathBuf::from("/tmp/archive") });
+        let action: BulkAction = serde_json::from_str(r#"{"action":"tag","tag":"taxes"}"#).unwrap();
+        assert_eq!(action, BulkAction::Tag { tag: "taxes".to_string() });
+    }
+}
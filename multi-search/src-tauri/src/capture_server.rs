@@ -0,0 +1,292 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::commands::AppState;
+use crate::webhooks::{route_webhook, WebhookAction, WEBHOOK_PATH_PREFIX};
+use anyhow::Result;
+use rand::distributions::{Alphanumeric, DistString};
+use serde::Deserialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest request body accepted, enough for the HTML of a long article.
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+/// Guards against clients that send endless headers.
+const MAX_HEADER_LINES: usize = 100;
+/// Longest request or header line accepted, so one endless line can't fill memory either.
+const MAX_HEADER_LINE_BYTES: u64 = 8 * 1024;
+/// How long a client gets to send its request, so a stalled one can't hold a connection open.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// The JSON body the bookmarklet posts to `/capture`.
+#[derive(Debug, Deserialize)]
+pub struct CaptureRequest {
+    /// Must match the capture token in settings, so other pages can't inject documents.
+    pub token: String,
+    pub url: String,
+    pub title: Option<String>,
+    /// The page as rendered in the browser. When missing, the URL is fetched instead.
+    pub html: Option<String>,
+}
+
+/// Why a capture wasn't indexed, deciding the status it is answered with.
+#[derive(Debug)]
+enum CaptureError {
+    /// The endpoint is off or the token is wrong.
+    Forbidden(String),
+    /// The capture was genuine but indexing it failed.
+    Failed(String),
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Parses the request line and headers, returning the method, path and body length.
+/// None if the request line is missing or the body length isn't a number that fits.
+fn parse_request_head(lines: &[String]) -> Option<(String, String, usize)> {
+    let mut request_line = lines.first()?.split_whitespace();
+    let method = request_line.next()?.to_uppercase();
+    let path = request_line.next()?.to_string();
+
+    let content_length = match lines[1..]
+        .iter()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+    {
+        Some((_, value)) => value.trim().parse().ok()?,
+        None => 0,
+    };
+    Some((method, path, content_length))
+}
+
+/// Writes a minimal HTTP response. CORS headers let the bookmarklet call us from any page,
+/// including the private network access preflight Chromium sends for localhost.
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Content-Type\r\n\
+         Access-Control-Allow-Private-Network: true\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// Validates a capture against the current settings and indexes it.
+async fn capture(app: &AppHandle, request: CaptureRequest) -> Result<(), CaptureError> {
+    let state = app.state::<AppState>();
+    {
+        let settings = state.settings.lock().unwrap();
+        if !settings.capture_endpoint_enabled {
+            return Err(CaptureError::Forbidden("The capture endpoint is disabled".to_string()));
+        }
+        if settings.capture_token.is_empty() || !tokens_match(&request.token, &settings.capture_token) {
+            return Err(CaptureError::Forbidden("Invalid capture token".to_string()));
+        }
+    }
+
+    let orchestrator = state.orchestrator().map_err(CaptureError::Failed)?;
+    let result = match request.html {
+        Some(html) => orchestrator.index_web_page(&request.url, &html, request.title).await,
+        None => orchestrator.index_url(&request.url).await,
+    };
+    result.map_err(|e| CaptureError::Failed(e.to_string()))
+}
+
+/// Reads the request line and headers, stopping at the blank line that ends them.
+/// None if there are too many lines or one is too long.
+async fn read_head(reader: &mut BufReader<&mut TcpStream>) -> Result<Option<Vec<String>>> {
+    let mut lines = Vec::new();
+    loop {
+        let mut line = Vec::new();
+        let read = (&mut *reader).take(MAX_HEADER_LINE_BYTES).read_until(b'\n', &mut line).await?;
+        if read as u64 == MAX_HEADER_LINE_BYTES && !line.ends_with(b"\n") {
+            return Ok(None);
+        }
+        let line = String::from_utf8_lossy(&line);
+        if read == 0 || line.trim().is_empty() {
+            break;
+        }
+        lines.push(line.trim_end().to_string());
+        if lines.len() > MAX_HEADER_LINES {
+            return Ok(None);
+        }
+    }
+    Ok(Some(lines))
+}
+
+/// Reads a body of `length` bytes, giving up if the client stalls.
+async fn read_body(reader: &mut BufReader<&mut TcpStream>, length: usize) -> Result<Vec<u8>> {
+    let mut body = vec![0; length];
+    tokio::time::timeout(READ_TIMEOUT, reader.read_exact(&mut body)).await
+        .map_err(|_| anyhow::anyhow!("Timed out reading the request body"))??;
+    Ok(body)
+}
+
+/// Answers a push notification, queueing a sync of its connector when it is genuine.
+async fn handle_webhook(stream: &mut TcpStream, app: &AppHandle, target: &str, body: &[u8]) -> Result<()> {
+    let state = app.state::<AppState>();
+    let action = {
+        let settings = state.settings.lock().unwrap();
+        if settings.webhooks_enabled {
+            route_webhook(target, body, &settings.webhook_token)
+        } else {
+            WebhookAction::Reject("Push notifications are disabled")
+        }
+    };
+    match action {
+        WebhookAction::Validate(validation_token) => respond(stream, "200 OK", &validation_token).await,
+        WebhookAction::Sync(connector) => {
+            state.request_sync(&connector);
+            respond(stream, "202 Accepted", "Sync queued").await
+        }
+        WebhookAction::Reject(reason) => respond(stream, "403 Forbidden", reason).await,
+    }
+}
+
+/// Reads one request from the connection and answers it.
+async fn handle_connection(mut stream: TcpStream, app: &AppHandle) -> Result<()> {
+    // 1. Read the request line and headers.
+    let mut reader = BufReader::new(&mut stream);
+    let lines = tokio::time::timeout(READ_TIMEOUT, read_head(&mut reader)).await
+        .map_err(|_| anyhow::anyhow!("Timed out reading the request"))??;
+    let Some(lines) = lines else {
+        return respond(&mut stream, "431 Request Header Fields Too Large", "Headers are too large").await;
+    };
+    let Some((method, path, content_length)) = parse_request_head(&lines) else {
+        return respond(&mut stream, "400 Bad Request", "Malformed request").await;
+    };
+
+    // 2. Answer CORS preflights, hand push notifications over and reject anything but a capture.
+    if method == "OPTIONS" {
+        return respond(&mut stream, "204 No Content", "").await;
+    }
+    if method == "POST" && path.starts_with(WEBHOOK_PATH_PREFIX) {
+        if content_length > MAX_BODY_BYTES {
+            return respond(&mut stream, "413 Payload Too Large", "Notification is too large").await;
+        }
+        let body = read_body(&mut reader, content_length).await?;
+        return handle_webhook(&mut stream, app, &path, &body).await;
+    }
+    if method != "POST" || path != "/capture" {
+        return respond(&mut stream, "404 Not Found", "Not found").await;
+    }
+    if content_length > MAX_BODY_BYTES {
+        return respond(&mut stream, "413 Payload Too Large", "Page is too large to capture").await;
+    }
+
+    // 3. Read and parse the body. The bookmarklet sends JSON as text/plain to avoid a preflight.
+    let body = read_body(&mut reader, content_length).await?;
+    let request: CaptureRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => return respond(&mut stream, "400 Bad Request", &format!("Invalid capture: {}", e)).await,
+    };
+
+    // 4. Index the page.
+    match capture(app, request).await {
+        Ok(()) => respond(&mut stream, "200 OK", "Captured").await,
+        Err(CaptureError::Forbidden(e)) => respond(&mut stream, "403 Forbidden", &e).await,
+        Err(CaptureError::Failed(e)) => respond(&mut stream, "500 Internal Server Error", &e).await,
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Compares two secrets in time that depends only on their lengths, so the token can't be
+/// guessed a character at a time from response times.
+pub fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Generates a random secret for authenticating bookmarklet captures.
+pub fn generate_token() -> String {
+    Alphanumeric.sample_string(&mut rand::thread_rng(), 32)
+}
+
+/// Builds the `javascript:` bookmarklet that posts the current page to the capture endpoint.
+pub fn bookmarklet(port: u16, token: &str) -> String {
+    format!(
+        "javascript:(()=>{{fetch('http://127.0.0.1:{}/capture',{{method:'POST',mode:'no-cors',\
+         headers:{{'Content-Type':'text/plain'}},body:JSON.stringify({{token:'{}',url:location.href,\
+         title:document.title,html:document.documentElement.outerHTML}})}})\
+         .then(()=>alert('Sent to Multi Search'),()=>alert('Multi Search is not running'))}})()",
+        port, token
+    )
+}
+
+/// Serves the capture endpoint and push notification webhooks on localhost until the app exits.
+pub async fn serve(app: AppHandle, port: u16) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let app = app.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &app).await {
+                eprintln!("Warning: Capture request failed: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_head() {
+        let lines = vec![
+            "POST /capture HTTP/1.1".to_string(),
+            "Host: 127.0.0.1:47615".to_string(),
+            "content-length: 42".to_string(),
+        ];
+        assert_eq!(parse_request_head(&lines), Some(("POST".to_string(), "/capture".to_string(), 42)));
+
+        // Without a Content-Length there is no body
+        let lines = vec!["OPTIONS /capture HTTP/1.1".to_string()];
+        assert_eq!(parse_request_head(&lines), Some(("OPTIONS".to_string(), "/capture".to_string(), 0)));
+    }
+
+    #[test]
+    fn test_parse_request_head_without_request_line() {
+        assert_eq!(parse_request_head(&[]), None);
+        assert_eq!(parse_request_head(&["".to_string()]), None);
+        assert_eq!(parse_request_head(&["POST".to_string(), "Content-Length: 2".to_string()]), None);
+    }
+
+    #[test]
+    fn test_parse_request_head_with_oversized_content_length() {
+        // A length that fits is passed on, for the caller to turn away as too large
+        let huge = (MAX_BODY_BYTES + 1).to_string();
+        let lines = vec!["POST /capture HTTP/1.1".to_string(), format!("Content-Length: {}", huge)];
+        assert_eq!(parse_request_head(&lines), Some(("POST".to_string(), "/capture".to_string(), MAX_BODY_BYTES + 1)));
+
+        // One that doesn't fit isn't mistaken for an empty body
+        let lines = vec!["POST /capture HTTP/1.1".to_string(), "Content-Length: 99999999999999999999999".to_string()];
+        assert_eq!(parse_request_head(&lines), None);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("s3cret", "s3cret"));
+        assert!(!tokens_match("s3creT", "s3cret"));
+        assert!(!tokens_match("s3cret-longer", "s3cret"));
+        assert!(!tokens_match("", "s3cret"));
+    }
+}
@@ -0,0 +1,89 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use std::collections::HashMap;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// What changed between a document's stored chunks and its new ones. Chunks are
+/// sentence-aligned, so an edit usually changes one or two and leaves the rest as they were.
+#[derive(Debug, Default, PartialEq)]
+pub struct ChunkDiff {
+    /// New chunks that need embedding, in document order.
+    pub added: Vec<String>,
+    /// Stored chunks to delete, each listed once; every stored copy goes.
+    pub removed: Vec<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Counts how many times each text occurs.
+fn count_texts(texts: &[String]) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for text in texts {
+        *counts.entry(text.as_str()).or_insert(0) += 1;
+    }
+    counts
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl ChunkDiff {
+    /// Returns true if the stored chunks already match.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Compares a document's stored chunk texts with its new ones. Chunks are deleted by
+/// text, so a chunk repeated a different number of times is removed and re-added as a
+/// whole rather than counted out.
+pub fn diff_chunks(stored: &[String], chunks: &[String]) -> ChunkDiff {
+    let stored_counts = count_texts(stored);
+    let new_counts = count_texts(chunks);
+    let changed = |text: &str| stored_counts.get(text) != new_counts.get(text);
+
+    let added = chunks.iter().filter(|text| changed(text)).cloned().collect();
+    let mut removed: Vec<String> = stored_counts.keys()
+        .filter(|text| changed(text))
+        .map(|text| text.to_string())
+        .collect();
+    removed.sort();
+    ChunkDiff { added, removed }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| text.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_chunks() {
+        let stored = texts(&["Intro.", "Budget is 10k.", "Next steps."]);
+        assert!(diff_chunks(&stored, &stored).is_empty());
+
+        let diff = diff_chunks(&stored, &texts(&["Intro.", "Budget is 12k.", "Next steps.", "Owners."]));
+        assert_eq!(diff.added, texts(&["Budget is 12k.", "Owners."]));
+        assert_eq!(diff.removed, texts(&["Budget is 10k."]));
+    }
+
+    #[test]
+    fn test_diff_chunks_with_repeated_text() {
+        let diff = diff_chunks(&texts(&["Signature.", "Body.", "Signature."]), &texts(&["Signature.", "Body."]));
+        assert_eq!(diff.added, texts(&["Signature."]));
+        assert_eq!(diff.removed, texts(&["Signature."]));
+    }
+}
@@ -0,0 +1,1500 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::app_context::{self, FrontmostContext};
+use crate::auth::{self, AuthStatus, OAuthClient};
+use crate::backdrop::WindowBackdrop;
+use crate::bulk_actions::{self, BulkAction, BulkActionReport, BulkProgress, OPEN_ALL_LIMIT};
+use crate::capture_server;
+use crate::config_import::{self, ImportSource, ImportedLocations};
+use crate::connectors::{self, ConnectorState};
+use crate::connectors::slack::{SlackChannel, SLACK_SOURCE};
+use crate::crawler;
+use crate::data_dir::{self, DataRoot};
+use crate::diagnostics;
+use crate::digests::Digest;
+use crate::disk_space::DiskSpaceStatus;
+use crate::docsets;
+use crate::fs_paths::long_path;
+use crate::health::HealthReport;
+use crate::experiments::{ExperimentReport, Variant};
+use crate::file_watcher::WatchCommand;
+use crate::highlights;
+use crate::identity::Identity;
+use crate::index_manager::MetadataField;
+use crate::notifications::NotificationCategory;
+use crate::permissions::{self, PermissionReport, PrivacyPane};
+use crate::rate_limit::ConnectorStatus;
+use crate::recorder::Recording;
+use crate::result_actions::{self, ResultAction};
+use crate::metrics::MetricsSnapshot;
+use crate::model_store::{self, CachedModel, EMBEDDING_MODEL};
+use crate::query_analytics::QueryAnalyticsReport;
+use crate::state_store::PendingDeletion;
+use crate::sync_cursors::SyncCursor;
+use crate::webhooks;
+use crate::password_manager::{OnePasswordProvider, ONE_PASSWORD_PROVIDER};
+use crate::scopes::Scope;
+use crate::search_orchestrator::{BatchSearchEntry, BrowsedTopic, DocumentPassage, HybridSearchResponse, HybridSearchResult, LiveSearchBatch, SearchOptions, SearchOrchestrator};
+use crate::settings::Settings;
+use crate::shortcuts::{self, CapturedShortcut, RawKeyChord};
+use crate::snapshots::{SnapshotInfo, SnapshotStore};
+use crate::snippets::{self, Snippet};
+use crate::storage_quota::EvictionReport;
+use crate::timeline::{Granularity, TimelineBucket, TimelineFilter, DEFAULT_TITLES_PER_BUCKET};
+use crate::transcriber::Transcriber;
+use crate::typing_cadence::TypingCadence;
+use crate::window_resize::{fit_height, resize_frames, RESIZE_DURATION, RESIZE_FRAME};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use tauri_plugin_opener::OpenerExt;
+use tokio::sync::{mpsc, Notify, OnceCell};
+
+/// How long to wait after hiding the launcher before pasting into the previous app.
+const PASTE_FOCUS_DELAY: Duration = Duration::from_millis(150);
+/// Failed queries listed per category when the UI doesn't ask for a number.
+const QUERY_ANALYTICS_LIMIT: usize = 20;
+/// Documents shown per topic when the UI doesn't ask for a number.
+const TOPIC_PREVIEW_DOCUMENTS: usize = 5;
+/// Recent documents listed before a query is typed, unless the caller asks for a number.
+const RECENT_DOCUMENTS: usize = 10;
+
+// ===================================================================
+//  SHARED STATE
+// ===================================================================
+
+/// State managed by Tauri and shared by every command handler.
+/// The orchestrator is filled in asynchronously because loading the model takes a while.
+pub struct AppState {
+    orchestrator: OnceCell<Arc<SearchOrchestrator>>,
+    pub settings: Mutex<Settings>,
+    session_context: Mutex<Option<FrontmostContext>>,
+    // Folders indexing couldn't read for lack of permission, retried once access is granted
+    blocked_folders: Mutex<BTreeSet<PathBuf>>,
+    pinned_search: Mutex<Option<PinnedSearch>>,
+    // Connectors a push notification asked to sync, deduplicated by `pending_syncs`
+    sync_tx: mpsc::UnboundedSender<String>,
+    sync_rx: Mutex<Option<mpsc::UnboundedReceiver<String>>>,
+    pending_syncs: Mutex<HashSet<String>>,
+    // Folders added or removed while the file watcher runs
+    watch_tx: mpsc::UnboundedSender<WatchCommand>,
+    watch_rx: Mutex<Option<mpsc::UnboundedReceiver<WatchCommand>>>,
+    // Bumped by each launcher resize, so a running animation stops when a newer one starts
+    resize_generation: AtomicU64,
+    // Bumped by each live search, so batches of a query the user typed past aren't sent
+    live_search_generation: AtomicU64,
+    // Signalled by each live search, so the one it replaces stops where it is
+    live_search_superseded: Notify,
+    // How fast the user types, which sets how long a live search waits for the next key
+    typing_cadence: Mutex<TypingCadence>,
+    // Signalled when the embedding model finishes downloading, for a startup waiting on it
+    pub model_downloaded: Notify,
+    // The voice query being recorded while its shortcut is held
+    pub voice_recording: Mutex<Option<Recording>>,
+    // Loaded the first time a voice query is recorded
+    transcriber: OnceCell<Arc<Transcriber>>,
+    // The startup self-test's report, kept for a frontend that missed the event
+    pub health: Mutex<Option<HealthReport>>,
+}
+
+/// A search the user pinned: the launcher stays up and the query re-runs as the index changes.
+struct PinnedSearch {
+    query: String,
+    // Paths already shown, so re-runs can tell which matches are new
+    seen_paths: HashSet<String>,
+}
+
+/// Payload of the `pinned-search-updated` event, sent when a pinned query is re-run.
+#[derive(serde::Serialize)]
+pub struct PinnedSearchUpdate {
+    pub query: String,
+    pub response: HybridSearchResponse,
+    /// Results that weren't in any earlier run of this pin, for the UI to highlight.
+    pub new_paths: Vec<String>,
+}
+
+/// Payload of the `live-search-batch` event: what one retrieval leg found for the query
+/// being typed.
+#[derive(serde::Serialize)]
+pub struct LiveSearchUpdate {
+    /// Increases with every live search, so the UI can drop batches of older queries.
+    pub search_id: u64,
+    pub query: String,
+    #[serde(flatten)]
+    pub batch: LiveSearchBatch,
+}
+
+/// Payload of the `voice-search` event: what the user said, with its results.
+#[derive(serde::Serialize)]
+pub struct VoiceSearch {
+    pub query: String,
+    pub response: HybridSearchResponse,
+}
+
+/// Payload of the `deep-link-search` event: the query a `multisearch://search` link
+/// filled in, with its results.
+#[derive(serde::Serialize)]
+pub struct DeepLinkSearch {
+    pub query: String,
+    pub response: HybridSearchResponse,
+}
+
+impl AppState {
+    pub fn new(settings: Settings) -> Self {
+        let (sync_tx, sync_rx) = mpsc::unbounded_channel();
+        let (watch_tx, watch_rx) = mpsc::unbounded_channel();
+        Self {
+            orchestrator: OnceCell::new(),
+            settings: Mutex::new(settings),
+            session_context: Mutex::new(None),
+            blocked_folders: Mutex::new(BTreeSet::new()),
+            pinned_search: Mutex::new(None),
+            sync_tx,
+            sync_rx: Mutex::new(Some(sync_rx)),
+            pending_syncs: Mutex::new(HashSet::new()),
+            watch_tx,
+            watch_rx: Mutex::new(Some(watch_rx)),
+            resize_generation: AtomicU64::new(0),
+            live_search_generation: AtomicU64::new(0),
+            live_search_superseded: Notify::new(),
+            typing_cadence: Mutex::new(TypingCadence::default()),
+            model_downloaded: Notify::new(),
+            voice_recording: Mutex::new(None),
+            transcriber: OnceCell::new(),
+            health: Mutex::new(None),
+        }
+    }
+
+    /// Remembers folders that indexing was denied access to.
+    pub fn record_blocked_folders(&self, folders: Vec<PathBuf>) {
+        self.blocked_folders.lock().unwrap().extend(folders);
+    }
+
+    /// Removes and returns the blocked folders that have become readable.
+    pub fn take_unblocked_folders(&self) -> Vec<PathBuf> {
+        let mut blocked = self.blocked_folders.lock().unwrap();
+        let unblocked: Vec<PathBuf> = blocked
+            .iter()
+            .filter(|folder| permissions::check_folder_access(folder) != permissions::PermissionStatus::Denied)
+            .cloned()
+            .collect();
+        for folder in &unblocked {
+            blocked.remove(folder);
+        }
+        unblocked
+    }
+
+    /// Captures the frontmost app and project as the launcher opens, if the user enabled it,
+    /// and hands its folder to the orchestrator as the `scope:current-project` scope.
+    pub fn capture_session_context(&self) {
+        if !self.settings.lock().unwrap().context_provider_enabled {
+            return;
+        }
+        let context = app_context::detect_frontmost_context();
+        if let Ok(orchestrator) = self.orchestrator() {
+            orchestrator.set_session_scope(context.as_ref().and_then(|c| c.scope()));
+        }
+        *self.session_context.lock().unwrap() = context;
+    }
+
+    /// Queues a sync of a connector, unless one is already waiting.
+    pub fn request_sync(&self, connector: &str) {
+        if self.pending_syncs.lock().unwrap().insert(connector.to_string()) {
+            let _ = self.sync_tx.send(connector.to_string());
+        }
+    }
+
+    /// Hands out the queue of requested syncs; only the first caller gets it.
+    pub fn take_sync_requests(&self) -> Option<mpsc::UnboundedReceiver<String>> {
+        self.sync_rx.lock().unwrap().take()
+    }
+
+    /// Tells the file watcher to start or stop watching a folder. Sent before the watcher
+    /// starts, the command waits for it.
+    pub fn update_watched_folders(&self, command: WatchCommand) {
+        let _ = self.watch_tx.send(command);
+    }
+
+    /// Hands out the queue of watched-folder changes; only the first caller gets it.
+    pub fn take_watch_commands(&self) -> Option<mpsc::UnboundedReceiver<WatchCommand>> {
+        self.watch_rx.lock().unwrap().take()
+    }
+
+    /// Marks a requested sync as started, so a push arriving during it queues another.
+    pub fn start_requested_sync(&self, connector: &str) {
+        self.pending_syncs.lock().unwrap().remove(connector);
+    }
+
+    /// Returns the pinned query, if a search is pinned.
+    pub fn pinned_query(&self) -> Option<String> {
+        self.pinned_search.lock().unwrap().as_ref().map(|pin| pin.query.clone())
+    }
+
+    /// Marks the results of a pinned query as seen and returns the paths that are new.
+    /// Returns `None` if the query is no longer pinned, e.g. because it was unpinned mid-search.
+    pub fn record_pinned_results(&self, query: &str, response: &HybridSearchResponse) -> Option<Vec<String>> {
+        let mut pinned = self.pinned_search.lock().unwrap();
+        let pin = pinned.as_mut().filter(|pin| pin.query == query)?;
+        Some(
+            response.results.iter()
+                .filter(|result| pin.seen_paths.insert(result.path.clone()))
+                .map(|result| result.path.clone())
+                .collect(),
+        )
+    }
+
+    /// Stores the orchestrator once its background initialization finishes.
+    pub fn set_orchestrator(&self, orchestrator: SearchOrchestrator) {
+        let _ = self.orchestrator.set(Arc::new(orchestrator));
+    }
+
+    /// Returns the speech model for voice queries, loading it on first use. Callers
+    /// arriving while it loads wait for the same load.
+    pub async fn transcriber(&self) -> Result<Arc<Transcriber>, String> {
+        let offline = self.settings.lock().unwrap().offline_mode;
+        self.transcriber
+            .get_or_try_init(|| async { Transcriber::load(offline).await.map(Arc::new) })
+            .await
+            .cloned()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns the orchestrator, or an error the UI can display while it is still loading.
+    pub fn orchestrator(&self) -> Result<Arc<SearchOrchestrator>, String> {
+        self.orchestrator
+            .get()
+            .cloned()
+            .ok_or_else(|| "Search engine is still starting up".to_string())
+    }
+}
+
+// ===================================================================
+//  COMMANDS
+// ===================================================================
+
+/// Returns locally recorded metrics (latency percentiles, cache hit rate, errors, index sizes).
+#[tauri::command]
+pub fn get_stats(state: tauri::State<'_, AppState>) -> Result<MetricsSnapshot, String> {
+    Ok(state.orchestrator()?.stats())
+}
+
+/// Returns the queries that most often found nothing or were abandoned, worst first.
+/// Kept on this machine only; metrics exports carry just the counts.
+#[tauri::command]
+pub fn get_query_analytics(state: tauri::State<'_, AppState>, limit: Option<usize>) -> Result<QueryAnalyticsReport, String> {
+    Ok(state.orchestrator()?.query_analytics(limit.unwrap_or(QUERY_ANALYTICS_LIMIT)))
+}
+
+/// Returns the recent digests of newly indexed content, newest first.
+#[tauri::command]
+pub fn get_digests(state: tauri::State<'_, AppState>) -> Result<Vec<Digest>, String> {
+    Ok(state.orchestrator()?.digests())
+}
+
+/// Writes a digest of everything indexed since the last one right away, instead of
+/// waiting for the daily or weekly run. Returns None if nothing changed.
+#[tauri::command]
+pub async fn generate_digest(state: tauri::State<'_, AppState>) -> Result<Option<Digest>, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.generate_digest().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn clear_query_analytics(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.orchestrator()?.clear_query_analytics().map_err(|e| e.to_string())
+}
+
+/// Exports aggregated metrics to a JSON file if the user has opted in. Returns the file path.
+#[tauri::command]
+pub fn export_metrics(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let opted_in = state.settings.lock().unwrap().metrics_export_enabled;
+    let path = state.orchestrator()?
+        .metrics()
+        .export(opted_in)
+        .map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+/// Turns the metrics export opt-in on or off and persists the choice.
+#[tauri::command]
+pub fn set_metrics_export_enabled(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.metrics_export_enabled = enabled;
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Builds a diagnostic zip (logs, stats, redacted settings, environment) and returns its path.
+#[tauri::command]
+pub fn generate_diagnostics(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let stats = state.orchestrator().ok().map(|orchestrator| orchestrator.stats());
+    let settings = state.settings.lock().unwrap().clone();
+    let path = diagnostics::generate_diagnostics(stats.as_ref(), &settings)
+        .map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+/// Runs a hybrid search for the launcher. Pass `options` to fetch further pages of the
+/// same query; without them the first page is returned.
+#[tauri::command]
+pub async fn search(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<HybridSearchResponse, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.hybrid_search(&query, options.unwrap_or_default()).await.map_err(|e| e.to_string())
+}
+
+/// Searches as the user types: each retrieval leg's hits are sent as a `live-search-batch`
+/// event as soon as the leg finishes, and the fused results are returned at the end.
+/// Each search first waits for the next keystroke, longer the faster the user types.
+/// A search superseded by a newer keystroke returns None, without running if that
+/// happened during the wait, and is otherwise cut short where it is.
+#[tauri::command]
+pub async fn live_search(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<Option<HybridSearchResponse>, String> {
+    let search_id = state.live_search_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    state.live_search_superseded.notify_waiters();
+    let superseded = state.live_search_superseded.notified();
+    tokio::pin!(superseded);
+    let debounce = state.typing_cadence.lock().unwrap().debounce(Instant::now());
+    tokio::select! {
+        _ = tokio::time::sleep(debounce) => {}
+        _ = &mut superseded => return Ok(None),
+    }
+    let is_current = || state.live_search_generation.load(Ordering::SeqCst) == search_id;
+    if !is_current() {
+        return Ok(None);
+    }
+
+    let orchestrator = state.orchestrator()?;
+    let on_batch = |batch: LiveSearchBatch| {
+        if !is_current() {
+            return;
+        }
+        let update = LiveSearchUpdate { search_id, query: query.clone(), batch };
+        if let Err(e) = app.emit("live-search-batch", update) {
+            eprintln!("Warning: Could not emit live-search-batch event: {}", e);
+        }
+    };
+    let response = tokio::select! {
+        response = orchestrator.live_search(&query, options.unwrap_or_default(), on_batch) => response,
+        _ = &mut superseded => return Ok(None),
+    };
+    let response = response.map_err(|e| e.to_string())?;
+    Ok(is_current().then_some(response))
+}
+
+/// Indexes a file or folder in the background, as if it were dropped on the launcher.
+/// Each file reports back through a `file-indexed` event.
+#[tauri::command]
+pub fn index_path(app: AppHandle, path: String) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    if !long_path(&path).exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+    crawler::crawl(&app, vec![path]);
+    Ok(())
+}
+
+/// Adds a folder to the indexed folders, crawls it, reporting through `crawl-progress`
+/// events, and has the file watcher follow its changes from now on.
+#[tauri::command]
+pub fn add_indexed_folder(app: AppHandle, state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    let folder = PathBuf::from(path);
+    if !long_path(&folder).is_dir() {
+        return Err(format!("{} is not a folder", folder.display()));
+    }
+    {
+        let mut settings = state.settings.lock().unwrap();
+        if !settings.indexed_folders.contains(&folder) {
+            settings.indexed_folders.push(folder.clone());
+            settings.save().map_err(|e| e.to_string())?;
+        }
+    }
+    state.update_watched_folders(WatchCommand::Add(folder.clone()));
+    crawler::crawl(&app, vec![folder]);
+    Ok(())
+}
+
+/// Removes a folder from the indexed folders and stops watching it. Documents already
+/// indexed from it stay searchable.
+#[tauri::command]
+pub fn remove_indexed_folder(state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    let folder = PathBuf::from(path);
+    {
+        let mut settings = state.settings.lock().unwrap();
+        let Some(index) = settings.indexed_folders.iter().position(|indexed| *indexed == folder) else {
+            return Err(format!("{} is not an indexed folder", folder.display()));
+        };
+        settings.indexed_folders.remove(index);
+        settings.save().map_err(|e| e.to_string())?;
+    }
+    state.update_watched_folders(WatchCommand::Remove(folder));
+    Ok(())
+}
+
+/// Removes a document from both indexes and forgets its usage state.
+#[tauri::command]
+pub async fn delete_path(state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.delete_document(&path).await.map_err(|e| e.to_string())
+}
+
+/// Runs several queries concurrently, e.g. for a dashboard of active projects.
+#[tauri::command]
+pub async fn batch_search(
+    state: tauri::State<'_, AppState>,
+    queries: Vec<String>,
+) -> Result<Vec<BatchSearchEntry>, String> {
+    let orchestrator = state.orchestrator()?;
+    Ok(orchestrator.batch_search(queries).await)
+}
+
+/// Returns a document's stored summary for the preview pane.
+#[tauri::command]
+pub async fn get_document_summary(state: tauri::State<'_, AppState>, path: String) -> Result<Option<String>, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.document_summary(&path).await.map_err(|e| e.to_string())
+}
+
+/// Returns the most recently opened or changed documents, with previews, for the launcher
+/// to show before a query is typed.
+#[tauri::command]
+pub async fn get_recent_documents(
+    state: tauri::State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<HybridSearchResult>, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.recent_documents(limit.unwrap_or(RECENT_DOCUMENTS)).await.map_err(|e| e.to_string())
+}
+
+/// Returns a document's full text for the in-app reader, without refetching remote sources.
+#[tauri::command]
+pub async fn get_reader_text(state: tauri::State<'_, AppState>, path: String) -> Result<Option<String>, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.reader_text(&path).await.map_err(|e| e.to_string())
+}
+
+/// Returns the topics the corpus was clustered into, each with its most representative
+/// documents, for exploring without a query.
+#[tauri::command]
+pub async fn browse_topics(
+    state: tauri::State<'_, AppState>,
+    documents_per_topic: Option<usize>,
+) -> Result<Vec<BrowsedTopic>, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.browse_topics(documents_per_topic.unwrap_or(TOPIC_PREVIEW_DOCUMENTS)).await.map_err(|e| e.to_string())
+}
+
+/// Counts documents by the day, week or month they were last modified, with a few titles
+/// per bucket, for a "what was I working on" timeline.
+#[tauri::command]
+pub async fn get_timeline(
+    state: tauri::State<'_, AppState>,
+    filter: Option<TimelineFilter>,
+    granularity: Granularity,
+) -> Result<Vec<TimelineBucket>, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.timeline(filter.unwrap_or_default(), granularity, DEFAULT_TITLES_PER_BUCKET).await
+        .map_err(|e| e.to_string())
+}
+
+/// Lists the metadata keys documents carry, such as `status` or `project` from notes'
+/// frontmatter, with common values, so the search box can suggest `key:value` filters.
+#[tauri::command]
+pub async fn get_metadata_fields(state: tauri::State<'_, AppState>) -> Result<Vec<MetadataField>, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.metadata_fields().await.map_err(|e| e.to_string())
+}
+
+/// Finds the passages inside one document that best match the query.
+#[tauri::command]
+pub async fn search_in_document(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    query: String,
+) -> Result<Vec<DocumentPassage>, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.search_in_document(&path, &query).await.map_err(|e| e.to_string())
+}
+
+/// Returns the user's query aliases.
+#[tauri::command]
+pub fn get_aliases(state: tauri::State<'_, AppState>) -> HashMap<String, String> {
+    state.settings.lock().unwrap().aliases.clone()
+}
+
+/// Replaces the user's query aliases, persisting them and applying them to new searches.
+#[tauri::command]
+pub fn set_aliases(state: tauri::State<'_, AppState>, aliases: HashMap<String, String>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.aliases = aliases.clone();
+    settings.save().map_err(|e| e.to_string())?;
+
+    if let Ok(orchestrator) = state.orchestrator() {
+        orchestrator.set_aliases(aliases);
+    }
+    Ok(())
+}
+
+/// Sets how much documents of a kind the query names, like "slides" or `.pdf`, are boosted
+/// (`None` to turn the boost off).
+#[tauri::command]
+pub fn set_content_type_boost(state: tauri::State<'_, AppState>, boost: Option<f32>) -> Result<(), String> {
+    if boost.is_some_and(|boost| !boost.is_finite() || boost <= 0.0) {
+        return Err("The boost must be a positive number".to_string());
+    }
+    let mut settings = state.settings.lock().unwrap();
+    settings.content_type_boost = boost;
+    settings.save().map_err(|e| e.to_string())?;
+
+    if let Ok(orchestrator) = state.orchestrator() {
+        orchestrator.set_content_type_boost(boost);
+    }
+    Ok(())
+}
+
+/// Returns the user's author identity table.
+#[tauri::command]
+pub fn get_identities(state: tauri::State<'_, AppState>) -> Vec<Identity> {
+    state.settings.lock().unwrap().identities.clone()
+}
+
+/// Replaces the author identity table. It applies to documents indexed from now on.
+#[tauri::command]
+pub fn set_identities(state: tauri::State<'_, AppState>, identities: Vec<Identity>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.identities = identities.clone();
+    settings.save().map_err(|e| e.to_string())?;
+
+    if let Ok(orchestrator) = state.orchestrator() {
+        orchestrator.set_identities(identities);
+    }
+    Ok(())
+}
+
+/// Returns the user's named search scopes.
+#[tauri::command]
+pub fn get_scopes(state: tauri::State<'_, AppState>) -> HashMap<String, Scope> {
+    state.settings.lock().unwrap().scopes.clone()
+}
+
+/// Replaces the named search scopes. The UI's scope toggle adds `scope:name` to the query.
+#[tauri::command]
+pub fn set_scopes(state: tauri::State<'_, AppState>, scopes: HashMap<String, Scope>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.scopes = scopes.clone();
+    settings.save().map_err(|e| e.to_string())?;
+
+    if let Ok(orchestrator) = state.orchestrator() {
+        orchestrator.set_scopes(scopes);
+    }
+    Ok(())
+}
+
+/// Returns how each part of the search engine fared in the startup self-test, and
+/// whether search is keyword-only. None while the search engine is still starting.
+#[tauri::command]
+pub fn get_health_report(state: tauri::State<'_, AppState>) -> Option<HealthReport> {
+    state.health.lock().unwrap().clone()
+}
+
+/// Returns the data directory this run uses and whether it came from `--data-dir`, the
+/// environment, the app's setting or the platform default.
+#[tauri::command]
+pub fn get_data_dir() -> Result<DataRoot, String> {
+    data_dir::current().map_err(|e| e.to_string())
+}
+
+/// Moves the app to another data directory from the next launch, or back to the default
+/// with `None`. Fails if the directory can't be created or written to. Data already in
+/// the current directory stays there.
+#[tauri::command]
+pub fn set_data_dir(path: Option<PathBuf>) -> Result<(), String> {
+    data_dir::set_configured_data_dir(path.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Returns the app and project that were frontmost when the launcher opened, so the UI
+/// can offer a one-keystroke "search within current project" toggle.
+#[tauri::command]
+pub fn get_session_context(state: tauri::State<'_, AppState>) -> Option<FrontmostContext> {
+    state.session_context.lock().unwrap().clone()
+}
+
+/// Re-parses and re-indexes a result's local file.
+#[tauri::command]
+pub async fn reindex_document(state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.reindex_document(&path).await.map_err(|e| e.to_string())
+}
+
+/// Hides a result and skips it when indexing in the future. It is removed from the
+/// indexes once the undo window passes; until then `undo_exclude_document` restores it.
+#[tauri::command]
+pub async fn exclude_document(state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.exclude_document(&path).await.map_err(|e| e.to_string())
+}
+
+/// Restores a result hidden with `exclude_document` while its undo window is open.
+#[tauri::command]
+pub fn undo_exclude_document(state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.restore_document(&path).map_err(|e| e.to_string())
+}
+
+/// Lists hidden results that can still be restored, with when each will be purged.
+#[tauri::command]
+pub fn get_pending_deletions(state: tauri::State<'_, AppState>) -> Result<Vec<PendingDeletion>, String> {
+    Ok(state.orchestrator()?.pending_deletions())
+}
+
+/// Applies one action to every document of a result set, emitting `bulk-action-progress`
+/// as it goes. Documents that fail are reported and don't stop the rest.
+#[tauri::command]
+pub async fn run_bulk_action(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    paths: Vec<String>,
+    action: BulkAction,
+) -> Result<BulkActionReport, String> {
+    let orchestrator = state.orchestrator()?;
+    let total = paths.len();
+    let mut report = BulkActionReport::default();
+
+    // 1. Actions that apply to the whole set at once.
+    let whole_set = match &action {
+        BulkAction::Tag { tag } => Some(orchestrator.tag_documents(&paths, tag.trim()).map_err(|e| e.to_string())),
+        BulkAction::CopyPaths => Some(app.clipboard().write_text(paths.join("\n"))
+            .map_err(|e| format!("Could not write the clipboard: {}", e))),
+        BulkAction::OpenAll if total > OPEN_ALL_LIMIT => {
+            return Err(format!("Refusing to open {} documents at once; narrow the results to {} or fewer", total, OPEN_ALL_LIMIT));
+        }
+        _ => None,
+    };
+    if let Some(result) = whole_set {
+        result?;
+        report.succeeded = total;
+        let _ = app.emit("bulk-action-progress", BulkProgress { done: total, total });
+        return Ok(report);
+    }
+
+    // 2. Actions applied document by document.
+    for (done, path) in paths.iter().enumerate() {
+        let result = match &action {
+            BulkAction::Exclude => orchestrator.exclude_document(path).await,
+            BulkAction::OpenAll if bulk_actions::is_url(path) => app.opener().open_url(path.as_str(), None::<&str>)
+                .map_err(|e| anyhow::anyhow!("Could not open {}: {}", path, e)),
+            BulkAction::OpenAll => app.opener().open_path(path.as_str(), None::<&str>)
+                .map_err(|e| anyhow::anyhow!("Could not open {}: {}", path, e)),
+            BulkAction::MoveTo { folder } => match bulk_actions::move_file(std::path::Path::new(path), folder) {
+                Ok(destination) => orchestrator.move_document(path, &destination).await,
+                Err(e) => Err(e),
+            },
+            BulkAction::Tag { .. } | BulkAction::CopyPaths => Ok(()),
+        };
+        report.record(path, result);
+        let _ = app.emit("bulk-action-progress", BulkProgress { done: done + 1, total });
+    }
+    Ok(report)
+}
+
+/// Lists the user's actions offered on results from the given source.
+#[tauri::command]
+pub fn get_result_actions(state: tauri::State<'_, AppState>, source_type: String) -> Vec<ResultAction> {
+    let settings = state.settings.lock().unwrap();
+    result_actions::actions_for(&settings.result_actions, &source_type).into_iter().cloned().collect()
+}
+
+/// Replaces the user's result actions and saves them, refusing a set with a duplicate id
+/// or a command that doesn't parse.
+#[tauri::command]
+pub fn set_result_actions(state: tauri::State<'_, AppState>, actions: Vec<ResultAction>) -> Result<(), String> {
+    result_actions::validate_actions(&actions).map_err(|e| e.to_string())?;
+    let mut settings = state.settings.lock().unwrap();
+    settings.result_actions = actions;
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Runs one of the user's actions on a result, if it is offered on the result's source.
+#[tauri::command]
+pub fn run_result_action(state: tauri::State<'_, AppState>, id: String, path: String, source_type: String) -> Result<(), String> {
+    let settings = state.settings.lock().unwrap();
+    let action = result_actions::actions_for(&settings.result_actions, &source_type).into_iter()
+        .find(|action| action.id == id)
+        .ok_or_else(|| format!("No action '{}' for {} results", id, source_type))?;
+    result_actions::run_action(action, &path).map_err(|e| e.to_string())
+}
+
+/// Fetches a web page and indexes its main content as a `web` document.
+#[tauri::command]
+pub async fn index_url(state: tauri::State<'_, AppState>, url: String) -> Result<(), String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.index_url(&url).await.map_err(|e| e.to_string())
+}
+
+/// Returns the bookmarklet that sends the current browser page to the capture endpoint,
+/// generating the capture token the first time it is needed.
+#[tauri::command]
+pub fn get_bookmarklet(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let mut settings = state.settings.lock().unwrap();
+    if settings.capture_token.is_empty() {
+        settings.capture_token = capture_server::generate_token();
+        settings.save().map_err(|e| e.to_string())?;
+    }
+    Ok(capture_server::bookmarklet(settings.capture_endpoint_port, &settings.capture_token))
+}
+
+/// Returns the local URL a relay or tunnel should forward a connector's push notifications
+/// to, generating the webhook token the first time it is needed.
+#[tauri::command]
+pub fn get_webhook_url(state: tauri::State<'_, AppState>, connector: String) -> Result<String, String> {
+    let mut settings = state.settings.lock().unwrap();
+    if settings.webhook_token.is_empty() {
+        settings.webhook_token = capture_server::generate_token();
+        settings.save().map_err(|e| e.to_string())?;
+    }
+    Ok(webhooks::webhook_url(settings.capture_endpoint_port, &connector, &settings.webhook_token))
+}
+
+/// Returns true if the embedding model is on disk, so search can start without the network.
+#[tauri::command]
+pub fn is_model_ready() -> bool {
+    model_store::is_model_ready(&EMBEDDING_MODEL)
+}
+
+/// Downloads the embedding model into the app data directory, reporting progress as
+/// `model-download-progress` events. Refused in offline mode.
+#[tauri::command]
+pub async fn download_model(app: AppHandle, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if state.settings.lock().unwrap().offline_mode {
+        return Err("Offline mode is on; turn it off to download the model".to_string());
+    }
+    model_store::download_model(&EMBEDDING_MODEL, |progress| {
+        if let Err(e) = app.emit("model-download-progress", progress) {
+            eprintln!("Warning: Could not emit model-download-progress event: {}", e);
+        }
+    }).await.map_err(|e| e.to_string())?;
+    state.model_downloaded.notify_one();
+    Ok(())
+}
+
+/// Lists the downloaded models with their sizes, marking the ones the settings need.
+#[tauri::command]
+pub fn list_cached_models(state: tauri::State<'_, AppState>) -> Vec<CachedModel> {
+    let pinned = model_store::pinned_models(&state.settings.lock().unwrap());
+    model_store::cached_models(&pinned)
+}
+
+/// Deletes a downloaded model that the settings don't need, returning the bytes freed.
+#[tauri::command]
+pub fn delete_cached_model(state: tauri::State<'_, AppState>, repo: String) -> Result<u64, String> {
+    let pinned = model_store::pinned_models(&state.settings.lock().unwrap());
+    model_store::delete_model(&repo, &pinned).map_err(|e| e.to_string())
+}
+
+/// Turns offline mode on or off. Models missing while it is on are never downloaded.
+#[tauri::command]
+pub fn set_offline_mode(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.offline_mode = enabled;
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Turns push notifications on or off. Disabling applies immediately; enabling takes
+/// effect on the next launch if the local endpoint isn't running yet.
+#[tauri::command]
+pub fn set_webhooks_enabled(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.webhooks_enabled = enabled;
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Turns native notifications for one kind of background event on or off.
+#[tauri::command]
+pub fn set_notification_enabled(
+    state: tauri::State<'_, AppState>,
+    category: NotificationCategory,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.notifications.set_enabled(category, enabled);
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Turns the bookmarklet capture endpoint on or off. Disabling applies immediately;
+/// enabling takes effect on the next launch, when the endpoint starts listening.
+#[tauri::command]
+pub fn set_capture_endpoint_enabled(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.capture_endpoint_enabled = enabled;
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Imports the highlights and notes of a Kindle `My Clippings.txt` file. Returns how many were indexed.
+#[tauri::command]
+pub async fn import_kindle_clippings(state: tauri::State<'_, AppState>, path: String) -> Result<usize, String> {
+    let orchestrator = state.orchestrator()?;
+    let highlights = highlights::read_kindle_clippings(&PathBuf::from(path)).map_err(|e| e.to_string())?;
+    Ok(orchestrator.import_highlights(highlights).await)
+}
+
+/// Saves the Readwise access token, or removes it when `None`.
+#[tauri::command]
+pub fn set_readwise_token(state: tauri::State<'_, AppState>, token: Option<String>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.readwise_token = token.filter(|token| !token.trim().is_empty());
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Downloads the Readwise highlights changed since the last sync and indexes them,
+/// resuming an interrupted sync. Returns how many were indexed.
+#[tauri::command]
+pub async fn sync_readwise(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let token = state.settings.lock().unwrap().readwise_token.clone()
+        .ok_or_else(|| "Add a Readwise access token first".to_string())?;
+    let orchestrator = state.orchestrator()?;
+    orchestrator.sync_readwise(&token).await.map_err(|e| e.to_string())
+}
+
+/// Indexes the notes changed in Apple Notes since the last sync and removes deleted ones.
+/// Returns how many were indexed.
+#[tauri::command]
+pub async fn sync_apple_notes(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.sync_apple_notes().await.map_err(|e| e.to_string())
+}
+
+/// Sets how many minutes apart a connector syncs in the background. `None` stops its
+/// scheduled syncs. A connector never synced before syncs right away.
+#[tauri::command]
+pub fn set_connector_schedule(state: tauri::State<'_, AppState>, connector: String, minutes: Option<u64>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    match minutes {
+        Some(minutes) => settings.connector_sync_minutes.insert(connector, minutes),
+        None => settings.connector_sync_minutes.remove(&connector),
+    };
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Turns a connector's scheduled syncs on, syncing it right away, or off. Turning one off
+/// also removes what it indexed. Connectors that sign in with OAuth must be signed in first.
+#[tauri::command]
+pub async fn set_connector_enabled(state: tauri::State<'_, AppState>, connector: String, enabled: bool) -> Result<(), String> {
+    if !enabled {
+        // The connector stays on until its documents are gone, so a failed removal can be retried
+        let orchestrator = state.orchestrator()?;
+        orchestrator.remove_connector_documents(&connector).await.map_err(|e| e.to_string())?;
+        let mut settings = state.settings.lock().unwrap();
+        settings.connector_sync_minutes.remove(&connector);
+        return settings.save().map_err(|e| e.to_string());
+    }
+
+    {
+        let mut settings = state.settings.lock().unwrap();
+        if connectors::requires_sign_in(&connector) {
+            let client = settings.oauth_clients.get(&connector);
+            if !auth::auth_status(&connector, client).signed_in {
+                return Err(format!("Sign in to {} first", connector));
+            }
+        }
+        settings.connector_sync_minutes.entry(connector.clone()).or_insert(connectors::DEFAULT_SYNC_MINUTES);
+        settings.save().map_err(|e| e.to_string())?;
+    }
+    state.request_sync(&connector);
+    Ok(())
+}
+
+/// Lists the Slack channels and DMs the signed-in user is in, for picking which to index.
+#[tauri::command]
+pub async fn list_slack_channels(state: tauri::State<'_, AppState>) -> Result<Vec<SlackChannel>, String> {
+    let client = state.settings.lock().unwrap().oauth_clients.get(SLACK_SOURCE).cloned()
+        .ok_or_else(|| format!("Add an OAuth client ID for {} first", SLACK_SOURCE))?;
+    let orchestrator = state.orchestrator()?;
+    orchestrator.slack_channels(&client).await.map_err(|e| e.to_string())
+}
+
+/// Returns the IDs of the Slack channels and DMs being indexed.
+#[tauri::command]
+pub fn get_slack_channels(state: tauri::State<'_, AppState>) -> Vec<String> {
+    state.settings.lock().unwrap().slack_channels.clone()
+}
+
+/// Picks the Slack channels and DMs to index. If Slack sync is on, it syncs right away,
+/// indexing channels added and removing the messages of channels dropped.
+#[tauri::command]
+pub fn set_slack_channels(state: tauri::State<'_, AppState>, channels: Vec<String>) -> Result<(), String> {
+    let enabled = {
+        let mut settings = state.settings.lock().unwrap();
+        settings.slack_channels = channels;
+        settings.save().map_err(|e| e.to_string())?;
+        settings.connector_sync_minutes.contains_key(SLACK_SOURCE)
+    };
+    if enabled {
+        state.request_sync(SLACK_SOURCE);
+    }
+    Ok(())
+}
+
+/// Returns whether a connector is on, signed in and synced, and how much it has indexed.
+#[tauri::command]
+pub async fn get_connector_state(state: tauri::State<'_, AppState>, connector: String) -> Result<ConnectorState, String> {
+    let (sync_minutes, auth) = {
+        let settings = state.settings.lock().unwrap();
+        let auth = connectors::requires_sign_in(&connector)
+            .then(|| auth::auth_status(&connector, settings.oauth_clients.get(&connector)));
+        (settings.connector_sync_minutes.get(&connector).copied(), auth)
+    };
+    let orchestrator = state.orchestrator()?;
+    let cursor = orchestrator.sync_cursors().remove(&connector).unwrap_or_default();
+    Ok(ConnectorState {
+        enabled: sync_minutes.is_some(),
+        sync_minutes,
+        auth,
+        last_synced_at: cursor.last_synced_at,
+        resuming: cursor.pending.is_some(),
+        documents: orchestrator.source_document_count(&connector).await.map_err(|e| e.to_string())?,
+        connector,
+    })
+}
+
+/// Returns where each connector's next sync will start, for the sync dashboard.
+#[tauri::command]
+pub fn get_sync_cursors(state: tauri::State<'_, AppState>) -> Result<HashMap<String, SyncCursor>, String> {
+    Ok(state.orchestrator()?.sync_cursors())
+}
+
+/// Makes a connector's next sync fetch everything again instead of only what changed.
+#[tauri::command]
+pub fn reset_sync_cursor(state: tauri::State<'_, AppState>, connector: String) -> Result<(), String> {
+    state.orchestrator()?.reset_sync_cursor(&connector).map_err(|e| e.to_string())
+}
+
+/// Returns request, throttling and backoff counts per API connector for the sync dashboard.
+#[tauri::command]
+pub fn get_connector_status(state: tauri::State<'_, AppState>) -> Result<Vec<ConnectorStatus>, String> {
+    Ok(state.orchestrator()?.connector_statuses())
+}
+
+/// Reports whether a connector is set up for OAuth and signed in.
+#[tauri::command]
+pub fn get_auth_status(state: tauri::State<'_, AppState>, connector: String) -> AuthStatus {
+    let client = state.settings.lock().unwrap().oauth_clients.get(&connector).cloned();
+    auth::auth_status(&connector, client.as_ref())
+}
+
+/// Saves the OAuth app a connector signs in with.
+#[tauri::command]
+pub fn set_oauth_client(state: tauri::State<'_, AppState>, connector: String, client: OAuthClient) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.oauth_clients.insert(connector, client);
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Signs a connector in through the browser and stores its tokens in the OS keychain.
+#[tauri::command]
+pub async fn start_auth(app: AppHandle, state: tauri::State<'_, AppState>, connector: String) -> Result<AuthStatus, String> {
+    let client = state.settings.lock().unwrap().oauth_clients.get(&connector).cloned()
+        .ok_or_else(|| format!("Add an OAuth client ID for {} first", connector))?;
+    let open_browser = |url: &str| {
+        app.opener().open_url(url, None::<&str>).map_err(|e| anyhow::anyhow!("Could not open the browser: {}", e))
+    };
+    auth::authorize(&connector, &client, open_browser).await.map_err(|e| e.to_string())
+}
+
+/// Forgets a connector's tokens.
+#[tauri::command]
+pub fn sign_out(connector: String) -> Result<(), String> {
+    auth::sign_out(&connector).map_err(|e| e.to_string())
+}
+
+/// Imports a Zotero library's references and attached PDFs. `data_dir` is the folder
+/// holding `zotero.sqlite` and defaults to `~/Zotero`. Returns how many were indexed.
+#[tauri::command]
+pub async fn import_zotero_library(state: tauri::State<'_, AppState>, data_dir: Option<String>) -> Result<usize, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.import_zotero_library(data_dir.map(PathBuf::from)).await.map_err(|e| e.to_string())
+}
+
+/// Turns the 1Password provider on or off, taking effect from the next search.
+#[tauri::command]
+pub fn set_password_manager_provider_enabled(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    {
+        let mut settings = state.settings.lock().unwrap();
+        settings.password_manager_provider_enabled = enabled;
+        settings.save().map_err(|e| e.to_string())?;
+    }
+    let orchestrator = state.orchestrator()?;
+    if enabled {
+        orchestrator.register_provider(Arc::new(OnePasswordProvider::default()));
+    } else {
+        orchestrator.unregister_provider(ONE_PASSWORD_PROVIDER);
+    }
+    Ok(())
+}
+
+/// Indexes a local git repository and remembers it so new commits are picked up at startup.
+/// Returns how many commits were indexed.
+#[tauri::command]
+pub async fn index_git_repository(state: tauri::State<'_, AppState>, path: String) -> Result<usize, String> {
+    let path = PathBuf::from(path);
+    let commit_limit = {
+        let mut settings = state.settings.lock().unwrap();
+        if !settings.git_repositories.contains(&path) {
+            settings.git_repositories.push(path.clone());
+            settings.save().map_err(|e| e.to_string())?;
+        }
+        settings.git_commit_limit
+    };
+    let orchestrator = state.orchestrator()?;
+    orchestrator.index_git_repository(&path, commit_limit).await.map_err(|e| e.to_string())
+}
+
+/// Turns shell history indexing on or off. Turning it on indexes the history right away;
+/// turning it off stops future imports but keeps commands already indexed.
+#[tauri::command]
+pub async fn set_shell_history_enabled(state: tauri::State<'_, AppState>, enabled: bool) -> Result<usize, String> {
+    let limit = {
+        let mut settings = state.settings.lock().unwrap();
+        settings.shell_history_enabled = enabled;
+        settings.save().map_err(|e| e.to_string())?;
+        settings.shell_history_limit
+    };
+    if !enabled {
+        return Ok(0);
+    }
+    let orchestrator = state.orchestrator()?;
+    orchestrator.index_shell_history(limit).await.map_err(|e| e.to_string())
+}
+
+/// Lists the launchers and indexers installed whose search locations can be imported.
+#[tauri::command]
+pub fn detect_import_sources() -> Vec<ImportSource> {
+    config_import::detect_sources()
+}
+
+/// Adds the folders another tool searches, and the ones it skips, to the indexed and
+/// excluded folders in settings, and crawls the new folders. Returns only the locations
+/// that weren't there yet.
+#[tauri::command]
+pub fn import_locations(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    source: ImportSource,
+) -> Result<ImportedLocations, String> {
+    let imported = config_import::import_locations(source).map_err(|e| e.to_string())?;
+    let mut settings = state.settings.lock().unwrap();
+    let mut added = ImportedLocations::default();
+    for folder in imported.folders {
+        if !settings.indexed_folders.contains(&folder) {
+            settings.indexed_folders.push(folder.clone());
+            added.folders.push(folder);
+        }
+    }
+    for folder in imported.excluded {
+        if !settings.excluded_folders.contains(&folder) {
+            settings.excluded_folders.push(folder.clone());
+            added.excluded.push(folder);
+        }
+    }
+    settings.save().map_err(|e| e.to_string())?;
+    drop(settings);
+    for folder in &added.folders {
+        state.update_watched_folders(WatchCommand::Add(folder.clone()));
+    }
+    if !added.folders.is_empty() {
+        crawler::crawl(&app, added.folders.clone());
+    }
+    Ok(added)
+}
+
+/// Lists the docsets installed by Dash or Zeal, for the UI to offer.
+#[tauri::command]
+pub fn get_installed_docsets() -> Vec<String> {
+    docsets::installed_docsets().iter().map(|path| path.display().to_string()).collect()
+}
+
+/// Indexes a docset for offline documentation search and remembers it in settings.
+/// Returns how many pages were indexed.
+#[tauri::command]
+pub async fn index_docset(state: tauri::State<'_, AppState>, path: String) -> Result<usize, String> {
+    let path = PathBuf::from(path);
+    {
+        let mut settings = state.settings.lock().unwrap();
+        if !settings.docsets.contains(&path) {
+            settings.docsets.push(path.clone());
+            settings.save().map_err(|e| e.to_string())?;
+        }
+    }
+    let orchestrator = state.orchestrator()?;
+    orchestrator.index_docset(&path).await.map_err(|e| e.to_string())
+}
+
+/// Returns the saved snippets, most recently updated first.
+#[tauri::command]
+pub fn list_snippets(state: tauri::State<'_, AppState>) -> Result<Vec<Snippet>, String> {
+    Ok(state.orchestrator()?.list_snippets())
+}
+
+/// Saves a snippet from the given text, or from the clipboard when `body` is omitted.
+#[tauri::command]
+pub async fn create_snippet(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    title: Option<String>,
+    body: Option<String>,
+) -> Result<Snippet, String> {
+    let body = match body {
+        Some(body) => body,
+        None => app.clipboard().read_text().map_err(|e| format!("Could not read the clipboard: {}", e))?,
+    };
+    let orchestrator = state.orchestrator()?;
+    orchestrator.create_snippet(title, body).await.map_err(|e| e.to_string())
+}
+
+/// Replaces a snippet's title and text.
+#[tauri::command]
+pub async fn update_snippet(
+    state: tauri::State<'_, AppState>,
+    id: u64,
+    title: Option<String>,
+    body: String,
+) -> Result<Snippet, String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.update_snippet(id, title, body).await.map_err(|e| e.to_string())
+}
+
+/// Deletes a snippet.
+#[tauri::command]
+pub async fn delete_snippet(state: tauri::State<'_, AppState>, id: u64) -> Result<(), String> {
+    let orchestrator = state.orchestrator()?;
+    orchestrator.delete_snippet(id).await.map_err(|e| e.to_string())
+}
+
+/// The open action of snippet results: copies the snippet, hides the launcher so focus
+/// returns to the previous app, and pastes it there where supported. Returns true if it
+/// was pasted, false if it was only copied.
+#[tauri::command]
+pub async fn paste_snippet(app: AppHandle, state: tauri::State<'_, AppState>, id: u64) -> Result<bool, String> {
+    let orchestrator = state.orchestrator()?;
+    let snippet = orchestrator.snippet(id).ok_or_else(|| format!("Snippet {} not found", id))?;
+    // Picking a snippet is a successful search
+    orchestrator.end_search_session(true);
+    app.clipboard().write_text(snippet.body).map_err(|e| format!("Could not write the clipboard: {}", e))?;
+
+    // A pinned launcher stays up; the snippet is left on the clipboard instead
+    if state.pinned_query().is_some() {
+        return Ok(false);
+    }
+    if let Some(window) = app.get_webview_window("launcher") {
+        let _ = window.hide();
+    }
+    // Give the previous app a moment to regain focus before sending the keystroke
+    tokio::time::sleep(PASTE_FOCUS_DELAY).await;
+    Ok(tokio::task::spawn_blocking(snippets::simulate_paste).await.unwrap_or(false))
+}
+
+/// Pins a search: the launcher stays open on top of other windows and the query is re-run
+/// whenever the index changes, with results sent as `pinned-search-updated` events.
+/// Returns the first run's results.
+#[tauri::command]
+pub async fn pin_search(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    query: String,
+) -> Result<HybridSearchResponse, String> {
+    let orchestrator = state.orchestrator()?;
+    *state.pinned_search.lock().unwrap() = Some(PinnedSearch {
+        query: query.clone(),
+        seen_paths: HashSet::new(),
+    });
+    if let Some(window) = app.get_webview_window("launcher") {
+        let _ = window.set_always_on_top(true);
+    }
+
+    let response = orchestrator.hybrid_search(&query, SearchOptions::default()).await.map_err(|e| e.to_string())?;
+    state.record_pinned_results(&query, &response);
+    Ok(response)
+}
+
+/// Unpins the current search, returning the launcher to normal behaviour.
+#[tauri::command]
+pub fn unpin_search(app: AppHandle, state: tauri::State<'_, AppState>) {
+    *state.pinned_search.lock().unwrap() = None;
+    if let Some(window) = app.get_webview_window("launcher") {
+        let _ = window.set_always_on_top(false);
+    }
+}
+
+/// Turns a chord recorded by the "record new hotkey" control into an accelerator for the
+/// user's keyboard layout, listing conflicts including shortcuts another app holds.
+#[tauri::command]
+pub fn capture_shortcut(app: AppHandle, chord: RawKeyChord) -> Result<CapturedShortcut, String> {
+    let launcher_shortcut = app.state::<AppState>().settings.lock().unwrap().launcher_shortcut.clone();
+    let mut captured = shortcuts::capture_shortcut(&chord, cfg!(target_os = "macos"), &launcher_shortcut)
+        .map_err(|e| e.to_string())?;
+    let shortcut: Shortcut = captured.accelerator.parse()
+        .map_err(|e| format!("Invalid shortcut {}: {}", captured.accelerator, e))?;
+
+    // Registering fails while another app holds the shortcut; release it right away if it works
+    let global_shortcut = app.global_shortcut();
+    if !global_shortcut.is_registered(shortcut) {
+        match global_shortcut.register(shortcut) {
+            Ok(()) => {
+                let _ = global_shortcut.unregister(shortcut);
+            }
+            Err(_) => captured.conflicts.push("Used by another application".to_string()),
+        }
+    }
+    Ok(captured)
+}
+
+/// Makes an accelerator, usually one from `capture_shortcut`, the launcher's hotkey and
+/// saves it. The new shortcut is registered before the old one is released, so the
+/// launcher stays reachable if it's taken.
+#[tauri::command]
+pub fn set_hotkey(app: AppHandle, state: tauri::State<'_, AppState>, accelerator: String) -> Result<(), String> {
+    let shortcut: Shortcut = accelerator.parse()
+        .map_err(|e| format!("Invalid shortcut {}: {}", accelerator, e))?;
+    let mut settings = state.settings.lock().unwrap();
+    let previous: Shortcut = settings.launcher_shortcut.parse()
+        .map_err(|e| format!("Invalid shortcut {}: {}", settings.launcher_shortcut, e))?;
+    if shortcut == previous {
+        return Ok(());
+    }
+
+    crate::register_launcher_shortcut(&app, &accelerator)?;
+    if let Err(e) = app.global_shortcut().unregister(previous) {
+        eprintln!("Warning: Could not release the shortcut {}: {}", settings.launcher_shortcut, e);
+    }
+    settings.launcher_shortcut = accelerator;
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Moves an optional global shortcut from `previous` to `accelerator`, either of which
+/// may be None for off. The new shortcut is registered before the old one is released,
+/// so the old one stays in place if the new one is taken.
+fn replace_optional_shortcut(
+    app: &AppHandle,
+    settings: &Settings,
+    previous: Option<&str>,
+    accelerator: Option<&str>,
+    register: fn(&AppHandle, &str) -> Result<(), String>,
+) -> Result<(), String> {
+    if let Some(accelerator) = accelerator {
+        let shortcut: Shortcut = accelerator.parse()
+            .map_err(|e| format!("Invalid shortcut {}: {}", accelerator, e))?;
+        let launcher: Result<Shortcut, _> = settings.launcher_shortcut.parse();
+        if launcher.is_ok_and(|launcher| launcher == shortcut) {
+            return Err(format!("{} already shows and hides the launcher", accelerator));
+        }
+        register(app, accelerator)?;
+    }
+    if let Some(previous) = previous {
+        if let Err(e) = app.global_shortcut().unregister(previous) {
+            eprintln!("Warning: Could not release the shortcut {}: {}", previous, e);
+        }
+    }
+    Ok(())
+}
+
+/// Sets the shortcut that opens the launcher searching for the selected text and saves
+/// it, or turns it off with None.
+#[tauri::command]
+pub fn set_selection_shortcut(app: AppHandle, state: tauri::State<'_, AppState>, accelerator: Option<String>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    if accelerator == settings.selection_shortcut {
+        return Ok(());
+    }
+    let previous = settings.selection_shortcut.clone();
+    replace_optional_shortcut(&app, &settings, previous.as_deref(), accelerator.as_deref(), crate::register_selection_shortcut)?;
+    settings.selection_shortcut = accelerator;
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Sets the hold-to-talk shortcut for voice queries and saves it, or turns voice
+/// queries off with None. The speech model is downloaded on first use.
+#[tauri::command]
+pub fn set_voice_shortcut(app: AppHandle, state: tauri::State<'_, AppState>, accelerator: Option<String>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    if accelerator == settings.voice_shortcut {
+        return Ok(());
+    }
+    let previous = settings.voice_shortcut.clone();
+    replace_optional_shortcut(&app, &settings, previous.as_deref(), accelerator.as_deref(), crate::register_voice_shortcut)?;
+    settings.voice_shortcut = accelerator;
+    settings.save().map_err(|e| e.to_string())
+}
+
+/// Sets the launcher's backdrop material on Windows and applies it right away. Other
+/// platforms keep the setting for when it is synced to a Windows machine.
+#[tauri::command]
+pub fn set_window_backdrop(app: AppHandle, state: tauri::State<'_, AppState>, backdrop: WindowBackdrop) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    settings.window_backdrop = backdrop;
+    settings.save().map_err(|e| e.to_string())?;
+
+    #[cfg(target_os = "windows")]
+    if let Some(window) = app.get_webview_window("launcher") {
+        crate::apply_window_backdrop(&window, backdrop);
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = app;
+    Ok(())
+}
+
+/// Animates the launcher to a new height as the result list grows or shrinks, keeping
+/// its top edge in place. The height is in logical pixels, so it comes out the same on
+/// every display; each frame re-reads the scale factor in case the window moved to
+/// another one. Resizing natively avoids the jank of the webview resizing itself.
+#[tauri::command]
+pub async fn resize_launcher(app: AppHandle, state: tauri::State<'_, AppState>, height: f64) -> Result<(), String> {
+    let window = app.get_webview_window("launcher").ok_or("The launcher window is missing")?;
+    let generation = state.resize_generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    // 1. Fit the height to the space below the window on its current display
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let size = window.inner_size().map_err(|e| e.to_string())?.to_logical::<f64>(scale);
+    let available = match (window.current_monitor(), window.outer_position()) {
+        (Ok(Some(monitor)), Ok(position)) => {
+            let work_area = monitor.work_area();
+            let bottom = work_area.position.y + work_area.size.height as i32;
+            f64::from(bottom - position.y) / scale
+        }
+        _ => f64::INFINITY,
+    };
+    let target = fit_height(height, available);
+
+    // 2. Step through the frames, giving way to any resize requested in the meantime
+    for frame_height in resize_frames(size.height, target, RESIZE_DURATION, RESIZE_FRAME) {
+        if state.resize_generation.load(Ordering::SeqCst) != generation {
+            return Ok(());
+        }
+        window.set_size(tauri::LogicalSize::new(size.width, frame_height)).map_err(|e| e.to_string())?;
+        tokio::time::sleep(RESIZE_FRAME).await;
+    }
+    Ok(())
+}
+
+/// Lists the index snapshots taken before upgrades and migrations, newest first.
+#[tauri::command]
+pub fn list_snapshots() -> Result<Vec<SnapshotInfo>, String> {
+    SnapshotStore::open().and_then(|store| store.list()).map_err(|e| e.to_string())
+}
+
+/// Rolls the indexes back to a snapshot. The app restarts to restore it, since the
+/// indexes can't be replaced while they are open.
+#[tauri::command]
+pub fn rollback_to_snapshot(app: AppHandle, id: String) -> Result<(), String> {
+    SnapshotStore::open()
+        .and_then(|store| store.request_rollback(&id))
+        .map_err(|e| e.to_string())?;
+    app.restart()
+}
+
+/// Reports Full Disk Access and folder permissions, plus folders indexing was blocked on.
+#[tauri::command]
+pub fn get_permission_status(state: tauri::State<'_, AppState>) -> PermissionReport {
+    let blocked: Vec<PathBuf> = state.blocked_folders.lock().unwrap().iter().cloned().collect();
+    permissions::permission_report(&blocked)
+}
+
+/// Opens the System Settings privacy pane so the user can grant access.
+#[tauri::command]
+pub fn open_privacy_settings(pane: PrivacyPane) -> Result<(), String> {
+    permissions::open_privacy_settings(pane).map_err(|e| e.to_string())
+}
+
+/// Records that the user opened a result, so frequently used documents are kept longest.
+#[tauri::command]
+pub fn record_document_opened(state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    state.orchestrator()?.record_document_opened(&path).map_err(|e| e.to_string())
+}
+
+/// Tells the backend which file is open in the preview or an editor (`None` when it is
+/// closed), so saves to it show up in search within a second. The file that was active
+/// before gets a full re-index in the background if its summary fell behind.
+#[tauri::command]
+pub fn set_active_file(state: tauri::State<'_, AppState>, path: Option<String>) -> Result<(), String> {
+    let orchestrator = state.orchestrator()?;
+    if let Some(previous) = orchestrator.set_active_file(path.as_deref()) {
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = orchestrator.index_file(Path::new(&previous)).await {
+                eprintln!("Warning: Could not re-index {}: {}", previous, e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Records a click on a result from a response tagged with a ranking experiment arm.
+#[tauri::command]
+pub fn record_experiment_click(
+    state: tauri::State<'_, AppState>,
+    experiment: String,
+    variant: Variant,
+) -> Result<(), String> {
+    state.orchestrator()?.record_experiment_click(&experiment, variant).map_err(|e| e.to_string())
+}
+
+/// Returns comparative click-through stats for the current ranking experiment.
+#[tauri::command]
+pub fn get_experiment_report(state: tauri::State<'_, AppState>) -> Result<Option<ExperimentReport>, String> {
+    Ok(state.orchestrator()?.experiment_report())
+}
+
+/// Returns free space on the volumes holding the indexes and whether indexing is paused for it.
+#[tauri::command]
+pub fn get_disk_space_status(state: tauri::State<'_, AppState>) -> Result<DiskSpaceStatus, String> {
+    Ok(state.orchestrator()?.disk_space_status())
+}
+
+/// Sets how many megabytes indexing keeps free on the data volumes (`None` to never pause).
+#[tauri::command]
+pub fn set_min_free_disk(state: tauri::State<'_, AppState>, min_free_mb: Option<u64>) -> Result<DiskSpaceStatus, String> {
+    {
+        let mut settings = state.settings.lock().unwrap();
+        settings.min_free_disk_mb = min_free_mb;
+        settings.save().map_err(|e| e.to_string())?;
+    }
+    let orchestrator = state.orchestrator()?;
+    orchestrator.set_min_free_disk(min_free_mb);
+    Ok(orchestrator.disk_space_status())
+}
+
+/// Sets the vector store quota in megabytes (`None` for unlimited) and enforces it right away.
+#[tauri::command]
+pub async fn set_storage_quota(
+    state: tauri::State<'_, AppState>,
+    quota_mb: Option<u64>,
+) -> Result<Option<EvictionReport>, String> {
+    let quota_bytes = quota_mb
+        .map(|quota_mb| quota_mb.checked_mul(1024 * 1024).ok_or_else(|| format!("A quota of {} MB is too large", quota_mb)))
+        .transpose()?;
+    {
+        let mut settings = state.settings.lock().unwrap();
+        settings.vector_store_quota_mb = quota_mb;
+        settings.save().map_err(|e| e.to_string())?;
+    }
+
+    match quota_bytes {
+        Some(quota_bytes) => {
+            let orchestrator = state.orchestrator()?;
+            let report = orchestrator.enforce_storage_quota(quota_bytes).await
+                .map_err(|e| e.to_string())?;
+            Ok(Some(report))
+        }
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,217 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::fs_paths::long_path;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Another launcher or desktop indexer whose search locations can be imported.
+/// Raycast is not among them: it keeps its settings in an encrypted database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    Alfred,
+    Recoll,
+}
+
+/// The folders another tool searches, and the ones it was told to skip.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportedLocations {
+    pub folders: Vec<PathBuf>,
+    pub excluded: Vec<PathBuf>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Expands a leading `~` to the home directory.
+fn expand_home(path: &str, home: &Path) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) => home.join(rest.trim_start_matches('/')),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Splits a Recoll list value into its entries. Entries containing spaces are
+/// double-quoted, e.g. `topdirs = ~/Documents "~/My Projects"`.
+fn split_recoll_list(value: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    entries.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        entries.push(current);
+    }
+    entries
+}
+
+/// Reads the folders Recoll indexes (`topdirs`) and skips (`skippedPaths`) from a
+/// `recoll.conf`. Skipped paths with wildcards can't be expressed as folders and are left out.
+fn parse_recoll_conf(contents: &str, home: &Path) -> ImportedLocations {
+    let mut locations = ImportedLocations::default();
+    // Lines ending in a backslash continue on the next one
+    let joined = contents.replace("\\\n", " ");
+    for line in joined.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((name, value)) = line.split_once('=') else { continue };
+        let target = match name.trim() {
+            "topdirs" => &mut locations.folders,
+            "skippedPaths" => &mut locations.excluded,
+            _ => continue,
+        };
+        *target = split_recoll_list(value)
+            .into_iter()
+            .filter(|entry| !entry.contains(['*', '?', '[']))
+            .map(|entry| expand_home(&entry, home))
+            .collect();
+    }
+    locations
+}
+
+/// Reads Alfred's file search scope from its default results preferences, an XML
+/// property list with the folders under the `scope` key.
+fn parse_alfred_scope(plist: &str, home: &Path) -> Vec<PathBuf> {
+    let Some(after_key) = plist.split_once("<key>scope</key>").map(|(_, rest)| rest) else {
+        return Vec::new();
+    };
+    let Some(array) = after_key.trim_start().strip_prefix("<array>")
+        .and_then(|rest| rest.split_once("</array>"))
+        .map(|(array, _)| array)
+    else {
+        return Vec::new();
+    };
+    array.split("<string>")
+        .skip(1)
+        .filter_map(|item| item.split_once("</string>").map(|(value, _)| value))
+        .map(|value| {
+            let value = value.replace("&lt;", "<").replace("&gt;", ">")
+                .replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&");
+            expand_home(&value, home)
+        })
+        .collect()
+}
+
+/// Returns Alfred's preferences bundle. Synced preferences live wherever the user pointed
+/// Alfred, recorded in `prefs.json`; otherwise they are in Application Support.
+fn alfred_preferences(home: &Path) -> PathBuf {
+    let support = home.join("Library/Application Support/Alfred");
+    std::fs::read_to_string(support.join("prefs.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+        .and_then(|prefs| prefs.get("current")?.as_str().map(|current| expand_home(current, home)))
+        .unwrap_or_else(|| support.join("Alfred.alfredpreferences"))
+}
+
+/// Returns the config file a tool keeps its search locations in.
+fn config_path(source: ImportSource, home: &Path) -> PathBuf {
+    match source {
+        ImportSource::Alfred => alfred_preferences(home).join("preferences/features/defaultresults/prefs.plist"),
+        ImportSource::Recoll => home.join(".recoll/recoll.conf"),
+    }
+}
+
+/// Reads a property list as XML. Alfred may write binary ones, which macOS's `plutil`
+/// converts.
+fn read_plist(path: &Path) -> Result<String> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("plutil")
+            .args(["-convert", "xml1", "-o", "-"])
+            .arg(path)
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("plutil could not read {}", path.display()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        Ok(std::fs::read_to_string(long_path(path))?)
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns the tools whose config files exist, for offering an import on first run.
+pub fn detect_sources() -> Vec<ImportSource> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    [ImportSource::Alfred, ImportSource::Recoll]
+        .into_iter()
+        .filter(|source| long_path(&config_path(*source, &home)).is_file())
+        .collect()
+}
+
+/// Reads the folders another tool searches and the ones it skips. Folders that no
+/// longer exist are left out.
+pub fn import_locations(source: ImportSource) -> Result<ImportedLocations> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find the home directory"))?;
+    let path = config_path(source, &home);
+    let mut locations = match source {
+        ImportSource::Alfred => ImportedLocations {
+            folders: parse_alfred_scope(&read_plist(&path)?, &home),
+            excluded: Vec::new(),
+        },
+        ImportSource::Recoll => {
+            let contents = std::fs::read_to_string(long_path(&path))
+                .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+            parse_recoll_conf(&contents, &home)
+        }
+    };
+    locations.folders.retain(|folder| long_path(folder).is_dir());
+    Ok(locations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recoll_conf() {
+        let conf = "# Recoll config\n\
+            topdirs = ~/Documents \"~/My Projects\" \\\n    /srv/notes\n\
+            skippedPaths = ~/Documents/tmp /srv/notes/*.bak\n\
+            skippedNames = *.log\n";
+        let locations = parse_recoll_conf(conf, Path::new("/home/ana"));
+        assert_eq!(locations.folders, vec![
+            PathBuf::from("/home/ana/Documents"),
+            PathBuf::from("/home/ana/My Projects"),
+            PathBuf::from("/srv/notes"),
+        ]);
+        assert_eq!(locations.excluded, vec![PathBuf::from("/home/ana/Documents/tmp")]);
+    }
+
+    #[test]
+    fn test_parse_alfred_scope() {
+        let plist = "<plist><dict>\n<key>defaultresults</key><array><string>ignored</string></array>\n\
+            <key>scope</key>\n<array>\n<string>/Applications</string>\n<string>~/Notes &amp; Drafts</string>\n</array>\n\
+            </dict></plist>";
+        assert_eq!(parse_alfred_scope(plist, Path::new("/Users/ana")), vec![
+            PathBuf::from("/Applications"),
+            PathBuf::from("/Users/ana/Notes & Drafts"),
+        ]);
+        assert!(parse_alfred_scope("<plist><dict></dict></plist>", Path::new("/Users/ana")).is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::auth::AuthStatus;
+use crate::sync_cursors::SyncCursor;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub mod apple_notes;
+pub mod gmail;
+pub mod slack;
+
+/// How often the sync schedule is checked for connectors that are due.
+pub const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Minutes between syncs of a connector turned on without an interval of its own.
+pub const DEFAULT_SYNC_MINUTES: u64 = 15;
+/// Shortest interval between scheduled syncs, so a typo can't sync a source nonstop.
+const MIN_SYNC_INTERVAL_MINUTES: u64 = 5;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Where a connector stands, for its settings page.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectorState {
+    pub connector: String,
+    /// True while the connector syncs on a schedule.
+    pub enabled: bool,
+    pub sync_minutes: Option<u64>,
+    /// Sign-in status of connectors that sign in with OAuth.
+    pub auth: Option<AuthStatus>,
+    /// Unix timestamp (seconds) of the last complete sync.
+    pub last_synced_at: Option<u64>,
+    /// True when a sync was interrupted and the next one resumes it.
+    pub resuming: bool,
+    /// Documents from the connector in the index.
+    pub documents: usize,
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns true for connectors that need an OAuth sign-in before they can sync.
+pub fn requires_sign_in(connector: &str) -> bool {
+    connector == gmail::GMAIL_SOURCE || connector == slack::SLACK_SOURCE
+}
+
+/// Returns the seconds between scheduled syncs for an interval set in minutes.
+pub fn sync_interval_secs(minutes: u64) -> u64 {
+    minutes.max(MIN_SYNC_INTERVAL_MINUTES) * 60
+}
+
+/// Returns the scheduled connectors due for a sync at `now` (Unix seconds): those never
+/// synced, and those whose last complete sync is older than their interval in minutes.
+pub fn due_syncs(schedule: &HashMap<String, u64>, cursors: &HashMap<String, SyncCursor>, now: u64) -> Vec<String> {
+    let mut due: Vec<String> = schedule.iter()
+        .filter(|(connector, &minutes)| {
+            match cursors.get(*connector).and_then(|cursor| cursor.last_synced_at) {
+                Some(last) => now.saturating_sub(last) >= sync_interval_secs(minutes),
+                None => true,
+            }
+        })
+        .map(|(connector, _)| connector.clone())
+        .collect();
+    due.sort();
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_syncs() {
+        let schedule = HashMap::from([
+            ("apple_notes".to_string(), 30),
+            ("readwise".to_string(), 60),
+            ("zotero".to_string(), 1),
+        ]);
+        let synced_at = |secs| SyncCursor { last_synced_at: Some(secs), ..Default::default() };
+        let cursors = HashMap::from([
+            ("apple_notes".to_string(), synced_at(10_000)),
+            ("zotero".to_string(), synced_at(10_000)),
+        ]);
+
+        // Never synced is due right away; one minute is raised to the five minute floor
+        assert_eq!(due_syncs(&schedule, &cursors, 10_000 + 120), vec!["readwise"]);
+        assert_eq!(due_syncs(&schedule, &cursors, 10_000 + 300), vec!["readwise", "zotero"]);
+        assert_eq!(due_syncs(&schedule, &cursors, 10_000 + 1_800), vec!["apple_notes", "readwise", "zotero"]);
+    }
+}
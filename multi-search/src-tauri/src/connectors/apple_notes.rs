@@ -0,0 +1,250 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::fs_paths::long_path;
+use crate::search_orchestrator::RawDocument;
+use crate::settings::app_data_dir;
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use rusqlite::Connection;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source type of notes read from Apple Notes.
+pub const APPLE_NOTES_SOURCE: &str = "apple_notes";
+
+/// Seconds between the Unix epoch and 2001-01-01, the epoch Core Data dates count from.
+const CORE_DATA_EPOCH_OFFSET: f64 = 978_307_200.0;
+/// Folder type of "Recently Deleted", whose notes are left out.
+const TRASH_FOLDER_TYPE: i64 = 1;
+/// Character Notes puts where an attachment (image, table, sketch) sits in the text.
+const ATTACHMENT_MARKER: char = '\u{FFFC}';
+
+/// Every readable note with its folder and body. Locked notes are encrypted and skipped.
+const NOTES_QUERY: &str = "
+    SELECT note.ZIDENTIFIER, note.ZTITLE1, note.ZMODIFICATIONDATE1, folder.ZTITLE2, data.ZDATA
+    FROM ZICCLOUDSYNCINGOBJECT AS note
+    JOIN ZICNOTEDATA AS data ON data.ZNOTE = note.Z_PK
+    LEFT JOIN ZICCLOUDSYNCINGOBJECT AS folder ON folder.Z_PK = note.ZFOLDER
+    WHERE COALESCE(note.ZMARKEDFORDELETION, 0) = 0
+      AND COALESCE(note.ZISPASSWORDPROTECTED, 0) = 0
+      AND folder.ZFOLDERTYPE IS NOT ?1";
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// One note from the Notes database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppleNote {
+    /// The note's UUID, stable across edits and devices.
+    pub identifier: String,
+    pub title: String,
+    pub folder: Option<String>,
+    /// Plain text of the note, its first line being the title.
+    pub body: String,
+    pub modified: Option<SystemTime>,
+}
+
+impl AppleNote {
+    /// Returns a link that opens the note in the Notes app, used as its document key.
+    pub fn link(&self) -> String {
+        format!("notes://showNote?identifier={}", self.identifier)
+    }
+
+    /// Builds the note's document, keeping its folder as metadata.
+    pub fn into_raw_document(self) -> RawDocument {
+        let path = self.link();
+        let mut metadata = BTreeMap::new();
+        if let Some(folder) = self.folder {
+            metadata.insert("folder".to_string(), folder);
+        }
+        RawDocument {
+            path,
+            title: self.title,
+            body: self.body,
+            summary: None,
+            source_type: APPLE_NOTES_SOURCE.to_string(),
+            author: None,
+            modified_date: self.modified.unwrap_or_else(SystemTime::now),
+            metadata,
+        }
+    }
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns where Notes keeps its database on macOS.
+fn default_store_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join("Library/Group Containers/group.com.apple.notes/NoteStore.sqlite"))
+}
+
+/// Notes keeps its database open while running, so a snapshot is read instead. Recent
+/// edits still sit in the write-ahead log, which is copied along with it.
+fn open_snapshot(store: &Path) -> Result<Connection> {
+    let snapshot = app_data_dir()?.join("apple_notes_snapshot.sqlite");
+    if let Some(parent) = snapshot.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(long_path(store), &snapshot)
+        .map_err(|e| anyhow::anyhow!("Failed to read the Notes database {}: {}", store.display(), e))?;
+
+    let wal = PathBuf::from(format!("{}-wal", store.display()));
+    let snapshot_wal = PathBuf::from(format!("{}-wal", snapshot.display()));
+    if long_path(&wal).is_file() {
+        std::fs::copy(long_path(&wal), &snapshot_wal)?;
+    } else if snapshot_wal.is_file() {
+        // A log left from the previous snapshot would be replayed onto this one
+        std::fs::remove_file(&snapshot_wal)?;
+    }
+    // Opened writable so SQLite can replay the copied log; only the snapshot changes
+    Ok(Connection::open(&snapshot)?)
+}
+
+/// Reads a protobuf varint at `pos`, advancing past it.
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Returns the first length-delimited field numbered `field` of a protobuf message,
+/// skipping over every other field.
+fn protobuf_field(message: &[u8], field: u64) -> Option<&[u8]> {
+    let mut pos = 0;
+    while pos < message.len() {
+        let key = read_varint(message, &mut pos)?;
+        match key & 0x7 {
+            0 => {
+                read_varint(message, &mut pos)?;
+            }
+            1 => pos += 8,
+            2 => {
+                let len = read_varint(message, &mut pos)? as usize;
+                let value = message.get(pos..pos.checked_add(len)?)?;
+                if key >> 3 == field {
+                    return Some(value);
+                }
+                pos += len;
+            }
+            5 => pos += 4,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Extracts the plain text of a note from its stored body: a gzipped protobuf document
+/// whose text sits at field 2 (document) → 3 (note) → 2 (text).
+fn note_text(data: &[u8]) -> Option<String> {
+    let mut message = Vec::new();
+    if data.starts_with(&[0x1f, 0x8b]) {
+        GzDecoder::new(data).read_to_end(&mut message).ok()?;
+    } else {
+        message.extend_from_slice(data);
+    }
+    let text = [2, 3, 2].iter()
+        .try_fold(message.as_slice(), |message, &field| protobuf_field(message, field))?;
+    Some(String::from_utf8_lossy(text).replace(ATTACHMENT_MARKER, "").trim().to_string())
+}
+
+/// Converts a Core Data timestamp, in seconds since 2001-01-01, to a system time.
+fn core_data_time(seconds: f64) -> Option<SystemTime> {
+    let unix = seconds + CORE_DATA_EPOCH_OFFSET;
+    (unix.is_finite() && unix >= 0.0).then(|| UNIX_EPOCH + Duration::from_secs_f64(unix))
+}
+
+/// Reads every note that has a readable body.
+fn read_notes_from(connection: &Connection) -> Result<Vec<AppleNote>> {
+    let mut statement = connection.prepare(NOTES_QUERY)?;
+    let rows = statement.query_map([TRASH_FOLDER_TYPE], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, Option<f64>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, Option<Vec<u8>>>(4)?,
+        ))
+    })?;
+
+    let mut notes = Vec::new();
+    for row in rows {
+        let (Some(identifier), title, modified, folder, Some(data)) = row? else { continue };
+        let Some(body) = note_text(&data).filter(|body| !body.is_empty()) else { continue };
+        // Untitled notes are titled by their first line, as Notes shows them
+        let title = title
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| body.lines().next().unwrap_or_default().trim().to_string());
+        notes.push(AppleNote {
+            identifier,
+            title,
+            folder,
+            body,
+            modified: modified.and_then(core_data_time),
+        });
+    }
+    Ok(notes)
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Reads every note from the Notes database. `store` is the path to `NoteStore.sqlite`
+/// and defaults to where Notes keeps it on macOS.
+pub fn read_notes(store: Option<&Path>) -> Result<Vec<AppleNote>> {
+    let store = match store {
+        Some(store) => store.to_path_buf(),
+        None => default_store_path().ok_or_else(|| anyhow::anyhow!("Could not find the home directory"))?,
+    };
+    if !long_path(&store).is_file() {
+        return Err(anyhow::anyhow!("No Apple Notes database found at {}", store.display()));
+    }
+    let connection = open_snapshot(&store)?;
+    read_notes_from(&connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    /// Encodes a length-delimited protobuf field.
+    fn field(number: u8, value: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![(number << 3) | 2, value.len() as u8];
+        bytes.extend_from_slice(value);
+        bytes
+    }
+
+    #[test]
+    fn test_note_text() {
+        // Version and other scalar fields come before the text and are skipped
+        let mut note = vec![0x08, 0x96, 0x01];
+        note.extend(field(2, "Groceries\nMilk \u{FFFC}".as_bytes()));
+        let document = [vec![0x08, 0x00], field(3, &note)].concat();
+        let root = [vec![0x08, 0x00], field(2, &document)].concat();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&root).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        assert_eq!(note_text(&gzipped).as_deref(), Some("Groceries\nMilk"));
+        assert_eq!(note_text(&root).as_deref(), Some("Groceries\nMilk"));
+        assert_eq!(note_text(&[0x12, 0x40]), None);
+
+        assert_eq!(core_data_time(0.0), Some(UNIX_EPOCH + Duration::from_secs(978_307_200)));
+    }
+}
@@ -0,0 +1,385 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::html_text::parse_html;
+use crate::rate_limit::RateLimiter;
+use crate::search_orchestrator::RawDocument;
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source type of messages synced from Gmail; also the connector's name for sign-in,
+/// rate limits and sync cursors.
+pub const GMAIL_SOURCE: &str = "gmail";
+
+const GMAIL_API_URL: &str = "https://gmail.googleapis.com/gmail/v1/users/me";
+const GMAIL_TIMEOUT: Duration = Duration::from_secs(30);
+/// The first sync reaches back a year; older mail is left to Gmail's own search.
+const FULL_SYNC_QUERY: &str = "newer_than:1y";
+/// Message ids listed per page of the first sync.
+const PAGE_SIZE: &str = "100";
+/// Labels whose messages aren't indexed, and are removed once they get one.
+const SKIPPED_LABELS: &[&str] = &["SPAM", "TRASH", "DRAFT"];
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// The mailbox's history moved past the point a sync asked for; Gmail keeps about a week
+/// of it. The mailbox has to be synced from scratch.
+#[derive(Debug)]
+pub struct HistoryExpired;
+
+impl std::fmt::Display for HistoryExpired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Gmail no longer has the history since the last sync")
+    }
+}
+
+impl std::error::Error for HistoryExpired {}
+
+/// A page of message ids, with the token of the next one.
+#[derive(Debug, Default, PartialEq)]
+pub struct MessagePage {
+    pub ids: Vec<String>,
+    pub next_page: Option<String>,
+}
+
+/// The messages added and removed on one page of mailbox history. A message both added
+/// and removed within the page is only removed.
+#[derive(Debug, Default, PartialEq)]
+pub struct HistoryPage {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub next_page: Option<String>,
+    /// The mailbox's current history id, where the next sync starts once this one is done.
+    pub history_id: String,
+}
+
+/// One message with the parts that get indexed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GmailMessage {
+    pub id: String,
+    pub thread_id: String,
+    pub labels: Vec<String>,
+    pub subject: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub received: Option<SystemTime>,
+    pub body: String,
+}
+
+/// Talks to the Gmail API with one access token, within the connector's rate limit.
+pub struct GmailClient<'a> {
+    client: reqwest::Client,
+    token: String,
+    limiter: &'a RateLimiter,
+}
+
+// ===================================================================
+//  API RESPONSES
+// ===================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Profile {
+    history_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageRef {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageList {
+    #[serde(default)]
+    messages: Vec<MessageRef>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryMessage {
+    message: MessageRef,
+    #[serde(default)]
+    label_ids: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct HistoryRecord {
+    messages_added: Vec<HistoryMessage>,
+    messages_deleted: Vec<HistoryMessage>,
+    labels_added: Vec<HistoryMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryList {
+    #[serde(default)]
+    history: Vec<HistoryRecord>,
+    next_page_token: Option<String>,
+    history_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Header {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PartBody {
+    data: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct MessagePart {
+    mime_type: String,
+    headers: Vec<Header>,
+    body: PartBody,
+    parts: Vec<MessagePart>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Message {
+    id: String,
+    thread_id: String,
+    #[serde(default)]
+    label_ids: Vec<String>,
+    #[serde(default)]
+    snippet: String,
+    internal_date: Option<String>,
+    #[serde(default)]
+    payload: MessagePart,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Decodes a part body, which Gmail sends as URL-safe base64 with or without padding.
+fn decode_body(data: &str) -> Option<String> {
+    let bytes = URL_SAFE_NO_PAD.decode(data.trim_end_matches('=')).ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Finds the first part of a MIME type, depth first, skipping attachments.
+fn find_part<'a>(part: &'a MessagePart, mime_type: &str) -> Option<&'a MessagePart> {
+    if part.mime_type == mime_type && part.body.data.is_some() {
+        return Some(part);
+    }
+    part.parts.iter().find_map(|child| find_part(child, mime_type))
+}
+
+/// Returns the readable text of a message: its plain-text part, else its HTML part with
+/// the markup stripped.
+fn message_text(payload: &MessagePart) -> Option<String> {
+    if let Some(text) = find_part(payload, "text/plain").and_then(|part| decode_body(part.body.data.as_deref()?)) {
+        return Some(text.trim().to_string());
+    }
+    let html = find_part(payload, "text/html").and_then(|part| decode_body(part.body.data.as_deref()?))?;
+    Some(parse_html(&html).body)
+}
+
+/// Folds history records, oldest first, into the messages to index and to remove.
+fn history_changes(records: Vec<HistoryRecord>) -> (Vec<String>, Vec<String>) {
+    let mut added: Vec<String> = Vec::new();
+    let mut removed: Vec<String> = Vec::new();
+    for record in records {
+        for change in record.messages_added {
+            removed.retain(|id| *id != change.message.id);
+            if !added.contains(&change.message.id) {
+                added.push(change.message.id);
+            }
+        }
+        let trashed = record.labels_added.into_iter()
+            .filter(|change| change.label_ids.iter().any(|label| SKIPPED_LABELS.contains(&label.as_str())));
+        for change in record.messages_deleted.into_iter().chain(trashed) {
+            added.retain(|id| *id != change.message.id);
+            if !removed.contains(&change.message.id) {
+                removed.push(change.message.id);
+            }
+        }
+    }
+    (added, removed)
+}
+
+impl From<Message> for GmailMessage {
+    fn from(message: Message) -> Self {
+        let header = |name: &str| {
+            message.payload.headers.iter()
+                .find(|header| header.name.eq_ignore_ascii_case(name))
+                .map(|header| header.value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let received = message.internal_date.as_deref()
+            .and_then(|millis| millis.parse().ok())
+            .map(|millis| UNIX_EPOCH + Duration::from_millis(millis));
+        Self {
+            subject: header("Subject"),
+            from: header("From"),
+            to: header("To"),
+            received,
+            body: message_text(&message.payload).unwrap_or(message.snippet),
+            id: message.id,
+            thread_id: message.thread_id,
+            labels: message.label_ids,
+        }
+    }
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl GmailMessage {
+    /// Returns false for spam, trash and drafts, which are left out of the index.
+    pub fn is_indexable(&self) -> bool {
+        !self.labels.iter().any(|label| SKIPPED_LABELS.contains(&label.as_str()))
+    }
+
+    /// Builds the message's document: titled by its subject, authored by its sender, with
+    /// a link to its thread in Gmail kept as metadata.
+    pub fn into_raw_document(self) -> RawDocument {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("url".to_string(), format!("https://mail.google.com/mail/#all/{}", self.thread_id));
+        metadata.insert("thread".to_string(), self.thread_id);
+        if let Some(to) = self.to {
+            metadata.insert("to".to_string(), to);
+        }
+        if !self.labels.is_empty() {
+            metadata.insert("labels".to_string(), self.labels.join("; "));
+        }
+        RawDocument {
+            path: message_key(&self.id),
+            title: self.subject.unwrap_or_else(|| "(no subject)".to_string()),
+            body: self.body,
+            summary: None,
+            source_type: GMAIL_SOURCE.to_string(),
+            author: self.from,
+            modified_date: self.received.unwrap_or_else(SystemTime::now),
+            metadata,
+        }
+    }
+}
+
+impl<'a> GmailClient<'a> {
+    pub fn new(token: &str, limiter: &'a RateLimiter) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(GMAIL_TIMEOUT).build()?;
+        Ok(Self { client, token: token.to_string(), limiter })
+    }
+
+    /// Sends a GET request to an endpoint under the user's mailbox.
+    async fn get(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<reqwest::Response> {
+        let url = format!("{}/{}", GMAIL_API_URL, endpoint);
+        let build_request = || self.client.get(&url).bearer_auth(&self.token).query(query);
+        self.limiter.send(build_request).await
+    }
+
+    /// Returns the mailbox's current history id, where syncing after a full sync starts.
+    pub async fn history_id(&self) -> Result<String> {
+        let profile: Profile = self.get("profile", &[]).await?.error_for_status()?.json().await?;
+        Ok(profile.history_id)
+    }
+
+    /// Lists one page of the messages the first sync indexes.
+    pub async fn list_messages(&self, page: Option<&str>) -> Result<MessagePage> {
+        let mut query = vec![("q", FULL_SYNC_QUERY), ("maxResults", PAGE_SIZE)];
+        query.extend(page.map(|page| ("pageToken", page)));
+        let list: MessageList = self.get("messages", &query).await?.error_for_status()?.json().await?;
+        Ok(MessagePage {
+            ids: list.messages.into_iter().map(|message| message.id).collect(),
+            next_page: list.next_page_token,
+        })
+    }
+
+    /// Reads one page of the mailbox's history since `start_history_id`. Fails with
+    /// `HistoryExpired` when Gmail no longer keeps history that old.
+    pub async fn history(&self, start_history_id: &str, page: Option<&str>) -> Result<HistoryPage> {
+        let mut query = vec![
+            ("startHistoryId", start_history_id),
+            ("historyTypes", "messageAdded"),
+            ("historyTypes", "messageDeleted"),
+            ("historyTypes", "labelAdded"),
+        ];
+        query.extend(page.map(|page| ("pageToken", page)));
+        let response = self.get("history", &query).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(HistoryExpired.into());
+        }
+        let list: HistoryList = response.error_for_status()?.json().await?;
+        let (added, removed) = history_changes(list.history);
+        Ok(HistoryPage { added, removed, next_page: list.next_page_token, history_id: list.history_id })
+    }
+
+    /// Fetches a message with its headers and body. `None` if it was deleted meanwhile.
+    pub async fn message(&self, id: &str) -> Result<Option<GmailMessage>> {
+        let response = self.get(&format!("messages/{}", id), &[("format", "full")]).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let message: Message = response.error_for_status()?.json().await?;
+        Ok(Some(message.into()))
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns the document key of a message.
+pub fn message_key(id: &str) -> String {
+    format!("gmail://msg/{}", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_document() {
+        let html = URL_SAFE_NO_PAD.encode("<html><body><p>See you <b>Friday</b></p></body></html>");
+        let json = format!(r#"{{
+            "id": "18c2", "threadId": "18c0", "labelIds": ["INBOX", "IMPORTANT"],
+            "snippet": "See you Friday", "internalDate": "1700000000000",
+            "payload": {{
+                "mimeType": "multipart/alternative",
+                "headers": [{{"name": "Subject", "value": "Offsite"}}, {{"name": "from", "value": "Ana <ana@example.com>"}}],
+                "parts": [{{"mimeType": "text/html", "body": {{"data": "{}"}}}}]
+            }}
+        }}"#, html);
+        let message: GmailMessage = serde_json::from_str::<Message>(&json).unwrap().into();
+        assert!(message.is_indexable());
+
+        let doc = message.into_raw_document();
+        assert_eq!(doc.path, "gmail://msg/18c2");
+        assert_eq!(doc.title, "Offsite");
+        assert_eq!(doc.author.as_deref(), Some("Ana <ana@example.com>"));
+        assert_eq!(doc.body, "See you Friday");
+        assert_eq!(doc.modified_date, UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        assert_eq!(doc.metadata["labels"], "INBOX; IMPORTANT");
+
+        // Padded base64 decodes too
+        assert_eq!(decode_body("aGk=").as_deref(), Some("hi"));
+    }
+
+    #[test]
+    fn test_history_changes() {
+        let json = r#"[
+            {"messagesAdded": [{"message": {"id": "a"}}, {"message": {"id": "b"}}]},
+            {"messagesDeleted": [{"message": {"id": "a"}}]},
+            {"labelsAdded": [{"message": {"id": "c"}, "labelIds": ["TRASH"]}, {"message": {"id": "b"}, "labelIds": ["STARRED"]}]},
+            {"messagesAdded": [{"message": {"id": "c"}}]}
+        ]"#;
+        let records: Vec<HistoryRecord> = serde_json::from_str(json).unwrap();
+        assert_eq!(history_changes(records), (vec!["b".to_string(), "c".to_string()], vec!["a".to_string()]));
+    }
+}
@@ -0,0 +1,473 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::rate_limit::RateLimiter;
+use crate::search_orchestrator::RawDocument;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source type of messages synced from Slack; also the connector's name for sign-in,
+/// rate limits and sync cursors.
+pub const SLACK_SOURCE: &str = "slack";
+
+const SLACK_API_URL: &str = "https://slack.com/api";
+const SLACK_TIMEOUT: Duration = Duration::from_secs(30);
+/// The first sync of a channel reaches back a year; older messages are left to Slack's search.
+pub const FULL_SYNC_SECS: u64 = 365 * 24 * 3600;
+/// Messages or conversations listed per page.
+const PAGE_SIZE: &str = "200";
+/// Longest title taken from a message's first line.
+const MAX_TITLE_CHARS: usize = 80;
+/// Message subtypes that are channel bookkeeping rather than something someone wrote.
+const SKIPPED_SUBTYPES: &[&str] = &[
+    "channel_join", "channel_leave", "channel_topic", "channel_purpose", "channel_name",
+    "channel_archive", "channel_unarchive", "group_join", "group_leave",
+];
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// What kind of conversation a channel is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelKind {
+    Public,
+    Private,
+    DirectMessage,
+    GroupMessage,
+}
+
+/// A channel or DM the user can pick for indexing.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlackChannel {
+    pub id: String,
+    /// `#name` for channels, the other person's name for DMs.
+    pub name: String,
+    pub kind: ChannelKind,
+}
+
+/// A page of a channel's messages, newest first, with the cursor of the next one.
+#[derive(Debug, Default, PartialEq)]
+pub struct MessagePage {
+    pub messages: Vec<SlackMessage>,
+    pub next_page: Option<String>,
+}
+
+/// One message with the parts that get indexed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlackMessage {
+    /// Slack's id for the message: its timestamp in the channel, e.g. `1700000000.000100`.
+    pub ts: String,
+    pub user: Option<String>,
+    pub text: String,
+    /// Set on replies shown in the channel, pointing at the thread's first message.
+    pub thread_ts: Option<String>,
+    /// Replies in the thread this message starts; 0 if it starts none.
+    pub reply_count: u32,
+}
+
+/// Talks to the Slack Web API with one user token, within the connector's rate limit.
+pub struct SlackClient<'a> {
+    client: reqwest::Client,
+    token: String,
+    limiter: &'a RateLimiter,
+}
+
+// ===================================================================
+//  API RESPONSES
+// ===================================================================
+
+/// Slack answers errors with HTTP 200 and `ok: false`.
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    ok: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ResponseMetadata {
+    next_cursor: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthTest {
+    url: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Conversation {
+    id: String,
+    name: Option<String>,
+    is_im: bool,
+    is_mpim: bool,
+    is_private: bool,
+    /// The other person in a DM.
+    user: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConversationList {
+    #[serde(default)]
+    channels: Vec<Conversation>,
+    #[serde(default)]
+    response_metadata: ResponseMetadata,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Profile {
+    display_name: String,
+    real_name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Member {
+    id: String,
+    name: String,
+    profile: Profile,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserList {
+    #[serde(default)]
+    members: Vec<Member>,
+    #[serde(default)]
+    response_metadata: ResponseMetadata,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Message {
+    ts: String,
+    subtype: Option<String>,
+    user: Option<String>,
+    text: String,
+    thread_ts: Option<String>,
+    reply_count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct History {
+    #[serde(default)]
+    messages: Vec<Message>,
+    #[serde(default)]
+    response_metadata: ResponseMetadata,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the cursor of the next page; Slack sends an empty one on the last page.
+fn next_page(metadata: ResponseMetadata) -> Option<String> {
+    Some(metadata.next_cursor).filter(|cursor| !cursor.is_empty())
+}
+
+/// Turns a page of `conversations.history` or `conversations.replies` into the messages
+/// worth indexing, leaving out bookkeeping messages such as joins and empty ones.
+fn message_page(history: History) -> MessagePage {
+    let messages = history.messages.into_iter()
+        .filter(|message| !message.subtype.as_deref().is_some_and(|subtype| SKIPPED_SUBTYPES.contains(&subtype)))
+        .filter(|message| !message.text.trim().is_empty())
+        .map(SlackMessage::from)
+        .collect();
+    MessagePage { messages, next_page: next_page(history.response_metadata) }
+}
+
+/// Returns the name a workspace member goes by: their display name, else their full name.
+fn member_name(member: Member) -> String {
+    [member.profile.display_name, member.profile.real_name]
+        .into_iter()
+        .find(|name| !name.trim().is_empty())
+        .unwrap_or(member.name)
+}
+
+/// Converts Slack's message markup to plain text: `<@U1>` mentions become `@name`,
+/// `<#C1|general>` becomes `#general`, links keep their label or URL, and the three
+/// escaped characters are unescaped.
+fn plain_text(text: &str, users: &HashMap<String, String>) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        plain.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let (target, label) = match rest[start + 1..start + end].split_once('|') {
+            Some((target, label)) => (target, Some(label)),
+            None => (&rest[start + 1..start + end], None),
+        };
+        if let Some(user) = target.strip_prefix('@') {
+            plain.push('@');
+            plain.push_str(label.or(users.get(user).map(String::as_str)).unwrap_or(user));
+        } else if let Some(channel) = target.strip_prefix('#') {
+            plain.push('#');
+            plain.push_str(label.unwrap_or(channel));
+        } else if let Some(special) = target.strip_prefix('!') {
+            // `<!here>`, `<!channel>` and dates, which carry their fallback text as the label
+            plain.push_str(label.unwrap_or(&format!("@{}", special)));
+        } else {
+            plain.push_str(label.unwrap_or(target));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    plain.push_str(rest);
+    plain.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+/// Converts a message timestamp, seconds since the Unix epoch with a sequence number
+/// after the dot, to a system time.
+fn ts_time(ts: &str) -> Option<SystemTime> {
+    let secs: u64 = ts.split('.').next()?.parse().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Names a conversation for the channel picker: `#name` for channels, the other person
+/// for DMs, and Slack's own `mpdm-…` name for group DMs.
+fn channel_from(conversation: Conversation, users: &HashMap<String, String>) -> SlackChannel {
+    let kind = if conversation.is_im {
+        ChannelKind::DirectMessage
+    } else if conversation.is_mpim {
+        ChannelKind::GroupMessage
+    } else if conversation.is_private {
+        ChannelKind::Private
+    } else {
+        ChannelKind::Public
+    };
+    let name = match kind {
+        ChannelKind::DirectMessage => conversation.user.as_ref()
+            .map(|user| users.get(user).cloned().unwrap_or_else(|| user.clone()))
+            .unwrap_or_else(|| conversation.id.clone()),
+        ChannelKind::GroupMessage => conversation.name.unwrap_or_else(|| conversation.id.clone()),
+        ChannelKind::Public | ChannelKind::Private => {
+            format!("#{}", conversation.name.unwrap_or_else(|| conversation.id.clone()))
+        }
+    };
+    SlackChannel { id: conversation.id, name, kind }
+}
+
+impl From<Message> for SlackMessage {
+    fn from(message: Message) -> Self {
+        Self {
+            ts: message.ts,
+            user: message.user,
+            text: message.text,
+            thread_ts: message.thread_ts,
+            reply_count: message.reply_count,
+        }
+    }
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl SlackMessage {
+    /// Builds the message's document, keyed by its permalink so opening the result opens
+    /// the message in Slack. `workspace_url` is the workspace's web address, and `users`
+    /// maps member ids to names for the author and mentions.
+    pub fn into_raw_document(self, workspace_url: &str, channel: &SlackChannel, users: &HashMap<String, String>) -> RawDocument {
+        let body = plain_text(&self.text, users);
+        let first_line = body.lines().find(|line| !line.trim().is_empty()).unwrap_or_default().trim();
+        let title = match first_line.char_indices().nth(MAX_TITLE_CHARS) {
+            Some((cut, _)) => format!("{}…", &first_line[..cut]),
+            None => first_line.to_string(),
+        };
+
+        let mut metadata = BTreeMap::new();
+        metadata.insert("channel".to_string(), channel.name.clone());
+        metadata.insert("channel_id".to_string(), channel.id.clone());
+        if let Some(thread_ts) = &self.thread_ts {
+            metadata.insert("thread".to_string(), thread_ts.clone());
+        }
+        RawDocument {
+            path: permalink(workspace_url, &channel.id, &self.ts, self.thread_ts.as_deref()),
+            title,
+            body,
+            summary: None,
+            source_type: SLACK_SOURCE.to_string(),
+            author: self.user.map(|user| users.get(&user).cloned().unwrap_or(user)),
+            modified_date: ts_time(&self.ts).unwrap_or_else(SystemTime::now),
+            metadata,
+        }
+    }
+}
+
+impl<'a> SlackClient<'a> {
+    pub fn new(token: &str, limiter: &'a RateLimiter) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(SLACK_TIMEOUT).build()?;
+        Ok(Self { client, token: token.to_string(), limiter })
+    }
+
+    /// Calls a Web API method and decodes its response, turning `ok: false` into an error
+    /// carrying Slack's error code.
+    async fn call<T: DeserializeOwned>(&self, method: &str, query: &[(&str, &str)]) -> Result<T> {
+        let url = format!("{}/{}", SLACK_API_URL, method);
+        let build_request = || self.client.get(&url).bearer_auth(&self.token).query(query);
+        let body: serde_json::Value = self.limiter.send(build_request).await?.error_for_status()?.json().await?;
+        let envelope = Envelope::deserialize(&body)?;
+        if !envelope.ok {
+            let error = envelope.error.unwrap_or_else(|| "unknown_error".to_string());
+            return Err(anyhow::anyhow!("Slack {} failed: {}", method, error));
+        }
+        Ok(serde_json::from_value(body)?)
+    }
+
+    /// Returns the workspace's web address, e.g. `https://acme.slack.com/`, which message
+    /// permalinks start with.
+    pub async fn workspace_url(&self) -> Result<String> {
+        let auth: AuthTest = self.call("auth.test", &[]).await?;
+        Ok(auth.url)
+    }
+
+    /// Returns the names workspace members go by, keyed by member id.
+    pub async fn users(&self) -> Result<HashMap<String, String>> {
+        let mut users = HashMap::new();
+        let mut page: Option<String> = None;
+        loop {
+            let mut query = vec![("limit", PAGE_SIZE)];
+            query.extend(page.as_deref().map(|page| ("cursor", page)));
+            let list: UserList = self.call("users.list", &query).await?;
+            users.extend(list.members.into_iter().map(|member| (member.id.clone(), member_name(member))));
+            match next_page(list.response_metadata) {
+                Some(next) => page = Some(next),
+                None => return Ok(users),
+            }
+        }
+    }
+
+    /// Lists the channels and DMs the user is in that aren't archived, naming DMs after
+    /// the other person with `users`.
+    pub async fn channels(&self, users: &HashMap<String, String>) -> Result<Vec<SlackChannel>> {
+        let mut channels = Vec::new();
+        let mut page: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("types", "public_channel,private_channel,im,mpim"),
+                ("exclude_archived", "true"),
+                ("limit", PAGE_SIZE),
+            ];
+            query.extend(page.as_deref().map(|page| ("cursor", page)));
+            let list: ConversationList = self.call("users.conversations", &query).await?;
+            channels.extend(list.channels.into_iter().map(|conversation| channel_from(conversation, users)));
+            match next_page(list.response_metadata) {
+                Some(next) => page = Some(next),
+                None => return Ok(channels),
+            }
+        }
+    }
+
+    /// Reads one page of a channel's messages posted after `oldest`, a message timestamp.
+    /// Only a thread's first message is listed; its replies come from `replies`.
+    /// Bookkeeping messages such as joins and empty ones are left out.
+    pub async fn history(&self, channel: &str, oldest: &str, page: Option<&str>) -> Result<MessagePage> {
+        let mut query = vec![("channel", channel), ("oldest", oldest), ("limit", PAGE_SIZE)];
+        query.extend(page.map(|page| ("cursor", page)));
+        let history: History = self.call("conversations.history", &query).await?;
+        Ok(message_page(history))
+    }
+
+    /// Reads one page of the replies in the thread started by message `thread_ts`. The
+    /// thread's first message, which Slack lists too, is left out.
+    pub async fn replies(&self, channel: &str, thread_ts: &str, page: Option<&str>) -> Result<MessagePage> {
+        let mut query = vec![("channel", channel), ("ts", thread_ts), ("limit", PAGE_SIZE)];
+        query.extend(page.map(|page| ("cursor", page)));
+        let history: History = self.call("conversations.replies", &query).await?;
+        let mut replies = message_page(history);
+        replies.messages.retain(|message| message.ts != thread_ts);
+        Ok(replies)
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns a message's permalink, the address Slack's "Copy link" gives: the timestamp
+/// without its dot, plus the thread for replies.
+pub fn permalink(workspace_url: &str, channel: &str, ts: &str, thread_ts: Option<&str>) -> String {
+    let mut link = format!("{}/archives/{}/p{}", workspace_url.trim_end_matches('/'), channel, ts.replace('.', ""));
+    if let Some(thread_ts) = thread_ts.filter(|thread_ts| *thread_ts != ts) {
+        link.push_str(&format!("?thread_ts={}&cid={}", thread_ts, channel));
+    }
+    link
+}
+
+/// Returns the channel id of a message permalink.
+pub fn permalink_channel(path: &str) -> Option<&str> {
+    path.split_once("/archives/")?.1.split('/').next()
+}
+
+/// Returns true if message timestamp `ts` is later than `other`. Timestamps are compared
+/// as numbers, since their digits needn't line up.
+pub fn ts_is_after(ts: &str, other: &str) -> bool {
+    let parse = |ts: &str| {
+        let (secs, sequence) = ts.split_once('.').unwrap_or((ts, "0"));
+        (secs.parse::<u64>().unwrap_or(0), sequence.parse::<u64>().unwrap_or(0))
+    };
+    parse(ts) > parse(other)
+}
+
+/// Returns where a channel's next sync starts: the newest message synced that is older
+/// than every message that failed, so failed ones are fetched again. `synced` pairs each
+/// message's timestamp with whether it was indexed; `oldest` is where this sync started.
+pub fn resume_point(oldest: &str, synced: &[(String, bool)]) -> String {
+    let oldest_failure = synced.iter()
+        .filter(|(_, indexed)| !indexed)
+        .map(|(ts, _)| ts.as_str())
+        .reduce(|a, b| if ts_is_after(a, b) { b } else { a });
+    synced.iter()
+        .filter(|(ts, indexed)| *indexed && oldest_failure.is_none_or(|failure| ts_is_after(failure, ts)))
+        .map(|(ts, _)| ts.as_str())
+        .fold(oldest, |latest, ts| if ts_is_after(ts, latest) { ts } else { latest })
+        .to_string()
+}
+
+/// Returns the Slack timestamp of `time`, for asking for messages posted after it.
+pub fn ts_at(time: u64) -> String {
+    format!("{}.000000", time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_document() {
+        let users = HashMap::from([("U1".to_string(), "Ana".to_string()), ("U2".to_string(), "Ben".to_string())]);
+        let json = r#"{
+            "type": "message", "user": "U1", "ts": "1700000000.000100", "thread_ts": "1699999000.000200",
+            "text": "&lt;ping&gt; <@U2> see <https://example.com/spec|the spec> in <#C9|design> &amp; <!here>"
+        }"#;
+        let message: SlackMessage = serde_json::from_str::<Message>(json).unwrap().into();
+        let channel = SlackChannel { id: "C1".to_string(), name: "#general".to_string(), kind: ChannelKind::Public };
+
+        let doc = message.into_raw_document("https://acme.slack.com/", &channel, &users);
+        assert_eq!(doc.path, "https://acme.slack.com/archives/C1/p1700000000000100?thread_ts=1699999000.000200&cid=C1");
+        assert_eq!(doc.body, "<ping> @Ben see the spec in #design & @here");
+        assert_eq!(doc.title, doc.body);
+        assert_eq!(doc.author.as_deref(), Some("Ana"));
+        assert_eq!(doc.modified_date, UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        assert_eq!(permalink_channel(&doc.path), Some("C1"));
+
+        assert!(ts_is_after("1700000000.000100", &ts_at(1_700_000_000)));
+        assert!(!ts_is_after("999999999.000001", "1700000000.000000"));
+
+        // A failed message holds the channel's cursor back to just before it
+        let synced = |entries: &[(&str, bool)]| entries.iter().map(|(ts, ok)| (ts.to_string(), *ok)).collect::<Vec<_>>();
+        assert_eq!(resume_point("1.0", &synced(&[("4.0", true), ("3.0", false), ("2.0", true)])), "2.0");
+        assert_eq!(resume_point("1.0", &synced(&[("4.0", true), ("2.0", true)])), "4.0");
+        assert_eq!(resume_point("1.0", &synced(&[("2.0", false)])), "1.0");
+
+        // A thread's first message links to itself, not to a thread
+        assert_eq!(permalink("https://acme.slack.com", "D4", "1.2", Some("1.2")), "https://acme.slack.com/archives/D4/p12");
+    }
+}
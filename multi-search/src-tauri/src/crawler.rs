@@ -0,0 +1,98 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::backlog::{BACKLOG_PRIORITY_FILES, BACKLOG_TAIL_DELAY, LARGE_BACKLOG_FILES};
+use crate::commands::AppState;
+use crate::file_ingest::{self, FileIndexedEvent};
+use crate::notifications::{notify, Notification, INDEXING_NOTIFICATION_MIN_FILES};
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager};
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Emitted as `crawl-progress` once the files to index are known and after each one, so
+/// the UI can show a progress bar for the whole crawl.
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlProgress {
+    pub indexed: usize,
+    pub failed: usize,
+    pub total: usize,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+fn emit_progress(app: &AppHandle, progress: CrawlProgress) {
+    if let Err(e) = app.emit("crawl-progress", progress) {
+        eprintln!("Warning: Could not emit crawl-progress event: {}", e);
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Walks files and folders recursively and indexes every supported file in the
+/// background. Emits `crawl-progress` for the crawl as a whole, a `file-indexed` event per
+/// file so the UI can show a toast as soon as it becomes searchable, and a native
+/// notification once a large folder is done. Files go in priority order, recently
+/// modified and frequently opened first, skipping excluded folders. Folders the OS
+/// refuses to read are remembered and retried once permission is granted.
+pub fn crawl(app: &AppHandle, roots: Vec<PathBuf>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // 1. Walk the roots for supported files
+        let excluded_folders = app.state::<AppState>().settings.lock().unwrap().excluded_folders.clone();
+        let mut files = Vec::new();
+        for root in &roots {
+            let scan = file_ingest::collect_supported_files(root);
+            files.extend(scan.files.into_iter()
+                .filter(|file| !excluded_folders.iter().any(|folder| file.starts_with(folder))));
+            if !scan.permission_denied.is_empty() {
+                app.state::<AppState>().record_blocked_folders(scan.permission_denied);
+                let _ = app.emit("permissions-needed", ());
+            }
+        }
+
+        // 2. Index what the user most likely needs first; in a large backlog, slow down once
+        //    that is in so the long tail fills in without hogging the machine
+        let orchestrator = app.state::<AppState>().orchestrator();
+        if let Ok(orchestrator) = &orchestrator {
+            files = orchestrator.prioritize_backlog(files);
+        }
+        let total = files.len();
+        let mut progress = CrawlProgress { indexed: 0, failed: 0, total };
+        emit_progress(&app, progress.clone());
+        for (position, file) in files.into_iter().enumerate() {
+            if total >= LARGE_BACKLOG_FILES && position >= BACKLOG_PRIORITY_FILES {
+                tokio::time::sleep(BACKLOG_TAIL_DELAY).await;
+            }
+            let result = match &orchestrator {
+                Ok(orchestrator) => orchestrator.index_file(&file).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.clone()),
+            };
+            if result.is_ok() {
+                progress.indexed += 1;
+            } else {
+                progress.failed += 1;
+            }
+            let event = FileIndexedEvent {
+                path: file.display().to_string(),
+                error: result.err(),
+            };
+            if let Err(e) = app.emit("file-indexed", event) {
+                eprintln!("Warning: Could not emit file-indexed event: {}", e);
+            }
+            emit_progress(&app, progress.clone());
+        }
+
+        // 3. Large folders take long enough that the user has likely moved on
+        if total >= INDEXING_NOTIFICATION_MIN_FILES {
+            notify(&app, Notification::indexing_finished(progress.indexed, progress.failed));
+        }
+    });
+}
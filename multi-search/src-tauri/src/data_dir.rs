@@ -0,0 +1,203 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Command-line flag that points the app at another data directory.
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+/// Environment variable that does the same, for launchers that can't pass flags.
+pub const DATA_DIR_ENV: &str = "MULTI_SEARCH_DATA_DIR";
+/// File in the default data directory holding the data directory chosen in the app.
+const DATA_LOCATION_FILE: &str = "data_location";
+
+/// The data directory this run uses, fixed at startup.
+static DATA_ROOT: OnceLock<DataRoot> = OnceLock::new();
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Where the data directory setting came from, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataRootSource {
+    Flag,
+    Environment,
+    Configured,
+    Default,
+}
+
+/// The directory holding the app's settings, indexes and state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DataRoot {
+    pub path: PathBuf,
+    pub source: DataRootSource,
+}
+
+impl DataRoot {
+    /// True for a directory given on the command line or in the environment, as test and
+    /// portable runs do. Those run beside the normal app instead of handing over to it.
+    pub fn is_explicit(&self) -> bool {
+        matches!(self.source, DataRootSource::Flag | DataRootSource::Environment)
+    }
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the platform's per-user data directory for the app, e.g.
+/// `~/Library/Application Support/multi-search` on macOS.
+fn default_data_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not find application data directory"))?;
+    Ok(data_dir.join("multi-search"))
+}
+
+/// Returns the path of the file remembering the data directory chosen in the app. It stays
+/// in the default directory, since it is read before the chosen one is known.
+fn data_location_path() -> Result<PathBuf> {
+    Ok(default_data_dir()?.join(DATA_LOCATION_FILE))
+}
+
+/// Reads the data directory chosen in the app, if one was.
+fn configured_data_dir() -> Option<PathBuf> {
+    let contents = std::fs::read_to_string(data_location_path().ok()?).ok()?;
+    let path = contents.trim();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// Picks the data directory by precedence: the flag, the environment variable, the
+/// directory chosen in the app, then the platform default.
+fn resolve(flag: Option<PathBuf>, environment: Option<PathBuf>, configured: Option<PathBuf>, default: PathBuf) -> DataRoot {
+    let candidates = [
+        (flag, DataRootSource::Flag),
+        (environment, DataRootSource::Environment),
+        (configured, DataRootSource::Configured),
+    ];
+    candidates.into_iter()
+        .find_map(|(path, source)| path.map(|path| DataRoot { path, source }))
+        .unwrap_or(DataRoot { path: default, source: DataRootSource::Default })
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Finds the data directory passed on the command line, as `--data-dir <path>` or
+/// `--data-dir=<path>`.
+pub fn data_dir_arg(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix(DATA_DIR_FLAG).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/// Checks that a directory can hold the app's data, creating it if needed. Relative paths
+/// are taken from the working directory. Returns the absolute path.
+pub fn validate_data_dir(path: &Path) -> Result<PathBuf> {
+    let path = std::path::absolute(path)
+        .map_err(|e| anyhow::anyhow!("Invalid data directory {}: {}", path.display(), e))?;
+    if path.exists() && !path.is_dir() {
+        return Err(anyhow::anyhow!("Data directory {} is a file", path.display()));
+    }
+    std::fs::create_dir_all(&path)
+        .map_err(|e| anyhow::anyhow!("Could not create data directory {}: {}", path.display(), e))?;
+
+    // A read-only volume or missing permission would otherwise only show on the first write
+    let probe = path.join(".write_test");
+    std::fs::write(&probe, b"")
+        .map_err(|e| anyhow::anyhow!("Data directory {} is not writable: {}", path.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(path)
+}
+
+/// Picks and validates the data directory for this run; call once, before anything reads
+/// settings or opens an index. A directory given by flag or environment variable must be
+/// usable, so a test run never falls back to real data. One chosen in the app that has
+/// gone missing, such as an unplugged drive, falls back to the default with a warning.
+pub fn init(args: impl IntoIterator<Item = String>) -> Result<&'static DataRoot> {
+    let environment = std::env::var_os(DATA_DIR_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+    let mut root = resolve(data_dir_arg(args), environment, configured_data_dir(), default_data_dir()?);
+    root.path = match validate_data_dir(&root.path) {
+        Ok(path) => path,
+        Err(e) if root.source == DataRootSource::Configured => {
+            eprintln!("Warning: Using the default data directory instead: {}", e);
+            root.source = DataRootSource::Default;
+            validate_data_dir(&default_data_dir()?)?
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(DATA_ROOT.get_or_init(|| root))
+}
+
+/// Returns the data directory this run uses, or the platform default before `init`.
+pub fn data_root() -> Result<PathBuf> {
+    match DATA_ROOT.get() {
+        Some(root) => Ok(root.path.clone()),
+        None => default_data_dir(),
+    }
+}
+
+/// Returns the data directory this run uses and where that setting came from.
+pub fn current() -> Result<DataRoot> {
+    match DATA_ROOT.get() {
+        Some(root) => Ok(root.clone()),
+        None => Ok(DataRoot { path: default_data_dir()?, source: DataRootSource::Default }),
+    }
+}
+
+/// Remembers a data directory for the next launch, after checking it is usable. `None`
+/// goes back to the default. Existing data is not moved.
+pub fn set_configured_data_dir(path: Option<&Path>) -> Result<()> {
+    let location = data_location_path()?;
+    match path {
+        Some(path) => {
+            let path = validate_data_dir(path)?;
+            if let Some(parent) = location.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&location, path.to_string_lossy().as_bytes())?;
+        }
+        None if location.exists() => std::fs::remove_file(&location)?,
+        None => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_dir_resolution() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(data_dir_arg(args(&["app", "--data-dir", "/mnt/drive"])), Some(PathBuf::from("/mnt/drive")));
+        assert_eq!(data_dir_arg(args(&["app", "--data-dir=portable"])), Some(PathBuf::from("portable")));
+        assert_eq!(data_dir_arg(args(&["app", "--data-directory=x", "--data-dir"])), None);
+
+        let default = PathBuf::from("/default");
+        let root = resolve(Some("/flag".into()), Some("/env".into()), Some("/chosen".into()), default.clone());
+        assert_eq!(root.source, DataRootSource::Flag);
+        let root = resolve(None, None, Some("/chosen".into()), default.clone());
+        assert_eq!(root, DataRoot { path: PathBuf::from("/chosen"), source: DataRootSource::Configured });
+        assert_eq!(resolve(None, None, None, default.clone()).source, DataRootSource::Default);
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        assert_eq!(validate_data_dir(&dir.join("nested")).unwrap(), dir.join("nested"));
+        std::fs::write(dir.join("file"), b"").unwrap();
+        assert!(validate_data_dir(&dir.join("file")).is_err());
+    }
+}
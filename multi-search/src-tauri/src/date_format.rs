@@ -0,0 +1,140 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serializer;
+use std::time::SystemTime;
+
+// ===================================================================
+//  PRIVATE STRUCT
+// ===================================================================
+
+/// Phrases for relative times in one language. `{}` is replaced by the count.
+struct RelativePhrases {
+    just_now: &'static str,
+    minutes: (&'static str, &'static str),
+    hours: (&'static str, &'static str),
+    days: (&'static str, &'static str),
+    months: (&'static str, &'static str),
+    years: (&'static str, &'static str),
+}
+
+const ENGLISH: RelativePhrases = RelativePhrases {
+    just_now: "just now",
+    minutes: ("1 minute ago", "{} minutes ago"),
+    hours: ("1 hour ago", "{} hours ago"),
+    days: ("yesterday", "{} days ago"),
+    months: ("1 month ago", "{} months ago"),
+    years: ("1 year ago", "{} years ago"),
+};
+
+const SPANISH: RelativePhrases = RelativePhrases {
+    just_now: "ahora mismo",
+    minutes: ("hace 1 minuto", "hace {} minutos"),
+    hours: ("hace 1 hora", "hace {} horas"),
+    days: ("ayer", "hace {} días"),
+    months: ("hace 1 mes", "hace {} meses"),
+    years: ("hace 1 año", "hace {} años"),
+};
+
+const FRENCH: RelativePhrases = RelativePhrases {
+    just_now: "à l'instant",
+    minutes: ("il y a 1 minute", "il y a {} minutes"),
+    hours: ("il y a 1 heure", "il y a {} heures"),
+    days: ("hier", "il y a {} jours"),
+    months: ("il y a 1 mois", "il y a {} mois"),
+    years: ("il y a 1 an", "il y a {} ans"),
+};
+
+const GERMAN: RelativePhrases = RelativePhrases {
+    just_now: "gerade eben",
+    minutes: ("vor 1 Minute", "vor {} Minuten"),
+    hours: ("vor 1 Stunde", "vor {} Stunden"),
+    days: ("gestern", "vor {} Tagen"),
+    months: ("vor 1 Monat", "vor {} Monaten"),
+    years: ("vor 1 Jahr", "vor {} Jahren"),
+};
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Picks the phrase table for a locale such as "fr" or "de-AT", defaulting to English.
+fn phrases_for(locale: &str) -> &'static RelativePhrases {
+    let language = locale.split(['-', '_']).next().unwrap_or("").to_lowercase();
+    match language.as_str() {
+        "es" => &SPANISH,
+        "fr" => &FRENCH,
+        "de" => &GERMAN,
+        _ => &ENGLISH,
+    }
+}
+
+/// Chooses the singular or plural phrase and fills in the count.
+fn pluralize(forms: (&str, &str), count: u64) -> String {
+    if count == 1 {
+        forms.0.to_string()
+    } else {
+        forms.1.replace("{}", &count.to_string())
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Formats a timestamp as an ISO-8601 / RFC 3339 string in UTC, e.g. "2024-03-05T14:30:00Z".
+pub fn to_iso8601(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Serde helper so `SystemTime` fields reach the frontend as ISO-8601 strings.
+pub fn serialize_iso8601<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&to_iso8601(*time))
+}
+
+/// Describes how long ago `time` was relative to `now`, e.g. "3 days ago", in the given locale.
+pub fn humanize_relative(time: SystemTime, now: SystemTime, locale: &str) -> String {
+    let phrases = phrases_for(locale);
+    // Timestamps in the future (clock skew) are treated as "just now"
+    let seconds = now.duration_since(time).map(|d| d.as_secs()).unwrap_or(0);
+
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const MONTH: u64 = 30 * DAY;
+    const YEAR: u64 = 365 * DAY;
+
+    match seconds {
+        s if s < MINUTE => phrases.just_now.to_string(),
+        s if s < HOUR => pluralize(phrases.minutes, s / MINUTE),
+        s if s < DAY => pluralize(phrases.hours, s / HOUR),
+        s if s < MONTH => pluralize(phrases.days, s / DAY),
+        s if s < YEAR => pluralize(phrases.months, s / MONTH),
+        s => pluralize(phrases.years, s / YEAR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_to_iso8601() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_649_000);
+        assert_eq!(to_iso8601(time), "2024-03-05T14:30:00Z");
+    }
+
+    #[test]
+    fn test_humanize_relative() {
+        let now = UNIX_EPOCH + Duration::from_secs(100 * 24 * 3600);
+        let ago = |secs: u64| now - Duration::from_secs(secs);
+
+        assert_eq!(humanize_relative(ago(10), now, "en"), "just now");
+        assert_eq!(humanize_relative(ago(3 * 24 * 3600), now, "en"), "3 days ago");
+        assert_eq!(humanize_relative(ago(24 * 3600), now, "en-US"), "yesterday");
+        assert_eq!(humanize_relative(ago(2 * 3600), now, "de"), "vor 2 Stunden");
+        assert_eq!(humanize_relative(ago(60 * 24 * 3600), now, "xx"), "2 months ago");
+    }
+}
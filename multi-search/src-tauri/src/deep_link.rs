@@ -0,0 +1,70 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use anyhow::Result;
+use reqwest::Url;
+
+/// URL scheme registered with the OS, e.g. `multisearch://search?q=quarterly+report`.
+pub const DEEP_LINK_SCHEME: &str = "multisearch";
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// What an external app asked the launcher to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    /// `multisearch://search?q=<query>`: open the launcher with the query filled in and run.
+    Search { query: String },
+    /// `multisearch://open?path=<path>`: open the launcher on the preview of one result.
+    Open { path: String },
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Parses a `multisearch://` URL. The action is the host, so both `multisearch://search?q=`
+/// and `multisearch://search/?q=` work; parameters are percent-decoded.
+pub fn parse_deep_link(link: &str) -> Result<DeepLink> {
+    let url = Url::parse(link).map_err(|e| anyhow::anyhow!("Malformed link {}: {}", link, e))?;
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(anyhow::anyhow!("Not a {}:// link: {}", DEEP_LINK_SCHEME, link));
+    }
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    match url.host_str().unwrap_or_default() {
+        "search" => param("q")
+            .map(|query| DeepLink::Search { query })
+            .ok_or_else(|| anyhow::anyhow!("Search link has no query: {}", link)),
+        "open" => param("path")
+            .map(|path| DeepLink::Open { path })
+            .ok_or_else(|| anyhow::anyhow!("Open link has no path: {}", link)),
+        action => Err(anyhow::anyhow!("Unknown link action '{}': {}", action, link)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deep_link() {
+        assert_eq!(
+            parse_deep_link("multisearch://search?q=quarterly+report%202024").unwrap(),
+            DeepLink::Search { query: "quarterly report 2024".to_string() }
+        );
+        assert_eq!(
+            parse_deep_link("multisearch://open/?path=%2FUsers%2Fme%2Fnotes.md").unwrap(),
+            DeepLink::Open { path: "/Users/me/notes.md".to_string() }
+        );
+        assert!(parse_deep_link("multisearch://search?q=%20").is_err());
+        assert!(parse_deep_link("multisearch://delete?path=/tmp/a").is_err());
+        assert!(parse_deep_link("https://search?q=report").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::metrics::MetricsSnapshot;
+use crate::settings::{app_data_dir, Settings};
+use anyhow::Result;
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Settings keys containing any of these words have their values replaced before bundling.
+const SENSITIVE_KEY_PARTS: &[&str] = &["token", "secret", "password", "api_key", "credential"];
+
+// ===================================================================
+//  PUBLIC STRUCT
+// ===================================================================
+
+/// Basic information about the machine and build, included in every bundle.
+#[derive(Debug, serde::Serialize)]
+struct EnvironmentInfo {
+    app_version: &'static str,
+    os: &'static str,
+    os_family: &'static str,
+    arch: &'static str,
+    cpu_count: usize,
+    debug_build: bool,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Recursively replaces the values of sensitive keys with a placeholder.
+fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, entry) in map.iter_mut() {
+                let key_lower = key.to_lowercase();
+                if SENSITIVE_KEY_PARTS.iter().any(|part| key_lower.contains(part)) {
+                    *entry = Value::String("[REDACTED]".to_string());
+                } else {
+                    redact_secrets(entry);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+/// Lists every file under a directory with its size, one per line, relative to the root.
+fn list_files(root: &Path, dir: &Path, lines: &mut Vec<String>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => list_files(root, &path, lines),
+            Ok(metadata) => {
+                let relative = path.strip_prefix(root).unwrap_or(&path);
+                lines.push(format!("{}\t{}", metadata.len(), relative.display()));
+            }
+            Err(_) => continue,
+        }
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Builds a zip bundle with logs, index stats, redacted settings, and environment info
+/// for attaching to bug reports. Returns the path of the created archive.
+pub fn generate_diagnostics(stats: Option<&MetricsSnapshot>, settings: &Settings) -> Result<PathBuf> {
+    let data_dir = app_data_dir()?;
+    let output_dir = data_dir.join("diagnostics");
+    std::fs::create_dir_all(&output_dir)?;
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let bundle_path = output_dir.join(format!("diagnostics-{}.zip", timestamp));
+    let mut zip = ZipWriter::new(std::fs::File::create(&bundle_path)?);
+    let options = SimpleFileOptions::default();
+
+    // 1. Environment information.
+    let environment = EnvironmentInfo {
+        app_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        os_family: std::env::consts::FAMILY,
+        arch: std::env::consts::ARCH,
+        cpu_count: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        debug_build: cfg!(debug_assertions),
+    };
+    zip.start_file("environment.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&environment)?.as_bytes())?;
+
+    // 2. Index stats, if the search engine finished starting up.
+    zip.start_file("stats.json", options)?;
+    match stats {
+        Some(stats) => zip.write_all(serde_json::to_string_pretty(stats)?.as_bytes())?,
+        None => zip.write_all(b"{\"error\": \"Search engine was not initialized\"}")?,
+    }
+
+    // 3. Settings with secrets redacted.
+    let mut settings_value = serde_json::to_value(settings)?;
+    redact_secrets(&mut settings_value);
+    zip.start_file("settings.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&settings_value)?.as_bytes())?;
+
+    // 4. A listing of index files and their sizes (names only, never contents).
+    let mut index_files = Vec::new();
+    let locations = &settings.index_locations;
+    let mut index_dirs = vec![locations.keyword_index_dir()?];
+    index_dirs.extend(locations.embedding_dirs()?);
+    for index_dir in &index_dirs {
+        list_files(&data_dir, index_dir, &mut index_files);
+    }
+    zip.start_file("index_files.txt", options)?;
+    zip.write_all(index_files.join("\n").as_bytes())?;
+
+    // 5. Log files, if any have been written.
+    if let Ok(entries) = std::fs::read_dir(data_dir.join("logs")) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if path.is_file() {
+                let name = format!("logs/{}", entry.file_name().to_string_lossy());
+                zip.start_file(name, options)?;
+                zip.write_all(&std::fs::read(&path)?)?;
+            }
+        }
+    }
+
+    zip.finish()?;
+    Ok(bundle_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_secrets_nested() {
+        let mut value = json!({
+            "metrics_export_enabled": true,
+            "connectors": [{ "name": "gmail", "access_token": "abc", "client_secret": "xyz" }]
+        });
+        redact_secrets(&mut value);
+        assert_eq!(value["metrics_export_enabled"], json!(true));
+        assert_eq!(value["connectors"][0]["name"], json!("gmail"));
+        assert_eq!(value["connectors"][0]["access_token"], json!("[REDACTED]"));
+        assert_eq!(value["connectors"][0]["client_secret"], json!("[REDACTED]"));
+    }
+}
@@ -0,0 +1,295 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::index_events::{IndexEvent, IndexEventKind};
+use crate::settings::app_data_dir;
+use crate::state_store::now_secs;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Digests kept for the UI; older ones are dropped.
+const MAX_KEPT_DIGESTS: usize = 14;
+/// Changes remembered between digests; the oldest are dropped beyond this.
+const MAX_PENDING_CHANGES: usize = 10_000;
+/// Minimum seconds between writes while changes stream in, so indexing a large folder
+/// doesn't rewrite the file once per document. A crash loses at most this much.
+const PERSIST_INTERVAL_SECS: u64 = 30;
+/// Documents listed, and summarized, per digest section.
+pub const DIGEST_SECTION_DOCUMENTS: usize = 5;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// How often a digest of new and changed documents is put together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFrequency {
+    Off,
+    #[default]
+    Daily,
+    Weekly,
+}
+
+/// A document added or changed since the last digest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestChange {
+    pub path: String,
+    pub source_type: String,
+    /// True if the document is new rather than changed.
+    pub added: bool,
+    /// Unix timestamp (seconds) of the latest change.
+    pub at: u64,
+}
+
+/// Pending changes from one source, or for files one folder, most recent first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestGroup {
+    pub source_type: String,
+    /// What the section is about: the folder name for files, else the source type.
+    pub name: String,
+    pub changes: Vec<DigestChange>,
+}
+
+/// What changed in one source or folder, e.g. "4 new emails" with a short summary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestSection {
+    pub source_type: String,
+    pub name: String,
+    pub added: usize,
+    pub updated: usize,
+    /// Titles of the most recent documents.
+    pub titles: Vec<String>,
+    /// A sentence or two summarizing those documents.
+    pub summary: String,
+}
+
+/// A summary of what was indexed since the previous digest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    /// Unix timestamps (seconds).
+    pub created_at: u64,
+    pub since: u64,
+    pub sections: Vec<DigestSection>,
+}
+
+/// Everything persisted in the digest file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DigestData {
+    pending: Vec<DigestChange>,
+    /// Newest first.
+    digests: Vec<Digest>,
+    last_digest_at: Option<u64>,
+    #[serde(skip)]
+    last_persisted_at: u64,
+}
+
+/// Collects index changes between digests and keeps the recent digests, as JSON next
+/// to the indexes.
+pub struct DigestLog {
+    path: PathBuf,
+    data: Mutex<DigestData>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Groups a file change by the folder it is in, so "changed docs in project Y" read as one
+/// section. Other sources are grouped as a whole.
+fn group_of(change: &DigestChange) -> (String, String) {
+    if change.source_type != "file" {
+        return (change.source_type.clone(), change.source_type.clone());
+    }
+    let folder = Path::new(&change.path).parent().unwrap_or(Path::new(""));
+    let name = folder.file_name().map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| folder.display().to_string());
+    (folder.display().to_string(), name)
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl DigestFrequency {
+    /// Seconds between digests, or None when digests are off.
+    pub fn interval_secs(&self) -> Option<u64> {
+        match self {
+            DigestFrequency::Off => None,
+            DigestFrequency::Daily => Some(24 * 60 * 60),
+            DigestFrequency::Weekly => Some(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+impl DigestLog {
+    /// Opens the digest file in the app data directory, starting empty if it doesn't exist.
+    pub fn open() -> Result<Self> {
+        Self::open_at(app_data_dir()?.join("digests.json"))
+    }
+
+    fn open_at(path: PathBuf) -> Result<Self> {
+        let data = if path.exists() {
+            let contents = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Failed to parse digest file {}: {}", path.display(), e))?
+        } else {
+            DigestData::default()
+        };
+        Ok(Self {
+            path,
+            data: Mutex::new(data),
+        })
+    }
+
+    /// Writes the digests via a temporary file so a crash never truncates them.
+    fn persist(&self, data: &mut DigestData) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(&*data)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        data.last_persisted_at = now_secs();
+        Ok(())
+    }
+
+    /// Notes an index change for the next digest. A document deleted before the digest
+    /// is forgotten; one added and then changed still counts as new.
+    pub fn record(&self, event: &IndexEvent) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        let existing = data.pending.iter().position(|change| change.path == event.path);
+        match (event.kind, existing) {
+            (IndexEventKind::Deleted, Some(index)) => {
+                data.pending.remove(index);
+            }
+            (IndexEventKind::Deleted, None) => return Ok(()),
+            (kind, existing) => {
+                let added = kind == IndexEventKind::Added
+                    || existing.is_some_and(|index| data.pending[index].added);
+                if let Some(index) = existing {
+                    data.pending.remove(index);
+                }
+                data.pending.push(DigestChange {
+                    path: event.path.clone(),
+                    source_type: event.source_type.clone().unwrap_or_else(|| "unknown".to_string()),
+                    added,
+                    at: now_secs(),
+                });
+                if data.pending.len() > MAX_PENDING_CHANGES {
+                    let excess = data.pending.len() - MAX_PENDING_CHANGES;
+                    data.pending.drain(..excess);
+                }
+            }
+        }
+        if now_secs().saturating_sub(data.last_persisted_at) >= PERSIST_INTERVAL_SECS {
+            self.persist(&mut data)?;
+        }
+        Ok(())
+    }
+
+    /// Returns true when a digest is due: there are changes, and the interval has passed
+    /// since the previous digest or, before the first one, since the oldest change.
+    pub fn is_due(&self, frequency: DigestFrequency, now: u64) -> bool {
+        let Some(interval) = frequency.interval_secs() else { return false };
+        let data = self.data.lock().unwrap();
+        let Some(oldest) = data.pending.first() else { return false };
+        now.saturating_sub(data.last_digest_at.unwrap_or(oldest.at)) >= interval
+    }
+
+    /// Returns the pending changes grouped by source and folder, largest group first, and
+    /// when the period they cover started.
+    pub fn pending_groups(&self) -> (Vec<DigestGroup>, u64) {
+        let data = self.data.lock().unwrap();
+        let since = data.last_digest_at
+            .or_else(|| data.pending.first().map(|change| change.at))
+            .unwrap_or_else(now_secs);
+        (group_changes(&data.pending), since)
+    }
+
+    /// Stores a finished digest and forgets the changes it covered. Changes recorded while
+    /// it was being written are kept for the next one.
+    pub fn complete(&self, digest: Digest) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        data.pending.retain(|change| change.at > digest.created_at);
+        data.last_digest_at = Some(digest.created_at);
+        if !digest.sections.is_empty() {
+            data.digests.insert(0, digest);
+            data.digests.truncate(MAX_KEPT_DIGESTS);
+        }
+        self.persist(&mut data)
+    }
+
+    /// Returns the kept digests, newest first.
+    pub fn digests(&self) -> Vec<Digest> {
+        self.data.lock().unwrap().digests.clone()
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Groups changes by source, and files by folder. Groups with the most changes come
+/// first; changes within a group are most recent first.
+pub fn group_changes(changes: &[DigestChange]) -> Vec<DigestGroup> {
+    let mut groups: BTreeMap<String, DigestGroup> = BTreeMap::new();
+    for change in changes {
+        let (key, name) = group_of(change);
+        groups.entry(key)
+            .or_insert_with(|| DigestGroup { source_type: change.source_type.clone(), name, changes: Vec::new() })
+            .changes
+            .push(change.clone());
+    }
+    let mut groups: Vec<DigestGroup> = groups.into_values().collect();
+    for group in groups.iter_mut() {
+        group.changes.sort_by_key(|change| std::cmp::Reverse(change.at));
+    }
+    // Stable sort keeps groups with equal counts in key order
+    groups.sort_by_key(|group| std::cmp::Reverse(group.changes.len()));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: IndexEventKind, path: &str, source_type: &str) -> IndexEvent {
+        IndexEvent { kind, path: path.to_string(), source_type: Some(source_type.to_string()) }
+    }
+
+    #[test]
+    fn test_digest_log_groups_pending_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let log = DigestLog::open_at(dir.join("digests.json")).unwrap();
+        log.record(&event(IndexEventKind::Added, "/work/projecty/plan.md", "file")).unwrap();
+        log.record(&event(IndexEventKind::Updated, "/work/projecty/plan.md", "file")).unwrap();
+        log.record(&event(IndexEventKind::Updated, "/work/projecty/notes.md", "file")).unwrap();
+        log.record(&event(IndexEventKind::Added, "gmail://msg/1", "gmail")).unwrap();
+        log.record(&event(IndexEventKind::Added, "/tmp/scratch.txt", "file")).unwrap();
+        log.record(&event(IndexEventKind::Deleted, "/tmp/scratch.txt", "file")).unwrap();
+
+        let (groups, since) = log.pending_groups();
+        assert!(since > 0);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "projecty");
+        let plan = groups[0].changes.iter().find(|change| change.path.ends_with("plan.md")).unwrap();
+        assert!(plan.added);
+        assert_eq!(groups[0].changes.len(), 2);
+        assert_eq!(groups[1].name, "gmail");
+
+        let now = now_secs();
+        assert!(!log.is_due(DigestFrequency::Daily, now));
+        assert!(log.is_due(DigestFrequency::Daily, now + 24 * 60 * 60));
+        assert!(!log.is_due(DigestFrequency::Off, now + 24 * 60 * 60));
+
+        log.complete(Digest { created_at: now, since, sections: Vec::new() }).unwrap();
+        assert!(log.pending_groups().0.is_empty());
+        assert!(log.digests().is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How often free space is checked again while indexing is paused.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Space freed beyond the threshold before paused indexing resumes, so indexing doesn't
+/// flap between paused and running while it writes right at the limit.
+const RESUME_HEADROOM_BYTES: u64 = 100 * 1024 * 1024;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Free space on the fullest data volume, sent to the frontend as `disk-space`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DiskSpaceStatus {
+    /// A directory on the fullest volume holding the indexes or app data.
+    pub volume: PathBuf,
+    pub free_bytes: u64,
+    pub min_free_bytes: u64,
+    /// True while indexing waits for space to be freed.
+    pub paused: bool,
+}
+
+/// Holds indexing back while the volumes holding the indexes are nearly full. Running out
+/// of space in the middle of a write can leave the vector store's files half written.
+pub struct DiskGuard {
+    dirs: Vec<PathBuf>,
+    min_free_bytes: AtomicU64,
+    status: watch::Sender<DiskSpaceStatus>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the free space on the volume holding `path`, asking about its nearest existing
+/// ancestor if it hasn't been created yet.
+fn free_bytes(path: &Path) -> Result<u64> {
+    let existing = path.ancestors()
+        .find(|ancestor| ancestor.exists())
+        .ok_or_else(|| anyhow::anyhow!("{} is not on a mounted volume", path.display()))?;
+    Ok(fs2::available_space(existing)?)
+}
+
+/// Decides whether indexing should be paused. Once paused, it stays paused until the
+/// threshold is cleared by the resume headroom.
+fn should_pause(free: u64, min_free: u64, paused: bool) -> bool {
+    if min_free == 0 {
+        return false;
+    }
+    if paused {
+        free < min_free.saturating_add(RESUME_HEADROOM_BYTES)
+    } else {
+        free < min_free
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Converts a threshold in megabytes from settings to bytes, `None` meaning no threshold.
+/// An absurdly large value saturates, which keeps indexing paused instead of wrapping
+/// around to a tiny threshold.
+pub fn min_free_bytes(min_free_mb: Option<u64>) -> u64 {
+    min_free_mb.unwrap_or(0).saturating_mul(1024 * 1024)
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl DiskGuard {
+    /// Guards the volumes holding `dirs`. A `min_free_bytes` of 0 turns the guard off.
+    pub fn new(dirs: Vec<PathBuf>, min_free_bytes: u64) -> Self {
+        let (status, _) = watch::channel(DiskSpaceStatus { min_free_bytes, ..Default::default() });
+        Self { dirs, min_free_bytes: AtomicU64::new(min_free_bytes), status }
+    }
+
+    /// Changes the threshold; takes effect at the next check.
+    pub fn set_min_free_bytes(&self, min_free_bytes: u64) {
+        self.min_free_bytes.store(min_free_bytes, Ordering::Relaxed);
+    }
+
+    /// Returns the status of the last check.
+    pub fn status(&self) -> DiskSpaceStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Returns a receiver that sees the status every time indexing pauses or resumes.
+    pub fn subscribe(&self) -> watch::Receiver<DiskSpaceStatus> {
+        self.status.subscribe()
+    }
+
+    /// Measures free space on every guarded volume and updates the status. Volumes that
+    /// can't be measured are skipped rather than blocking indexing.
+    pub fn check(&self) -> DiskSpaceStatus {
+        let min_free_bytes = self.min_free_bytes.load(Ordering::Relaxed);
+        let fullest = self.dirs.iter()
+            .filter_map(|dir| match free_bytes(dir) {
+                Ok(free) => Some((dir.clone(), free)),
+                Err(e) => {
+                    eprintln!("Warning: Could not measure free space for {}: {}", dir.display(), e);
+                    None
+                }
+            })
+            .min_by_key(|(_, free)| *free);
+
+        let mut status = self.status();
+        if let Some((volume, free)) = fullest {
+            status.paused = should_pause(free, min_free_bytes, status.paused);
+            status.volume = volume;
+            status.free_bytes = free;
+        }
+        status.min_free_bytes = min_free_bytes;
+        // Only a pause or resume wakes subscribers; the free space itself changes constantly
+        self.status.send_if_modified(|current| {
+            let toggled = current.paused != status.paused;
+            *current = status.clone();
+            toggled
+        });
+        status
+    }
+
+    /// Returns once there is room to write, waiting while the data volumes are too full.
+    pub async fn wait_for_space(&self) {
+        while self.check().paused {
+            tokio::time::sleep(RECHECK_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_pause() {
+        const MB: u64 = 1024 * 1024;
+        assert!(should_pause(500 * MB, 1024 * MB, false));
+        assert!(!should_pause(2048 * MB, 1024 * MB, false));
+        // Resuming needs the headroom on top of the threshold
+        assert!(should_pause(1050 * MB, 1024 * MB, true));
+        assert_eq!(min_free_bytes(Some(u64::MAX)), u64::MAX);
+        assert_eq!(min_free_bytes(None), 0);
+        assert!(!should_pause(1200 * MB, 1024 * MB, true));
+        // A threshold of 0 turns the guard off
+        assert!(!should_pause(0, 0, true));
+
+        let guard = DiskGuard::new(vec![std::env::temp_dir().join("not-created-yet")], 0);
+        let status = guard.check();
+        assert!(!status.paused);
+        assert!(status.free_bytes > 0);
+    }
+}
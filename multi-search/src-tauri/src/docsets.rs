@@ -0,0 +1,183 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::fs_paths::{display_path, long_path};
+use crate::search_orchestrator::RawDocument;
+use crate::web_capture::extract_page_text;
+use anyhow::Result;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Source type of pages read from offline documentation sets.
+pub const DOCS_SOURCE: &str = "docs";
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// One HTML page of a docset with the API entries that point into it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocsetPage {
+    pub file: PathBuf,
+    /// Entry names such as "str.split", in index order.
+    pub entries: Vec<String>,
+    /// Entry types such as "Method" or "Guide", deduplicated.
+    pub entry_types: Vec<String>,
+}
+
+/// A Dash/Zeal docset: a `.docset` bundle with a SQLite search index over HTML pages.
+#[derive(Debug, Clone)]
+pub struct Docset {
+    pub root: PathBuf,
+    pub name: String,
+    /// Language or platform tag from `Info.plist`, e.g. "python".
+    pub language: Option<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Reads a string value from the simple XML property lists docsets ship with.
+fn plist_string(plist: &str, key: &str) -> Option<String> {
+    let after_key = &plist[plist.find(&format!("<key>{}</key>", key))?..];
+    let start = after_key.find("<string>")? + "<string>".len();
+    let end = after_key[start..].find("</string>")?;
+    let value = after_key[start..start + end].trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Strips the `<dash_entry_...>` markers and `#anchor` from an index path, leaving the
+/// page path relative to the `Documents` folder.
+fn page_path(index_path: &str) -> &str {
+    let path = match index_path.rfind('>') {
+        Some(end) if index_path.starts_with('<') => &index_path[end + 1..],
+        _ => index_path,
+    };
+    path.split(['#', '?']).next().unwrap_or(path)
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl Docset {
+    /// Opens a `.docset` bundle, reading its name and language from `Info.plist`.
+    pub fn open(root: &Path) -> Result<Self> {
+        let index = root.join("Contents/Resources/docSet.dsidx");
+        if !long_path(&index).is_file() {
+            return Err(anyhow::anyhow!("{} is not a docset with a search index", root.display()));
+        }
+        let plist = std::fs::read_to_string(long_path(&root.join("Contents/Info.plist"))).unwrap_or_default();
+        let name = plist_string(&plist, "CFBundleName").unwrap_or_else(|| {
+            root.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+        });
+        let language = plist_string(&plist, "DocSetPlatformFamily").map(|family| family.to_lowercase());
+        Ok(Self { root: root.to_path_buf(), name, language })
+    }
+
+    /// Groups the search index by page, so each page is indexed once with all its entries.
+    pub fn pages(&self) -> Result<Vec<DocsetPage>> {
+        let index = self.root.join("Contents/Resources/docSet.dsidx");
+        let documents = self.root.join("Contents/Resources/Documents");
+        let connection = Connection::open_with_flags(long_path(&index), OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut statement = connection.prepare("SELECT name, type, path FROM searchIndex ORDER BY id")?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })?;
+
+        let mut pages: BTreeMap<String, DocsetPage> = BTreeMap::new();
+        for row in rows {
+            let (name, entry_type, path) = row?;
+            let relative = page_path(&path).to_string();
+            let page = pages.entry(relative.clone()).or_insert_with(|| DocsetPage {
+                file: documents.join(&relative),
+                entries: Vec::new(),
+                entry_types: Vec::new(),
+            });
+            page.entries.push(name);
+            if !page.entry_types.contains(&entry_type) {
+                page.entry_types.push(entry_type);
+            }
+        }
+        Ok(pages.into_values().filter(|page| long_path(&page.file).is_file()).collect())
+    }
+
+    /// Reads a page and builds its document: titled by its first entry, with every entry
+    /// name ahead of the page text so API names match by keyword as well as meaning.
+    pub fn raw_document(&self, page: &DocsetPage) -> Result<RawDocument> {
+        let bytes = std::fs::read(long_path(&page.file))?;
+        let modified_date = std::fs::metadata(long_path(&page.file))?.modified()?;
+        let path = display_path(&page.file);
+        let (_, text) = extract_page_text(&String::from_utf8_lossy(&bytes), &path);
+
+        let mut metadata = BTreeMap::new();
+        metadata.insert("docset".to_string(), self.name.clone());
+        metadata.insert("entry_type".to_string(), page.entry_types.join("; "));
+        if let Some(language) = &self.language {
+            metadata.insert("language".to_string(), language.clone());
+        }
+        Ok(RawDocument {
+            title: format!("{} - {}", page.entries.first().cloned().unwrap_or_default(), self.name),
+            body: format!("{}\n\n{}", page.entries.join("\n"), text),
+            summary: None,
+            path,
+            source_type: DOCS_SOURCE.to_string(),
+            author: None,
+            modified_date,
+            metadata,
+        })
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Finds the docsets installed by Dash (macOS) or Zeal (Linux and Windows).
+pub fn installed_docsets() -> Vec<PathBuf> {
+    let folders = [
+        dirs::data_dir().map(|dir| dir.join("Dash/DocSets")),
+        dirs::data_local_dir().map(|dir| dir.join("Zeal/Zeal/docsets")),
+        dirs::data_dir().map(|dir| dir.join("Zeal/Zeal/docsets")),
+    ];
+    let mut docsets: Vec<PathBuf> = Vec::new();
+    for folder in folders.into_iter().flatten() {
+        // Dash nests each docset in a folder of its own; Zeal keeps them side by side
+        let Ok(entries) = std::fs::read_dir(long_path(&folder)) else { continue };
+        for entry in entries.flatten() {
+            let path = folder.join(entry.file_name());
+            let candidates: Vec<PathBuf> = match std::fs::read_dir(long_path(&path)) {
+                Ok(children) if path.extension().is_none() => {
+                    children.flatten().map(|child| path.join(child.file_name())).collect()
+                }
+                _ => vec![path],
+            };
+            for candidate in candidates {
+                if candidate.extension().is_some_and(|ext| ext == "docset") && !docsets.contains(&candidate) {
+                    docsets.push(candidate);
+                }
+            }
+        }
+    }
+    docsets.sort();
+    docsets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docset_helpers() {
+        let plist = "<dict><key>CFBundleName</key>\n<string>Python 3</string>\
+                     <key>DocSetPlatformFamily</key><string>Python</string></dict>";
+        assert_eq!(plist_string(plist, "CFBundleName").as_deref(), Some("Python 3"));
+        assert_eq!(plist_string(plist, "DocSetPlatformFamily").as_deref(), Some("Python"));
+        assert_eq!(plist_string(plist, "Missing"), None);
+
+        assert_eq!(page_path("library/stdtypes.html#str.split"), "library/stdtypes.html");
+        assert_eq!(page_path("<dash_entry_name=split>library/stdtypes.html#str.split"), "library/stdtypes.html");
+    }
+}
@@ -2,10 +2,17 @@ use anyhow::{Error as E, Result};
 use candle_core::{Device, Tensor, DType};
 use candle_nn::VarBuilder;
 use candle_transformers::models::bert::{BertModel, Config};
-use hf_hub::{api::tokio::Api, Repo, RepoType};
 use tokenizers::Tokenizer;
 use unicode_segmentation::UnicodeSegmentation;
+use crate::summary_budget::SummaryBudget;
 use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use crate::abstractive_summarizer::AbstractiveSummarizer;
+use crate::model_store::{model_files, EMBEDDING_MODEL};
+
+/// Texts embedded in one forward pass. Larger batches pad more and hold more
+/// activations in memory for little extra speed on CPU.
+const EMBEDDING_BATCH_SIZE: usize = 32;
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -16,27 +23,33 @@ pub struct EmbeddingRecord {
     pub embedding_type: String,
 }
 
+/// A chunk of document text along with its byte range in the original text.
+#[derive(Debug, Clone)]
+pub struct TextChunk {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
 #[allow(dead_code)]
 pub struct EmbeddingGenerator {
     model: BertModel,
     tokenizer: Tokenizer,
     device: Device,
+    // Set once the optional abstractive model has loaded; summaries are extractive until then
+    abstractive_summarizer: OnceLock<AbstractiveSummarizer>,
 }
 
 #[allow(dead_code)]
 impl EmbeddingGenerator {
-    pub async fn new() -> Result<Self> {
+    /// Loads the embedding model from the app data directory, downloading it first
+    /// unless `offline` is set.
+    pub async fn new(offline: bool) -> Result<Self> {
         let device = Device::Cpu;
 
-        let api = Api::new()?;
-        let repo = api.repo(Repo::new(
-            "sentence-transformers/all-MiniLM-L6-v2".to_string(),
-            RepoType::Model,
-        ));
-
-        let config_filename = repo.get("config.json").await?;
-        let tokenizer_filename = repo.get("tokenizer.json").await?;
-        let weights_filename = repo.get("model.safetensors").await?;
+        let files = model_files(&EMBEDDING_MODEL, offline).await?;
+        let [config_filename, tokenizer_filename, weights_filename] = <[_; 3]>::try_from(files)
+            .map_err(|_| E::msg("Unexpected embedding model files"))?;
 
         let config_str = std::fs::read_to_string(config_filename)?;
         let config: Config = serde_json::from_str(&config_str)?;
@@ -52,82 +65,155 @@ impl EmbeddingGenerator {
             model,
             tokenizer,
             device,
+            abstractive_summarizer: OnceLock::new(),
         })
     }
 
+    /// Embeds a document's title, a summary of its body and the given chunks of it. The
+    /// chunks are usually all of `chunk_text(body)`, or only those not stored yet. A
+    /// summary the source provides is used as it is instead of summarizing the body.
     pub fn generate_embeddings_for_document(
         &self,
         title: &str,
         body: &str,
+        provided_summary: Option<String>,
+        chunks: Vec<String>,
         document_path: &str,
+        summary_budget: SummaryBudget,
     ) -> Result<Vec<EmbeddingRecord>> {
-        let mut records = Vec::new();
-
-        // Process title if not empty
-        if !title.trim().is_empty() {
-            let title_embedding = self.generate_single_embedding(title)?;
-            records.push(EmbeddingRecord {
-                embedding: title_embedding,
-                text_chunk: title.to_string(),
+        // Embed the title, summary and chunks together, skipping any that are empty
+        let summary = provided_summary
+            .filter(|summary| !summary.trim().is_empty())
+            .unwrap_or_else(|| self.summarize(body, summary_budget));
+        let mut records: Vec<EmbeddingRecord> = [("title", title.to_string()), ("summary", summary)]
+            .into_iter()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .map(|(embedding_type, text)| EmbeddingRecord {
+                embedding: Vec::new(),
+                text_chunk: text,
                 document_path: document_path.to_string(),
-                embedding_type: "title".to_string(),
-            });
-        }
+                embedding_type: embedding_type.to_string(),
+            })
+            .collect();
+        records.extend(Self::chunk_records(chunks, document_path));
+        self.fill_embeddings(&mut records)?;
+        Ok(records)
+    }
+
+    /// Generates only the chunk embeddings for a document body, filtering out empty chunks.
+    pub fn generate_chunk_embeddings(&self, body: &str, document_path: &str) -> Result<Vec<EmbeddingRecord>> {
+        self.embed_chunks(self.chunk_text(body), document_path)
+    }
 
-        // Process summary if not empty
-        let summary = self.summarize_text(body);
-        if !summary.trim().is_empty() {
-            let summary_embedding = self.generate_single_embedding(&summary)?;
-            records.push(EmbeddingRecord {
-                embedding: summary_embedding,
-                text_chunk: summary,
+    /// Embeds chunks already split from a document, e.g. only those that changed.
+    pub fn embed_chunks(&self, chunks: Vec<String>, document_path: &str) -> Result<Vec<EmbeddingRecord>> {
+        let mut records = Self::chunk_records(chunks, document_path);
+        self.fill_embeddings(&mut records)?;
+        Ok(records)
+    }
+
+    /// Turns chunks into records whose embeddings are still to be filled in, dropping empty ones.
+    fn chunk_records(chunks: Vec<String>, document_path: &str) -> Vec<EmbeddingRecord> {
+        chunks
+            .into_iter()
+            .filter(|chunk| !chunk.trim().is_empty())
+            .map(|chunk| EmbeddingRecord {
+                embedding: Vec::new(),
+                text_chunk: chunk,
                 document_path: document_path.to_string(),
-                embedding_type: "summary".to_string(),
-            });
-        }
+                embedding_type: "chunk".to_string(),
+            })
+            .collect()
+    }
 
-        // Process chunks, filtering out empty ones
-        let chunks = self.chunk_text(body);
-        for chunk in chunks {
-            if !chunk.trim().is_empty() {
-                let chunk_embedding = self.generate_single_embedding(&chunk)?;
-                records.push(EmbeddingRecord {
-                    embedding: chunk_embedding,
-                    text_chunk: chunk,
-                    document_path: document_path.to_string(),
-                    embedding_type: "chunk".to_string(),
-                });
-            }
+    /// Embeds the text of every record in batches.
+    fn fill_embeddings(&self, records: &mut [EmbeddingRecord]) -> Result<()> {
+        let texts: Vec<&str> = records.iter().map(|record| record.text_chunk.as_str()).collect();
+        let embeddings = self.generate_batch_embeddings(&texts)?;
+        for (record, embedding) in records.iter_mut().zip(embeddings) {
+            record.embedding = embedding;
         }
-
-        Ok(records)
+        Ok(())
     }
 
     pub fn generate_single_embedding(&self, text: &str) -> Result<Vec<f32>> {
-        if text.trim().is_empty() {
+        self.generate_batch_embeddings(&[text])?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("No embedding was generated"))
+    }
+
+    /// Embeds several texts, returned in the same order. Texts of similar length are
+    /// padded into one tensor and run through the model together, which is several
+    /// times faster than one forward pass per text.
+    pub fn generate_batch_embeddings(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        if texts.iter().any(|text| text.trim().is_empty()) {
             return Err(anyhow::anyhow!("Cannot generate embedding for empty text"));
         }
+        let encodings = self.tokenizer.encode_batch(texts.to_vec(), true).map_err(E::msg)?;
+
+        // Batch texts of similar length so little of each batch is padding
+        let mut order: Vec<usize> = (0..encodings.len()).collect();
+        order.sort_by_key(|&i| encodings[i].len());
+
+        let mut embeddings = vec![Vec::new(); encodings.len()];
+        for batch in order.chunks(EMBEDDING_BATCH_SIZE) {
+            let max_len = batch.iter().map(|&i| encodings[i].len()).max().unwrap_or(0);
+            let padded = |field: fn(&tokenizers::Encoding) -> &[u32]| -> Vec<u32> {
+                batch.iter()
+                    .flat_map(|&i| {
+                        let values = field(&encodings[i]);
+                        values.iter().copied().chain(std::iter::repeat(0).take(max_len - values.len()))
+                    })
+                    .collect()
+            };
+            let shape = (batch.len(), max_len);
+            let input_ids = Tensor::from_vec(padded(|e| e.get_ids()), shape, &self.device)?;
+            let attention_mask = Tensor::from_vec(padded(|e| e.get_attention_mask()), shape, &self.device)?;
+            let token_type_ids = Tensor::from_vec(padded(|e| e.get_type_ids()), shape, &self.device)?;
 
-        let tokens = self.tokenizer.encode(text, true).map_err(E::msg)?;
-        let input_ids = Tensor::new(tokens.get_ids(), &self.device)?.unsqueeze(0)?;
-        let attention_mask = Tensor::new(tokens.get_attention_mask(), &self.device)?.unsqueeze(0)?;
-        let token_type_ids = Tensor::new(tokens.get_type_ids(), &self.device)?.unsqueeze(0)?;
+            let token_embeddings = self.model.forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
 
-        let token_embeddings = self.model.forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
+            // Mean-pool over real tokens only; padding is masked out
+            let expanded_mask = attention_mask.to_dtype(DType::F32)?.unsqueeze(2)?.expand(token_embeddings.shape())?;
+            let masked_embeddings = (token_embeddings * &expanded_mask)?;
+            let sum_embeddings = masked_embeddings.sum(1)?;
+            let sum_mask = expanded_mask.sum(1)?;
+            let mean_pooled_embeddings = (sum_embeddings / sum_mask)?;
 
-        let expanded_mask = attention_mask.unsqueeze(2)?.expand(token_embeddings.shape())?;
-        let masked_embeddings = (token_embeddings * &expanded_mask)?;
-        let sum_embeddings = masked_embeddings.sum(1)?;
-        let sum_mask = expanded_mask.sum(1)?;
-        let mean_pooled_embedding = (sum_embeddings / sum_mask)?;
+            let norm = mean_pooled_embeddings.sqr()?.sum_keepdim(1)?.sqrt()?;
+            let normalized_embeddings = mean_pooled_embeddings.broadcast_div(&norm)?;
+
+            for (&i, embedding) in batch.iter().zip(normalized_embeddings.to_vec2::<f32>()?) {
+                embeddings[i] = embedding;
+            }
+        }
+        Ok(embeddings)
+    }
 
-        let norm = mean_pooled_embedding.sqr()?.sum_keepdim(1)?.sqrt()?;
-        let normalized_embedding = (mean_pooled_embedding / norm)?;
+    /// Switches summaries to the abstractive model for documents indexed from now on.
+    pub fn set_abstractive_summarizer(&self, summarizer: AbstractiveSummarizer) {
+        let _ = self.abstractive_summarizer.set(summarizer);
+    }
 
-        Ok(normalized_embedding.squeeze(0)?.to_vec1::<f32>()?)
+    /// Summarizes with the abstractive model when it is loaded, falling back to picking
+    /// sentences when it isn't or fails. Texts already within budget are kept as they are.
+    pub fn summarize(&self, text: &str, budget: SummaryBudget) -> String {
+        let sentences = text.unicode_sentences().count();
+        if let Some(summarizer) = self.abstractive_summarizer.get() {
+            if sentences > budget.sentence_count(sentences) {
+                match summarizer.summarize(text, budget, sentences) {
+                    Ok(summary) if !summary.is_empty() => return summary,
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Warning: Abstractive summary failed, using extractive: {}", e),
+                }
+            }
+        }
+        self.summarize_text(text, budget)
     }
 
-    fn summarize_text(&self, text: &str) -> String {
+    /// Extracts the highest-scoring sentences in document order, as many as the budget
+    /// allows for a document of this length.
+    fn summarize_text(&self, text: &str, budget: SummaryBudget) -> String {
         // Comprehensive stop words list
         let stop_words: HashSet<&str> = [
             // Articles
@@ -224,8 +310,9 @@ impl EmbeddingGenerator {
 
         // Split text into sentences
         let sentences: Vec<&str> = text.unicode_sentences().collect();
+        let num_sentences = budget.sentence_count(sentences.len());
         
-        if sentences.len() <= 3 {
+        if sentences.len() <= num_sentences {
             return text.to_string();
         }
 
@@ -269,10 +356,9 @@ impl EmbeddingGenerator {
             sentence_scores.push((i, score));
         }
 
-        // Sort sentences by score (descending) and take top 3-5
+        // Sort sentences by score (descending) and take as many as the budget allows
         sentence_scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
         
-        let num_sentences = std::cmp::min(5, std::cmp::max(3, sentences.len() / 3));
         let mut selected_indices: Vec<usize> = sentence_scores
             .iter()
             .take(num_sentences)
@@ -290,33 +376,57 @@ impl EmbeddingGenerator {
             .join(" ")
     }
 
-    fn chunk_text(&self, text: &str) -> Vec<String> {
-        // Define our target chunk size in characters.
-        const TARGET_CHUNK_SIZE: usize = 1000; // Approx 200-250 tokens
-        let mut chunks = Vec::new();
-        let mut current_chunk = String::new();
-
-        // Split the text into sentences using the unicode-segmentation crate.
-        // This is a robust form of semantic chunking.
-        for sentence in text.unicode_sentences() {
-            // Check if adding the new sentence would exceed the limit.
-            // Add 1 for the space we'll add.
-            if !current_chunk.is_empty() && current_chunk.len() + sentence.len() + 1 > TARGET_CHUNK_SIZE {
-                chunks.push(current_chunk);
-                current_chunk = String::new();
-            }
-            // Add a space before the new sentence if the chunk isn't empty.
-            if !current_chunk.is_empty() {
-                current_chunk.push(' ');
-            }
-            current_chunk.push_str(sentence);
-        }
+    pub fn chunk_text(&self, text: &str) -> Vec<String> {
+        chunk_text_with_offsets(text)
+            .into_iter()
+            .map(|chunk| chunk.text)
+            .collect()
+    }
+}
+
+/// Splits text into sentence-aligned chunks, keeping each chunk's byte range in the source.
+/// Needs no model, so keyword-only search can split text the same way.
+pub fn chunk_text_with_offsets(text: &str) -> Vec<TextChunk> {
+    // Define our target chunk size in characters.
+    const TARGET_CHUNK_SIZE: usize = 1000; // Approx 200-250 tokens
+    let mut chunks = Vec::new();
+    let mut current_chunk = String::new();
+    let mut chunk_start = 0;
+    let mut chunk_end = 0;
 
-        // Add the last remaining chunk if it's not empty.
-        if !current_chunk.trim().is_empty() {
-            chunks.push(current_chunk);
+    // Split the text into sentences using the unicode-segmentation crate.
+    // This is a robust form of semantic chunking. Like `unicode_sentences`,
+    // we skip segments without any alphanumeric characters.
+    let sentences = text.split_sentence_bound_indices()
+        .filter(|(_, sentence)| sentence.chars().any(char::is_alphanumeric));
+    for (offset, sentence) in sentences {
+        // Check if adding the new sentence would exceed the limit.
+        // Add 1 for the space we'll add.
+        if !current_chunk.is_empty() && current_chunk.len() + sentence.len() + 1 > TARGET_CHUNK_SIZE {
+            chunks.push(TextChunk {
+                text: std::mem::take(&mut current_chunk),
+                start: chunk_start,
+                end: chunk_end,
+            });
         }
+        // Add a space before the new sentence if the chunk isn't empty.
+        if current_chunk.is_empty() {
+            chunk_start = offset;
+        } else {
+            current_chunk.push(' ');
+        }
+        current_chunk.push_str(sentence);
+        chunk_end = offset + sentence.len();
+    }
 
-        chunks
+    // Add the last remaining chunk if it's not empty.
+    if !current_chunk.trim().is_empty() {
+        chunks.push(TextChunk {
+            text: current_chunk,
+            start: chunk_start,
+            end: chunk_end,
+        });
     }
-}
\ No newline at end of file
+
+    chunks
+}
@@ -0,0 +1,272 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::settings::app_data_dir;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Query counts are saved at most this often, keeping disk writes off most searches.
+/// Counts since the last save are lost if the app exits without a click to save them.
+const STATS_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// The weights used to fuse and re-rank results in hybrid search.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    /// Multiplier on the RRF score of keyword matches.
+    pub keyword_boost: f32,
+    /// Multiplier on the RRF score of semantic title matches.
+    pub title_boost: f32,
+    /// Weight of the recency score in the final score.
+    pub recency_weight: f32,
+    /// Weight of the fused RRF score in the final score.
+    pub rrf_weight: f32,
+    /// Weight of the chunk aggregation score: how many chunks of a document matched,
+    /// and how closely on average.
+    pub chunk_aggregation_weight: f32,
+    /// Weight of how often the user opened a document, in the app or elsewhere.
+    pub frequency_weight: f32,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            keyword_boost: 1.2,
+            title_boost: 1.1,
+            recency_weight: 0.3,
+            rrf_weight: 0.7,
+            chunk_aggregation_weight: 0.02,
+            frequency_weight: 0.1,
+        }
+    }
+}
+
+/// An A/B experiment read from `experiments.json` in the app data directory.
+/// Edits to the file take effect on the next query, without restarting the app.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExperimentDefinition {
+    pub enabled: bool,
+    /// Stats are kept per name, so renaming starts a fresh comparison.
+    pub name: String,
+    pub control: RankingConfig,
+    pub treatment: RankingConfig,
+}
+
+/// Which ranking configuration produced a set of results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    Control,
+    Treatment,
+}
+
+/// Tags a search response with the experiment arm that ranked it, so the UI can
+/// report it back when the user clicks a result.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExperimentAssignment {
+    pub experiment: String,
+    pub variant: Variant,
+}
+
+/// Raw counters for one arm of an experiment.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VariantStats {
+    pub queries: u64,
+    pub clicks: u64,
+}
+
+/// Comparative click-through stats for the current experiment.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExperimentReport {
+    pub experiment: String,
+    pub enabled: bool,
+    pub control: VariantStats,
+    pub treatment: VariantStats,
+    pub control_click_through_rate: f64,
+    pub treatment_click_through_rate: f64,
+}
+
+/// Counters for both arms of one experiment, as persisted on disk.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ExperimentStats {
+    control: VariantStats,
+    treatment: VariantStats,
+}
+
+impl ExperimentStats {
+    fn variant_mut(&mut self, variant: Variant) -> &mut VariantStats {
+        match variant {
+            Variant::Control => &mut self.control,
+            Variant::Treatment => &mut self.treatment,
+        }
+    }
+}
+
+/// Mutable state behind the `Experiments` lock.
+struct ExperimentState {
+    definition: ExperimentDefinition,
+    definition_modified: Option<SystemTime>,
+    stats: HashMap<String, ExperimentStats>,
+    next_is_treatment: bool,
+    /// When the stats were last written, or None if they never were this run.
+    stats_saved: Option<Instant>,
+}
+
+/// Runs ranking experiments: assigns queries to arms and tracks click-through per arm.
+pub struct Experiments {
+    definition_path: PathBuf,
+    stats_path: PathBuf,
+    state: Mutex<ExperimentState>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the modification time of a file, or `None` if it doesn't exist.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reads the experiment definition, treating a missing file as "no experiment".
+fn load_definition(path: &Path) -> Result<ExperimentDefinition> {
+    if !path.exists() {
+        return Ok(ExperimentDefinition::default());
+    }
+    let contents = std::fs::read_to_string(path)?;
+    serde_json::from_str(&contents)
+        .map_err(|e| anyhow::anyhow!("Failed to parse experiments file {}: {}", path.display(), e))
+}
+
+/// Returns clicks per query, or zero before any queries were assigned.
+fn click_through_rate(stats: &VariantStats) -> f64 {
+    if stats.queries == 0 {
+        0.0
+    } else {
+        stats.clicks as f64 / stats.queries as f64
+    }
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl Experiments {
+    /// Loads the experiment definition and any stats recorded by earlier runs.
+    pub fn open() -> Result<Self> {
+        let data_dir = app_data_dir()?;
+        let definition_path = data_dir.join("experiments.json");
+        let stats_path = data_dir.join("experiment_stats.json");
+
+        let definition = load_definition(&definition_path)?;
+        let stats = if stats_path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&stats_path)?)
+                .map_err(|e| anyhow::anyhow!("Failed to parse experiment stats {}: {}", stats_path.display(), e))?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            state: Mutex::new(ExperimentState {
+                definition,
+                definition_modified: modified_time(&definition_path),
+                stats,
+                next_is_treatment: false,
+                stats_saved: None,
+            }),
+            definition_path,
+            stats_path,
+        })
+    }
+
+    /// Re-reads the definition file if it changed since it was last loaded.
+    /// A broken edit keeps the previous definition running.
+    fn reload_if_changed(&self, state: &mut ExperimentState) {
+        let modified = modified_time(&self.definition_path);
+        if modified == state.definition_modified {
+            return;
+        }
+        match load_definition(&self.definition_path) {
+            Ok(definition) => state.definition = definition,
+            Err(e) => eprintln!("Warning: Keeping previous ranking experiment: {}", e),
+        }
+        state.definition_modified = modified;
+    }
+
+    /// Writes the stats via a temporary file so a crash never truncates them.
+    fn persist(&self, state: &mut ExperimentState) -> Result<()> {
+        if let Some(parent) = self.stats_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.stats_path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(&state.stats)?)?;
+        std::fs::rename(&tmp_path, &self.stats_path)?;
+        state.stats_saved = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Picks the ranking configuration for a new query. Arms alternate so both see a
+    /// similar mix of queries; without an active experiment the control weights are used.
+    pub fn assign(&self) -> (RankingConfig, Option<ExperimentAssignment>) {
+        let mut state = self.state.lock().unwrap();
+        self.reload_if_changed(&mut state);
+        if !state.definition.enabled || state.definition.name.is_empty() {
+            return (state.definition.control, None);
+        }
+
+        let variant = if state.next_is_treatment { Variant::Treatment } else { Variant::Control };
+        state.next_is_treatment = !state.next_is_treatment;
+
+        let name = state.definition.name.clone();
+        state.stats.entry(name.clone()).or_default().variant_mut(variant).queries += 1;
+        if state.stats_saved.is_none_or(|saved| saved.elapsed() >= STATS_SAVE_INTERVAL) {
+            if let Err(e) = self.persist(&mut state) {
+                eprintln!("Warning: Could not save experiment stats: {}", e);
+            }
+        }
+
+        let config = match variant {
+            Variant::Control => state.definition.control,
+            Variant::Treatment => state.definition.treatment,
+        };
+        (config, Some(ExperimentAssignment { experiment: name, variant }))
+    }
+
+    /// Records a click on a result that was ranked by the given experiment arm. Clicks
+    /// are saved right away, along with any query counts not saved yet.
+    pub fn record_click(&self, experiment: &str, variant: Variant) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.stats.entry(experiment.to_string()).or_default().variant_mut(variant).clicks += 1;
+        self.persist(&mut state)
+    }
+
+    /// Returns the comparative stats for the experiment currently in the definition file.
+    pub fn report(&self) -> Option<ExperimentReport> {
+        let mut state = self.state.lock().unwrap();
+        self.reload_if_changed(&mut state);
+        if state.definition.name.is_empty() {
+            return None;
+        }
+
+        let stats = state.stats.get(&state.definition.name).copied().unwrap_or_default();
+        Some(ExperimentReport {
+            experiment: state.definition.name.clone(),
+            enabled: state.definition.enabled,
+            control: stats.control,
+            treatment: stats.treatment,
+            control_click_through_rate: click_through_rate(&stats.control),
+            treatment_click_through_rate: click_through_rate(&stats.treatment),
+        })
+    }
+}
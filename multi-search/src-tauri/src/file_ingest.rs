@@ -0,0 +1,142 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::frontmatter::parse_frontmatter;
+use crate::fs_paths::{display_path, long_path};
+use crate::permissions::is_permission_denied;
+use crate::parsers::{is_supported_file_type, parse_document, parse_html_file};
+use crate::search_orchestrator::RawDocument;
+use anyhow::Result;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Source type recorded for documents read from the local file system.
+pub const LOCAL_FILE_SOURCE: &str = "file";
+
+// ===================================================================
+//  PUBLIC STRUCT
+// ===================================================================
+
+/// Payload of the `file-indexed` event, sent once a dropped file is searchable or has failed.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FileIndexedEvent {
+    pub path: String,
+    pub error: Option<String>,
+}
+
+/// The result of expanding dropped or crawled paths into indexable files.
+#[derive(Debug, Default)]
+pub struct FileScan {
+    pub files: Vec<PathBuf>,
+    /// Folders the OS refused to list, e.g. because macOS privacy access is missing.
+    pub permission_denied: Vec<PathBuf>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns true for dotfiles and dot-directories such as `.git`.
+pub fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+/// Returns true if the parsers can read this file.
+pub fn is_supported_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(is_supported_file_type)
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Expands a file or folder into the supported files it contains, skipping hidden
+/// entries. Folders blocked by permissions are reported so they can be retried later;
+/// other unreadable subdirectories are skipped with a warning.
+pub fn collect_supported_files(root: &Path) -> FileScan {
+    let mut scan = FileScan::default();
+    if long_path(root).is_file() {
+        if is_supported_file(root) {
+            scan.files.push(root.to_path_buf());
+        }
+        return scan;
+    }
+
+    // Canonical targets of followed links, so a link back to an ancestor can't loop forever
+    let mut visited_links: HashSet<PathBuf> = std::fs::canonicalize(long_path(root)).into_iter().collect();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match std::fs::read_dir(long_path(&dir)) {
+            Ok(entries) => entries,
+            Err(e) if is_permission_denied(&e) => {
+                scan.permission_denied.push(dir);
+                continue;
+            }
+            Err(e) => {
+                eprintln!("Warning: Could not read directory {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            // Join onto `dir` rather than using `entry.path()`, which would carry the `\\?\` prefix
+            let path = dir.join(entry.file_name());
+            if is_hidden(&path) {
+                continue;
+            }
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => pending.push(path),
+                Ok(file_type) if file_type.is_file() && is_supported_file(&path) => scan.files.push(path),
+                // Symlinks and Windows junctions (e.g. redirected or OneDrive folders) are followed once
+                Ok(file_type) if file_type.is_symlink() => {
+                    let Ok(target) = std::fs::canonicalize(long_path(&path)) else { continue };
+                    if target.is_dir() {
+                        if visited_links.insert(target) {
+                            pending.push(path);
+                        }
+                    } else if target.is_file() && is_supported_file(&path) {
+                        scan.files.push(path);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    scan.files.sort();
+    scan
+}
+
+/// Parses a local file into a document ready for indexing, titled by its file name.
+/// A Markdown note's frontmatter supplies its author and filterable fields, and an HTML
+/// page's `<title>` and meta description its title and summary.
+pub fn raw_document_from_file(path: &Path) -> Result<RawDocument> {
+    let is_html = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+    let (body, page_title, summary) = if is_html {
+        let page = parse_html_file(path)?;
+        (page.body, page.title, page.description)
+    } else {
+        (parse_document(path)?, None, None)
+    };
+    let modified_date = std::fs::metadata(long_path(path))?.modified()?;
+    let title = page_title.unwrap_or_else(|| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| display_path(path))
+    });
+    let is_markdown = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+    let frontmatter = is_markdown.then(|| parse_frontmatter(&body)).flatten().unwrap_or_default();
+
+    Ok(RawDocument {
+        path: display_path(path),
+        title,
+        body,
+        summary,
+        source_type: LOCAL_FILE_SOURCE.to_string(),
+        author: frontmatter.author,
+        modified_date,
+        metadata: frontmatter.fields,
+    })
+}
@@ -0,0 +1,97 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use chrono::DateTime;
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// How the user has used a file outside the app, as far as the OS knows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileUsage {
+    /// Unix seconds of the last time the file was opened.
+    pub last_used: Option<u64>,
+    /// How many times it was opened; 0 where the OS doesn't count.
+    pub use_count: u64,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Reads Spotlight's last-used date and use count from `mdls` output, e.g.
+/// `kMDItemLastUsedDate = 2024-03-05 14:30:00 +0000`. Missing values read as `(null)`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_mdls(output: &str) -> FileUsage {
+    let mut usage = FileUsage::default();
+    for line in output.lines() {
+        let Some((name, value)) = line.split_once('=') else { continue };
+        let value = value.trim();
+        match name.trim() {
+            "kMDItemLastUsedDate" => {
+                usage.last_used = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z")
+                    .ok()
+                    .and_then(|date| u64::try_from(date.timestamp()).ok());
+            }
+            "kMDItemUseCount" => usage.use_count = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    usage
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns when a file was last read, from its access time. Only trusted when it is later
+/// than the modification time: many systems mount with `noatime` or `relatime`, which
+/// leave the access time at or near the last write.
+pub fn accessed_after_modified(metadata: &Metadata) -> Option<u64> {
+    let secs = |time: std::io::Result<std::time::SystemTime>| {
+        time.ok()?.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+    };
+    let accessed = secs(metadata.accessed())?;
+    (accessed > secs(metadata.modified()).unwrap_or(0)).then_some(accessed)
+}
+
+/// Looks up how often and how recently the user opened a file. On macOS this asks
+/// Spotlight, which tracks opens from every app; elsewhere it falls back to the access
+/// time. Spawns a process on macOS, so call it once per file at indexing time, not per query.
+pub fn file_usage(path: &Path) -> FileUsage {
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("mdls")
+            .args(["-name", "kMDItemLastUsedDate", "-name", "kMDItemUseCount"])
+            .arg(path)
+            .output();
+        if let Ok(output) = output {
+            let usage = parse_mdls(&String::from_utf8_lossy(&output.stdout));
+            if usage.last_used.is_some() {
+                return usage;
+            }
+        }
+    }
+    FileUsage {
+        last_used: std::fs::metadata(path).ok().and_then(|metadata| accessed_after_modified(&metadata)),
+        use_count: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mdls() {
+        let output = "kMDItemLastUsedDate = 2024-03-05 14:30:00 +0000\nkMDItemUseCount     = 12\n";
+        assert_eq!(parse_mdls(output), FileUsage { last_used: Some(1_709_649_000), use_count: 12 });
+
+        let never_opened = "kMDItemLastUsedDate = (null)\nkMDItemUseCount     = (null)\n";
+        assert_eq!(parse_mdls(never_opened), FileUsage::default());
+    }
+}
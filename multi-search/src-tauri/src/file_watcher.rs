@@ -0,0 +1,154 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::file_ingest::{is_hidden, is_supported_file};
+use crate::fs_paths::long_path;
+use crate::search_orchestrator::SearchOrchestrator;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Quiet period after the last change to a file before it is re-indexed, so an editor's
+/// save (often several writes and a rename) triggers one update.
+const DEBOUNCE: Duration = Duration::from_secs(2);
+/// Quiet period for the file the user is working on, short enough that a search right
+/// after saving finds the new text.
+const ACTIVE_FILE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A change to the watched folders while the watcher runs, e.g. a folder added in settings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchCommand {
+    Add(PathBuf),
+    Remove(PathBuf),
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns true if a changed path is one the watcher should index: a supported file
+/// that isn't hidden below its watched folder or inside an excluded one.
+fn is_watched_file(path: &Path, folders: &[PathBuf], excluded: &[PathBuf]) -> bool {
+    let Some(relative) = folders.iter().find_map(|folder| path.strip_prefix(folder).ok()) else {
+        return false;
+    };
+    is_supported_file(path)
+        && !relative.components().any(|component| is_hidden(Path::new(component.as_os_str())))
+        && !excluded.iter().any(|folder| path.starts_with(folder))
+}
+
+/// Brings the index in line with a file that changed: indexed if it exists, removed from
+/// the index if it was deleted or moved away. The active file only has its changed
+/// chunks re-embedded.
+async fn sync_file(orchestrator: &SearchOrchestrator, path: &Path) -> anyhow::Result<()> {
+    if long_path(path).is_file() {
+        if orchestrator.is_active_file(path) {
+            return orchestrator.refresh_active_file(path).await;
+        }
+        return orchestrator.index_file(path).await;
+    }
+    let path = path.display().to_string();
+    if orchestrator.document_result(&path).await?.is_some() {
+        orchestrator.delete_document(&path).await?;
+    }
+    Ok(())
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Watches the configured folders and keeps the index current as files are created,
+/// changed, renamed or deleted, without a manual re-scan. Folders added or removed in
+/// settings arrive as `commands` and are watched or dropped right away. Runs until the
+/// app exits. Documents under a deleted folder are not removed here; the stale-result
+/// check drops them the next time they show up in a search.
+pub async fn watch_folders(
+    orchestrator: Arc<SearchOrchestrator>,
+    mut folders: Vec<PathBuf>,
+    excluded: Vec<PathBuf>,
+    mut commands: mpsc::UnboundedReceiver<WatchCommand>,
+) {
+
+    // 1. Forward changed paths from the OS watcher. Reads are ignored, or indexing a file
+    //    would report it as changed again.
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Warning: File watcher error: {}", e),
+    });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Warning: Could not start the file watcher: {}", e);
+            return;
+        }
+    };
+    for folder in &folders {
+        if let Err(e) = watcher.watch(folder, RecursiveMode::Recursive) {
+            eprintln!("Warning: Could not watch {}: {}", folder.display(), e);
+        }
+    }
+
+    // 2. Collect changes per file, and sync each once it has been quiet for the debounce
+    //    period, waking up in time for the next file due.
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    loop {
+        let wait = pending.values()
+            .map(|due| due.saturating_duration_since(Instant::now()))
+            .min()
+            .unwrap_or(DEBOUNCE);
+        tokio::select! {
+            event = tokio::time::timeout(wait, rx.recv()) => match event {
+                Ok(Some(path)) => {
+                    if is_watched_file(&path, &folders, &excluded) {
+                        let debounce = if orchestrator.is_active_file(&path) { ACTIVE_FILE_DEBOUNCE } else { DEBOUNCE };
+                        pending.insert(path, Instant::now() + debounce);
+                    }
+                }
+                Ok(None) => break,
+                Err(_) => {}
+            },
+            Some(command) = commands.recv() => match command {
+                WatchCommand::Add(folder) if !folders.contains(&folder) => {
+                    if let Err(e) = watcher.watch(&folder, RecursiveMode::Recursive) {
+                        eprintln!("Warning: Could not watch {}: {}", folder.display(), e);
+                    }
+                    folders.push(folder);
+                }
+                WatchCommand::Add(_) => {}
+                WatchCommand::Remove(folder) => {
+                    if let Some(index) = folders.iter().position(|watched| *watched == folder) {
+                        folders.remove(index);
+                        let _ = watcher.unwatch(&folder);
+                        // Changes already seen under the folder are dropped too
+                        pending.retain(|path, _| is_watched_file(path, &folders, &excluded));
+                    }
+                }
+            },
+        }
+        let now = Instant::now();
+        let ready: Vec<PathBuf> = pending.iter()
+            .filter(|(_, due)| **due <= now)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in ready {
+            pending.remove(&path);
+            if let Err(e) = sync_file(&orchestrator, &path).await {
+                eprintln!("Warning: Could not update {} in the index: {}", path.display(), e);
+            }
+        }
+    }
+}
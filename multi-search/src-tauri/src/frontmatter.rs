@@ -0,0 +1,164 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use std::collections::BTreeMap;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// The YAML frontmatter of a Markdown note, e.g. `status: draft` and `tags: [atlas, q3]`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Frontmatter {
+    /// From an `author` or `authors` key, indexed as the document's author.
+    pub author: Option<String>,
+    /// Every other key, filterable as `status:draft`. Lists are joined with "; ".
+    pub fields: BTreeMap<String, String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Turns a frontmatter key into one the query syntax can filter on: lowercase, with
+/// anything but letters, digits and `_` replaced by `_`, so `Due Date` becomes `due_date`.
+/// None for keys that don't start with a letter.
+fn field_key(key: &str) -> Option<String> {
+    let key: String = key.trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    let key = key.trim_matches('_');
+    key.starts_with(|c: char| c.is_ascii_alphabetic()).then(|| key.to_string())
+}
+
+/// Strips matching single or double quotes around a scalar.
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|rest| rest.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    value
+}
+
+/// Reads a value that may be an inline list such as `[atlas, "q3 plan"]` into its items.
+fn inline_items(value: &str) -> Vec<String> {
+    match value.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+        Some(list) => list.split(',').map(|item| unquote(item).to_string()).collect(),
+        None => vec![unquote(value).to_string()],
+    }
+}
+
+/// Joins a key's items into its metadata value. Tags drop a leading `#`, as Obsidian
+/// allows either spelling.
+fn field_value(key: &str, items: Vec<String>) -> String {
+    items.into_iter()
+        .map(|item| if key == "tags" { item.trim_start_matches('#').to_string() } else { item })
+        .filter(|item| !item.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Parses the frontmatter block at the start of a note, between `---` lines. Handles
+/// the flat subset of YAML notes use: scalars, inline lists and `- item` lists. Nested
+/// maps are skipped. None if the note has no frontmatter.
+pub fn parse_frontmatter(text: &str) -> Option<Frontmatter> {
+    let mut lines = text.trim_start_matches('\u{feff}').lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+
+    // 1. Collect each key's items, up to the closing line.
+    let mut entries: Vec<(String, Vec<String>)> = Vec::new();
+    let mut closed = false;
+    for line in lines.by_ref() {
+        if matches!(line.trim_end(), "---" | "...") {
+            closed = true;
+            break;
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        // An item of a `- item` list under the last key
+        if let Some(item) = trimmed.strip_prefix("- ").or_else(|| (trimmed == "-").then_some("")) {
+            if let Some((_, items)) = entries.last_mut() {
+                items.push(unquote(item).to_string());
+            }
+            continue;
+        }
+        // Indented keys belong to a nested map
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else { continue };
+        let Some(key) = field_key(key) else { continue };
+        let value = value.trim();
+        let items = if value.is_empty() { Vec::new() } else { inline_items(value) };
+        entries.push((key, items));
+    }
+    if !closed {
+        return None;
+    }
+
+    // 2. Split the author off from the filterable fields.
+    let mut frontmatter = Frontmatter::default();
+    for (key, items) in entries {
+        let value = field_value(&key, items);
+        if value.is_empty() {
+            continue;
+        }
+        if key == "author" || key == "authors" {
+            frontmatter.author = Some(value);
+        } else {
+            frontmatter.fields.insert(key, value);
+        }
+    }
+    Some(frontmatter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frontmatter() {
+        let note = "---\n\
+            title: \"Atlas: launch plan\"\n\
+            Status: draft\n\
+            Due Date: 2024-10-01\n\
+            tags: [atlas, \"#q3\"]\n\
+            aliases:\n  - Launch\n  - 'Go live'\n\
+            author: Dana Li\n\
+            links:\n  source: notion\n\
+            # a comment\n\
+            empty:\n\
+            ---\n\
+            # Atlas\nbody: not frontmatter\n";
+        let frontmatter = parse_frontmatter(note).unwrap();
+        assert_eq!(frontmatter.author.as_deref(), Some("Dana Li"));
+        let fields: Vec<(&str, &str)> = frontmatter.fields.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(fields, vec![
+            ("aliases", "Launch; Go live"),
+            ("due_date", "2024-10-01"),
+            ("status", "draft"),
+            ("tags", "atlas; q3"),
+            ("title", "Atlas: launch plan"),
+        ]);
+    }
+
+    #[test]
+    fn test_no_frontmatter() {
+        assert_eq!(parse_frontmatter("# Notes\nstatus: draft\n"), None);
+        // A horizontal rule that is never closed isn't frontmatter
+        assert_eq!(parse_frontmatter("---\nstatus: draft\n"), None);
+        assert_eq!(field_key("2024"), None);
+        assert_eq!(field_key(" Project-Name "), Some("project_name".to_string()));
+    }
+}
@@ -0,0 +1,138 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use std::borrow::Cow;
+use std::path::{Component, Path, PathBuf};
+
+/// Prefix that lifts the 260-character MAX_PATH limit on Windows file APIs.
+const VERBATIM_PREFIX: &str = r"\\?\";
+/// Verbatim form of a UNC share, e.g. `\\?\UNC\server\share`.
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Rewrites an absolute Windows path in verbatim form: `C:\a` becomes `\\?\C:\a` and
+/// `\\server\share\a` becomes `\\?\UNC\server\share\a`. Verbatim paths skip Win32
+/// normalization, so forward slashes are converted here.
+fn to_verbatim(path: &str) -> String {
+    if path.starts_with(VERBATIM_PREFIX) {
+        return path.to_string();
+    }
+    let path = path.replace('/', "\\");
+    match path.strip_prefix(r"\\") {
+        Some(share) => format!("{}{}", VERBATIM_UNC_PREFIX, share),
+        None => format!("{}{}", VERBATIM_PREFIX, path),
+    }
+}
+
+/// Undoes `to_verbatim`, giving the familiar form used for display and as a document key.
+fn from_verbatim(path: &str) -> String {
+    if let Some(share) = path.strip_prefix(VERBATIM_UNC_PREFIX) {
+        format!(r"\\{}", share)
+    } else if let Some(rest) = path.strip_prefix(VERBATIM_PREFIX) {
+        rest.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Resolves `.` and `..` components without touching the file system, for paths
+/// that can't be canonicalized because they no longer exist.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns the form of a path to hand to file system calls. On Windows, absolute paths
+/// are converted to verbatim (`\\?\`) form so deep folder trees and UNC shares beyond
+/// MAX_PATH still open. Elsewhere the path is returned unchanged.
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    if !cfg!(windows) || !path.is_absolute() {
+        return Cow::Borrowed(path);
+    }
+    // Verbatim paths are taken literally, so `.` and `..` must already be resolved
+    if path.components().any(|c| matches!(c, Component::CurDir | Component::ParentDir)) {
+        return Cow::Borrowed(path);
+    }
+    Cow::Owned(PathBuf::from(to_verbatim(&path.to_string_lossy())))
+}
+
+/// Returns the string used to identify a file as a document: the verbatim prefix that
+/// Windows APIs such as `canonicalize` add is stripped so the same file always has one key.
+pub fn display_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if cfg!(windows) {
+        from_verbatim(&path)
+    } else {
+        path.into_owned()
+    }
+}
+
+/// Resolves a local file path to the single form stored as its document key: symlinks,
+/// `.` and `..` are resolved and the file system's own casing is used, so aliases of one
+/// file don't become duplicate documents. Non-file paths such as URLs are left unchanged.
+pub fn canonical_path(path: &str) -> String {
+    let file_path = Path::new(path);
+    if !file_path.is_absolute() {
+        return path.to_string();
+    }
+    match std::fs::canonicalize(long_path(file_path)) {
+        Ok(canonical) => display_path(&canonical),
+        Err(_) => display_path(&normalize_lexically(file_path)),
+    }
+}
+
+/// Returns the key used to decide whether two stored documents are the same file: their
+/// path, case-folded on macOS and Windows whose file systems ignore case by default.
+/// Stored paths were already resolved by `canonical_path` at index time, so this never
+/// touches the file system and is cheap enough to run for every search result.
+pub fn path_key(stored_path: &str) -> String {
+    if cfg!(any(target_os = "macos", windows)) {
+        stored_path.to_lowercase()
+    } else {
+        stored_path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbatim_round_trip() {
+        assert_eq!(to_verbatim(r"C:\Users\me\doc.txt"), r"\\?\C:\Users\me\doc.txt");
+        assert_eq!(to_verbatim("C:/Users/me/doc.txt"), r"\\?\C:\Users\me\doc.txt");
+        assert_eq!(to_verbatim(r"\\nas\share\doc.txt"), r"\\?\UNC\nas\share\doc.txt");
+        assert_eq!(to_verbatim(r"\\?\C:\already"), r"\\?\C:\already");
+
+        assert_eq!(from_verbatim(r"\\?\C:\Users\me\doc.txt"), r"C:\Users\me\doc.txt");
+        assert_eq!(from_verbatim(r"\\?\UNC\nas\share\doc.txt"), r"\\nas\share\doc.txt");
+        assert_eq!(from_verbatim("/home/me/doc.txt"), "/home/me/doc.txt");
+    }
+
+    #[test]
+    fn test_canonical_path_without_file() {
+        if cfg!(unix) {
+            assert_eq!(canonical_path("/no/such/./dir/../doc.txt"), "/no/such/doc.txt");
+        }
+        assert_eq!(canonical_path("https://example.com/a/../b"), "https://example.com/a/../b");
+
+        // Keys come from stored paths alone, so a missing file still has one
+        assert_eq!(path_key("/no/such/doc.txt") == path_key("/no/such/Doc.txt"), cfg!(any(target_os = "macos", windows)));
+    }
+}
@@ -0,0 +1,106 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::file_ingest::LOCAL_FILE_SOURCE;
+use crate::settings::app_data_dir;
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+/// zstd level for stored text. Plain text compresses about 3-4x at this level, and
+/// writing stays fast enough to keep up with indexing.
+const COMPRESSION_LEVEL: i32 = 3;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Keeps the full parsed text of documents, zstd-compressed with one file per document,
+/// so the reader can show sources whose originals are remote without fetching them again.
+pub struct FullTextStore {
+    dir: PathBuf,
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl FullTextStore {
+    /// Opens the store in the app data directory.
+    pub fn open() -> Result<Self> {
+        Ok(Self::open_at(app_data_dir()?.join("full_text")))
+    }
+
+    fn open_at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the file a document's text is kept in, named by a hash of its path.
+    fn file_for(&self, path: &str) -> PathBuf {
+        let key = format!("{:x}", Sha256::digest(path.as_bytes()));
+        self.dir.join(format!("{}.txt.zst", key))
+    }
+
+    /// Stores a document's text, replacing any earlier version. Written via a temporary
+    /// file so a crash never leaves a truncated one.
+    pub fn put(&self, path: &str, text: &str) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let file = self.file_for(path);
+        let tmp_file = file.with_extension("zst.tmp");
+        std::fs::write(&tmp_file, zstd::encode_all(text.as_bytes(), COMPRESSION_LEVEL)?)?;
+        std::fs::rename(&tmp_file, &file)?;
+        Ok(())
+    }
+
+    /// Returns a document's stored text, or None if it wasn't stored.
+    pub fn get(&self, path: &str) -> Result<Option<String>> {
+        let compressed = match std::fs::read(self.file_for(path)) {
+            Ok(compressed) => compressed,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let text = zstd::decode_all(compressed.as_slice())?;
+        Ok(Some(String::from_utf8(text)?))
+    }
+
+    /// Forgets a document's text. Does nothing if none was stored.
+    pub fn remove(&self, path: &str) -> Result<()> {
+        match std::fs::remove_file(self.file_for(path)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns true for sources worth keeping the text of: everything except local files,
+/// which can be re-parsed from disk whenever the reader opens them.
+pub fn should_store(source_type: &str) -> bool {
+    source_type != LOCAL_FILE_SOURCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_get_remove() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let store = FullTextStore::open_at(dir.to_path_buf());
+        let path = "gmail://msg/42";
+        let text = "Hi team,\n\nThe offsite moves to Thursday. ".repeat(200);
+
+        assert_eq!(store.get(path).unwrap(), None);
+        store.put(path, &text).unwrap();
+        assert_eq!(store.get(path).unwrap().as_deref(), Some(text.as_str()));
+        assert!(std::fs::metadata(store.file_for(path)).unwrap().len() < text.len() as u64 / 4);
+
+        store.remove(path).unwrap();
+        assert_eq!(store.get(path).unwrap(), None);
+        store.remove(path).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::fs_paths::{display_path, long_path};
+use crate::search_orchestrator::RawDocument;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source type of commits and branch listings read from local git repositories.
+pub const GIT_SOURCE: &str = "git";
+
+/// README file names looked for at the repository root, in order of preference.
+const README_NAMES: &[&str] = &["README.md", "README.txt", "readme.md", "Readme.md"];
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Everything indexed from one repository.
+#[derive(Default)]
+pub struct RepositoryScan {
+    /// One document per commit on the current branch, newest first.
+    pub commits: Vec<RawDocument>,
+    /// A single document listing the local branch names.
+    pub branches: Option<RawDocument>,
+    /// The README at the root of the work tree, indexed as a regular file.
+    pub readme: Option<PathBuf>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the repository's folder name, used in titles.
+fn repository_name(root: &Path) -> String {
+    root.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| display_path(root))
+}
+
+/// Builds the document key of a repository or one of its commits. A `git+file` URL
+/// rather than a plain path, so these documents aren't mistaken for local files.
+fn document_key(root: &Path, commit: Option<&str>) -> String {
+    let base = format!("git+file://{}", display_path(root).replace('\\', "/"));
+    match commit {
+        Some(commit) => format!("{}#{}", base, commit),
+        None => base,
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns the document key of a commit, so callers can skip commits already indexed.
+pub fn commit_key(root: &Path, commit: &str) -> String {
+    document_key(root, Some(commit))
+}
+
+/// Reads up to `commit_limit` commits reachable from HEAD, the local branch names and the
+/// README of a repository. `is_indexed` is asked for each commit key; commits never
+/// change, so the walk stops at the first one already in the index.
+pub fn scan_repository(path: &Path, commit_limit: usize, is_indexed: impl Fn(&str) -> bool) -> Result<RepositoryScan> {
+    let repo = gix::discover(long_path(path))
+        .map_err(|e| anyhow::anyhow!("{} is not a git repository: {}", path.display(), e))?;
+    let root = repo.work_dir().unwrap_or_else(|| repo.git_dir()).to_path_buf();
+    let root = PathBuf::from(display_path(&root));
+    let name = repository_name(&root);
+    let mut scan = RepositoryScan::default();
+
+    // 1. Commits on the current branch. An unborn HEAD (no commits yet) has none.
+    if let Ok(head) = repo.head_id() {
+        for info in head.ancestors().all()?.take(commit_limit) {
+            let info = info?;
+            let sha = info.id.to_string();
+            let path = commit_key(&root, &sha);
+            if is_indexed(&path) {
+                break;
+            }
+
+            let commit = info.object()?;
+            let message = commit.message_raw_sloppy().to_string();
+            let summary = message.lines().next().unwrap_or_default().trim().to_string();
+            let author = commit.author()?;
+            let seconds = commit.time()?.seconds.max(0) as u64;
+
+            let mut metadata = BTreeMap::new();
+            metadata.insert("repository".to_string(), name.clone());
+            metadata.insert("commit".to_string(), sha.clone());
+            scan.commits.push(RawDocument {
+                path,
+                title: format!("{} ({})", summary, &sha[..7.min(sha.len())]),
+                body: message.trim().to_string(),
+                summary: None,
+                source_type: GIT_SOURCE.to_string(),
+                author: Some(format!("{} <{}>", author.name, author.email)),
+                modified_date: UNIX_EPOCH + Duration::from_secs(seconds),
+                metadata,
+            });
+        }
+    }
+
+    // 2. Local branch names, so a branch named after a ticket is findable too.
+    let branches: Vec<String> = repo
+        .references()?
+        .local_branches()?
+        .flatten()
+        .map(|reference| reference.name().shorten().to_string())
+        .collect();
+    if !branches.is_empty() {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("repository".to_string(), name.clone());
+        scan.branches = Some(RawDocument {
+            path: document_key(&root, None),
+            title: format!("{} branches", name),
+            body: branches.join("\n"),
+            summary: None,
+            source_type: GIT_SOURCE.to_string(),
+            author: None,
+            modified_date: SystemTime::now(),
+            metadata,
+        });
+    }
+
+    // 3. The README, if the repository has a work tree.
+    if repo.work_dir().is_some() {
+        scan.readme = README_NAMES
+            .iter()
+            .map(|readme| root.join(readme))
+            .find(|readme| long_path(readme).is_file());
+    }
+    Ok(scan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_key() {
+        let root = Path::new("/home/me/code/app");
+        assert_eq!(document_key(root, None), "git+file:///home/me/code/app");
+        assert_eq!(commit_key(root, "abc123"), "git+file:///home/me/code/app#abc123");
+    }
+}
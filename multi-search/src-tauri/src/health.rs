@@ -0,0 +1,111 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use serde::Serialize;
+use std::time::Duration;
+
+/// The search engine as a whole, failed when it couldn't start at all.
+pub const SEARCH_ENGINE: &str = "search_engine";
+/// The keyword index, checked by opening it and running a tiny query.
+pub const KEYWORD_INDEX: &str = "keyword_index";
+/// The embedding model, checked by embedding a short phrase.
+pub const EMBEDDING_MODEL: &str = "embedding_model";
+/// The vector store, checked by searching it with that embedding.
+pub const VECTOR_STORE: &str = "vector_store";
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// How one part of the search engine fared in the startup self-test.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentHealth {
+    pub component: &'static str,
+    pub healthy: bool,
+    /// What went wrong, for a failed check.
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// The result of the startup self-test, sent to the frontend as `startup-health`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub components: Vec<ComponentHealth>,
+    /// True when semantic search is off and only the keyword index is searched.
+    pub keyword_only: bool,
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl ComponentHealth {
+    /// Records a check that failed with the given error.
+    pub fn failed(component: &'static str, error: impl std::fmt::Display, elapsed: Duration) -> Self {
+        Self {
+            component,
+            healthy: false,
+            error: Some(error.to_string()),
+            elapsed_ms: elapsed.as_millis() as u64,
+        }
+    }
+
+    /// Records the outcome of checking a component.
+    pub fn from_check<T, E: std::fmt::Display>(component: &'static str, result: &Result<T, E>, elapsed: Duration) -> Self {
+        match result {
+            Ok(_) => Self { component, healthy: true, error: None, elapsed_ms: elapsed.as_millis() as u64 },
+            Err(e) => Self::failed(component, e, elapsed),
+        }
+    }
+}
+
+impl HealthReport {
+    /// Builds the report from every check run. Semantic search needs both the embedding
+    /// model and the vector store, so losing either leaves only keyword search.
+    pub fn new(components: Vec<ComponentHealth>) -> Self {
+        let keyword_only = components.iter()
+            .any(|check| !check.healthy && matches!(check.component, EMBEDDING_MODEL | VECTOR_STORE));
+        Self { components, keyword_only }
+    }
+
+    /// The first failed check of the vector stack, explaining keyword-only mode.
+    pub fn vector_stack_error(&self) -> Option<String> {
+        self.components.iter()
+            .filter(|check| matches!(check.component, EMBEDDING_MODEL | VECTOR_STORE))
+            .find_map(|check| check.error.clone())
+    }
+
+    /// Returns true if every component passed.
+    pub fn is_healthy(&self) -> bool {
+        self.components.iter().all(|check| check.healthy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_only_when_vector_stack_fails() {
+        let check = |component, result: Result<(), &str>| ComponentHealth::from_check(component, &result, Duration::from_millis(3));
+
+        let healthy = HealthReport::new(vec![check(KEYWORD_INDEX, Ok(())), check(EMBEDDING_MODEL, Ok(())), check(VECTOR_STORE, Ok(()))]);
+        assert!(healthy.is_healthy());
+        assert!(!healthy.keyword_only);
+        assert_eq!(healthy.vector_stack_error(), None);
+
+        let degraded = HealthReport::new(vec![
+            check(KEYWORD_INDEX, Ok(())),
+            check(EMBEDDING_MODEL, Ok(())),
+            check(VECTOR_STORE, Err("table not found")),
+        ]);
+        assert!(degraded.keyword_only);
+        assert_eq!(degraded.vector_stack_error().as_deref(), Some("table not found"));
+        assert_eq!(degraded.components[2].elapsed_ms, 3);
+
+        // A broken keyword index is reported, but doesn't turn semantic search off
+        let keyword_broken = HealthReport::new(vec![check(KEYWORD_INDEX, Err("locked"))]);
+        assert!(!keyword_broken.is_healthy());
+        assert!(!keyword_broken.keyword_only);
+    }
+}
@@ -0,0 +1,321 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::fs_paths::long_path;
+use crate::rate_limit::RateLimiter;
+use crate::search_orchestrator::RawDocument;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Source type of highlights imported from a Kindle `My Clippings.txt` file.
+pub const KINDLE_SOURCE: &str = "kindle";
+/// Source type of highlights synced from Readwise.
+pub const READWISE_SOURCE: &str = "readwise";
+
+/// Line that ends every entry in `My Clippings.txt`.
+const CLIPPING_SEPARATOR: &str = "==========";
+/// First page of the Readwise export API; later pages are reached through `nextPageCursor`.
+const READWISE_EXPORT_URL: &str = "https://readwise.io/api/v2/export/";
+const READWISE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// One highlight with the book it came from. Each highlight is indexed as its own
+/// document, so a remembered quote finds the exact passage rather than the whole book.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    /// Stable identifier, so importing the same highlight again replaces it.
+    pub path: String,
+    pub book_title: String,
+    pub book_author: Option<String>,
+    pub text: String,
+    pub note: Option<String>,
+    pub location: Option<String>,
+    pub highlighted_at: Option<SystemTime>,
+    pub source_type: &'static str,
+}
+
+impl Highlight {
+    /// Converts the highlight into a document titled by its book. The note is kept in the
+    /// body so searching for one's own comments finds the highlight too.
+    pub fn into_raw_document(self, fallback_date: SystemTime) -> RawDocument {
+        let body = match &self.note {
+            Some(note) => format!("{}\n\nNote: {}", self.text, note),
+            None => self.text.clone(),
+        };
+        RawDocument {
+            path: self.path,
+            title: self.book_title,
+            body,
+            summary: None,
+            source_type: self.source_type.to_string(),
+            author: self.book_author,
+            modified_date: self.highlighted_at.unwrap_or(fallback_date),
+            metadata: BTreeMap::new(),
+        }
+    }
+}
+
+/// A book in the Readwise export API response.
+#[derive(Debug, Deserialize)]
+struct ReadwiseBook {
+    #[serde(default)]
+    title: String,
+    author: Option<String>,
+    #[serde(default)]
+    highlights: Vec<ReadwiseHighlight>,
+}
+
+/// A highlight in the Readwise export API response.
+#[derive(Debug, Deserialize)]
+struct ReadwiseHighlight {
+    id: u64,
+    #[serde(default)]
+    text: String,
+    note: Option<String>,
+    location: Option<u64>,
+    highlighted_at: Option<String>,
+    readwise_url: Option<String>,
+    #[serde(default)]
+    is_deleted: bool,
+}
+
+/// One page of the Readwise export API response.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadwiseExportPage {
+    #[serde(default)]
+    results: Vec<ReadwiseBook>,
+    next_page_cursor: Option<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Splits a clipping's first line, "Book Title (Author Name)", into title and author.
+fn split_title_author(line: &str) -> (String, Option<String>) {
+    // The BOM Kindle writes at the start of the file ends up on the first title
+    let line = line.trim_start_matches('\u{feff}').trim();
+    if let (Some(open), true) = (line.rfind('('), line.ends_with(')')) {
+        let author = line[open + 1..line.len() - 1].trim();
+        let title = line[..open].trim();
+        if !author.is_empty() && !title.is_empty() {
+            return (title.to_string(), Some(author.to_string()));
+        }
+    }
+    (line.to_string(), None)
+}
+
+/// Parses the metadata line of a clipping, e.g.
+/// "- Your Highlight on page 12 | Location 180-182 | Added on Monday, March 4, 2024 3:14:15 PM".
+/// Returns the kind ("highlight", "note" or "bookmark"), the location and the date added.
+fn parse_clipping_meta(line: &str) -> (String, Option<String>, Option<SystemTime>) {
+    let mut kind = String::new();
+    let mut location = None;
+    let mut added = None;
+    for (index, part) in line.trim_start_matches('-').split('|').map(str::trim).enumerate() {
+        let lower = part.to_lowercase();
+        if index == 0 {
+            kind = ["highlight", "note", "bookmark"]
+                .into_iter()
+                .find(|kind| lower.contains(kind))
+                .unwrap_or("highlight")
+                .to_string();
+        }
+        if let Some(position) = lower.find("location ") {
+            location = Some(part[position + "location ".len()..].trim().to_string());
+        } else if location.is_none() && lower.contains("page ") {
+            location = lower.split("page ").nth(1).map(|page| format!("page {}", page.trim()));
+        }
+        if let Some(date) = part.strip_prefix("Added on ") {
+            added = NaiveDateTime::parse_from_str(date.trim(), "%A, %B %d, %Y %I:%M:%S %p")
+                .ok()
+                .map(|date| SystemTime::from(date.and_utc()));
+        }
+    }
+    (kind, location, added)
+}
+
+/// Returns the first number of a location such as "180-182".
+fn location_start(location: &str) -> Option<u64> {
+    location.split('-').next()?.trim().parse().ok()
+}
+
+/// Returns the last number of a location such as "180-182".
+fn location_end(location: &str) -> Option<u64> {
+    location.rsplit('-').next()?.trim().parse().ok()
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Parses the contents of a Kindle `My Clippings.txt` file. Bookmarks are dropped and
+/// notes are attached to the highlight they were written on; notes without one are kept
+/// as highlights of their own. Kindle appends a new entry each time a highlight is
+/// edited, so a later entry at the same location replaces the earlier one.
+pub fn parse_kindle_clippings(contents: &str) -> Vec<Highlight> {
+    let mut highlights: Vec<Highlight> = Vec::new();
+
+    for entry in contents.split(CLIPPING_SEPARATOR) {
+        let mut lines = entry.lines().map(str::trim).skip_while(|line| line.is_empty());
+        let (Some(title_line), Some(meta_line)) = (lines.next(), lines.next()) else {
+            continue;
+        };
+        let text = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+
+        let (book_title, book_author) = split_title_author(title_line);
+        let (kind, location, highlighted_at) = parse_clipping_meta(meta_line);
+        match kind.as_str() {
+            "bookmark" => continue,
+            "note" => {
+                // Kindle records a note at the last location of the highlight it belongs to
+                let note_at = location.as_deref().and_then(location_end);
+                let parent = highlights.iter_mut().rev().find(|highlight| {
+                    highlight.book_title == book_title
+                        && highlight.location.as_deref().and_then(location_end) == note_at
+                        && note_at.is_some()
+                });
+                if let Some(parent) = parent {
+                    parent.note = Some(text);
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        let anchor = location.as_deref()
+            .and_then(location_start)
+            .map(|start| start.to_string())
+            .unwrap_or_else(|| highlights.len().to_string());
+        let path = format!("kindle://{}#{}", book_title, anchor);
+        let highlight = Highlight {
+            path,
+            book_title,
+            book_author,
+            text,
+            note: None,
+            location,
+            highlighted_at,
+            source_type: KINDLE_SOURCE,
+        };
+        match highlights.iter_mut().find(|existing| existing.path == highlight.path) {
+            Some(existing) => *existing = highlight,
+            None => highlights.push(highlight),
+        }
+    }
+    highlights
+}
+
+/// Reads and parses a `My Clippings.txt` file copied from a Kindle.
+pub fn read_kindle_clippings(path: &Path) -> Result<Vec<Highlight>> {
+    let contents = std::fs::read_to_string(long_path(path))
+        .map_err(|e| anyhow::anyhow!("Failed to read clippings file {}: {}", path.display(), e))?;
+    Ok(parse_kindle_clippings(&contents))
+}
+
+/// One page of the Readwise export, with the cursor of the next page if there is one.
+pub struct ReadwisePage {
+    pub highlights: Vec<Highlight>,
+    pub next_page: Option<String>,
+}
+
+/// Downloads one page of highlights from the Readwise export API, starting at `page`
+/// (the first page when `None`), optionally only those updated after `updated_after`.
+/// Syncs page by page so progress can be saved after each one.
+pub async fn fetch_readwise_page(
+    token: &str,
+    updated_after: Option<SystemTime>,
+    page: Option<&str>,
+    limiter: &RateLimiter,
+) -> Result<ReadwisePage> {
+    let client = reqwest::Client::builder().timeout(READWISE_TIMEOUT).build()?;
+
+    // 1. Request the page of books with their highlights, within Readwise's rate limit.
+    let build_request = || {
+        let mut request = client
+            .get(READWISE_EXPORT_URL)
+            .header(reqwest::header::AUTHORIZATION, format!("Token {}", token));
+        if let Some(updated_after) = updated_after {
+            request = request.query(&[("updatedAfter", DateTime::<Utc>::from(updated_after).to_rfc3339())]);
+        }
+        if let Some(page) = page {
+            request = request.query(&[("pageCursor", page)]);
+        }
+        request
+    };
+    let export: ReadwiseExportPage = limiter.send(build_request).await?.error_for_status()?.json().await?;
+
+    // 2. Flatten the books into one highlight per document.
+    let mut highlights = Vec::new();
+    for book in export.results {
+        for highlight in book.highlights {
+            if highlight.is_deleted || highlight.text.trim().is_empty() {
+                continue;
+            }
+            highlights.push(Highlight {
+                path: highlight
+                    .readwise_url
+                    .unwrap_or_else(|| format!("https://readwise.io/open/{}", highlight.id)),
+                book_title: book.title.clone(),
+                book_author: book.author.clone().filter(|author| !author.is_empty()),
+                text: highlight.text,
+                note: highlight.note.filter(|note| !note.trim().is_empty()),
+                location: highlight.location.map(|location| location.to_string()),
+                highlighted_at: highlight
+                    .highlighted_at
+                    .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+                    .map(SystemTime::from),
+                source_type: READWISE_SOURCE,
+            });
+        }
+    }
+    Ok(ReadwisePage {
+        highlights,
+        next_page: export.next_page_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kindle_clippings() {
+        let contents = "\u{feff}Deep Work (Cal Newport)\n\
+            - Your Highlight on page 12 | Location 180-182 | Added on Monday, March 4, 2024 3:14:15 PM\n\
+            \n\
+            Clarity about what matters provides clarity about what does not.\n\
+            ==========\n\
+            Deep Work (Cal Newport)\n\
+            - Your Note on page 12 | Location 182 | Added on Monday, March 4, 2024 3:15:00 PM\n\
+            \n\
+            Use for the intro.\n\
+            ==========\n\
+            Deep Work (Cal Newport)\n\
+            - Your Bookmark on page 20 | Location 301 | Added on Monday, March 4, 2024 3:20:00 PM\n\
+            \n\
+            \n\
+            ==========\n";
+
+        let highlights = parse_kindle_clippings(contents);
+        assert_eq!(highlights.len(), 1);
+        let highlight = &highlights[0];
+        assert_eq!(highlight.path, "kindle://Deep Work#180");
+        assert_eq!(highlight.book_title, "Deep Work");
+        assert_eq!(highlight.book_author.as_deref(), Some("Cal Newport"));
+        assert_eq!(highlight.note.as_deref(), Some("Use for the intro."));
+        assert_eq!(highlight.location.as_deref(), Some("180-182"));
+        assert!(highlight.highlighted_at.is_some());
+    }
+}
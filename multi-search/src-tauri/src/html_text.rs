@@ -0,0 +1,127 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use scraper::{ElementRef, Html, Selector};
+
+/// Containers that usually hold a page's main content, in order of preference.
+const CONTENT_SELECTORS: &[&str] = &["article", "main", "[role=main]", "body"];
+/// Meta tags that hold a page's description, in order of preference.
+const DESCRIPTION_SELECTORS: &[&str] = &[
+    "meta[name=description]",
+    "meta[property='og:description']",
+    "meta[name='twitter:description']",
+];
+/// Elements whose text is navigation, chrome, or code rather than content.
+const BOILERPLATE_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "nav", "header", "footer", "aside", "form",
+    "button", "iframe",
+];
+/// Elements that start a new line of text.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "section", "article", "li", "tr", "br", "h1", "h2", "h3", "h4", "h5", "h6",
+    "blockquote", "pre", "dt", "dd", "figcaption",
+];
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// The parts of an HTML page worth indexing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HtmlPage {
+    /// The `<title>` element, if the page has a non-empty one.
+    pub title: Option<String>,
+    /// The page's meta description, used as its summary.
+    pub description: Option<String>,
+    /// The readable text of the main content, without markup or boilerplate.
+    pub body: String,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Collapses the whitespace HTML formatting leaves behind within each line.
+fn collapse_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Collects the readable text under `root`, keeping block boundaries as line breaks.
+fn readable_text(root: ElementRef<'_>) -> String {
+    let mut text = String::new();
+    for node in root.descendants() {
+        if let Some(element) = ElementRef::wrap(node) {
+            if BLOCK_ELEMENTS.contains(&element.value().name()) && !text.ends_with('\n') {
+                text.push('\n');
+            }
+        } else if let Some(fragment) = node.value().as_text() {
+            // Skip text inside a boilerplate element below `root`
+            let in_boilerplate = node.ancestors()
+                .take_while(|ancestor| ancestor.id() != root.id())
+                .filter_map(ElementRef::wrap)
+                .any(|element| BOILERPLATE_ELEMENTS.contains(&element.value().name()));
+            if !in_boilerplate {
+                text.push_str(fragment);
+            }
+        }
+    }
+    collapse_whitespace(&text)
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Parses an HTML page into its title, description and main text, dropping markup,
+/// navigation, scripts and other boilerplate.
+pub fn parse_html(html: &str) -> HtmlPage {
+    let document = Html::parse_document(html);
+
+    let title = Selector::parse("title").ok()
+        .and_then(|selector| document.select(&selector).next())
+        .map(|title| collapse_whitespace(&title.text().collect::<String>()).replace('\n', " "))
+        .filter(|title| !title.is_empty());
+
+    let description = DESCRIPTION_SELECTORS.iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .flat_map(|selector| document.select(&selector).collect::<Vec<_>>())
+        .filter_map(|meta| meta.value().attr("content"))
+        .map(|content| content.split_whitespace().collect::<Vec<_>>().join(" "))
+        .find(|content| !content.is_empty());
+
+    let body = CONTENT_SELECTORS.iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .find_map(|selector| document.select(&selector).next())
+        .map(readable_text)
+        .unwrap_or_default();
+
+    HtmlPage { title, description, body }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_html() {
+        let html = r#"<!doctype html><html><head>
+            <title>Team
+              handbook</title>
+            <meta property="og:description" content="Social card text">
+            <meta name="description" content="  How the team   works. ">
+            <style>body { color: red }</style></head>
+            <body><header>Intranet</header><div><h1>Handbook</h1><p>Be <b>kind</b>.</p></div></body></html>"#;
+
+        let page = parse_html(html);
+        assert_eq!(page.title.as_deref(), Some("Team handbook"));
+        assert_eq!(page.description.as_deref(), Some("How the team works."));
+        assert_eq!(page.body, "Handbook\nBe kind.");
+
+        let fragment = parse_html("<p>Just a <i>fragment</i></p>");
+        assert_eq!(fragment, HtmlPage { title: None, description: None, body: "Just a fragment".to_string() });
+    }
+}
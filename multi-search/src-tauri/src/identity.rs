@@ -0,0 +1,83 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use serde::{Deserialize, Serialize};
+
+// ===================================================================
+//  PUBLIC STRUCT
+// ===================================================================
+
+/// One person and every way sources refer to them, e.g. "Alice Smith" with the
+/// variants "A. Smith" and "asmith@corp.com". Edited by the user in settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Identity {
+    pub name: String,
+    #[serde(default)]
+    pub variants: Vec<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the forms of an author string worth matching: the whole string and, for
+/// git-style "Alice Smith <asmith@corp.com>" authors, the name and the email separately.
+fn author_keys(author: &str) -> Vec<String> {
+    let mut keys = vec![author.trim().to_lowercase()];
+    if let (Some(open), Some(close)) = (author.find('<'), author.rfind('>')) {
+        if open < close {
+            keys.push(author[..open].trim().to_lowercase());
+            keys.push(author[open + 1..close].trim().to_lowercase());
+        }
+    }
+    keys.retain(|key| !key.is_empty());
+    keys
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Finds the identity an author string refers to, comparing case-insensitively
+/// against each identity's name and variants.
+pub fn resolve_author<'a>(author: &str, identities: &'a [Identity]) -> Option<&'a Identity> {
+    let keys = author_keys(author);
+    identities.iter().find(|identity| {
+        std::iter::once(&identity.name)
+            .chain(identity.variants.iter())
+            .any(|name| keys.contains(&name.trim().to_lowercase()))
+    })
+}
+
+/// Returns the additional names to index alongside an author, so that searching
+/// `author:alice` also finds documents written as "A. Smith" or "asmith@corp.com".
+pub fn author_aliases(author: &str, identities: &[Identity]) -> Vec<String> {
+    match resolve_author(author, identities) {
+        Some(identity) => std::iter::once(&identity.name)
+            .chain(identity.variants.iter())
+            .filter(|name| !name.eq_ignore_ascii_case(author.trim()))
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_author_aliases() {
+        let identities = vec![Identity {
+            name: "Alice Smith".to_string(),
+            variants: vec!["A. Smith".to_string(), "asmith@corp.com".to_string()],
+        }];
+
+        assert_eq!(author_aliases("a. smith", &identities), vec!["Alice Smith", "asmith@corp.com"]);
+        assert_eq!(
+            author_aliases("Al <ASmith@corp.com>", &identities),
+            vec!["Alice Smith", "A. Smith", "asmith@corp.com"]
+        );
+        assert!(author_aliases("Bob Jones", &identities).is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// How many events a slow subscriber may fall behind before it starts missing them.
+const EVENT_BUFFER: usize = 1024;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// What happened to a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexEventKind {
+    Added,
+    Updated,
+    Deleted,
+}
+
+/// A change to the indexes, published after both stores have been written.
+/// Also the payload of the `index-changed` event sent to the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexEvent {
+    pub kind: IndexEventKind,
+    pub path: String,
+    /// The document's source type; unknown for deletions.
+    pub source_type: Option<String>,
+}
+
+/// Fans index changes out to any number of subscribers, so the code that writes
+/// documents doesn't need to know who reacts to them.
+pub struct IndexEventBus {
+    sender: broadcast::Sender<IndexEvent>,
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl Default for IndexEventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+}
+
+impl IndexEventBus {
+    /// Returns a receiver for every event published from now on. A receiver that lags
+    /// behind gets `RecvError::Lagged` and should treat it as "the index changed".
+    pub fn subscribe(&self) -> broadcast::Receiver<IndexEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publishes an event. Having no subscribers is not an error.
+    pub fn publish(&self, kind: IndexEventKind, path: &str, source_type: Option<&str>) {
+        let _ = self.sender.send(IndexEvent {
+            kind,
+            path: path.to_string(),
+            source_type: source_type.map(str::to_string),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_reach_every_subscriber() {
+        let bus = IndexEventBus::default();
+        bus.publish(IndexEventKind::Added, "/unseen.txt", None);
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        bus.publish(IndexEventKind::Updated, "/notes.md", Some("file"));
+
+        let event = first.try_recv().unwrap();
+        assert_eq!(event.kind, IndexEventKind::Updated);
+        assert_eq!(event.source_type.as_deref(), Some("file"));
+        assert_eq!(second.try_recv().unwrap(), event);
+        assert!(first.try_recv().is_err());
+    }
+}
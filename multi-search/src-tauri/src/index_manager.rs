@@ -1,9 +1,62 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::path::Path;
+use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tantivy::collector::TopDocs;
-use tantivy::query::{QueryParser, TermQuery};
-use tantivy::schema::{Schema, TEXT, STORED, FAST, Field, Value};
+use tantivy::collector::{Count, DocSetCollector, TopDocs};
+use tantivy::merge_policy::{LogMergePolicy, MergePolicy, NoMergePolicy};
+use tantivy::query::{AllQuery, BooleanQuery, BoostQuery, Occur, PhraseQuery, Query, QueryParser, RangeQuery, RegexQuery, TermQuery};
+use tantivy::tokenizer::TokenStream;
+use tantivy::schema::{Schema, TEXT, STORED, FAST, INDEXED, Field, Value, TextOptions, TextFieldIndexing, IndexRecordOption, JsonObjectOptions, OwnedValue, Type};
 // Import the concrete `TantivyDocument` struct and the `doc!` macro
-use tantivy::{doc, Index, IndexWriter, DateTime, TantivyDocument, Term};
+use tantivy::{doc, Index, IndexReader, IndexWriter, DateTime, ReloadPolicy, TantivyDocument, Term};
+use crate::query_preprocessor::{free_text_words, qualify_metadata_fields};
+use crate::text_normalization::{build_analyzer, expand_emoji_shortcodes, NORMALIZED_TOKENIZER};
+
+/// How many positions apart query words may be and still earn the proximity boost.
+const PROXIMITY_SLOP: u32 = 3;
+/// Score multiplier of the clause matching query words near each other.
+const PROXIMITY_BOOST: f32 = 1.5;
+/// Score multiplier of the clause matching the query words as an exact phrase. Exact
+/// phrases also match the proximity clause, so they outrank near matches.
+const EXACT_PHRASE_BOOST: f32 = 2.0;
+/// Weight of matches on a document's extracted keywords relative to title and body
+/// matches; a light nudge towards documents that are about the query.
+const KEYWORDS_FIELD_BOOST: f32 = 1.5;
+/// Bytes of a document's text stored for result previews, so rendering one doesn't
+/// re-parse the file.
+const PREVIEW_MAX_BYTES: usize = 2048;
+/// Smallest writer memory budget Tantivy accepts.
+const MIN_WRITER_HEAP: usize = 15_000_000;
+/// Largest writer memory budget picked automatically, for big batches.
+const MAX_AUTO_WRITER_HEAP: usize = 200_000_000;
+/// Segments whose share of deleted documents passes this are merged to reclaim them.
+/// Every update deletes a document's previous version, so deletions add up quickly.
+const MERGE_DELETED_RATIO: f32 = 0.2;
+/// Byte ending the key of a term in a JSON field, before the value's type code.
+const JSON_END_OF_PATH: u8 = 0;
+/// Most common values listed per metadata field.
+const FIELD_VALUES_LIMIT: usize = 10;
+
+/// How the keyword index writer is sized and when segments are merged. Read at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexTuning {
+    /// Writer memory budget in megabytes. `None` sizes it from the documents being written.
+    pub writer_heap_mb: Option<usize>,
+    /// Segments of a similar size merged at once. Most commits add a single document, so
+    /// this is lower than Tantivy's default to keep searches from visiting many tiny segments.
+    pub merge_min_segments: usize,
+}
+
+/// Restricts a search to documents modified from `after` (inclusive) until `before`
+/// (exclusive), both Unix seconds. Either end may be left open.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DateRange {
+    pub after: Option<u64>,
+    pub before: Option<u64>,
+}
 
 /// Represents a document from any source, ready to be indexed.
 #[derive(Debug, Clone)]
@@ -14,8 +67,14 @@ pub struct IndexableDocument {
     pub body: String,
     pub source_type: String,
     pub author: Option<String>,
+    /// Other names of the author from the identity table, indexed so any of them matches.
+    pub author_aliases: Vec<String>,
     pub modified_date: SystemTime,
     pub content_hash: String,
+    /// Source-specific fields such as a paper's year or journal, filterable as `year:2020`.
+    pub metadata: BTreeMap<String, String>,
+    /// Salient phrases extracted at indexing time, filterable as `keywords:"query latency"`.
+    pub keywords: Vec<String>,
 }
 
 /// A struct to hold the results of a search query.
@@ -27,62 +86,260 @@ pub struct SearchResult {
     pub score: f32,
     pub source_type: String,
     pub modified_date: SystemTime,
+    pub keywords: Vec<String>,
+    /// The start of the document's text, None if the index predates previews.
+    pub preview: Option<String>,
+    /// SHA-256 of the indexed text, for telling whether a document changed.
+    #[serde(skip)]
+    pub content_hash: String,
+}
+
+/// A key found in document metadata, such as a note's `status` frontmatter. Filterable
+/// as `status:draft` like a built-in field, though the schema never declared it.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct MetadataField {
+    pub name: String,
+    /// Its most common indexed terms, most frequent first, for suggesting filters.
+    pub values: Vec<String>,
+}
+
+/// The top keyword matches along with the total number of matching documents.
+#[derive(Debug, Clone)]
+pub struct KeywordSearchResults {
+    pub results: Vec<SearchResult>,
+    pub total_hits: usize,
 }
 
 /// Manages the Tantivy keyword index.
 #[allow(dead_code)]
 pub struct IndexManager {
     index: Index,
+    // A single long-lived reader shared by all searches; each search takes a cheap searcher from it
+    reader: IndexReader,
     path_field: Field,
     title_field: Field,
     body_field: Field,
     source_type_field: Field,
     author_field: Field,
     modified_date_field: Field,
+    // False for indexes created before dates were indexed; date ranges are then left to the caller
+    modified_date_indexed: bool,
     content_hash_field: Field,
+    // `None` for indexes created before metadata fields existed, until they are rebuilt
+    metadata_field: Option<Field>,
+    // `None` for indexes created before keyword extraction, until they are rebuilt
+    keywords_field: Option<Field>,
+    preview_field: Option<Field>,
+    tuning: IndexTuning,
+    // Only one writer may hold the index at a time; a merge would otherwise make writes fail
+    write_lock: Mutex<()>,
+}
+
+impl Default for IndexTuning {
+    fn default() -> Self {
+        Self { writer_heap_mb: None, merge_min_segments: 4 }
+    }
+}
+
+/// Adds the document's metadata to the JSON field, if the index has one.
+fn add_metadata(tantivy_doc: &mut TantivyDocument, metadata_field: Option<Field>, metadata: &BTreeMap<String, String>) {
+    let Some(field) = metadata_field else { return };
+    if metadata.is_empty() {
+        return;
+    }
+    let object = metadata
+        .iter()
+        .map(|(key, value)| (key.clone(), OwnedValue::Str(value.clone())))
+        .collect();
+    tantivy_doc.add_object(field, object);
+}
+
+/// Adds the document's extracted keywords, if the index has a field for them.
+fn add_keywords(tantivy_doc: &mut TantivyDocument, keywords_field: Option<Field>, keywords: &[String]) {
+    let Some(field) = keywords_field else { return };
+    for keyword in keywords {
+        tantivy_doc.add_text(field, keyword);
+    }
+}
+
+/// Stores the start of the document's text, cut at a word boundary, if the index has a
+/// field for it.
+fn add_preview(tantivy_doc: &mut TantivyDocument, preview_field: Option<Field>, body: &str) {
+    let Some(field) = preview_field else { return };
+    let body = body.trim_start();
+    let preview = if body.len() <= PREVIEW_MAX_BYTES {
+        body
+    } else {
+        let mut end = PREVIEW_MAX_BYTES;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        let cut = &body[..end];
+        cut.rfind(char::is_whitespace).map_or(cut, |space| &cut[..space])
+    };
+    tantivy_doc.add_text(field, preview.trim_end());
+}
+
+/// Splits a term of the metadata field into its key and value. Terms are the key, an
+/// end-of-path byte and the value's type code, then the value; only text values are kept.
+fn split_metadata_term(term: &[u8]) -> Option<(&str, &str)> {
+    let end_of_path = term.iter().position(|&byte| byte == JSON_END_OF_PATH)?;
+    let (key, rest) = term.split_at(end_of_path);
+    let value = rest.get(1..)?.strip_prefix(&[Type::Str.to_code()])?;
+    Some((std::str::from_utf8(key).ok()?, std::str::from_utf8(value).ok()?))
+}
+
+/// Reads the stored keywords of a retrieved document.
+fn stored_keywords(tantivy_doc: &TantivyDocument, keywords_field: Option<Field>) -> Vec<String> {
+    let Some(field) = keywords_field else { return Vec::new() };
+    tantivy_doc.get_all(field).filter_map(|value| value.as_str()).map(str::to_string).collect()
 }
 
 #[allow(dead_code)]
 impl IndexManager {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let data_dir = dirs::data_dir().ok_or("Could not find application data directory")?;
-        let index_path = data_dir.join("multi-search").join("keyword_index");
-        std::fs::create_dir_all(&index_path)?;
+    /// Opens or creates the keyword index. `fold_diacritics` controls whether accents
+    /// are folded away (so "résumé" matches "resume") for both indexing and queries.
+    pub fn new(index_path: &Path, fold_diacritics: bool, tuning: IndexTuning) -> Result<Self, Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(index_path)?;
 
         let mut schema_builder = Schema::builder();
 
-        let path_field = schema_builder.add_text_field("path", TEXT | STORED | FAST);
-        let title_field = schema_builder.add_text_field("title", TEXT | STORED);
-        let body_field = schema_builder.add_text_field("body", TEXT);
-        let source_type_field = schema_builder.add_text_field("source_type", TEXT | STORED | FAST);
-        let author_field = schema_builder.add_text_field("author", TEXT | STORED);
-        let modified_date_field = schema_builder.add_date_field("modified_date", STORED);
-        let content_hash_field = schema_builder.add_text_field("content_hash", TEXT | STORED | FAST);
+        // Human-language fields go through the normalizing analyzer; identifiers keep the default one
+        let normalized_text = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(NORMALIZED_TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+
+        schema_builder.add_text_field("path", TEXT | STORED | FAST);
+        schema_builder.add_text_field("title", normalized_text.clone() | STORED);
+        schema_builder.add_text_field("body", normalized_text.clone());
+        schema_builder.add_text_field("source_type", TEXT | STORED | FAST);
+        schema_builder.add_text_field("author", normalized_text.clone() | STORED);
+        schema_builder.add_date_field("modified_date", INDEXED | STORED | FAST);
+        schema_builder.add_text_field("content_hash", TEXT | STORED | FAST);
+        let metadata_options = JsonObjectOptions::default()
+            .set_stored()
+            .set_indexing_options(normalized_text.get_indexing_options().cloned().unwrap_or_default());
+        schema_builder.add_json_field("metadata", metadata_options);
+        schema_builder.add_text_field("keywords", normalized_text.clone() | STORED);
+        schema_builder.add_text_field("preview", STORED);
 
         let schema = schema_builder.build();
 
-        let index = match Index::open_in_dir(&index_path) {
+        let index = match Index::open_in_dir(index_path) {
             Ok(index) => index,
-            Err(_) => Index::create_in_dir(&index_path, schema.clone())?,
+            Err(_) => Index::create_in_dir(index_path, schema.clone())?,
         };
+        Self::from_index(index, fold_diacritics, tuning)
+    }
+
+    /// Opens an existing keyword index without ever writing to it, e.g. one a team
+    /// publishes on a network share. Fails if there is no index in `index_path`.
+    pub fn open_read_only(index_path: &Path, fold_diacritics: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_index(Index::open_in_dir(index_path)?, fold_diacritics, IndexTuning::default())
+    }
+
+    /// Wraps an opened index, looking its fields up in the index's own schema.
+    fn from_index(index: Index, fold_diacritics: bool, tuning: IndexTuning) -> Result<Self, Box<dyn std::error::Error>> {
+        index.tokenizers().register(NORMALIZED_TOKENIZER, build_analyzer(fold_diacritics));
+        let schema = index.schema();
+        let path_field = schema.get_field("path")?;
+        let title_field = schema.get_field("title")?;
+        let body_field = schema.get_field("body")?;
+        let source_type_field = schema.get_field("source_type")?;
+        let author_field = schema.get_field("author")?;
+        let modified_date_field = schema.get_field("modified_date")?;
+        let content_hash_field = schema.get_field("content_hash")?;
+
+        // Optional fields may be missing from indexes that predate them
+        let metadata_field = schema.get_field("metadata").ok();
+        if metadata_field.is_none() {
+            eprintln!("Warning: Keyword index has no metadata field; rebuild it to filter by fields such as year");
+        }
+        let keywords_field = schema.get_field("keywords").ok();
+        if keywords_field.is_none() {
+            eprintln!("Warning: Keyword index has no keywords field; rebuild it for keyword facets");
+        }
+        let modified_date_indexed = schema.get_field_entry(modified_date_field).is_indexed();
+        if !modified_date_indexed {
+            eprintln!("Warning: Keyword index has no indexed modification dates; rebuild it to filter by date in the index");
+        }
+        let preview_field = schema.get_field("preview").ok();
+        if preview_field.is_none() {
+            eprintln!("Warning: Keyword index has no preview field; rebuild it for instant previews");
+        }
+
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
 
         Ok(IndexManager {
             index,
+            reader,
             path_field,
             title_field,
             body_field,
             source_type_field,
             author_field,
             modified_date_field,
+            modified_date_indexed,
             content_hash_field,
+            metadata_field,
+            keywords_field,
+            preview_field,
+            tuning,
+            write_lock: Mutex::new(()),
         })
     }
 
+    /// Opens a single-threaded writer, so a commit adds one segment rather than one per
+    /// thread. Without a configured budget, it is sized from the `text_bytes` about to be
+    /// written. Callers hold `write_lock` while it is open.
+    fn writer(&self, text_bytes: usize) -> Result<IndexWriter, Box<dyn std::error::Error>> {
+        let heap = match self.tuning.writer_heap_mb {
+            Some(mb) => (mb * 1_000_000).max(MIN_WRITER_HEAP),
+            None => (text_bytes * 4).clamp(MIN_WRITER_HEAP, MAX_AUTO_WRITER_HEAP),
+        };
+        let writer: IndexWriter = self.index.writer_with_num_threads(1, heap)?;
+        // Merges a writer starts are cancelled when it is dropped, and writers here last one
+        // commit, so merging is left to `merge_segments`
+        writer.set_merge_policy(Box::new(NoMergePolicy));
+        Ok(writer)
+    }
+
+    fn merge_policy(&self) -> LogMergePolicy {
+        let mut policy = LogMergePolicy::default();
+        policy.set_min_num_segments(self.tuning.merge_min_segments);
+        policy.set_del_docs_ratio_before_merge(MERGE_DELETED_RATIO);
+        policy
+    }
+
+    /// Merges segments the merge policy picks and deletes the files they leave behind.
+    /// Returns how many segments were merged away; zero when the index is in good shape.
+    pub fn merge_segments(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let _guard = self.write_lock.lock().unwrap();
+        let candidates = self.merge_policy().compute_merge_candidates(&self.index.searchable_segment_metas()?);
+        if candidates.is_empty() {
+            return Ok(0);
+        }
+        let mut writer = self.writer(0)?;
+        for candidate in &candidates {
+            writer.merge(&candidate.0).wait()?;
+        }
+        writer.garbage_collect_files().wait()?;
+        writer.wait_merging_threads()?;
+        self.reader.reload()?;
+        Ok(candidates.iter().map(|candidate| candidate.0.len()).sum())
+    }
+
     pub fn add_document_batch(
         &self,
         docs: Vec<IndexableDocument>,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut writer: IndexWriter = self.index.writer(100_000_000)?;
+        let _guard = self.write_lock.lock().unwrap();
+        let mut writer = self.writer(docs.iter().map(|doc| doc.body.len()).sum())?;
         for doc in docs {
             let timestamp_secs = doc.modified_date.duration_since(UNIX_EPOCH)?.as_secs() as i64;
             let datetime = DateTime::from_timestamp_secs(timestamp_secs);
@@ -98,58 +355,177 @@ impl IndexManager {
             if let Some(author) = &doc.author {
                 tantivy_doc.add_text(self.author_field, author);
             }
+            for alias in &doc.author_aliases {
+                tantivy_doc.add_text(self.author_field, alias);
+            }
+            add_metadata(&mut tantivy_doc, self.metadata_field, &doc.metadata);
+            add_keywords(&mut tantivy_doc, self.keywords_field, &doc.keywords);
+            add_preview(&mut tantivy_doc, self.preview_field, &doc.body);
             
             writer.add_document(tantivy_doc)?;
         }
         writer.commit()?;
+        // Reload eagerly so reads right after a write see it instead of waiting for the policy delay
+        self.reader.reload()?;
         Ok(())
     }
 
 
-    pub fn search(&self, query_str: &str) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
-        let reader = self.index.reader()?;
-        let searcher = reader.searcher();
+    /// Returns the top `limit` keyword matches plus the total hit count from a `Count` collector.
+    /// Matches modified outside `modified` are left out, if the index has dates indexed.
+    pub fn search(&self, query_str: &str, limit: usize, modified: DateRange) -> Result<KeywordSearchResults, Box<dyn std::error::Error>> {
+        let query_str = self.rewrite_query(query_str);
+        let query = self.with_proximity_boost(self.query_parser().parse_query(&query_str)?, &query_str)?;
+        let query = self.with_date_range(query, modified);
+        self.top_results(&query, limit)
+    }
 
-        let query_parser = QueryParser::for_index(
-            &self.index,
-            vec![self.title_field, self.body_field, self.author_field],
-        );
+    /// Matches documents with a title word starting with `prefix`, for queries too short
+    /// to search in full. A non-empty `filter`, e.g. `source_type:gmail`, is parsed like a
+    /// query and must match as well.
+    pub fn search_title_prefix(&self, prefix: &str, filter: &str, limit: usize, modified: DateRange) -> Result<KeywordSearchResults, Box<dyn std::error::Error>> {
+        // Normalize the prefix like indexed titles, e.g. lowercased and without accents
+        let Some(prefix) = self.analyze_text(prefix)?.into_iter().next() else {
+            return Ok(KeywordSearchResults { results: Vec::new(), total_hits: 0 });
+        };
+        let mut pattern = String::new();
+        for c in prefix.chars() {
+            if "\\.+*?()|[]{}^$#&-~".contains(c) {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        pattern.push_str(".*");
+        let mut query: Box<dyn Query> = Box::new(RegexQuery::from_pattern(&pattern, self.title_field)?);
+        if !filter.trim().is_empty() {
+            let filter = self.query_parser().parse_query(&self.rewrite_query(filter))?;
+            query = Box::new(BooleanQuery::new(vec![(Occur::Must, query), (Occur::Must, filter)]));
+        }
+        let query = self.with_date_range(query, modified);
+        self.top_results(&query, limit)
+    }
 
-        let query = query_parser.parse_query(query_str)?;
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(20))?;
+    /// A query parser over the title, body, author and keywords fields.
+    fn query_parser(&self) -> QueryParser {
+        let mut default_fields = vec![self.title_field, self.body_field, self.author_field];
+        default_fields.extend(self.keywords_field);
+        let mut query_parser = QueryParser::for_index(&self.index, default_fields);
+        if let Some(keywords_field) = self.keywords_field {
+            query_parser.set_field_boost(keywords_field, KEYWORDS_FIELD_BOOST);
+        }
+        query_parser
+    }
+
+    /// Prepares a query for the parser. Shortcodes like `:rocket:` would otherwise be read
+    /// as field syntax, and filters on unknown fields refer to document metadata.
+    fn rewrite_query(&self, query_str: &str) -> String {
+        let query_str = expand_emoji_shortcodes(query_str);
+        match self.metadata_field {
+            Some(_) => {
+                let schema = self.index.schema();
+                let known_fields: Vec<&str> = schema.fields().map(|(_, entry)| entry.name()).collect();
+                qualify_metadata_fields(&query_str, &known_fields)
+            }
+            None => query_str,
+        }
+    }
+
+    /// Runs a query, returning the top `limit` matches and how many there are in all.
+    fn top_results(&self, query: &dyn Query, limit: usize) -> Result<KeywordSearchResults, Box<dyn std::error::Error>> {
+        let searcher = self.reader.searcher();
+        let (top_docs, total_hits) = searcher.search(query, &(TopDocs::with_limit(limit), Count))?;
 
         let mut results = Vec::new();
         for (score, doc_address) in top_docs {
             // Retrieve the concrete `TantivyDocument` struct.
             let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
+            results.push(self.stored_result(&retrieved_doc, score));
+        }
+
+        Ok(KeywordSearchResults { results, total_hits })
+    }
+
+    /// Reads a retrieved document's stored fields into a result.
+    fn stored_result(&self, retrieved_doc: &TantivyDocument, score: f32) -> SearchResult {
+        // Use the correct `.as_str()` method to extract the text.
+        let path = retrieved_doc.get_first(self.path_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let title = retrieved_doc.get_first(self.title_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let source_type = retrieved_doc.get_first(self.source_type_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let content_hash = retrieved_doc.get_first(self.content_hash_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let modified_date = retrieved_doc.get_first(self.modified_date_field)
+            .and_then(|v| v.as_datetime())
+            .map(|d| {
+                let timestamp_secs = d.into_timestamp_secs();
+                UNIX_EPOCH + std::time::Duration::from_secs(timestamp_secs as u64)
+            })
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        SearchResult {
+            path,
+            title,
+            score,
+            source_type,
+            modified_date,
+            keywords: stored_keywords(retrieved_doc, self.keywords_field),
+            preview: self.preview_field
+                .and_then(|field| retrieved_doc.get_first(field))
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            content_hash,
+        }
+    }
+
+    /// Requires matches to fall within a modification date range.
+    fn with_date_range(&self, query: Box<dyn Query>, modified: DateRange) -> Box<dyn Query> {
+        if modified == DateRange::default() || !self.modified_date_indexed {
+            return query;
+        }
+        let date = |secs: u64| DateTime::from_timestamp_secs(secs as i64);
+        let range = RangeQuery::new_date_bounds(
+            "modified_date".to_string(),
+            modified.after.map_or(Bound::Unbounded, |secs| Bound::Included(date(secs))),
+            modified.before.map_or(Bound::Unbounded, |secs| Bound::Excluded(date(secs))),
+        );
+        Box::new(BooleanQuery::new(vec![(Occur::Must, query), (Occur::Must, Box::new(range))]))
+    }
 
-            // Use the correct `.as_str()` method to extract the text.
-            let path = retrieved_doc.get_first(self.path_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
-            let title = retrieved_doc.get_first(self.title_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
-            let source_type = retrieved_doc.get_first(self.source_type_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
-            let modified_date = retrieved_doc.get_first(self.modified_date_field)
-                .and_then(|v| v.as_datetime())
-                .map(|d| {
-                    let timestamp_secs = d.into_timestamp_secs();
-                    UNIX_EPOCH + std::time::Duration::from_secs(timestamp_secs as u64)
-                })
-                .unwrap_or(SystemTime::UNIX_EPOCH);
+    /// Wraps a parsed query so documents where the plain query words appear close together,
+    /// or as an exact phrase, score higher. The extra clauses are optional, so they only
+    /// re-order the documents the query matches and never change the hit count.
+    fn with_proximity_boost(&self, query: Box<dyn Query>, query_str: &str) -> Result<Box<dyn Query>, Box<dyn std::error::Error>> {
+        let words = free_text_words(query_str).join(" ");
+        let terms = self.analyze_text(&words)?;
+        if terms.len() < 2 {
+            return Ok(query);
+        }
 
-            results.push(SearchResult {
-                path,
-                title,
-                score,
-                source_type,
-                modified_date,
-            });
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, query)];
+        for field in [self.title_field, self.body_field] {
+            let field_terms: Vec<Term> = terms.iter().map(|term| Term::from_field_text(field, term)).collect();
+            let mut near = PhraseQuery::new(field_terms.clone());
+            near.set_slop(PROXIMITY_SLOP);
+            clauses.push((Occur::Should, Box::new(BoostQuery::new(Box::new(near), PROXIMITY_BOOST))));
+            let exact = PhraseQuery::new(field_terms);
+            clauses.push((Occur::Should, Box::new(BoostQuery::new(Box::new(exact), EXACT_PHRASE_BOOST))));
         }
+        Ok(Box::new(BooleanQuery::new(clauses)))
+    }
 
-        Ok(results)
+    /// Runs text through the same analyzer used for the body field and returns its terms.
+    pub fn analyze_text(&self, text: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut analyzer = self.index.tokenizer_for_field(self.body_field)?;
+        let mut stream = analyzer.token_stream(text);
+        let mut terms = Vec::new();
+        while stream.advance() {
+            terms.push(stream.token().text.clone());
+        }
+        Ok(terms)
     }
 
     /// Updates a document in the index by deleting the old version and adding the new one.
     pub fn update_document(&self, doc: IndexableDocument) -> Result<(), Box<dyn std::error::Error>> {
-        let mut writer: IndexWriter = self.index.writer(100_000_000)?;
+        let _guard = self.write_lock.lock().unwrap();
+        let mut writer = self.writer(doc.body.len())?;
 
         // First, delete the old document by its unique path
         let path_term = Term::from_field_text(self.path_field, &doc.path);
@@ -170,27 +546,35 @@ impl IndexManager {
         if let Some(author) = &doc.author {
             tantivy_doc.add_text(self.author_field, author);
         }
+        for alias in &doc.author_aliases {
+            tantivy_doc.add_text(self.author_field, alias);
+        }
+        add_metadata(&mut tantivy_doc, self.metadata_field, &doc.metadata);
+        add_keywords(&mut tantivy_doc, self.keywords_field, &doc.keywords);
+        add_preview(&mut tantivy_doc, self.preview_field, &doc.body);
         
         writer.add_document(tantivy_doc)?;
 
         // Commit both the deletion and addition in one transaction
         writer.commit()?;
+        self.reader.reload()?;
         Ok(())
     }
 
     /// Deletes a document from the index using its unique path.
     pub fn delete_document(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut writer: IndexWriter = self.index.writer(100_000_000)?;
+        let _guard = self.write_lock.lock().unwrap();
+        let mut writer = self.writer(0)?;
         let path_term = Term::from_field_text(self.path_field, path);
         writer.delete_term(path_term);
         writer.commit()?;
+        self.reader.reload()?;
         Ok(())
     }
 
     /// Looks up document metadata by path. Returns None if document is not found.
     pub fn get_document_metadata(&self, path: &str) -> Result<Option<SearchResult>, Box<dyn std::error::Error>> {
-        let reader = self.index.reader()?;
-        let searcher = reader.searcher();
+        let searcher = self.reader.searcher();
 
         // Create a term query for the exact path
         let path_term = Term::from_field_text(self.path_field, path);
@@ -200,27 +584,57 @@ impl IndexManager {
 
         if let Some((score, doc_address)) = top_docs.first() {
             let retrieved_doc: TantivyDocument = searcher.doc(*doc_address)?;
-
-            let path = retrieved_doc.get_first(self.path_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
-            let title = retrieved_doc.get_first(self.title_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
-            let source_type = retrieved_doc.get_first(self.source_type_field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
-            let modified_date = retrieved_doc.get_first(self.modified_date_field)
-                .and_then(|v| v.as_datetime())
-                .map(|d| {
-                    let timestamp_secs = d.into_timestamp_secs();
-                    UNIX_EPOCH + std::time::Duration::from_secs(timestamp_secs as u64)
-                })
-                .unwrap_or(SystemTime::UNIX_EPOCH);
-
-            Ok(Some(SearchResult {
-                path,
-                title,
-                score: *score,
-                source_type,
-                modified_date,
-            }))
+            Ok(Some(self.stored_result(&retrieved_doc, *score)))
         } else {
             Ok(None)
         }
     }
+
+    /// Lists the keys documents have put in the metadata field, with their most common
+    /// values. Read from the term dictionaries, so new keys show up as soon as a document
+    /// carrying them is committed; terms of deleted documents linger until segments merge.
+    pub fn metadata_fields(&self) -> Result<Vec<MetadataField>, Box<dyn std::error::Error>> {
+        let Some(field) = self.metadata_field else { return Ok(Vec::new()) };
+        let searcher = self.reader.searcher();
+
+        let mut term_counts: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(field)?;
+            let mut terms = inverted_index.terms().stream()?;
+            while terms.advance() {
+                let Some((key, value)) = split_metadata_term(terms.key()) else { continue };
+                *term_counts.entry(key.to_string()).or_default().entry(value.to_string()).or_default() += terms.value().doc_freq;
+            }
+        }
+
+        Ok(term_counts
+            .into_iter()
+            .map(|(name, counts)| {
+                let mut counts: Vec<(String, u32)> = counts.into_iter().collect();
+                counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                let values = counts.into_iter().take(FIELD_VALUES_LIMIT).map(|(value, _)| value).collect();
+                MetadataField { name, values }
+            })
+            .collect())
+    }
+
+    /// Checks every index file against its stored checksum and returns the damaged ones.
+    pub fn damaged_files(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let damaged = self.index.validate_checksum()?;
+        Ok(damaged.into_iter().map(|path| path.display().to_string()).collect())
+    }
+
+    /// Returns the stored fields of every indexed document, unscored, for corpus-wide
+    /// views such as the timeline.
+    pub fn all_documents(&self) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+        let searcher = self.reader.searcher();
+        let doc_addresses = searcher.search(&AllQuery, &DocSetCollector)?;
+
+        let mut documents = Vec::with_capacity(doc_addresses.len());
+        for doc_address in doc_addresses {
+            let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
+            documents.push(self.stored_result(&retrieved_doc, 0.0));
+        }
+        Ok(documents)
+    }
 }
\ No newline at end of file
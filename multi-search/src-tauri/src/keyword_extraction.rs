@@ -0,0 +1,192 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Keywords stored per document, enough for a row of facet chips.
+pub const MAX_KEYWORDS: usize = 8;
+/// Longer candidate phrases are usually sentence fragments rather than topics.
+const MAX_PHRASE_WORDS: usize = 3;
+/// Only the start of very long documents is scanned; topics show up early anyway.
+const MAX_SCANNED_CHARS: usize = 50_000;
+/// Phrases from the title count this many times over, since titles name the topic.
+const TITLE_WEIGHT: f32 = 2.0;
+
+/// Words that split candidate phrases. Short on purpose: RAKE only needs the function
+/// words that separate content words.
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and", "any",
+    "are", "as", "at", "be", "because", "been", "before", "being", "below", "between", "both",
+    "but", "by", "can", "could", "did", "do", "does", "doing", "down", "during", "each", "either",
+    "etc", "even", "every", "few", "for", "from", "further", "get", "got", "had", "has", "have",
+    "having", "he", "her", "here", "hers", "him", "his", "how", "however", "i", "if", "in", "into",
+    "is", "it", "its", "just", "let", "like", "may", "me", "might", "more", "most", "much", "must",
+    "my", "no", "nor", "not", "now", "of", "off", "on", "once", "one", "only", "or", "other", "our",
+    "out", "over", "own", "per", "same", "shall", "she", "should", "since", "so", "some", "such",
+    "than", "that", "the", "their", "them", "then", "there", "these", "they", "this", "those",
+    "through", "to", "too", "under", "until", "up", "upon", "us", "use", "used", "using", "very",
+    "via", "was", "we", "well", "were", "what", "when", "where", "whether", "which", "while", "who",
+    "whom", "why", "will", "with", "within", "without", "would", "yet", "you", "your",
+];
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A keyword shared by several results, shown as a chip that narrows the search to it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeywordFacet {
+    pub keyword: String,
+    /// Results carrying the keyword.
+    pub count: usize,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Splits text into candidate phrases: runs of content words between punctuation and
+/// stop words, lowercased. Runs longer than `MAX_PHRASE_WORDS` are cut into pieces.
+fn candidate_phrases(text: &str, stop_words: &HashSet<&str>) -> Vec<Vec<String>> {
+    let mut phrases = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut flush = |current: &mut Vec<String>| {
+        phrases.extend(current.chunks(MAX_PHRASE_WORDS).map(<[String]>::to_vec));
+        current.clear();
+    };
+
+    for token in text.split_inclusive(|c: char| c.is_whitespace() || ".,;:!?()[]{}\"<>|/\\".contains(c)) {
+        let breaks_phrase = token.ends_with(|c: char| !c.is_whitespace() && !c.is_alphanumeric() && c != '-' && c != '\'');
+        let word = token
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        let is_content = word.chars().count() > 2
+            && word.chars().any(char::is_alphabetic)
+            && !stop_words.contains(word.as_str());
+        if is_content {
+            current.push(word);
+        } else if !word.is_empty() {
+            flush(&mut current);
+        }
+        if breaks_phrase {
+            flush(&mut current);
+        }
+    }
+    flush(&mut current);
+    phrases
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Extracts a document's salient keywords with RAKE: each content word is scored by its
+/// degree over its frequency, favouring words that co-occur in multi-word phrases, and a
+/// phrase scores the sum of its words, weighted up by how often it occurs. Returns up to
+/// `limit` phrases, best first.
+pub fn extract_keywords(title: &str, body: &str, limit: usize) -> Vec<String> {
+    let stop_words: HashSet<&str> = STOP_WORDS.iter().copied().collect();
+    let scanned = match body.char_indices().nth(MAX_SCANNED_CHARS) {
+        Some((end, _)) => &body[..end],
+        None => body,
+    };
+
+    // 1. Collect candidate phrases, counting title occurrences extra.
+    let mut phrase_weights: HashMap<Vec<String>, f32> = HashMap::new();
+    for phrase in candidate_phrases(title, &stop_words) {
+        *phrase_weights.entry(phrase).or_insert(0.0) += TITLE_WEIGHT;
+    }
+    for phrase in candidate_phrases(scanned, &stop_words) {
+        *phrase_weights.entry(phrase).or_insert(0.0) += 1.0;
+    }
+
+    // 2. Score words by degree / frequency.
+    let mut frequency: HashMap<&str, f32> = HashMap::new();
+    let mut degree: HashMap<&str, f32> = HashMap::new();
+    for (phrase, weight) in &phrase_weights {
+        for word in phrase {
+            *frequency.entry(word).or_insert(0.0) += weight;
+            *degree.entry(word).or_insert(0.0) += weight * phrase.len() as f32;
+        }
+    }
+
+    // 3. Score phrases and keep the best, skipping ones contained in a better phrase.
+    let mut scored: Vec<(String, f32)> = phrase_weights
+        .iter()
+        .map(|(phrase, weight)| {
+            let words_score: f32 = phrase.iter().map(|word| degree[word.as_str()] / frequency[word.as_str()]).sum();
+            (phrase.join(" "), words_score * (1.0 + weight.ln()))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then_with(|| a.0.cmp(&b.0)));
+
+    let mut keywords: Vec<String> = Vec::new();
+    for (phrase, _) in scored {
+        if keywords.len() >= limit {
+            break;
+        }
+        let covered = keywords.iter().any(|kept| {
+            kept.split(' ').collect::<Vec<_>>().windows(phrase.split(' ').count())
+                .any(|window| window.join(" ") == phrase)
+        });
+        if !covered {
+            keywords.push(phrase);
+        }
+    }
+    keywords
+}
+
+/// Counts the keywords of a result list and returns those shared by at least two results,
+/// most common first, ties in order of first appearance.
+pub fn keyword_facets<'a>(result_keywords: impl IntoIterator<Item = &'a [String]>, limit: usize) -> Vec<KeywordFacet> {
+    let mut facets: Vec<KeywordFacet> = Vec::new();
+    for keywords in result_keywords {
+        for keyword in keywords {
+            match facets.iter_mut().find(|facet| &facet.keyword == keyword) {
+                Some(facet) => facet.count += 1,
+                None => facets.push(KeywordFacet { keyword: keyword.clone(), count: 1 }),
+            }
+        }
+    }
+    facets.retain(|facet| facet.count >= 2);
+    // Stable sort keeps first-appearance order among equal counts
+    facets.sort_by_key(|facet| std::cmp::Reverse(facet.count));
+    facets.truncate(limit);
+    facets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_keywords() {
+        let title = "Vector database migration plan";
+        let body = "We are moving the vector database to LanceDB. The vector database migration \
+                    starts in March. Query latency must stay low, so query latency is measured \
+                    before and after. The team will review the migration plan weekly.";
+        let keywords = extract_keywords(title, body, 4);
+        assert_eq!(keywords[0], "vector database migration");
+        assert!(keywords.contains(&"query latency".to_string()));
+        // Already covered by the first keyword
+        assert!(!keywords.contains(&"vector database".to_string()));
+        assert!(keywords.len() <= 4);
+
+        assert!(extract_keywords("", "the and of", MAX_KEYWORDS).is_empty());
+    }
+
+    #[test]
+    fn test_keyword_facets() {
+        let results = [
+            vec!["rust".to_string(), "tantivy".to_string()],
+            vec!["lancedb".to_string(), "rust".to_string()],
+            vec!["lancedb".to_string(), "rust".to_string(), "arrow".to_string()],
+        ];
+        let facets = keyword_facets(results.iter().map(Vec::as_slice), MAX_KEYWORDS);
+        assert_eq!(facets, vec![
+            KeywordFacet { keyword: "rust".to_string(), count: 3 },
+            KeywordFacet { keyword: "lancedb".to_string(), count: 2 },
+        ]);
+    }
+}
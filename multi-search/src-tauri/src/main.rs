@@ -3,29 +3,123 @@
 // Allow warnings from objc crate macros (external dependency issue)
 #![allow(unexpected_cfgs)]
 
-use tauri::{Manager, AppHandle};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use std::time::{Duration, Instant};
+use tauri::{Manager, AppHandle, DragDropEvent, Emitter, WindowEvent};
+use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
-use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
 
 mod index_manager;
 mod embedding_generator;
 mod vector_db;
+mod search_orchestrator;
+mod parsers;
+mod settings;
+mod metrics;
+mod commands;
+mod diagnostics;
+mod text_normalization;
+mod query_preprocessor;
+mod state_store;
+mod storage_quota;
+mod thumbnails;
+mod date_format;
+mod identity;
+mod scopes;
+mod app_context;
+mod experiments;
+mod file_ingest;
+mod permissions;
+mod fs_paths;
+mod web_capture;
+mod capture_server;
+mod highlights;
+mod zotero;
+mod providers;
+mod password_manager;
+mod git_repos;
+mod shell_history;
+mod docsets;
+mod snippets;
+mod index_events;
+mod rate_limit;
+mod auth;
+mod sync_cursors;
+mod webhooks;
+mod deep_link;
+mod shortcuts;
+mod query_analytics;
+mod summary_budget;
+mod abstractive_summarizer;
+mod keyword_extraction;
+mod topics;
+mod timeline;
+mod digests;
+mod notifications;
+mod backlog;
+mod file_usage;
+mod full_text;
+mod config_import;
+mod bulk_actions;
+mod file_watcher;
+mod crawler;
+mod team_index;
+mod snapshots;
+mod chunk_diff;
+mod backdrop;
+mod window_resize;
+mod selection;
+mod model_store;
+mod voice_audio;
+mod recorder;
+mod transcriber;
+mod result_actions;
+mod frontmatter;
+mod ocr;
+mod result_cache;
+mod typing_cadence;
+mod html_text;
+mod health;
+mod connectors;
+mod data_dir;
+mod disk_space;
 
+use abstractive_summarizer::AbstractiveSummarizer;
+use commands::{AppState, DeepLinkSearch, PinnedSearchUpdate, VoiceSearch};
+use deep_link::DeepLink;
+use health::{ComponentHealth, HealthReport};
+use notifications::{notify, Notification};
+use recorder::Recording;
+use search_orchestrator::{SearchOptions, SearchOrchestrator};
+use settings::Settings;
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use tauri::Theme;
 #[cfg(target_os = "macos")]
 use cocoa::appkit::NSColor;
 #[cfg(target_os = "macos")]
 use cocoa::base::{id, nil, YES};
 #[cfg(target_os = "macos")]
 use objc::{msg_send, sel, sel_impl};
+#[cfg(target_os = "macos")]
+use window_vibrancy::{apply_vibrancy, clear_vibrancy, NSVisualEffectMaterial};
 
 #[cfg(target_os = "windows")]
-use window_vibrancy::apply_blur;
+use window_vibrancy::{apply_acrylic, apply_blur, apply_mica, clear_acrylic, clear_blur, clear_mica};
+#[cfg(target_os = "windows")]
+use backdrop::{resolve_backdrop, WindowBackdrop};
 
 #[cfg(target_os = "windows")]
 use windows_sys::Win32::Foundation::HWND;
 #[cfg(target_os = "windows")]
 use windows_sys::Win32::Graphics::Dwm::{DwmSetWindowAttribute, DWMWA_WINDOW_CORNER_PREFERENCE};
 #[cfg(target_os = "windows")]
+use windows_sys::Win32::System::LibraryLoader::{GetModuleHandleA, GetProcAddress};
+#[cfg(target_os = "windows")]
+use windows_sys::Win32::System::SystemInformation::OSVERSIONINFOW;
+#[cfg(target_os = "windows")]
 use std::mem;
 
 #[cfg(target_os = "macos")]
@@ -79,6 +173,21 @@ fn force_vibrancy_active(window: &tauri::WebviewWindow) {
     }
 }
 
+/// Applies the vibrancy material for the OS appearance, replacing the current one. The
+/// HUD material stays dark and translucent; Popover reads best behind light content.
+#[cfg(target_os = "macos")]
+fn apply_launcher_vibrancy(window: &tauri::WebviewWindow, theme: Theme) {
+    let material = match theme {
+        Theme::Dark => NSVisualEffectMaterial::HudWindow,
+        _ => NSVisualEffectMaterial::Popover,
+    };
+    let _ = clear_vibrancy(window);
+    if let Err(e) = apply_vibrancy(window, material, None, None) {
+        eprintln!("Warning: Could not apply vibrancy: {}", e);
+    }
+    force_vibrancy_active(window);
+}
+
 // Windows-specific constants for corner preferences (Windows 11 Build 22000+)
 #[cfg(target_os = "windows")]
 const DWMWCP_DEFAULT: u32 = 0;
@@ -115,54 +224,732 @@ fn setup_rounded_transparent_window_windows(window: &tauri::WebviewWindow) {
     }
 }
 
+/// Returns the Windows build number, e.g. 22631, or 0 if it can't be read. Asks ntdll
+/// directly, since `GetVersionEx` reports Windows 8 to apps without a compatibility manifest.
+#[cfg(target_os = "windows")]
+fn windows_build_number() -> u32 {
+    type RtlGetVersion = unsafe extern "system" fn(*mut OSVERSIONINFOW) -> i32;
+    unsafe {
+        let ntdll = GetModuleHandleA(c"ntdll.dll".as_ptr() as *const u8);
+        if ntdll.is_null() {
+            return 0;
+        }
+        let Some(rtl_get_version) = GetProcAddress(ntdll, c"RtlGetVersion".as_ptr() as *const u8) else {
+            return 0;
+        };
+        let rtl_get_version: RtlGetVersion = mem::transmute(rtl_get_version);
+        let mut info: OSVERSIONINFOW = mem::zeroed();
+        info.dwOSVersionInfoSize = mem::size_of::<OSVERSIONINFOW>() as u32;
+        if rtl_get_version(&mut info) == 0 { info.dwBuildNumber } else { 0 }
+    }
+}
+
+/// Tints for Acrylic and the legacy blur, matching the frontend's dark and light themes.
+#[cfg(target_os = "windows")]
+const DARK_BACKDROP_TINT: (u8, u8, u8, u8) = (18, 18, 18, 125);
+#[cfg(target_os = "windows")]
+const LIGHT_BACKDROP_TINT: (u8, u8, u8, u8) = (238, 238, 238, 125);
+
+/// Applies the launcher's backdrop material, falling back to what this Windows build
+/// supports, in the window's current theme. The other materials are cleared first, so a
+/// changed setting or theme replaces the old one.
+#[cfg(target_os = "windows")]
+pub(crate) fn apply_window_backdrop(window: &tauri::WebviewWindow, backdrop: WindowBackdrop) {
+    let dark = window.theme().map_or(true, |theme| theme == Theme::Dark);
+    let tint = if dark { DARK_BACKDROP_TINT } else { LIGHT_BACKDROP_TINT };
+    let _ = clear_mica(window);
+    let _ = clear_acrylic(window);
+    let _ = clear_blur(window);
+    let result = match resolve_backdrop(backdrop, windows_build_number()) {
+        WindowBackdrop::Mica => apply_mica(window, Some(dark)),
+        WindowBackdrop::Acrylic => apply_acrylic(window, Some(tint)),
+        _ => apply_blur(window, Some(tint)),
+    };
+    if let Err(e) = result {
+        eprintln!("Warning: Could not apply the window backdrop: {}", e);
+    }
+}
+
 #[cfg(target_os = "windows")]
-fn force_blur_consistency_windows(window: &tauri::WebviewWindow) {
-    // Re-apply blur effect to ensure consistency
+fn force_backdrop_consistency_windows(app: &AppHandle, window: &tauri::WebviewWindow) {
+    // Re-apply the backdrop to ensure consistency
     // This can be called when the window becomes visible or loses/gains focus
-    let _ = apply_blur(window, Some((18, 18, 18, 125)));
+    let backdrop = app.state::<AppState>().settings.lock().unwrap().window_backdrop;
+    apply_window_backdrop(window, backdrop);
+}
+
+/// Notes what the user was working in, then shows the launcher. Detecting the frontmost
+/// app runs `osascript`, so it happens on a blocking thread rather than the event loop,
+/// and the window is only shown once it's done, before the launcher steals focus.
+fn show_launcher_window(app: &AppHandle, window: &tauri::WebviewWindow) {
+    let (app, window) = (app.clone(), window.clone());
+    tauri::async_runtime::spawn(async move {
+        let context_app = app.clone();
+        let captured = tokio::task::spawn_blocking(move || context_app.state::<AppState>().capture_session_context()).await;
+        if let Err(e) = captured {
+            eprintln!("Warning: Could not detect the frontmost app: {}", e);
+        }
+        if let Err(e) = app.run_on_main_thread(move || reveal_launcher_window(&window)) {
+            eprintln!("Warning: Could not show the launcher: {}", e);
+        }
+    });
+}
+
+/// Shows and focuses the launcher, re-applying the window effects. Runs on the main thread.
+fn reveal_launcher_window(window: &tauri::WebviewWindow) {
+    let _ = window.show();
+    let _ = window.set_focus();
+    
+    #[cfg(target_os = "macos")]
+    {
+        // Re-force vibrancy to be active when showing the window
+        force_vibrancy_active(window);
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        // Re-force backdrop consistency when showing the window
+        force_backdrop_consistency_windows(window.app_handle(), window);
+    }
+}
+
+/// Registers the global shortcut that shows and hides the launcher.
+fn register_launcher_shortcut(app: &AppHandle, accelerator: &str) -> Result<(), String> {
+    let handle = app.clone();
+    app.global_shortcut()
+        .on_shortcut(accelerator, move |_app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                toggle_launcher_window(&handle);
+            }
+        })
+        .map_err(|e| format!("Could not register the shortcut {}: {}", accelerator, e))
+}
+
+/// Registers the global shortcut that opens the launcher searching for the selected text.
+fn register_selection_shortcut(app: &AppHandle, accelerator: &str) -> Result<(), String> {
+    let handle = app.clone();
+    app.global_shortcut()
+        .on_shortcut(accelerator, move |_app, _shortcut, event| {
+            // Wait for the key to come up, so a simulated Copy isn't mixed with the chord
+            if event.state == ShortcutState::Released {
+                search_selected_text(&handle);
+            }
+        })
+        .map_err(|e| format!("Could not register the shortcut {}: {}", accelerator, e))
+}
+
+/// Grabs the text selected in the frontmost app, then opens the launcher and sends it
+/// as a `search-selected-text` event for the search field to be filled in with. With
+/// nothing selected the launcher just opens.
+fn search_selected_text(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // The selection has to be read while the other app still has focus
+        let query = selection::selected_text_query(&app).await;
+        let Some(window) = app.get_webview_window("launcher") else { return };
+        if !window.is_visible().unwrap_or(false) {
+            show_launcher_window(&app, &window);
+        }
+        if let Some(query) = query {
+            if let Err(e) = app.emit("search-selected-text", query) {
+                eprintln!("Warning: Could not emit search-selected-text event: {}", e);
+            }
+        }
+    });
+}
+
+/// Registers the hold-to-talk shortcut: the microphone records while it is held, and
+/// what was said is searched once it is let go.
+fn register_voice_shortcut(app: &AppHandle, accelerator: &str) -> Result<(), String> {
+    let handle = app.clone();
+    app.global_shortcut()
+        .on_shortcut(accelerator, move |_app, _shortcut, event| match event.state {
+            ShortcutState::Pressed => start_voice_query(&handle),
+            ShortcutState::Released => finish_voice_query(&handle),
+        })
+        .map_err(|e| format!("Could not register the shortcut {}: {}", accelerator, e))
+}
+
+/// Starts recording a voice query, sending `voice-recording` so the UI can show it's
+/// listening, and loads the speech model meanwhile if this is the first one.
+fn start_voice_query(app: &AppHandle) {
+    {
+        let mut recording = app.state::<AppState>().voice_recording.lock().unwrap();
+        if recording.is_some() {
+            return;
+        }
+        match Recording::start() {
+            Ok(started) => *recording = Some(started),
+            Err(e) => {
+                eprintln!("Warning: {}", e);
+                return;
+            }
+        }
+    }
+    let _ = app.emit("voice-recording", true);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = app.state::<AppState>().transcriber().await {
+            eprintln!("Warning: Could not load the speech model: {}", e);
+        }
+    });
+}
+
+/// Stops recording and transcribes the query, or returns None if it was too short or
+/// nothing was said.
+async fn transcribe_voice_query(app: &AppHandle, recording: Recording) -> Result<Option<String>, String> {
+    let samples = tokio::task::spawn_blocking(move || recording.stop()).await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    if samples.len() < voice_audio::MIN_VOICE_SAMPLES {
+        return Ok(None);
+    }
+    let transcriber = app.state::<AppState>().transcriber().await?;
+    let query = tokio::task::spawn_blocking(move || transcriber.transcribe(samples)).await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok((!query.is_empty()).then_some(query))
+}
+
+/// Stops recording, then opens the launcher searching for what was said, with the
+/// results sent as a `voice-search` event.
+fn finish_voice_query(app: &AppHandle) {
+    let Some(recording) = app.state::<AppState>().voice_recording.lock().unwrap().take() else { return };
+    let _ = app.emit("voice-recording", false);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let query = match transcribe_voice_query(&app, recording).await {
+            Ok(Some(query)) => query,
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("Warning: Could not transcribe the voice query: {}", e);
+                return;
+            }
+        };
+        if let Some(window) = app.get_webview_window("launcher") {
+            if !window.is_visible().unwrap_or(false) {
+                show_launcher_window(&app, &window);
+            }
+        }
+        let orchestrator = match app.state::<AppState>().orchestrator() {
+            Ok(orchestrator) => orchestrator,
+            Err(e) => {
+                eprintln!("Warning: Could not run the voice query: {}", e);
+                return;
+            }
+        };
+        match orchestrator.hybrid_search(&query, SearchOptions::default()).await {
+            Ok(response) => {
+                if let Err(e) = app.emit("voice-search", VoiceSearch { query, response }) {
+                    eprintln!("Warning: Could not emit voice-search event: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Warning: Voice search for '{}' failed: {}", query, e),
+        }
+    });
 }
 
 fn toggle_launcher_window(app: &AppHandle) {
     if let Some(window) = app.get_webview_window("launcher") {
+        let pinned = app.state::<AppState>().pinned_query().is_some();
         if let Ok(true) = window.is_visible() {
-            let _ = window.hide();
+            // A pinned launcher stays open; the shortcut just brings it back to the front
+            if pinned {
+                let _ = window.set_focus();
+            } else {
+                let _ = window.hide();
+                if let Ok(orchestrator) = app.state::<AppState>().orchestrator() {
+                    orchestrator.end_search_session(false);
+                }
+            }
         } else {
-            let _ = window.show();
-            let _ = window.set_focus();
-            
-            #[cfg(target_os = "macos")]
-            {
-                // Re-force vibrancy to be active when showing the window
-                force_vibrancy_active(&window);
+            show_launcher_window(app, &window);
+        }
+    }
+}
+
+/// Opens the launcher for a `multisearch://` link from another app: a search link runs
+/// its query and sends the results as a `deep-link-search` event, an open link sends the
+/// linked document as a `deep-link-open` event so the launcher shows its preview.
+fn handle_deep_link(app: &AppHandle, link: &str) {
+    let deep_link = match deep_link::parse_deep_link(link) {
+        Ok(deep_link) => deep_link,
+        Err(e) => {
+            eprintln!("Warning: Ignoring deep link: {}", e);
+            return;
+        }
+    };
+    if let Some(window) = app.get_webview_window("launcher") {
+        if !window.is_visible().unwrap_or(false) {
+            show_launcher_window(app, &window);
+        }
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let orchestrator = match app.state::<AppState>().orchestrator() {
+            Ok(orchestrator) => orchestrator,
+            Err(e) => {
+                eprintln!("Warning: Could not follow deep link: {}", e);
+                return;
             }
-            
-            #[cfg(target_os = "windows")]
-            {
-                // Re-force blur consistency when showing the window
-                force_blur_consistency_windows(&window);
+        };
+        let emitted = match deep_link {
+            DeepLink::Search { query } => match orchestrator.hybrid_search(&query, SearchOptions::default()).await {
+                Ok(response) => app.emit("deep-link-search", DeepLinkSearch { query, response }),
+                Err(e) => {
+                    eprintln!("Warning: Deep link search for '{}' failed: {}", query, e);
+                    return;
+                }
+            },
+            DeepLink::Open { path } => match orchestrator.document_result(&path).await {
+                Ok(Some(result)) => app.emit("deep-link-open", result),
+                Ok(None) => {
+                    eprintln!("Warning: Deep link points to a document that isn't indexed: {}", path);
+                    return;
+                }
+                Err(e) => {
+                    eprintln!("Warning: Could not open deep link to {}: {}", path, e);
+                    return;
+                }
+            },
+        };
+        if let Err(e) = emitted {
+            eprintln!("Warning: Could not emit deep link event: {}", e);
+        }
+    });
+}
+
+/// How often folders blocked by missing permissions are checked again.
+const PERMISSION_RETRY_INTERVAL: Duration = Duration::from_secs(15);
+/// How often documents whose undo window has passed are removed from the indexes.
+const DELETION_PURGE_INTERVAL: Duration = Duration::from_secs(60);
+/// How often documents are re-clustered into topics. Topics drift slowly, and clustering
+/// reads every summary embedding, so once a day is enough.
+const TOPIC_CLUSTERING_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const TOPIC_CLUSTERING_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How often the keyword index is checked for segments to merge. A check that finds
+/// nothing to merge only reads the segment list.
+const SEGMENT_MERGE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// How often the digest job checks whether a daily or weekly digest is due.
+const DIGEST_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// How often connector sign-ins are refreshed and checked for expiry.
+const SIGN_IN_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// Quiet period after an index change before a pinned query re-runs, so a burst of
+/// indexed documents triggers one refresh instead of one per document.
+const PINNED_SEARCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Keeps the startup self-test's report for `get_health_report` and sends it to the
+/// frontend as a `startup-health` event.
+fn report_health(app: &AppHandle, report: HealthReport) {
+    if let Some(error) = report.vector_stack_error() {
+        eprintln!("Warning: Semantic search is off, searching keywords only: {}", error);
+    }
+    *app.state::<AppState>().health.lock().unwrap() = Some(report.clone());
+    if let Err(e) = app.emit("startup-health", report) {
+        eprintln!("Warning: Could not emit startup-health event: {}", e);
+    }
+}
+
+/// Drops cached searches each index change could alter, counts changes for the stats API
+/// and the next digest, and forwards each one to the frontend as an `index-changed` event,
+/// which drives the "new documents" badges.
+async fn forward_index_events(app: AppHandle, orchestrator: Arc<SearchOrchestrator>) {
+    let mut events = orchestrator.subscribe_index_events();
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                orchestrator.invalidate_cached_results(&event);
+                orchestrator.metrics().record_index_event(event.kind);
+                orchestrator.record_digest_change(&event);
+                if let Err(e) = app.emit("index-changed", event) {
+                    eprintln!("Warning: Could not emit index-changed event: {}", e);
+                }
+            }
+            Err(RecvError::Lagged(missed)) => {
+                eprintln!("Warning: Missed {} index events while busy", missed);
+                orchestrator.clear_result_cache();
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Sends the frontend a `disk-space` event whenever indexing pauses for lack of free space or
+/// resumes, and notifies the user when it pauses.
+async fn forward_disk_space(app: AppHandle, orchestrator: Arc<SearchOrchestrator>) {
+    let mut statuses = orchestrator.subscribe_disk_space();
+    while statuses.changed().await.is_ok() {
+        let status = statuses.borrow_and_update().clone();
+        if status.paused {
+            eprintln!("Warning: Indexing paused, only {} bytes free on {}", status.free_bytes, status.volume.display());
+            notify(&app, Notification::disk_space_low(&status));
+        }
+        if let Err(e) = app.emit("disk-space", status) {
+            eprintln!("Warning: Could not emit disk-space event: {}", e);
+        }
+    }
+}
+
+/// Runs the connector syncs that push notifications and the schedule ask for, one at a time.
+async fn run_requested_syncs(app: AppHandle, orchestrator: Arc<SearchOrchestrator>) {
+    let Some(mut requests) = app.state::<AppState>().take_sync_requests() else { return };
+    while let Some(connector) = requests.recv().await {
+        app.state::<AppState>().start_requested_sync(&connector);
+        let result = match connector.as_str() {
+            highlights::READWISE_SOURCE => {
+                let token = app.state::<AppState>().settings.lock().unwrap().readwise_token.clone();
+                match token {
+                    Some(token) => orchestrator.sync_readwise(&token).await.map(|_| ()),
+                    None => Err(anyhow::anyhow!("no Readwise access token is set")),
+                }
+            }
+            connectors::apple_notes::APPLE_NOTES_SOURCE => orchestrator.sync_apple_notes().await.map(|_| ()),
+            connectors::gmail::GMAIL_SOURCE => {
+                let (enabled, client) = {
+                    let settings = app.state::<AppState>().settings.lock().unwrap();
+                    (settings.connector_sync_minutes.contains_key(&connector), settings.oauth_clients.get(&connector).cloned())
+                };
+                match (enabled, client) {
+                    (false, _) => Err(anyhow::anyhow!("Gmail sync is turned off")),
+                    (true, None) => Err(anyhow::anyhow!("no OAuth client is set for Gmail")),
+                    (true, Some(client)) => orchestrator.sync_gmail(&client).await.map(|_| ()),
+                }
             }
+            connectors::slack::SLACK_SOURCE => {
+                let (enabled, client, channels) = {
+                    let settings = app.state::<AppState>().settings.lock().unwrap();
+                    (
+                        settings.connector_sync_minutes.contains_key(&connector),
+                        settings.oauth_clients.get(&connector).cloned(),
+                        settings.slack_channels.clone(),
+                    )
+                };
+                match (enabled, client) {
+                    (false, _) => Err(anyhow::anyhow!("Slack sync is turned off")),
+                    (true, None) => Err(anyhow::anyhow!("no OAuth client is set for Slack")),
+                    (true, Some(client)) => orchestrator.sync_slack(&client, &channels).await.map(|_| ()),
+                }
+            }
+            _ => Err(anyhow::anyhow!("no connector syncs {}", connector)),
+        };
+        if let Err(e) = result {
+            eprintln!("Warning: Requested sync of {} failed: {}", connector, e);
+        }
+    }
+}
+
+/// Requests a sync of each scheduled connector once its interval has passed since its last
+/// complete sync. A sync that fails is tried again on the next interval.
+async fn run_scheduled_syncs(app: AppHandle, orchestrator: Arc<SearchOrchestrator>) {
+    let mut requested: HashMap<String, u64> = HashMap::new();
+    loop {
+        let schedule = app.state::<AppState>().settings.lock().unwrap().connector_sync_minutes.clone();
+        let now = state_store::now_secs();
+        for connector in connectors::due_syncs(&schedule, &orchestrator.sync_cursors(), now) {
+            // Failed syncs never complete, so wait out the interval before asking again
+            let interval = connectors::sync_interval_secs(schedule.get(&connector).copied().unwrap_or_default());
+            if requested.get(&connector).is_some_and(|&at| now.saturating_sub(at) < interval) {
+                continue;
+            }
+            requested.insert(connector.clone(), now);
+            app.state::<AppState>().request_sync(&connector);
+        }
+        tokio::time::sleep(connectors::SCHEDULE_CHECK_INTERVAL).await;
+    }
+}
+
+/// Writes a digest whenever one is due under the current settings, announces it with a
+/// notification and sends it to the frontend as a `digest-ready` event.
+async fn run_digests(app: AppHandle, orchestrator: Arc<SearchOrchestrator>) {
+    loop {
+        tokio::time::sleep(DIGEST_CHECK_INTERVAL).await;
+        let frequency = app.state::<AppState>().settings.lock().unwrap().digest_frequency;
+        if !orchestrator.digest_due(frequency) {
+            continue;
+        }
+        match orchestrator.generate_digest().await {
+            Ok(Some(digest)) => {
+                notify(&app, Notification::digest_ready(&digest));
+                if let Err(e) = app.emit("digest-ready", digest) {
+                    eprintln!("Warning: Could not emit digest-ready event: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Warning: Could not generate digest: {}", e),
+        }
+    }
+}
+
+/// Keeps connector sign-ins fresh and notifies the user, once per lapse, about any that
+/// expired and need them to sign in again.
+async fn watch_connector_sign_ins(app: AppHandle) {
+    let mut notified = HashSet::new();
+    loop {
+        let clients = app.state::<AppState>().settings.lock().unwrap().oauth_clients.clone();
+        for (connector, client) in &clients {
+            match auth::sign_in_expired(connector, client).await {
+                Ok(true) => {
+                    if notified.insert(connector.clone()) {
+                        notify(&app, Notification::auth_expired(connector));
+                    }
+                }
+                Ok(false) => {
+                    notified.remove(connector);
+                }
+                Err(e) => eprintln!("Warning: Could not check the {} sign-in: {}", connector, e),
+            }
+        }
+        tokio::time::sleep(SIGN_IN_CHECK_INTERVAL).await;
+    }
+}
+
+/// Re-runs the pinned query after each burst of index changes and emits the results,
+/// flagging matches that are new since the query was pinned.
+async fn refresh_pinned_search(app: AppHandle, orchestrator: Arc<SearchOrchestrator>) {
+    let mut events = orchestrator.subscribe_index_events();
+    // Any event, or having lagged behind several, means the results may have changed
+    while !matches!(events.recv().await, Err(RecvError::Closed)) {
+        tokio::time::sleep(PINNED_SEARCH_DEBOUNCE).await;
+        while !matches!(events.try_recv(), Err(TryRecvError::Empty | TryRecvError::Closed)) {}
+
+        let Some(query) = app.state::<AppState>().pinned_query() else { continue };
+        let response = match orchestrator.hybrid_search(&query, SearchOptions::default()).await {
+            Ok(response) => response,
+            Err(e) => {
+                eprintln!("Warning: Could not refresh pinned search: {}", e);
+                continue;
+            }
+        };
+        let Some(new_paths) = app.state::<AppState>().record_pinned_results(&query, &response) else { continue };
+        let update = PinnedSearchUpdate { query, response, new_paths };
+        if let Err(e) = app.emit("pinned-search-updated", update) {
+            eprintln!("Warning: Could not emit pinned-search-updated event: {}", e);
         }
     }
 }
 
 fn main() {
-    tauri::Builder::default()
+    // Settle the data directory before anything reads settings or opens an index
+    let data_root = match data_dir::init(std::env::args().skip(1)) {
+        Ok(data_root) => data_root,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut builder = tauri::Builder::default();
+    // Must come first: a second launch (e.g. to open a link on Windows or Linux) hands
+    // its arguments to this instance, which turns links into deep link events, and
+    // brings the launcher forward. The lock is per app, not per data directory, so runs
+    // with their own directory skip it and start alongside the normal app.
+    if !data_root.is_explicit() {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("launcher") {
+                show_launcher_window(app, &window);
+            }
+        }));
+    }
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             let handle = app.handle().clone();
             let window = app.get_webview_window("launcher").unwrap();
 
+            // Load persisted settings and share them with command handlers
+            let settings = Settings::load().unwrap_or_else(|e| {
+                eprintln!("Warning: Could not load settings, using defaults: {}", e);
+                Settings::default()
+            });
+            app.manage(AppState::new(settings.clone()));
+
+            // Accept pages sent by the browser bookmarklet and push notifications from connectors
+            if settings.capture_endpoint_enabled || settings.webhooks_enabled {
+                let capture_handle = app.handle().clone();
+                let port = settings.capture_endpoint_port;
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = capture_server::serve(capture_handle, port).await {
+                        eprintln!("Warning: Capture endpoint stopped on port {}: {}", port, e);
+                    }
+                });
+            }
+
+            // Initialize the search engine in the background so the window appears immediately
+            let init_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                // Restore a snapshot the user rolled back to, and snapshot the indexes before an
+                // upgrade or migration changes them
+                if let Err(e) = snapshots::prepare_indexes(&settings.index_locations, settings.min_free_disk_mb) {
+                    eprintln!("Warning: Could not prepare index snapshots: {}", e);
+                }
+                // Free the space of models tried once and no longer needed
+                if let Some(days) = settings.model_retention_days {
+                    match model_store::prune_unused_models(&model_store::pinned_models(&settings), days) {
+                        Ok(freed) if freed > 0 => println!("Deleted unused models, freeing {} bytes", freed),
+                        Ok(_) => {}
+                        Err(e) => eprintln!("Warning: Could not delete unused models: {}", e),
+                    }
+                }
+                // In offline mode a missing model can't be fetched; wait for the user to
+                // download it or put its files in place
+                if settings.offline_mode && !model_store::is_model_ready(&model_store::EMBEDDING_MODEL) {
+                    eprintln!("Warning: The embedding model isn't downloaded; waiting for it before starting the search engine");
+                    let _ = init_handle.emit("model-missing", model_store::EMBEDDING_MODEL.repo);
+                    init_handle.state::<AppState>().model_downloaded.notified().await;
+                }
+                let init_started = Instant::now();
+                match SearchOrchestrator::new(&settings).await {
+                    Ok(orchestrator) => init_handle.state::<AppState>().set_orchestrator(orchestrator),
+                    Err(e) => {
+                        eprintln!("Error: Failed to initialize search engine: {}", e);
+                        let failure = ComponentHealth::failed(health::SEARCH_ENGINE, &e, init_started.elapsed());
+                        report_health(&init_handle, HealthReport::new(vec![failure]));
+                        return;
+                    }
+                }
+
+                let orchestrator = match init_handle.state::<AppState>().orchestrator() {
+                    Ok(orchestrator) => orchestrator,
+                    Err(_) => return,
+                };
+
+                // Check the engine end to end before relying on it. A broken embedding model
+                // or vector store leaves search keyword-only rather than not starting
+                report_health(&init_handle, orchestrator.self_test().await);
+                let requeued = orchestrator.requeue_missing_embeddings();
+                if requeued > 0 {
+                    println!("Re-indexing {} files indexed without embeddings", requeued);
+                }
+
+                // Load the optional summarization model without holding up search
+                if settings.abstractive_summaries_enabled {
+                    let summarizer_orchestrator = orchestrator.clone();
+                    let offline_mode = settings.offline_mode;
+                    tauri::async_runtime::spawn(async move {
+                        match AbstractiveSummarizer::load(offline_mode).await {
+                            Ok(summarizer) => summarizer_orchestrator.set_abstractive_summarizer(summarizer),
+                            Err(e) => eprintln!("Warning: Could not load the summarization model, using extractive summaries: {}", e),
+                        }
+                    });
+                }
+
+                // Follow the link the app was launched with, now that searches can run
+                if let Ok(Some(links)) = init_handle.deep_link().get_current() {
+                    for link in links {
+                        handle_deep_link(&init_handle, link.as_str());
+                    }
+                }
+
+                // React to documents coming and going: stats, badges, digests and a pinned search
+                tauri::async_runtime::spawn(forward_index_events(init_handle.clone(), orchestrator.clone()));
+                tauri::async_runtime::spawn(forward_disk_space(init_handle.clone(), orchestrator.clone()));
+                tauri::async_runtime::spawn(refresh_pinned_search(init_handle.clone(), orchestrator.clone()));
+                tauri::async_runtime::spawn(run_requested_syncs(init_handle.clone(), orchestrator.clone()));
+                tauri::async_runtime::spawn(run_scheduled_syncs(init_handle.clone(), orchestrator.clone()));
+                tauri::async_runtime::spawn(run_digests(init_handle.clone(), orchestrator.clone()));
+                tauri::async_runtime::spawn(watch_connector_sign_ins(init_handle.clone()));
+
+                // Keep the configured folders indexed as their files change
+                let (indexed_folders, excluded_folders) = {
+                    let settings = init_handle.state::<AppState>().settings.lock().unwrap();
+                    (settings.indexed_folders.clone(), settings.excluded_folders.clone())
+                };
+                if let Some(commands) = init_handle.state::<AppState>().take_watch_commands() {
+                    tauri::async_runtime::spawn(file_watcher::watch_folders(orchestrator.clone(), indexed_folders, excluded_folders, commands));
+                }
+
+                // Warn about a damaged keyword index rather than letting searches quietly miss documents
+                match orchestrator.damaged_index_files().await {
+                    Ok(damaged) if !damaged.is_empty() => {
+                        eprintln!("Error: Keyword index files failed their checksum: {}", damaged.join(", "));
+                        notify(&init_handle, Notification::index_corrupted(damaged.len()));
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Warning: Could not verify the keyword index: {}", e),
+                }
+
+                // Physically delete excluded documents once they can no longer be restored
+                let purge_orchestrator = orchestrator.clone();
+                tauri::async_runtime::spawn(async move {
+                    loop {
+                        if let Err(e) = purge_orchestrator.purge_expired_deletions().await {
+                            eprintln!("Warning: Could not purge deleted documents: {}", e);
+                        }
+                        tokio::time::sleep(DELETION_PURGE_INTERVAL).await;
+                    }
+                });
+
+                // Re-cluster topics for browsing once a day, checking hourly since sleep can
+                // pause the timer past a whole day
+                let topic_orchestrator = orchestrator.clone();
+                tauri::async_runtime::spawn(async move {
+                    loop {
+                        if let Err(e) = topic_orchestrator.refresh_topics(TOPIC_CLUSTERING_INTERVAL).await {
+                            eprintln!("Warning: Could not cluster topics: {}", e);
+                        }
+                        tokio::time::sleep(TOPIC_CLUSTERING_CHECK_INTERVAL).await;
+                    }
+                });
+
+                // Merge the small segments incremental updates leave behind
+                let merge_orchestrator = orchestrator.clone();
+                tauri::async_runtime::spawn(async move {
+                    loop {
+                        if let Err(e) = merge_orchestrator.merge_keyword_segments().await {
+                            eprintln!("Warning: Could not merge keyword index segments: {}", e);
+                        }
+                        tokio::time::sleep(SEGMENT_MERGE_INTERVAL).await;
+                    }
+                });
+
+                // Refresh stale documents that searches come across
+                let queue_orchestrator = orchestrator.clone();
+                tauri::async_runtime::spawn(async move {
+                    queue_orchestrator.run_reindex_queue().await;
+                });
+
+                // Pick up commits made since the last run in the tracked repositories
+                for repository in &settings.git_repositories {
+                    if let Err(e) = orchestrator.index_git_repository(repository, settings.git_commit_limit).await {
+                        eprintln!("Warning: Could not index git repository {}: {}", repository.display(), e);
+                    }
+                }
+
+                // Pick up commands run since the last launch, if the user opted in
+                if settings.shell_history_enabled {
+                    if let Err(e) = orchestrator.index_shell_history(settings.shell_history_limit).await {
+                        eprintln!("Warning: Could not index shell history: {}", e);
+                    }
+                }
+
+                // Drop chunks of long-untouched documents, then bring the vector store
+                // back under quota in case it grew since the last run
+                if let Some(retention_days) = settings.chunk_retention_days {
+                    if let Err(e) = orchestrator.apply_chunk_retention(retention_days).await {
+                        eprintln!("Warning: Could not apply chunk retention policy: {}", e);
+                    }
+                }
+                if let Some(quota_mb) = settings.vector_store_quota_mb {
+                    if let Err(e) = orchestrator.enforce_storage_quota(quota_mb.saturating_mul(1024 * 1024)).await {
+                        eprintln!("Warning: Could not enforce storage quota: {}", e);
+                    }
+                }
+            });
+
             #[cfg(target_os = "macos")]
             {
                 // Set up the transparent window with rounded corners
                 setup_rounded_transparent_window(&window);
                 
-                // Apply vibrancy that should maintain transparency
-                apply_vibrancy(&window, NSVisualEffectMaterial::Popover, None, None)
-                    .expect("Unsupported platform! 'apply_vibrancy' is only supported on macOS");
-                
-                // Force the vibrancy to always stay active
-                force_vibrancy_active(&window);
+                // Apply vibrancy for the current appearance, forced to always stay active
+                apply_launcher_vibrancy(&window, window.theme().unwrap_or(Theme::Dark));
             }
 
             #[cfg(target_os = "windows")]
@@ -170,23 +957,182 @@ fn main() {
                 // Set up rounded corners using DWM API (Windows 11 Build 22000+)
                 setup_rounded_transparent_window_windows(&window);
                 
-                // Apply blur effect for transparency
-                apply_blur(&window, Some((18, 18, 18, 125)))
-                    .expect("Unsupported platform! 'apply_blur' is only supported on Windows");
-                
-                // Force initial blur consistency
-                force_blur_consistency_windows(&window);
+                // Apply the configured backdrop material for transparency
+                force_backdrop_consistency_windows(app.handle(), &window);
+            }
+
+            // Dropping files or folders onto the launcher indexes them right away. When the
+            // OS appearance changes, the native chrome follows and the frontend is told, so
+            // the two never mismatch.
+            let event_handle = app.handle().clone();
+            let event_window = window.clone();
+            window.on_window_event(move |event| match event {
+                WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) => {
+                    crawler::crawl(&event_handle, paths.clone());
+                }
+                WindowEvent::ThemeChanged(theme) => {
+                    #[cfg(target_os = "macos")]
+                    apply_launcher_vibrancy(&event_window, *theme);
+                    #[cfg(target_os = "windows")]
+                    force_backdrop_consistency_windows(&event_handle, &event_window);
+                    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+                    let _ = &event_window;
+                    if let Err(e) = event_handle.emit("theme-changed", theme) {
+                        eprintln!("Warning: Could not emit theme-changed event: {}", e);
+                    }
+                }
+                _ => {}
+            });
+
+            // Open links from other apps in the launcher. Windows and Linux only learn the
+            // scheme at install time, so register it at runtime too for development builds.
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                eprintln!("Warning: Could not register the multisearch:// link handler: {}", e);
             }
+            let link_handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for link in event.urls() {
+                    handle_deep_link(&link_handle, link.as_str());
+                }
+            });
 
-            app.global_shortcut()
-                .on_shortcut("CmdOrCtrl+Shift+Space", move |_app, _shortcut, event| {
-                    if event.state == ShortcutState::Pressed {
-                        toggle_launcher_window(&handle);
+            // Retry folders that were blocked by macOS privacy settings once access is granted
+            let retry_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(PERMISSION_RETRY_INTERVAL).await;
+                    let unblocked = retry_handle.state::<AppState>().take_unblocked_folders();
+                    if !unblocked.is_empty() {
+                        crawler::crawl(&retry_handle, unblocked);
                     }
-                })
-                .expect("Failed to register global shortcut");
+                }
+            });
+
+            // Fall back to the default if the saved shortcut was taken by another app meanwhile
+            let launcher_shortcut = handle.state::<AppState>().settings.lock().unwrap().launcher_shortcut.clone();
+            if let Err(e) = register_launcher_shortcut(&handle, &launcher_shortcut) {
+                eprintln!("Warning: {}; using {} instead", e, shortcuts::DEFAULT_LAUNCHER_SHORTCUT);
+                register_launcher_shortcut(&handle, shortcuts::DEFAULT_LAUNCHER_SHORTCUT)
+                    .expect("Failed to register global shortcut");
+            }
+            let selection_shortcut = handle.state::<AppState>().settings.lock().unwrap().selection_shortcut.clone();
+            if let Some(accelerator) = selection_shortcut {
+                if let Err(e) = register_selection_shortcut(&handle, &accelerator) {
+                    eprintln!("Warning: {}; searching the selection by shortcut is off", e);
+                }
+            }
+            let voice_shortcut = handle.state::<AppState>().settings.lock().unwrap().voice_shortcut.clone();
+            if let Some(accelerator) = voice_shortcut {
+                if let Err(e) = register_voice_shortcut(&handle, &accelerator) {
+                    eprintln!("Warning: {}; voice queries are off", e);
+                }
+            }
             Ok(())
         })
+        .invoke_handler(tauri::generate_handler![
+            commands::get_stats,
+            commands::get_query_analytics,
+            commands::clear_query_analytics,
+            commands::export_metrics,
+            commands::set_metrics_export_enabled,
+            commands::generate_diagnostics,
+            commands::search,
+            commands::live_search,
+            commands::is_model_ready,
+            commands::download_model,
+            commands::set_offline_mode,
+            commands::list_cached_models,
+            commands::delete_cached_model,
+            commands::index_path,
+            commands::add_indexed_folder,
+            commands::remove_indexed_folder,
+            commands::delete_path,
+            commands::batch_search,
+            commands::search_in_document,
+            commands::get_document_summary,
+            commands::get_recent_documents,
+            commands::get_reader_text,
+            commands::browse_topics,
+            commands::get_timeline,
+            commands::get_metadata_fields,
+            commands::get_digests,
+            commands::generate_digest,
+            commands::set_notification_enabled,
+            commands::get_aliases,
+            commands::set_aliases,
+            commands::set_content_type_boost,
+            commands::get_identities,
+            commands::set_identities,
+            commands::get_scopes,
+            commands::set_scopes,
+            commands::get_session_context,
+            commands::get_health_report,
+            commands::get_data_dir,
+            commands::set_data_dir,
+            commands::record_document_opened,
+            commands::set_active_file,
+            commands::reindex_document,
+            commands::exclude_document,
+            commands::undo_exclude_document,
+            commands::get_pending_deletions,
+            commands::run_bulk_action,
+            commands::get_result_actions,
+            commands::set_result_actions,
+            commands::run_result_action,
+            commands::index_url,
+            commands::get_bookmarklet,
+            commands::set_capture_endpoint_enabled,
+            commands::get_webhook_url,
+            commands::set_webhooks_enabled,
+            commands::import_kindle_clippings,
+            commands::set_readwise_token,
+            commands::sync_readwise,
+            commands::sync_apple_notes,
+            commands::set_connector_schedule,
+            commands::set_connector_enabled,
+            commands::get_connector_state,
+            commands::list_slack_channels,
+            commands::get_slack_channels,
+            commands::set_slack_channels,
+            commands::get_connector_status,
+            commands::get_sync_cursors,
+            commands::reset_sync_cursor,
+            commands::get_auth_status,
+            commands::set_oauth_client,
+            commands::start_auth,
+            commands::sign_out,
+            commands::import_zotero_library,
+            commands::set_password_manager_provider_enabled,
+            commands::index_git_repository,
+            commands::set_shell_history_enabled,
+            commands::detect_import_sources,
+            commands::import_locations,
+            commands::get_installed_docsets,
+            commands::index_docset,
+            commands::list_snippets,
+            commands::create_snippet,
+            commands::update_snippet,
+            commands::delete_snippet,
+            commands::paste_snippet,
+            commands::pin_search,
+            commands::unpin_search,
+            commands::capture_shortcut,
+            commands::set_hotkey,
+            commands::set_window_backdrop,
+            commands::resize_launcher,
+            commands::set_selection_shortcut,
+            commands::set_voice_shortcut,
+            commands::list_snapshots,
+            commands::rollback_to_snapshot,
+            commands::get_permission_status,
+            commands::open_privacy_settings,
+            commands::record_experiment_click,
+            commands::get_experiment_report,
+            commands::set_storage_quota,
+            commands::get_disk_space_status,
+            commands::set_min_free_disk,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
\ No newline at end of file
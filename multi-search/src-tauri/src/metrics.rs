@@ -0,0 +1,280 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::index_events::IndexEventKind;
+use crate::model_store::cached_models;
+use crate::settings::app_data_dir;
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How many recent query latencies are kept for percentile calculations.
+const LATENCY_WINDOW: usize = 1000;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A point-in-time view of the locally recorded metrics, returned by the stats API.
+/// Contains only aggregates: no queries, paths, or document content.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MetricsSnapshot {
+    pub query_count: u64,
+    pub latency_p50_ms: f32,
+    pub latency_p90_ms: f32,
+    pub latency_p99_ms: f32,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub cache_hit_rate: f32,
+    pub error_counts: HashMap<String, u64>,
+    pub keyword_index_bytes: u64,
+    pub vector_store_bytes: u64,
+    /// Disk space of each downloaded model by repo, counting copies in the hf-hub cache.
+    pub model_bytes: BTreeMap<String, u64>,
+    /// Documents added, updated and deleted since the app started.
+    pub documents_added: u64,
+    pub documents_updated: u64,
+    pub documents_deleted: u64,
+    /// Searches since the app started that found nothing, and that the launcher was
+    /// closed after without opening a result. The queries themselves stay out of here.
+    pub zero_result_queries: u64,
+    pub abandoned_queries: u64,
+    /// Median and 90th percentile latency of each search phase, e.g. "keyword" or "chunks".
+    pub phase_latency_p50_ms: BTreeMap<String, f32>,
+    pub phase_latency_p90_ms: BTreeMap<String, f32>,
+}
+
+/// How long each phase of one search took, in milliseconds. The retrieval legs run
+/// concurrently, so the phases add up to more than the total.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct SearchTimings {
+    pub embedding_ms: f32,
+    pub keyword_ms: f32,
+    pub titles_ms: f32,
+    pub summaries_ms: f32,
+    pub chunks_ms: f32,
+    /// Looking up titles and dates of documents only the vector legs found.
+    pub metadata_ms: f32,
+    /// Rank fusion and final scoring.
+    pub fusion_ms: f32,
+    pub total_ms: f32,
+}
+
+/// Collects anonymous usage metrics in memory. Nothing leaves the machine
+/// unless the user has opted in and explicitly requests an export.
+pub struct Metrics {
+    query_count: AtomicU64,
+    latencies_ms: Mutex<VecDeque<f32>>,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    error_counts: Mutex<HashMap<String, u64>>,
+    documents_added: AtomicU64,
+    documents_updated: AtomicU64,
+    documents_deleted: AtomicU64,
+    zero_result_queries: AtomicU64,
+    abandoned_queries: AtomicU64,
+    phase_latencies_ms: Mutex<HashMap<&'static str, VecDeque<f32>>>,
+    // Where the indexes live, for reporting their size
+    keyword_index_dir: PathBuf,
+    embedding_dirs: Vec<PathBuf>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the value at the given percentile (0.0 to 1.0) of an already sorted slice.
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p * (sorted.len() - 1) as f32).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Converts a duration to fractional milliseconds, the unit latencies are reported in.
+pub fn millis(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+/// Recursively sums the size of all files under a directory. Missing directories count as zero.
+pub fn directory_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl SearchTimings {
+    /// Returns each phase's latency by name.
+    pub fn phases(&self) -> [(&'static str, f32); 7] {
+        [
+            ("embedding", self.embedding_ms),
+            ("keyword", self.keyword_ms),
+            ("titles", self.titles_ms),
+            ("summaries", self.summaries_ms),
+            ("chunks", self.chunks_ms),
+            ("metadata", self.metadata_ms),
+            ("fusion", self.fusion_ms),
+        ]
+    }
+}
+
+impl Metrics {
+    /// Starts empty metrics for indexes kept in the given directories.
+    pub fn new(keyword_index_dir: PathBuf, embedding_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            query_count: AtomicU64::new(0),
+            latencies_ms: Mutex::new(VecDeque::with_capacity(LATENCY_WINDOW)),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            error_counts: Mutex::new(HashMap::new()),
+            documents_added: AtomicU64::new(0),
+            documents_updated: AtomicU64::new(0),
+            documents_deleted: AtomicU64::new(0),
+            zero_result_queries: AtomicU64::new(0),
+            abandoned_queries: AtomicU64::new(0),
+            phase_latencies_ms: Mutex::new(HashMap::new()),
+            keyword_index_dir,
+            embedding_dirs,
+        }
+    }
+
+    /// Records the end-to-end latency of a single search.
+    pub fn record_query_latency(&self, latency: Duration) {
+        self.query_count.fetch_add(1, Ordering::Relaxed);
+        let mut latencies = self.latencies_ms.lock().unwrap();
+        if latencies.len() == LATENCY_WINDOW {
+            latencies.pop_front();
+        }
+        latencies.push_back(millis(latency));
+    }
+
+    /// Records how long each phase of a search took.
+    pub fn record_search_timings(&self, timings: &SearchTimings) {
+        let mut phase_latencies = self.phase_latencies_ms.lock().unwrap();
+        for (phase, latency_ms) in timings.phases() {
+            let latencies = phase_latencies.entry(phase).or_default();
+            if latencies.len() == LATENCY_WINDOW {
+                latencies.pop_front();
+            }
+            latencies.push_back(latency_ms);
+        }
+    }
+
+    pub fn record_cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_cache_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Increments the error counter for a category such as "search" or "indexing".
+    pub fn record_error(&self, category: &str) {
+        let mut errors = self.error_counts.lock().unwrap();
+        *errors.entry(category.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn record_zero_result_query(&self) {
+        self.zero_result_queries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_abandoned_query(&self) {
+        self.abandoned_queries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a document change published on the index event bus.
+    pub fn record_index_event(&self, kind: IndexEventKind) {
+        let counter = match kind {
+            IndexEventKind::Added => &self.documents_added,
+            IndexEventKind::Updated => &self.documents_updated,
+            IndexEventKind::Deleted => &self.documents_deleted,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Builds a snapshot of the current metrics, including on-disk index sizes.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let mut sorted: Vec<f32> = self.latencies_ms.lock().unwrap().iter().cloned().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let cache_hits = self.cache_hits.load(Ordering::Relaxed);
+        let cache_misses = self.cache_misses.load(Ordering::Relaxed);
+        let cache_lookups = cache_hits + cache_misses;
+        let cache_hit_rate = if cache_lookups > 0 {
+            cache_hits as f32 / cache_lookups as f32
+        } else {
+            0.0
+        };
+
+        let mut phase_latency_p50_ms = BTreeMap::new();
+        let mut phase_latency_p90_ms = BTreeMap::new();
+        for (phase, latencies) in self.phase_latencies_ms.lock().unwrap().iter() {
+            let mut sorted: Vec<f32> = latencies.iter().cloned().collect();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            phase_latency_p50_ms.insert(phase.to_string(), percentile(&sorted, 0.50));
+            phase_latency_p90_ms.insert(phase.to_string(), percentile(&sorted, 0.90));
+        }
+
+        let keyword_index_bytes = directory_size(&self.keyword_index_dir);
+        let vector_store_bytes = self.embedding_dirs.iter().map(|dir| directory_size(dir)).sum();
+        let mut model_bytes = BTreeMap::new();
+        for model in cached_models(&[]) {
+            *model_bytes.entry(model.repo).or_default() += model.bytes;
+        }
+
+        MetricsSnapshot {
+            query_count: self.query_count.load(Ordering::Relaxed),
+            latency_p50_ms: percentile(&sorted, 0.50),
+            latency_p90_ms: percentile(&sorted, 0.90),
+            latency_p99_ms: percentile(&sorted, 0.99),
+            cache_hits,
+            cache_misses,
+            cache_hit_rate,
+            error_counts: self.error_counts.lock().unwrap().clone(),
+            keyword_index_bytes,
+            vector_store_bytes,
+            model_bytes,
+            documents_added: self.documents_added.load(Ordering::Relaxed),
+            documents_updated: self.documents_updated.load(Ordering::Relaxed),
+            documents_deleted: self.documents_deleted.load(Ordering::Relaxed),
+            zero_result_queries: self.zero_result_queries.load(Ordering::Relaxed),
+            abandoned_queries: self.abandoned_queries.load(Ordering::Relaxed),
+            phase_latency_p50_ms,
+            phase_latency_p90_ms,
+        }
+    }
+
+    /// Writes the aggregated snapshot to a JSON file for attaching to bug reports.
+    /// Refuses to run unless the user has opted in to metrics export.
+    pub fn export(&self, opted_in: bool) -> Result<PathBuf> {
+        if !opted_in {
+            return Err(anyhow::anyhow!("Metrics export is disabled. Enable it in settings to export."));
+        }
+
+        let export_dir = app_data_dir()?.join("exports");
+        std::fs::create_dir_all(&export_dir)?;
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let export_path = export_dir.join(format!("metrics-{}.json", timestamp));
+        std::fs::write(&export_path, serde_json::to_string_pretty(&self.snapshot())?)?;
+        Ok(export_path)
+    }
+}
@@ -0,0 +1,383 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::metrics::directory_size;
+use crate::settings::{app_data_dir, Settings};
+use crate::state_store::now_secs;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+/// Where models are downloaded from.
+const HUB_URL: &str = "https://huggingface.co";
+/// Downloads can take minutes on a slow link, so only connecting is timed out.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// File in a model's folder holding when the app last loaded it, in Unix seconds.
+const LAST_USED_FILE: &str = ".last_used";
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A model on the Hugging Face Hub and the files needed to load it.
+pub struct ModelSpec {
+    pub repo: &'static str,
+    pub files: &'static [&'static str],
+}
+
+/// The sentence embedding model every index is built with.
+pub const EMBEDDING_MODEL: ModelSpec = ModelSpec {
+    repo: "sentence-transformers/all-MiniLM-L6-v2",
+    files: &["config.json", "tokenizer.json", "model.safetensors"],
+};
+
+/// Quantized Flan-T5 small (~60 MB): small enough to summarize on the CPU while indexing.
+pub const SUMMARIZER_MODEL: ModelSpec = ModelSpec {
+    repo: "lmz/candle-quantized-t5",
+    files: &["config-flan-t5-small.json", "tokenizer.json", "model-flan-t5-small.gguf"],
+};
+
+/// English-only Whisper tiny (~150 MB): transcribes a spoken query in well under a
+/// second on the CPU.
+pub const WHISPER_MODEL: ModelSpec = ModelSpec {
+    repo: "openai/whisper-tiny.en",
+    files: &["config.json", "tokenizer.json", "model.safetensors"],
+};
+
+/// Every model the app can load. Their copies in the shared hf-hub cache are listed
+/// along with the app's own; other models there belong to other tools.
+const KNOWN_MODELS: &[&ModelSpec] = &[&EMBEDDING_MODEL, &SUMMARIZER_MODEL, &WHISPER_MODEL];
+
+/// A downloaded model and the disk space it takes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CachedModel {
+    pub repo: String,
+    pub bytes: u64,
+    /// Unix seconds the app last loaded it, or when it was downloaded if not since.
+    pub last_used: Option<u64>,
+    /// True while the settings need it, e.g. the summarizer with abstractive summaries on.
+    /// Pinned models are never deleted.
+    pub pinned: bool,
+    /// True for a copy in the shared hf-hub cache that earlier versions downloaded to.
+    /// Other tools use that cache too, so these copies are listed but never deleted.
+    pub hf_cache: bool,
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+/// Emitted as `model-download-progress` while a model file downloads.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDownloadProgress {
+    pub repo: String,
+    pub file: String,
+    pub downloaded: u64,
+    /// None if the server didn't say how large the file is.
+    pub total: Option<u64>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// The folder holding downloaded models, one subfolder per model.
+fn models_dir() -> Result<PathBuf> {
+    Ok(app_data_dir()?.join("models"))
+}
+
+/// A model's folder, e.g. `models/sentence-transformers--all-MiniLM-L6-v2`.
+fn model_dir_in(models_dir: &Path, repo: &str) -> PathBuf {
+    models_dir.join(repo.replace('/', "--"))
+}
+
+/// The files of a model that aren't in its folder yet. Downloads are written under a
+/// temporary name and renamed when complete, so a present file is a whole one.
+fn missing_files<'a>(dir: &Path, spec: &'a ModelSpec) -> Vec<&'a str> {
+    spec.files.iter().copied().filter(|file| !dir.join(file).is_file()).collect()
+}
+
+/// The hf-hub cache that earlier versions downloaded models to, if there is one.
+fn hf_cache_dir() -> Option<PathBuf> {
+    match std::env::var_os("HF_HOME") {
+        Some(home) => Some(PathBuf::from(home).join("hub")),
+        None => dirs::home_dir().map(|home| home.join(".cache").join("huggingface").join("hub")),
+    }
+}
+
+/// Turns a model folder name such as `org--name` back into its repo, `org/name`.
+fn repo_from_dir_name(name: &str) -> Option<String> {
+    let (org, model) = name.split_once("--")?;
+    Some(format!("{}/{}", org, model))
+}
+
+/// When a model in `dir` was last loaded, falling back to when its folder last changed.
+fn last_used(dir: &Path) -> Option<u64> {
+    std::fs::read_to_string(dir.join(LAST_USED_FILE)).ok()
+        .and_then(|secs| secs.trim().parse().ok())
+        .or_else(|| {
+            let modified = std::fs::metadata(dir).ok()?.modified().ok()?;
+            Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+        })
+}
+
+/// Lists the models in the app's folder and the known models in the hf-hub cache.
+fn list_models(models_dir: &Path, cache_dir: Option<&Path>, pinned: &[&str]) -> Vec<CachedModel> {
+    let cached = |repo: String, dir: PathBuf, hf_cache: bool| CachedModel {
+        pinned: pinned.contains(&repo.as_str()),
+        bytes: directory_size(&dir),
+        last_used: last_used(&dir),
+        repo,
+        hf_cache,
+        dir,
+    };
+
+    let mut models: Vec<CachedModel> = std::fs::read_dir(models_dir).into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let repo = repo_from_dir_name(&entry.file_name().to_string_lossy())?;
+            Some(cached(repo, entry.path(), false))
+        })
+        .collect();
+    if let Some(cache_dir) = cache_dir {
+        for spec in KNOWN_MODELS {
+            let dir = cache_dir.join(format!("models--{}", spec.repo.replace('/', "--")));
+            if dir.is_dir() {
+                models.push(cached(spec.repo.to_string(), dir, true));
+            }
+        }
+    }
+    models.sort_by(|a, b| a.repo.cmp(&b.repo).then(a.hf_cache.cmp(&b.hf_cache)));
+    models
+}
+
+/// Picks the models to delete: unpinned ones in the app's folder not used for `max_age_secs`.
+fn unused_models(models: Vec<CachedModel>, now: u64, max_age_secs: u64) -> Vec<CachedModel> {
+    models.into_iter()
+        .filter(|model| !model.pinned && !model.hf_cache)
+        .filter(|model| model.last_used.is_none_or(|used| now.saturating_sub(used) >= max_age_secs))
+        .collect()
+}
+
+/// Deletes models from disk, returning how many bytes were freed.
+fn delete_models(models: &[CachedModel]) -> Result<u64> {
+    let mut freed = 0;
+    for model in models {
+        std::fs::remove_dir_all(&model.dir)
+            .map_err(|e| anyhow::anyhow!("Could not delete the {} model: {}", model.repo, e))?;
+        freed += model.bytes;
+    }
+    Ok(freed)
+}
+
+/// Finds a model file in the hf-hub cache, at the revision its `main` ref points to.
+fn hf_cached_file(cache_dir: &Path, repo: &str, file: &str) -> Option<PathBuf> {
+    let repo_dir = cache_dir.join(format!("models--{}", repo.replace('/', "--")));
+    let revision = std::fs::read_to_string(repo_dir.join("refs").join("main")).ok()?;
+    let path = repo_dir.join("snapshots").join(revision.trim()).join(file);
+    path.is_file().then_some(path)
+}
+
+/// Takes missing files from the hf-hub cache, so upgrading doesn't download a model
+/// again. Hard-linked where possible, copied where not.
+fn adopt_hf_cache(dir: &Path, spec: &ModelSpec, cache_dir: &Path) -> Result<()> {
+    for file in missing_files(dir, spec) {
+        let Some(cached) = hf_cached_file(cache_dir, spec.repo, file) else { continue };
+        std::fs::create_dir_all(dir)?;
+        // Cached files are usually symlinks into the cache's blob folder
+        let cached = std::fs::canonicalize(&cached)?;
+        if std::fs::hard_link(&cached, dir.join(file)).is_err() {
+            std::fs::copy(&cached, dir.join(file))?;
+        }
+    }
+    Ok(())
+}
+
+/// Downloads one file of a model into its folder, reporting progress as it goes.
+async fn download_file(
+    client: &reqwest::Client,
+    dir: &Path,
+    repo: &str,
+    file: &str,
+    on_progress: &impl Fn(ModelDownloadProgress),
+) -> Result<()> {
+    let url = format!("{}/{}/resolve/main/{}", HUB_URL, repo, file);
+    let mut response = client.get(&url).send().await?.error_for_status()?;
+    let total = response.content_length();
+    let partial = dir.join(format!("{}.part", file));
+    let mut output = tokio::fs::File::create(&partial).await?;
+    let mut downloaded = 0;
+    while let Some(chunk) = response.chunk().await? {
+        output.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+        on_progress(ModelDownloadProgress {
+            repo: repo.to_string(),
+            file: file.to_string(),
+            downloaded,
+            total,
+        });
+    }
+    output.flush().await?;
+    drop(output);
+    tokio::fs::rename(&partial, dir.join(file)).await?;
+    Ok(())
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns true if every file of the model is on disk, so it loads without the network.
+/// Files already in the hf-hub cache are taken from there first.
+pub fn is_model_ready(spec: &ModelSpec) -> bool {
+    let Ok(models_dir) = models_dir() else { return false };
+    let dir = model_dir_in(&models_dir, spec.repo);
+    if let Some(cache_dir) = hf_cache_dir() {
+        if let Err(e) = adopt_hf_cache(&dir, spec, &cache_dir) {
+            eprintln!("Warning: Could not reuse the cached {} model: {}", spec.repo, e);
+        }
+    }
+    missing_files(&dir, spec).is_empty()
+}
+
+/// Downloads the files of the model that aren't on disk yet into the app data directory.
+pub async fn download_model(spec: &ModelSpec, on_progress: impl Fn(ModelDownloadProgress)) -> Result<()> {
+    let dir = model_dir_in(&models_dir()?, spec.repo);
+    let missing = missing_files(&dir, spec);
+    if missing.is_empty() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(&dir)?;
+    let client = reqwest::Client::builder().connect_timeout(CONNECT_TIMEOUT).build()?;
+    for file in missing {
+        download_file(&client, &dir, spec.repo, file, &on_progress).await
+            .map_err(|e| anyhow::anyhow!("Could not download {} of {}: {}", file, spec.repo, e))?;
+    }
+    Ok(())
+}
+
+/// Returns the paths of the model's files, in the order of `spec.files`, downloading
+/// any that are missing. In offline mode nothing is downloaded and a missing model is
+/// an error.
+pub async fn model_files(spec: &ModelSpec, offline: bool) -> Result<Vec<PathBuf>> {
+    if !is_model_ready(spec) {
+        if offline {
+            return Err(anyhow::anyhow!("The {} model isn't downloaded and offline mode is on", spec.repo));
+        }
+        download_model(spec, |_| {}).await?;
+    }
+    let dir = model_dir_in(&models_dir()?, spec.repo);
+    if let Err(e) = std::fs::write(dir.join(LAST_USED_FILE), now_secs().to_string()) {
+        eprintln!("Warning: Could not record use of the {} model: {}", spec.repo, e);
+    }
+    Ok(spec.files.iter().map(|file| dir.join(file)).collect())
+}
+
+/// Returns the repos of the models the settings need: the embedding model always, the
+/// summarizer with abstractive summaries on, and Whisper while voice search has a shortcut.
+pub fn pinned_models(settings: &Settings) -> Vec<&'static str> {
+    let mut pinned = vec![EMBEDDING_MODEL.repo];
+    if settings.abstractive_summaries_enabled {
+        pinned.push(SUMMARIZER_MODEL.repo);
+    }
+    if settings.voice_shortcut.is_some() {
+        pinned.push(WHISPER_MODEL.repo);
+    }
+    pinned
+}
+
+/// Lists every downloaded model with its size and when it was last used.
+pub fn cached_models(pinned: &[&str]) -> Vec<CachedModel> {
+    match models_dir() {
+        Ok(models_dir) => list_models(&models_dir, hf_cache_dir().as_deref(), pinned),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Deletes the app's copy of a model, returning how many bytes were freed. Pinned models
+/// can't be deleted, and a copy in the shared hf-hub cache is left for the user to remove.
+pub fn delete_model(repo: &str, pinned: &[&str]) -> Result<u64> {
+    if pinned.contains(&repo) {
+        return Err(anyhow::anyhow!("The {} model is in use by the current settings", repo));
+    }
+    let models: Vec<CachedModel> = cached_models(pinned).into_iter().filter(|model| model.repo == repo).collect();
+    if models.is_empty() {
+        return Err(anyhow::anyhow!("The {} model isn't downloaded", repo));
+    }
+    let own: Vec<CachedModel> = models.into_iter().filter(|model| !model.hf_cache).collect();
+    if own.is_empty() {
+        return Err(anyhow::anyhow!("The {} model is only in the shared hf-hub cache, which other tools use", repo));
+    }
+    delete_models(&own)
+}
+
+/// Deletes unpinned models in the app's folder not used for `max_age_days`, returning
+/// how many bytes were freed.
+pub fn prune_unused_models(pinned: &[&str], max_age_days: u64) -> Result<u64> {
+    let max_age_secs = max_age_days.saturating_mul(24 * 60 * 60);
+    let unused = unused_models(cached_models(pinned), now_secs(), max_age_secs);
+    delete_models(&unused)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adopt_hf_cache() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let spec = ModelSpec { repo: "org/tiny-model", files: &["config.json", "model.safetensors"] };
+        let model_dir = model_dir_in(&dir.join("models"), spec.repo);
+        assert_eq!(missing_files(&model_dir, &spec), vec!["config.json", "model.safetensors"]);
+
+        // Only the config is in the hf-hub cache, at the revision `main` points to
+        let cache_dir = dir.join("hub");
+        let snapshot = cache_dir.join("models--org--tiny-model").join("snapshots").join("abc123");
+        std::fs::create_dir_all(&snapshot).unwrap();
+        std::fs::create_dir_all(cache_dir.join("models--org--tiny-model").join("refs")).unwrap();
+        std::fs::write(cache_dir.join("models--org--tiny-model").join("refs").join("main"), "abc123\n").unwrap();
+        std::fs::write(snapshot.join("config.json"), "{}").unwrap();
+
+        adopt_hf_cache(&model_dir, &spec, &cache_dir).unwrap();
+        assert_eq!(std::fs::read_to_string(model_dir.join("config.json")).unwrap(), "{}");
+        assert_eq!(missing_files(&model_dir, &spec), vec!["model.safetensors"]);
+    }
+
+    #[test]
+    fn test_unused_models() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let models_dir = dir.join("models");
+        for (repo, used) in [(EMBEDDING_MODEL.repo, 100), (WHISPER_MODEL.repo, 100), ("org/tried-once", 5_000)] {
+            let model_dir = model_dir_in(&models_dir, repo);
+            std::fs::create_dir_all(&model_dir).unwrap();
+            std::fs::write(model_dir.join("model.safetensors"), [0u8; 16]).unwrap();
+            std::fs::write(model_dir.join(LAST_USED_FILE), used.to_string()).unwrap();
+        }
+        // Only known models in the hf-hub cache are listed
+        let cache_dir = dir.join("hub");
+        std::fs::create_dir_all(cache_dir.join("models--openai--whisper-tiny.en")).unwrap();
+        std::fs::create_dir_all(cache_dir.join("models--someone--else")).unwrap();
+
+        let models = list_models(&models_dir, Some(&cache_dir), &[EMBEDDING_MODEL.repo]);
+        let repos: Vec<(&str, bool)> = models.iter().map(|model| (model.repo.as_str(), model.hf_cache)).collect();
+        assert_eq!(repos, vec![
+            ("openai/whisper-tiny.en", false),
+            ("openai/whisper-tiny.en", true),
+            ("org/tried-once", false),
+            ("sentence-transformers/all-MiniLM-L6-v2", false),
+        ]);
+        assert_eq!(models[0].bytes, 16 + "100".len() as u64);
+        assert!(models[3].pinned);
+
+        // The pinned embedding model stays however long it has gone unused, and the hf-hub
+        // copy is never pruned, since other tools share that cache
+        let unused = unused_models(models, u64::MAX, 1_000);
+        let repos: Vec<(&str, bool)> = unused.iter().map(|model| (model.repo.as_str(), model.hf_cache)).collect();
+        assert_eq!(repos, vec![("openai/whisper-tiny.en", false), ("org/tried-once", false)]);
+        assert!(unused_models(unused, 5_500, 1_000).iter().all(|model| model.repo != "org/tried-once"));
+    }
+}
@@ -0,0 +1,163 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::commands::AppState;
+use crate::digests::Digest;
+use crate::disk_space::DiskSpaceStatus;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Folders with fewer files than this index quickly enough that a toast in the launcher
+/// is all the feedback needed.
+pub const INDEXING_NOTIFICATION_MIN_FILES: usize = 50;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// The kinds of background events that can raise a native notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    IndexingFinished,
+    AuthExpired,
+    IndexCorrupted,
+    DigestReady,
+    DiskSpaceLow,
+}
+
+/// Which notification categories the user wants. All are on by default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub indexing_finished: bool,
+    pub auth_expired: bool,
+    pub index_corrupted: bool,
+    pub digest_ready: bool,
+    pub disk_space_low: bool,
+}
+
+/// A notification ready to be shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub category: NotificationCategory,
+    pub title: String,
+    pub body: String,
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            indexing_finished: true,
+            auth_expired: true,
+            index_corrupted: true,
+            digest_ready: true,
+            disk_space_low: true,
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn is_enabled(&self, category: NotificationCategory) -> bool {
+        match category {
+            NotificationCategory::IndexingFinished => self.indexing_finished,
+            NotificationCategory::AuthExpired => self.auth_expired,
+            NotificationCategory::IndexCorrupted => self.index_corrupted,
+            NotificationCategory::DigestReady => self.digest_ready,
+            NotificationCategory::DiskSpaceLow => self.disk_space_low,
+        }
+    }
+
+    pub fn set_enabled(&mut self, category: NotificationCategory, enabled: bool) {
+        let toggle = match category {
+            NotificationCategory::IndexingFinished => &mut self.indexing_finished,
+            NotificationCategory::AuthExpired => &mut self.auth_expired,
+            NotificationCategory::IndexCorrupted => &mut self.index_corrupted,
+            NotificationCategory::DigestReady => &mut self.digest_ready,
+            NotificationCategory::DiskSpaceLow => &mut self.disk_space_low,
+        };
+        *toggle = enabled;
+    }
+}
+
+impl Notification {
+    /// A folder finished indexing.
+    pub fn indexing_finished(indexed: usize, failed: usize) -> Self {
+        let body = match failed {
+            0 => format!("{} files are now searchable.", indexed),
+            _ => format!("{} files are now searchable; {} could not be indexed.", indexed, failed),
+        };
+        Self { category: NotificationCategory::IndexingFinished, title: "Indexing finished".to_string(), body }
+    }
+
+    /// A connector's sign-in could not be refreshed, so it stopped syncing.
+    pub fn auth_expired(connector: &str) -> Self {
+        Self {
+            category: NotificationCategory::AuthExpired,
+            title: format!("{} sign-in expired", connector),
+            body: format!("Sign in to {} again to keep it in your search results.", connector),
+        }
+    }
+
+    /// The keyword index failed its integrity check.
+    pub fn index_corrupted(damaged_files: usize) -> Self {
+        Self {
+            category: NotificationCategory::IndexCorrupted,
+            title: "Search index damaged".to_string(),
+            body: format!("{} index files failed their integrity check. Re-index your folders to repair it.", damaged_files),
+        }
+    }
+
+    /// A digest was written. The body lists the busiest sections.
+    pub fn digest_ready(digest: &Digest) -> Self {
+        let added: usize = digest.sections.iter().map(|section| section.added).sum();
+        let updated: usize = digest.sections.iter().map(|section| section.updated).sum();
+        let names: Vec<&str> = digest.sections.iter().take(3).map(|section| section.name.as_str()).collect();
+        let more = match digest.sections.len().saturating_sub(names.len()) {
+            0 => String::new(),
+            others => format!(" and {} more", others),
+        };
+        Self {
+            category: NotificationCategory::DigestReady,
+            title: "Your digest is ready".to_string(),
+            body: format!("{} new and {} changed documents in {}{}.", added, updated, names.join(", "), more),
+        }
+    }
+
+    /// Indexing paused because the data volume is nearly full.
+    pub fn disk_space_low(status: &DiskSpaceStatus) -> Self {
+        Self {
+            category: NotificationCategory::DiskSpaceLow,
+            title: "Indexing paused".to_string(),
+            body: format!(
+                "Only {} MB is free on the drive holding your index. Free up space and indexing resumes on its own.",
+                status.free_bytes / (1024 * 1024),
+            ),
+        }
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Shows a native notification unless the user turned its category off.
+pub fn notify(app: &AppHandle, notification: Notification) {
+    let enabled = app.state::<AppState>().settings.lock().unwrap().notifications.is_enabled(notification.category);
+    if !enabled {
+        return;
+    }
+    let shown = app.notification()
+        .builder()
+        .title(&notification.title)
+        .body(&notification.body)
+        .show();
+    if let Err(e) = shown {
+        eprintln!("Warning: Could not show notification '{}': {}", notification.title, e);
+    }
+}
@@ -0,0 +1,150 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use anyhow::Result;
+use image::{DynamicImage, GrayImage, ImageFormat, RgbImage};
+use lopdf::Document;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// Where Tesseract is looked for. Apps started from the Dock or Finder don't inherit the
+/// shell's PATH, so the Homebrew and Windows installer locations are tried as well.
+const TESSERACT_CANDIDATES: &[&str] = &[
+    "tesseract",
+    "/opt/homebrew/bin/tesseract",
+    "/usr/local/bin/tesseract",
+    r"C:\Program Files\Tesseract-OCR\tesseract.exe",
+];
+/// Pages of a scanned PDF read at most; each takes a second or two.
+const MAX_OCR_PAGES: usize = 50;
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Finds a working Tesseract once per run.
+fn tesseract_path() -> Option<&'static Path> {
+    static TESSERACT: OnceLock<Option<PathBuf>> = OnceLock::new();
+    TESSERACT
+        .get_or_init(|| {
+            TESSERACT_CANDIDATES.iter().map(PathBuf::from).find(|candidate| {
+                Command::new(candidate)
+                    .arg("--version")
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .is_ok_and(|status| status.success())
+            })
+        })
+        .as_deref()
+}
+
+/// Tidies Tesseract's output: trailing spaces and page-break characters go, and runs of
+/// blank lines collapse into one paragraph break.
+fn clean_ocr_text(text: &str) -> String {
+    let mut cleaned: Vec<&str> = Vec::new();
+    for line in text.lines().map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '\u{c}')) {
+        if line.is_empty() && cleaned.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        cleaned.push(line);
+    }
+    cleaned.join("\n").trim().to_string()
+}
+
+/// Pulls the scanned images out of a PDF page in a form Tesseract reads: JPEG streams
+/// as they are, and 8-bit grey or RGB pixel data re-encoded as PNG. Other encodings,
+/// such as CCITT fax and JBIG2, are skipped.
+fn page_images(document: &Document, page_id: lopdf::ObjectId) -> Vec<Vec<u8>> {
+    let Ok(images) = document.get_page_images(page_id) else { return Vec::new() };
+    images.into_iter()
+        .filter_map(|image| {
+            let filters = image.filters.unwrap_or_default();
+            if filters.iter().any(|filter| filter == "DCTDecode") {
+                return Some(image.content.to_vec());
+            }
+            if filters.iter().any(|filter| filter != "FlateDecode") || image.bits_per_component != Some(8) {
+                return None;
+            }
+            let pixels = document.get_object(image.id).ok()?.as_stream().ok()?.decompressed_content().ok()?;
+            let (width, height) = (u32::try_from(image.width).ok()?, u32::try_from(image.height).ok()?);
+            let decoded = match image.color_space.as_deref() {
+                Some("DeviceGray") => DynamicImage::ImageLuma8(GrayImage::from_raw(width, height, pixels)?),
+                Some("DeviceRGB") => DynamicImage::ImageRgb8(RgbImage::from_raw(width, height, pixels)?),
+                _ => return None,
+            };
+            let mut png = Cursor::new(Vec::new());
+            decoded.write_to(&mut png, ImageFormat::Png).ok()?;
+            Some(png.into_inner())
+        })
+        .collect()
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns true if Tesseract is installed, so images can be read.
+pub fn is_available() -> bool {
+    tesseract_path().is_some()
+}
+
+/// Reads the text in an image (PNG, JPEG or TIFF) with Tesseract. The image is piped in,
+/// so nothing is written to disk.
+pub fn ocr_image(image: &[u8]) -> Result<String> {
+    let tesseract = tesseract_path()
+        .ok_or_else(|| anyhow::anyhow!("Reading scans needs Tesseract, which isn't installed"))?;
+    let mut child = Command::new(tesseract)
+        .args(["stdin", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Tesseract reads the whole image before writing anything, so this can't deadlock
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(image)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("Tesseract failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(clean_ocr_text(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Reads a PDF without a text layer by running the images on each page through OCR,
+/// up to `MAX_OCR_PAGES` pages.
+pub fn ocr_pdf(document: &Document) -> Result<String> {
+    let pages = document.get_pages();
+    if pages.len() > MAX_OCR_PAGES {
+        eprintln!("Warning: Only reading the first {} of {} scanned pages", MAX_OCR_PAGES, pages.len());
+    }
+
+    let mut page_texts = Vec::new();
+    for (_, page_id) in pages.into_iter().take(MAX_OCR_PAGES) {
+        for image in page_images(document, page_id) {
+            let text = ocr_image(&image)?;
+            if !text.is_empty() {
+                page_texts.push(text);
+            }
+        }
+    }
+
+    if page_texts.is_empty() {
+        return Err(anyhow::anyhow!("No text found in the PDF's scanned pages"));
+    }
+    Ok(page_texts.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_ocr_text() {
+        let output = "INVOICE  \n\n\n  No. 4471\nTotal due: $120.00\n\n\u{c}";
+        assert_eq!(clean_ocr_text(output), "INVOICE\n\nNo. 4471\nTotal due: $120.00");
+        assert_eq!(clean_ocr_text("\u{c}\n  \n"), "");
+    }
+}
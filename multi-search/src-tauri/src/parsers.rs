@@ -6,6 +6,11 @@ use lopdf::Document;
 use pdf_extract::extract_text_from_mem;
 use dotext::*;
 use docx_rs::{read_docx, DocumentChild, ParagraphChild, RunChild};
+use calamine::{open_workbook_from_rs, Data, Reader, Xlsx};
+use crate::fs_paths::long_path;
+use crate::html_text::{parse_html, HtmlPage};
+use crate::ocr;
+use std::io::{Cursor, Read};
 use std::path::Path;
 use anyhow::Result;
 
@@ -22,17 +27,21 @@ pub fn parse_document(file_path: &Path) -> Result<String> {
         .ok_or_else(|| anyhow::anyhow!("File has no extension"))?;
 
     // 2. Read the entire file into a byte array (`Vec<u8>`).
-    let file_bytes = std::fs::read(file_path)
+    let file_bytes = std::fs::read(long_path(file_path))
         .map_err(|e| anyhow::anyhow!("Failed to read file {}: {}", file_path.display(), e))?;
 
     // 3. Use a `match` statement to call the correct private parser based on the extension.
     match extension.to_lowercase().as_str() {
-        "txt" | "md" | "log" | "csv" | "json" | "xml" | "html" | "css" | "js" | "ts" | "py" | "rs" | "c" | "cpp" | "h" | "hpp" | "java" | "go" | "php" | "rb" | "swift" | "kt" | "scala" | "sh" | "bat" | "yml" | "yaml" | "toml" | "ini" | "cfg" | "conf" => {
+        "txt" | "md" | "log" | "csv" | "json" | "xml" | "css" | "js" | "ts" | "py" | "rs" | "c" | "cpp" | "h" | "hpp" | "java" | "go" | "php" | "rb" | "swift" | "kt" | "scala" | "sh" | "bat" | "yml" | "yaml" | "toml" | "ini" | "cfg" | "conf" => {
             parse_plain_text(&file_bytes)
         },
+        "html" | "htm" => Ok(parse_html(&String::from_utf8_lossy(&file_bytes)).body),
         "pdf" => parse_pdf_content(&file_bytes, file_path),
         "docx" => parse_docx_content(&file_bytes, file_path),
-        // Add other file types here in the future (xlsx, pptx, odt, etc.)
+        "xlsx" => parse_xlsx_content(&file_bytes, file_path),
+        "pptx" => parse_pptx_content(&file_bytes, file_path),
+        "png" | "jpg" | "jpeg" | "tif" | "tiff" => parse_image_content(&file_bytes, file_path),
+        // Add other file types here in the future (odt, etc.)
         _ => Err(anyhow::anyhow!("Unsupported file type: {}", extension)),
     }
 }
@@ -78,14 +87,22 @@ fn parse_pdf_content(bytes: &[u8], file_path: &Path) -> Result<String> {
             return Ok(text);
         }
     }
+
+    // Strategy 3: No text layer, so it is probably a scan; read its page images with OCR
+    if ocr::is_available() {
+        match Document::load_mem(bytes).map_err(anyhow::Error::from).and_then(|document| ocr::ocr_pdf(&document)) {
+            Ok(text) => return Ok(text),
+            Err(e) => eprintln!("Warning: OCR of {} failed: {}", file_path.display(), e),
+        }
+    }
     
-    // If both methods fail, return a meaningful error
+    // If every method fails, return a meaningful error
     Err(anyhow::anyhow!("Failed to extract text from PDF: {}", file_path.display()))
 }
 
 /// Primary PDF parsing method using lopdf for maximum reliability.
 fn parse_pdf_with_lopdf(file_path: &Path) -> Result<String> {
-    let document = Document::load(file_path)
+    let document = Document::load(long_path(file_path))
         .map_err(|e| anyhow::anyhow!("Failed to load PDF document: {}", e))?;
     
     let pages = document.get_pages();
@@ -148,7 +165,7 @@ fn parse_docx_content(bytes: &[u8], file_path: &Path) -> Result<String> {
 
 /// Primary DOCX parsing method using dotext for speed and simplicity.
 fn parse_docx_with_dotext(file_path: &Path) -> Result<String> {
-    let mut file = Docx::open(file_path)
+    let mut file = Docx::open(long_path(file_path))
         .map_err(|e| anyhow::anyhow!("Failed to open DOCX file: {}", e))?;
     
     let mut content = String::new();
@@ -206,26 +223,156 @@ fn parse_docx_with_docx_rs(bytes: &[u8]) -> Result<String> {
     Ok(text_content.join("\n\n"))
 }
 
+/// Parses an HTML file into its title, meta description and readable text, for indexing
+/// the page with its own title and summary.
+pub fn parse_html_file(file_path: &Path) -> Result<HtmlPage> {
+    let bytes = std::fs::read(long_path(file_path))
+        .map_err(|e| anyhow::anyhow!("Failed to read file {}: {}", file_path.display(), e))?;
+    Ok(parse_html(&String::from_utf8_lossy(&bytes)))
+}
+
+/// Parses images such as scanned receipts and screenshots by reading their text with OCR.
+fn parse_image_content(bytes: &[u8], file_path: &Path) -> Result<String> {
+    let text = ocr::ocr_image(bytes)
+        .map_err(|e| anyhow::anyhow!("Failed to read text from image {}: {}", file_path.display(), e))?;
+    if text.is_empty() {
+        return Err(anyhow::anyhow!("No text found in image: {}", file_path.display()));
+    }
+    Ok(text)
+}
+
+/// Parses XLSX spreadsheets: each sheet's name, then its rows with the non-empty cells
+/// separated by tabs.
+fn parse_xlsx_content(bytes: &[u8], file_path: &Path) -> Result<String> {
+    let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(bytes))
+        .map_err(|e| anyhow::anyhow!("Failed to open XLSX file {}: {}", file_path.display(), e))?;
+
+    let mut sheets = Vec::new();
+    for sheet_name in workbook.sheet_names() {
+        let range = match workbook.worksheet_range(&sheet_name) {
+            Ok(range) => range,
+            Err(e) => {
+                // Skip sheets that can't be read, but continue with others
+                eprintln!("Warning: Could not read sheet '{}' of {}: {}", sheet_name, file_path.display(), e);
+                continue;
+            }
+        };
+        let rows: Vec<String> = range.rows()
+            .map(|row| {
+                row.iter()
+                    .filter(|cell| !matches!(cell, Data::Empty))
+                    .map(|cell| cell.to_string().trim().to_string())
+                    .filter(|cell| !cell.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\t")
+            })
+            .filter(|row| !row.is_empty())
+            .collect();
+        if !rows.is_empty() {
+            sheets.push(format!("{}\n{}", sheet_name, rows.join("\n")));
+        }
+    }
+
+    if sheets.is_empty() {
+        return Err(anyhow::anyhow!("No cell content found in XLSX: {}", file_path.display()));
+    }
+    Ok(sheets.join("\n\n"))
+}
+
+/// Parses PPTX decks: the text of each slide, in slide order.
+fn parse_pptx_content(bytes: &[u8], file_path: &Path) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| anyhow::anyhow!("Failed to open PPTX file {}: {}", file_path.display(), e))?;
+
+    // 1. Find the slides, ordered by their number rather than by name (slide10 after slide9).
+    let mut slides: Vec<(u32, String)> = archive.file_names()
+        .filter_map(|name| {
+            let number = name.strip_prefix("ppt/slides/slide")?.strip_suffix(".xml")?.parse().ok()?;
+            Some((number, name.to_string()))
+        })
+        .collect();
+    slides.sort();
+
+    // 2. Pull the text out of each slide's XML.
+    let mut slide_texts = Vec::new();
+    for (_, name) in slides {
+        let mut xml = String::new();
+        let read = match archive.by_name(&name) {
+            Ok(mut entry) => entry.read_to_string(&mut xml).map_err(anyhow::Error::from),
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = read {
+            // Skip slides that can't be read, but continue with others
+            eprintln!("Warning: Could not read {} of {}: {}", name, file_path.display(), e);
+            continue;
+        }
+        let text = pptx_slide_text(&xml);
+        if !text.is_empty() {
+            slide_texts.push(text);
+        }
+    }
+
+    if slide_texts.is_empty() {
+        return Err(anyhow::anyhow!("No text content found in PPTX: {}", file_path.display()));
+    }
+    Ok(slide_texts.join("\n\n"))
+}
+
+/// Extracts a slide's text from its XML: the `<a:t>` runs of each `<a:p>` paragraph
+/// joined, one paragraph per line.
+fn pptx_slide_text(xml: &str) -> String {
+    xml.split("</a:p>")
+        .map(|paragraph| {
+            paragraph.split("<a:t")
+                .skip(1)
+                // `<a:t>` or `<a:t xml:space="preserve">`, but not `<a:tab/>` or `<a:tbl>`
+                .filter(|run| run.starts_with('>') || run.starts_with(' '))
+                .filter_map(|run| run.split_once('>').and_then(|(_, rest)| rest.split_once("</a:t>")))
+                .map(|(text, _)| decode_xml_entities(text))
+                .collect::<String>()
+        })
+        .map(|paragraph| paragraph.trim().to_string())
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Decodes the five predefined XML entities; `&amp;` goes last so `&amp;lt;` stays `&lt;`.
+fn decode_xml_entities(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">")
+        .replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
 // ===================================================================
 //  UTILITY FUNCTIONS
 // ===================================================================
 
+/// Images read with OCR, supported only while Tesseract is installed.
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff"];
+
 /// Returns true if the given file extension is supported by this parser.
 pub fn is_supported_file_type(extension: &str) -> bool {
+    let extension = extension.to_lowercase();
     matches!(
-        extension.to_lowercase().as_str(),
-        "txt" | "md" | "log" | "csv" | "json" | "xml" | "html" | "css" | "js" | "ts" | "py" | "rs" | "c" | "cpp" | "h" | "hpp" | "java" | "go" | "php" | "rb" | "swift" | "kt" | "scala" | "sh" | "bat" | "yml" | "yaml" | "toml" | "ini" | "cfg" | "conf" | "pdf" | "docx"
-    )
+        extension.as_str(),
+        "txt" | "md" | "log" | "csv" | "json" | "xml" | "html" | "htm" | "css" | "js" | "ts" | "py" | "rs" | "c" | "cpp" | "h" | "hpp" | "java" | "go" | "php" | "rb" | "swift" | "kt" | "scala" | "sh" | "bat" | "yml" | "yaml" | "toml" | "ini" | "cfg" | "conf" | "pdf" | "docx" | "xlsx" | "pptx"
+    ) || (IMAGE_EXTENSIONS.contains(&extension.as_str()) && ocr::is_available())
 }
 
 /// Returns a list of all supported file extensions.
 pub fn supported_extensions() -> Vec<&'static str> {
-    vec![
+    let mut extensions = vec![
         // Plain text formats
-        "txt", "md", "log", "csv", "json", "xml", "html", "css", "js", "ts", "py", "rs", "c", "cpp", "h", "hpp", "java", "go", "php", "rb", "swift", "kt", "scala", "sh", "bat", "yml", "yaml", "toml", "ini", "cfg", "conf",
+        "txt", "md", "log", "csv", "json", "xml", "css", "js", "ts", "py", "rs", "c", "cpp", "h", "hpp", "java", "go", "php", "rb", "swift", "kt", "scala", "sh", "bat", "yml", "yaml", "toml", "ini", "cfg", "conf",
+        // Markup formats
+        "html", "htm",
         // Binary document formats
-        "pdf", "docx"
-    ]
+        "pdf", "docx", "xlsx", "pptx"
+    ];
+    if ocr::is_available() {
+        extensions.extend_from_slice(IMAGE_EXTENSIONS);
+    }
+    extensions
 }
 
 #[cfg(test)]
@@ -248,6 +395,19 @@ mod tests {
         assert!(extensions.contains(&"txt"));
         assert!(extensions.contains(&"pdf"));
         assert!(extensions.contains(&"docx"));
+        assert!(extensions.contains(&"xlsx"));
+        assert!(extensions.contains(&"pptx"));
+        assert!(extensions.contains(&"htm"));
+    }
+
+    #[test]
+    fn test_pptx_slide_text() {
+        let xml = r#"<p:sld><p:cSld><p:spTree><p:sp><p:txBody>
+            <a:p><a:r><a:rPr lang="en-US"/><a:t>Q3 </a:t></a:r><a:r><a:t xml:space="preserve">Roadmap</a:t></a:r></a:p>
+            <a:p><a:r><a:t>R&amp;D &lt;draft&gt;</a:t></a:r><a:tab/></a:p>
+            <a:p><a:endParaRPr/></a:p>
+            </p:txBody></p:sp></p:spTree></p:cSld></p:sld>"#;
+        assert_eq!(pptx_slide_text(xml), "Q3 Roadmap\nR&D <draft>");
     }
 
     #[test]
@@ -0,0 +1,122 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::providers::{matches_all_words, ProviderResult, ResultProvider};
+use anyhow::Result;
+use serde::Deserialize;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Provider id of the 1Password integration.
+pub const ONE_PASSWORD_PROVIDER: &str = "1password";
+
+/// How long the item list is reused before `op` is asked again.
+const ITEM_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+/// Caps the results so a short query doesn't flood the launcher.
+const MAX_RESULTS: usize = 5;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Item metadata as listed by `op item list --format json`. Listing never includes field
+/// values, and only the fields below are deserialized, so no secret is ever held in memory.
+#[derive(Debug, Clone, Deserialize)]
+struct OnePasswordItem {
+    id: String,
+    title: String,
+    #[serde(default)]
+    category: String,
+    vault: OnePasswordVault,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OnePasswordVault {
+    id: String,
+    #[serde(default)]
+    name: String,
+}
+
+/// Surfaces 1Password item titles as search results that open the item in 1Password.
+/// Items are only kept in memory and never reach the indexes.
+#[derive(Default)]
+pub struct OnePasswordProvider {
+    cache: Mutex<Option<(Instant, Vec<OnePasswordItem>)>>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Lists item metadata through the 1Password CLI, which handles unlocking.
+fn list_items() -> Result<Vec<OnePasswordItem>> {
+    let output = Command::new("op")
+        .args(["item", "list", "--format", "json"])
+        .output()
+        .map_err(|e| anyhow::anyhow!("Could not run the 1Password CLI (op): {}", e))?;
+    if !output.status.success() {
+        // stderr only carries sign-in problems, but it is not passed on in case it echoes anything
+        return Err(anyhow::anyhow!("The 1Password CLI could not list items; is it signed in?"));
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// Builds the link that opens an item in the 1Password app.
+fn deep_link(item: &OnePasswordItem) -> String {
+    format!("onepassword://view-item?v={}&i={}", item.vault.id, item.id)
+}
+
+/// Turns a category such as "SECURE_NOTE" into "Secure note".
+fn humanize_category(category: &str) -> String {
+    let lower = category.replace('_', " ").to_lowercase();
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl OnePasswordProvider {
+    /// Returns the cached item list, refreshing it once it has expired.
+    fn items(&self) -> Result<Vec<OnePasswordItem>> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some((fetched_at, items)) = cache.as_ref() {
+            if fetched_at.elapsed() < ITEM_CACHE_TTL {
+                return Ok(items.clone());
+            }
+        }
+        let items = list_items()?;
+        *cache = Some((Instant::now(), items.clone()));
+        Ok(items)
+    }
+}
+
+impl ResultProvider for OnePasswordProvider {
+    fn id(&self) -> &str {
+        ONE_PASSWORD_PROVIDER
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<ProviderResult>> {
+        Ok(self
+            .items()?
+            .iter()
+            .filter(|item| matches_all_words(&item.title, query))
+            .take(MAX_RESULTS)
+            .map(|item| ProviderResult {
+                provider: ONE_PASSWORD_PROVIDER.to_string(),
+                title: item.title.clone(),
+                subtitle: Some(format!("{} · {}", humanize_category(&item.category), item.vault.name)),
+                open_url: deep_link(item),
+            })
+            .collect())
+    }
+
+    fn handles_sensitive_data(&self) -> bool {
+        true
+    }
+}
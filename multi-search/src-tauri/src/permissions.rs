@@ -0,0 +1,132 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Whether the app may read a protected location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionStatus {
+    Granted,
+    Denied,
+    /// The platform has no such permission (anything but macOS), or the folder doesn't exist.
+    NotApplicable,
+}
+
+/// Access status of one folder.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FolderAccess {
+    pub path: String,
+    pub status: PermissionStatus,
+}
+
+/// Everything the UI needs to explain missing permissions and link to the fix.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PermissionReport {
+    pub full_disk_access: PermissionStatus,
+    /// Desktop, Documents and Downloads, which macOS guards individually.
+    pub protected_folders: Vec<FolderAccess>,
+    /// Folders an indexing run couldn't read; they are retried once access is granted.
+    pub blocked_folders: Vec<String>,
+}
+
+/// The System Settings privacy pane to open.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyPane {
+    FullDiskAccess,
+    FilesAndFolders,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Full Disk Access can't be queried directly; the TCC database is only readable with it.
+#[cfg(target_os = "macos")]
+fn check_full_disk_access() -> PermissionStatus {
+    let Some(home) = dirs::home_dir() else {
+        return PermissionStatus::NotApplicable;
+    };
+    let tcc_db = home.join("Library/Application Support/com.apple.TCC/TCC.db");
+    match std::fs::File::open(tcc_db) {
+        Ok(_) => PermissionStatus::Granted,
+        Err(e) if is_permission_denied(&e) => PermissionStatus::Denied,
+        Err(_) => PermissionStatus::NotApplicable,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn check_full_disk_access() -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
+/// The user folders macOS protects with per-folder consent prompts.
+fn protected_folders() -> Vec<PathBuf> {
+    if !cfg!(target_os = "macos") {
+        return Vec::new();
+    }
+    [dirs::desktop_dir(), dirs::document_dir(), dirs::download_dir()]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns true if an I/O error means the OS refused access. macOS privacy (TCC)
+/// denials surface as EPERM, which Rust also reports as `PermissionDenied`.
+pub fn is_permission_denied(error: &std::io::Error) -> bool {
+    error.kind() == std::io::ErrorKind::PermissionDenied
+}
+
+/// Checks whether a folder can be listed.
+pub fn check_folder_access(path: &Path) -> PermissionStatus {
+    match std::fs::read_dir(path) {
+        Ok(_) => PermissionStatus::Granted,
+        Err(e) if is_permission_denied(&e) => PermissionStatus::Denied,
+        Err(_) => PermissionStatus::NotApplicable,
+    }
+}
+
+/// Builds the current permission status, including folders indexing got blocked on.
+pub fn permission_report(blocked_folders: &[PathBuf]) -> PermissionReport {
+    PermissionReport {
+        full_disk_access: check_full_disk_access(),
+        protected_folders: protected_folders()
+            .into_iter()
+            .map(|path| FolderAccess {
+                status: check_folder_access(&path),
+                path: path.display().to_string(),
+            })
+            .collect(),
+        blocked_folders: blocked_folders.iter().map(|path| path.display().to_string()).collect(),
+    }
+}
+
+/// Opens the System Settings pane where the user can grant access.
+#[cfg(target_os = "macos")]
+pub fn open_privacy_settings(pane: PrivacyPane) -> Result<()> {
+    let anchor = match pane {
+        PrivacyPane::FullDiskAccess => "Privacy_AllFiles",
+        PrivacyPane::FilesAndFolders => "Privacy_FilesAndFolders",
+    };
+    let url = format!("x-apple.systempreferences:com.apple.preference.security?{}", anchor);
+    let status = std::process::Command::new("open").arg(url).status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("Could not open System Settings"));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn open_privacy_settings(_pane: PrivacyPane) -> Result<()> {
+    Err(anyhow::anyhow!("Privacy settings are only managed by the app on macOS"))
+}
@@ -0,0 +1,64 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use anyhow::Result;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A result supplied live by a provider rather than read from the indexes. It carries
+/// only display metadata and a link; there is deliberately no field for document text,
+/// so providers over sensitive sources have nowhere to put secrets.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ProviderResult {
+    /// The `id()` of the provider that produced the result.
+    pub provider: String,
+    pub title: String,
+    /// Secondary line, e.g. the vault or category of a password-manager item.
+    pub subtitle: Option<String>,
+    /// Deep link that opens the item in its own app when the result is activated.
+    pub open_url: String,
+}
+
+/// A source queried at search time instead of being indexed, e.g. an app whose data
+/// must never be copied into the indexes.
+pub trait ResultProvider: Send + Sync {
+    /// Stable identifier, used to register, remove and attribute results.
+    fn id(&self) -> &str;
+
+    /// Returns the items matching a query. Called on a blocking thread, so providers may
+    /// shell out or do file I/O.
+    fn search(&self, query: &str) -> Result<Vec<ProviderResult>>;
+
+    /// Whether the provider fronts sensitive data. Queries sent to such providers and the
+    /// results they return are never logged, persisted, cached or counted in metrics.
+    fn handles_sensitive_data(&self) -> bool {
+        false
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns true if every word of the query occurs in the text, ignoring case.
+/// Filter tokens such as `source_type:gdrive` are meant for the indexes and are skipped.
+pub fn matches_all_words(text: &str, query: &str) -> bool {
+    let text = text.to_lowercase();
+    let mut words = query.split_whitespace().filter(|word| !word.contains(':')).peekable();
+    words.peek().is_some() && words.all(|word| text.contains(&word.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_all_words() {
+        assert!(matches_all_words("GitHub (work)", "git WORK"));
+        assert!(matches_all_words("GitHub", "github source_type:file"));
+        assert!(!matches_all_words("GitHub", "gitlab"));
+        assert!(!matches_all_words("GitHub", "source_type:file"));
+    }
+}
@@ -0,0 +1,177 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::settings::app_data_dir;
+use crate::state_store::now_secs;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Distinct queries kept; the least recently seen are dropped beyond this.
+const MAX_TRACKED_QUERIES: usize = 500;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// How often one query failed the user.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryStats {
+    /// The query as last typed.
+    pub query: String,
+    /// Searches for it that found nothing.
+    pub zero_results: u64,
+    /// Searches for it after which the launcher was closed without opening anything.
+    pub abandoned: u64,
+    /// Unix timestamp (seconds) of the last failure.
+    pub last_seen: u64,
+}
+
+/// The queries that failed most often, worst first, for spotting content gaps and
+/// ranking failures.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryAnalyticsReport {
+    pub zero_result_queries: Vec<QueryStats>,
+    pub abandoned_queries: Vec<QueryStats>,
+}
+
+/// Failed queries, stored as JSON next to the indexes. Unlike the metrics this holds
+/// query text, so it never leaves the machine and isn't part of metrics exports.
+pub struct QueryAnalytics {
+    path: PathBuf,
+    queries: Mutex<HashMap<String, QueryStats>>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Folds case and spacing so "Q3 Budget" and "q3  budget" count as one query.
+fn query_key(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Returns the queries with a non-zero count, highest count first, then most recent.
+fn worst(queries: &HashMap<String, QueryStats>, count: fn(&QueryStats) -> u64, limit: usize) -> Vec<QueryStats> {
+    let mut failed: Vec<QueryStats> = queries.values().filter(|stats| count(stats) > 0).cloned().collect();
+    failed.sort_by(|a, b| count(b).cmp(&count(a)).then(b.last_seen.cmp(&a.last_seen)));
+    failed.truncate(limit);
+    failed
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl QueryAnalytics {
+    /// Opens the analytics file in the app data directory, starting empty if it doesn't exist.
+    pub fn open() -> Result<Self> {
+        Self::open_at(app_data_dir()?.join("query_analytics.json"))
+    }
+
+    fn open_at(path: PathBuf) -> Result<Self> {
+        let queries = if path.exists() {
+            let contents = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Failed to parse query analytics file {}: {}", path.display(), e))?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            path,
+            queries: Mutex::new(queries),
+        })
+    }
+
+    /// Writes the analytics via a temporary file so a crash never truncates them.
+    fn persist(&self, queries: &HashMap<String, QueryStats>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(queries)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Applies a change to one query's stats, dropping the stalest queries over the cap.
+    fn update<F: FnOnce(&mut QueryStats)>(&self, query: &str, update: F) -> Result<()> {
+        let key = query_key(query);
+        if key.is_empty() {
+            return Ok(());
+        }
+        let mut queries = self.queries.lock().unwrap();
+        let stats = queries.entry(key).or_default();
+        stats.query = query.trim().to_string();
+        stats.last_seen = now_secs();
+        update(stats);
+
+        if queries.len() > MAX_TRACKED_QUERIES {
+            let mut by_age: Vec<(u64, String)> =
+                queries.iter().map(|(key, stats)| (stats.last_seen, key.clone())).collect();
+            by_age.sort();
+            for (_, key) in by_age.into_iter().take(queries.len() - MAX_TRACKED_QUERIES) {
+                queries.remove(&key);
+            }
+        }
+        self.persist(&queries)
+    }
+
+    /// Records a search that found nothing.
+    pub fn record_zero_result(&self, query: &str) -> Result<()> {
+        self.update(query, |stats| stats.zero_results += 1)
+    }
+
+    /// Records a search the user gave up on.
+    pub fn record_abandoned(&self, query: &str) -> Result<()> {
+        self.update(query, |stats| stats.abandoned += 1)
+    }
+
+    /// Returns up to `limit` of the worst zero-result and abandoned queries.
+    pub fn report(&self, limit: usize) -> QueryAnalyticsReport {
+        let queries = self.queries.lock().unwrap();
+        QueryAnalyticsReport {
+            zero_result_queries: worst(&queries, |stats| stats.zero_results, limit),
+            abandoned_queries: worst(&queries, |stats| stats.abandoned, limit),
+        }
+    }
+
+    /// Forgets every recorded query.
+    pub fn clear(&self) -> Result<()> {
+        let mut queries = self.queries.lock().unwrap();
+        queries.clear();
+        self.persist(&queries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_ranks_failed_queries() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("query_analytics.json");
+        let analytics = QueryAnalytics::open_at(path.clone()).unwrap();
+        analytics.record_zero_result("Q3  budget").unwrap();
+        analytics.record_zero_result("q3 budget").unwrap();
+        analytics.record_zero_result("roadmap").unwrap();
+        analytics.record_abandoned("roadmap").unwrap();
+        analytics.record_abandoned("   ").unwrap();
+
+        let report = QueryAnalytics::open_at(path).unwrap().report(10);
+        let zero: Vec<(&str, u64)> = report.zero_result_queries.iter()
+            .map(|stats| (stats.query.as_str(), stats.zero_results))
+            .collect();
+        assert_eq!(zero, vec![("q3 budget", 2), ("roadmap", 1)]);
+        assert_eq!(report.abandoned_queries.len(), 1);
+        assert_eq!(report.abandoned_queries[0].query, "roadmap");
+
+        analytics.clear().unwrap();
+        assert!(analytics.report(10).zero_result_queries.is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use std::collections::HashMap;
+
+/// Words that mark a query as a question when they lead it.
+const QUESTION_WORDS: &[&str] = &[
+    "who", "what", "when", "where", "why", "how", "which",
+    "can", "could", "does", "do", "did", "is", "are", "should", "would",
+];
+/// Longest word searched as a title prefix alone; shorter queries match too much to rank.
+const MAX_PREFIX_QUERY_CHARS: usize = 2;
+/// Words naming a kind of document, with the file extensions and source types that hold it.
+const CONTENT_TYPE_WORDS: &[(&[&str], &[&str], &[&str])] = &[
+    (&["slides", "slide", "deck", "presentation", "presentations"], &["ppt", "pptx", "key", "odp"], &[]),
+    (&["spreadsheet", "spreadsheets", "sheet", "sheets"], &["xls", "xlsx", "csv", "ods", "numbers"], &[]),
+    (&["email", "emails", "mail"], &["eml", "msg", "mbox"], &["gmail"]),
+];
+/// Extensions that name a kind of document even without a leading dot, e.g. `budget pdf`.
+const BARE_EXTENSIONS: &[&str] = &["pdf", "doc", "docx", "ppt", "pptx", "xls", "xlsx", "csv", "epub"];
+
+// ===================================================================
+//  PUBLIC ENUM
+// ===================================================================
+
+/// The broad intent of a query, used to adapt the retrieval pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryKind {
+    /// Looking for a specific file by name, e.g. `budget_2024.xlsx`.
+    Navigational,
+    /// Describing a topic in natural language, e.g. `q3 hiring plan`.
+    Conceptual,
+    /// Asking something the content should answer, e.g. `how do I rotate the api key?`
+    Question,
+}
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// The kinds of document a query asks for by name, e.g. "slides" or `.pdf`. Matching
+/// documents are boosted, never required, since the word may just be part of the topic.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentIntent {
+    /// Lowercase file extensions, without the dot.
+    pub extensions: Vec<String>,
+    pub source_types: Vec<String>,
+}
+
+impl ContentIntent {
+    /// Returns true if a document is of a kind the query asked for.
+    pub fn matches(&self, path: &str, source_type: &str) -> bool {
+        if self.source_types.iter().any(|wanted| wanted == source_type) {
+            return true;
+        }
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        file_name.rsplit_once('.')
+            .is_some_and(|(_, extension)| self.extensions.iter().any(|wanted| wanted.eq_ignore_ascii_case(extension)))
+    }
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns true for words that look like file names or paths rather than prose:
+/// `report.pdf`, `src/main.rs`, `budget_final`, `HybridSearchResult`.
+fn looks_like_filename(word: &str) -> bool {
+    // Field filters such as `source_type:gdrive` aren't file names
+    if word.contains(':') && !word.contains(['/', '\\']) {
+        return false;
+    }
+    if word.contains(['/', '\\', '_']) {
+        return true;
+    }
+    if let Some((stem, extension)) = word.rsplit_once('.') {
+        if !stem.is_empty()
+            && (1..=5).contains(&extension.len())
+            && extension.chars().all(|c| c.is_ascii_alphanumeric())
+            && extension.chars().any(|c| c.is_ascii_alphabetic())
+        {
+            return true;
+        }
+    }
+    // camelCase or PascalCase identifiers
+    word.chars().zip(word.chars().skip(1)).any(|(a, b)| a.is_lowercase() && b.is_uppercase())
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Classifies a query with cheap heuristics: questions end in `?` or lead with a
+/// question word, navigational queries are one or two file-name-like words, and
+/// everything else is treated as conceptual.
+pub fn classify_query(query: &str) -> QueryKind {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let first_word = words.first().map(|w| w.to_lowercase()).unwrap_or_default();
+
+    if query.trim_end().ends_with('?')
+        || (words.len() >= 3 && QUESTION_WORDS.contains(&first_word.as_str()))
+    {
+        QueryKind::Question
+    } else if !words.is_empty() && words.len() <= 2 && words.iter().any(|w| looks_like_filename(w)) {
+        QueryKind::Navigational
+    } else {
+        QueryKind::Conceptual
+    }
+}
+
+/// Replaces whole-word aliases with their user-defined expansions, e.g. `gd budget`
+/// becomes `source_type:gdrive budget`. Matching is case-insensitive and expansions
+/// are not themselves expanded again, so aliases can't loop.
+pub fn expand_aliases(query: &str, aliases: &HashMap<String, String>) -> String {
+    if aliases.is_empty() {
+        return query.to_string();
+    }
+
+    query
+        .split_whitespace()
+        .map(|word| {
+            aliases
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(word))
+                .map(|(_, expansion)| expansion.as_str())
+                .unwrap_or(word)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Pulls a `scope:name` token out of the query, returning the remaining query and the
+/// scope name. If several are given, the last one wins.
+pub fn extract_scope(query: &str) -> (String, Option<String>) {
+    let mut scope = None;
+    let remaining: Vec<&str> = query
+        .split_whitespace()
+        .filter(|word| match word.strip_prefix("scope:") {
+            Some(name) if !name.is_empty() => {
+                scope = Some(name.to_string());
+                false
+            }
+            _ => true,
+        })
+        .collect();
+    (remaining.join(" "), scope)
+}
+
+/// Rewrites `key:value` filters on fields the keyword index doesn't have into filters on
+/// the metadata object, so `year:2020` becomes `metadata.year:2020`. Prefixes may be
+/// negated (`-journal:nature`), and known fields are left as they are. Dashes in keys
+/// become underscores, as in frontmatter keys, so `due-date:` finds `due_date`.
+pub fn qualify_metadata_fields(query: &str, known_fields: &[&str]) -> String {
+    query
+        .split_whitespace()
+        .map(|word| {
+            let (sign, filter) = match word.strip_prefix(['-', '+']) {
+                Some(rest) => (&word[..1], rest),
+                None => ("", word),
+            };
+            match filter.split_once(':') {
+                Some((key, value))
+                    if !value.is_empty()
+                        && !value.starts_with("//") // A URL, not a filter
+                        && key.starts_with(|c: char| c.is_ascii_alphabetic())
+                        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                        && !known_fields.contains(&key) =>
+                {
+                    format!("{}metadata.{}:{}", sign, key.to_lowercase().replace('-', "_"), value)
+                }
+                _ => word.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the plain words of a query, in order, for scoring how close together they
+/// appear. Field filters, `+`/`-` terms, groups and boolean operators are left out, and
+/// queries that already contain a quoted phrase yield nothing.
+pub fn free_text_words(query: &str) -> Vec<&str> {
+    if query.contains('"') {
+        return Vec::new();
+    }
+    query
+        .split_whitespace()
+        .filter(|word| {
+            !word.contains([':', '(', ')', '[', ']', '^', '~', '*'])
+                && !word.starts_with(['+', '-'])
+                && !matches!(*word, "AND" | "OR" | "NOT" | "IN")
+        })
+        .collect()
+}
+
+/// Splits a query of a character or two, such as `q3` or `re source_type:gmail`, into the
+/// short word and the filters around it. Searching such a word in full matches nearly
+/// everything and embeds a meaningless query, so it is matched against title prefixes
+/// instead. None for longer queries and for filters alone.
+pub fn short_query_prefix(query: &str) -> Option<(String, String)> {
+    let words = free_text_words(query);
+    let [word] = words.as_slice() else { return None };
+    if word.chars().count() > MAX_PREFIX_QUERY_CHARS {
+        return None;
+    }
+    let rest: Vec<&str> = query.split_whitespace().filter(|other| other != word).collect();
+    Some((word.to_string(), rest.join(" ")))
+}
+
+/// Infers the kinds of document a query names: words like "slides", "spreadsheet" or
+/// "email", and extensions such as `.xlsx`, `*.key` or a bare `pdf`. None if it names none.
+pub fn infer_content_intent(query: &str) -> Option<ContentIntent> {
+    let mut intent = ContentIntent::default();
+    // Field filters already restrict by kind themselves, and `-slides` excludes rather than asks
+    for word in query.split_whitespace().filter(|word| !word.contains(':') && !word.starts_with('-')) {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '.' && c != '*').to_lowercase();
+        if let Some((_, extensions, source_types)) = CONTENT_TYPE_WORDS.iter().find(|(words, _, _)| words.contains(&word.as_str())) {
+            intent.extensions.extend(extensions.iter().map(|extension| extension.to_string()));
+            intent.source_types.extend(source_types.iter().map(|source_type| source_type.to_string()));
+            continue;
+        }
+        // `.xlsx` and `*.xlsx` name any extension; a bare word only the unmistakable ones
+        let extension = match word.strip_prefix("*.").or_else(|| word.strip_prefix('.')) {
+            Some(extension) => extension,
+            None if BARE_EXTENSIONS.contains(&word.as_str()) => word.as_str(),
+            None => continue,
+        };
+        if (1..=5).contains(&extension.len()) && extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+            intent.extensions.push(extension.to_string());
+        }
+    }
+
+    intent.extensions.sort();
+    intent.extensions.dedup();
+    intent.source_types.sort();
+    intent.source_types.dedup();
+    (!intent.extensions.is_empty() || !intent.source_types.is_empty()).then_some(intent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_query_prefix() {
+        assert_eq!(short_query_prefix(" q3 "), Some(("q3".to_string(), String::new())));
+        assert_eq!(
+            short_query_prefix("re source_type:gmail"),
+            Some(("re".to_string(), "source_type:gmail".to_string()))
+        );
+        assert_eq!(short_query_prefix("roadmap"), None);
+        assert_eq!(short_query_prefix("q3 plan"), None);
+        assert_eq!(short_query_prefix("source_type:gmail"), None);
+    }
+
+    #[test]
+    fn test_free_text_words() {
+        assert_eq!(free_text_words("quarterly budget review"), vec!["quarterly", "budget", "review"]);
+        assert_eq!(free_text_words("budget AND review source_type:gdrive -draft"), vec!["budget", "review"]);
+        assert_eq!(free_text_words("\"budget review\" 2024"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_qualify_metadata_fields() {
+        let known = ["title", "source_type", "metadata"];
+        assert_eq!(
+            qualify_metadata_fields("year:2020 -Journal:nature source_type:zotero title:cells 10:30", &known),
+            "metadata.year:2020 -metadata.journal:nature source_type:zotero title:cells 10:30"
+        );
+        assert_eq!(
+            qualify_metadata_fields("status:draft Due-Date:2024-10-01", &known),
+            "metadata.status:draft metadata.due_date:2024-10-01"
+        );
+    }
+
+    #[test]
+    fn test_expand_aliases() {
+        let mut aliases = HashMap::new();
+        aliases.insert("gd".to_string(), "source_type:gdrive".to_string());
+        aliases.insert("mtg".to_string(), "source_type:calendar mtg".to_string());
+
+        assert_eq!(expand_aliases("gd budget", &aliases), "source_type:gdrive budget");
+        assert_eq!(expand_aliases("MTG standup", &aliases), "source_type:calendar mtg standup");
+        // Only whole words are replaced
+        assert_eq!(expand_aliases("gdpr notes", &aliases), "gdpr notes");
+    }
+
+    #[test]
+    fn test_classify_query() {
+        assert_eq!(classify_query("budget_2024.xlsx"), QueryKind::Navigational);
+        assert_eq!(classify_query("src/main.rs"), QueryKind::Navigational);
+        assert_eq!(classify_query("HybridSearchResult"), QueryKind::Navigational);
+        assert_eq!(classify_query("how do I rotate the api key"), QueryKind::Question);
+        assert_eq!(classify_query("onboarding checklist?"), QueryKind::Question);
+        assert_eq!(classify_query("q3 hiring plan"), QueryKind::Conceptual);
+        assert_eq!(classify_query("source_type:gdrive budget"), QueryKind::Conceptual);
+    }
+
+    #[test]
+    fn test_infer_content_intent() {
+        let intent = infer_content_intent("q3 planning slides").unwrap();
+        assert_eq!(intent.extensions, vec!["key", "odp", "ppt", "pptx"]);
+        assert!(intent.matches("/work/Q3 Plan.PPTX", "file"));
+        assert!(!intent.matches("/work/q3-plan.docx", "file"));
+
+        let intent = infer_content_intent("invoice email from acme").unwrap();
+        assert!(intent.matches("gmail://msg/18c2", "gmail"));
+        assert_eq!(infer_content_intent("budget .odt pdf").unwrap().extensions, vec!["odt", "pdf"]);
+        assert_eq!(infer_content_intent("*.tsv export").unwrap().extensions, vec!["tsv"]);
+
+        // File names and filters don't name a kind of document
+        assert_eq!(infer_content_intent("budget_2024.xlsx"), None);
+        assert_eq!(infer_content_intent("source_type:gmail budget"), None);
+        assert_eq!(infer_content_intent("quarterly budget review"), None);
+    }
+
+    #[test]
+    fn test_extract_scope() {
+        assert_eq!(
+            extract_scope("budget scope:work-projectx q3"),
+            ("budget q3".to_string(), Some("work-projectx".to_string()))
+        );
+        assert_eq!(extract_scope("scope: budget"), ("scope: budget".to_string(), None));
+    }
+}
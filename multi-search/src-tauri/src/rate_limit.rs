@@ -0,0 +1,270 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::state_store::now_secs;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// How hard one connector may hit its API. Overridable per connector in settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Sustained request rate. 0 disables the limit.
+    pub requests_per_minute: u32,
+    /// Requests that may be sent back to back before the rate applies.
+    pub burst: u32,
+    /// Retries after a 429 or 5xx response before giving up.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for every retry after it.
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 60,
+            burst: 5,
+            max_retries: 4,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 60_000,
+        }
+    }
+}
+
+/// What a connector's limiter has seen, for the sync dashboard.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConnectorStatus {
+    pub connector: String,
+    pub requests: u64,
+    /// Responses that were 429 or 5xx.
+    pub throttled: u64,
+    pub retries: u64,
+    pub last_error: Option<String>,
+    /// Unix timestamp (seconds) until which requests are held back, if backing off.
+    pub backoff_until: Option<u64>,
+}
+
+/// Paces and retries the requests of one connector. Requests wait for a token from
+/// the bucket, and a 429 or 5xx response pauses every request of the connector.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    bucket: Mutex<TokenBucket>,
+    status: Mutex<ConnectorStatus>,
+}
+
+/// The rate limiters of all connectors, created on first use.
+pub struct RateLimiters {
+    overrides: HashMap<String, RateLimitConfig>,
+    limiters: Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+/// A token bucket refilled continuously at the configured rate.
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+    // Set after a throttled response; nothing is sent before it
+    paused_until: Option<Instant>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns true for responses worth retrying: rate limited or a server error.
+fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Reads a `Retry-After` header given in seconds. HTTP dates are ignored.
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+/// Returns how long to wait before retry number `attempt` (starting at 0): exponential
+/// from the initial backoff, capped, but never shorter than what the server asked for.
+fn backoff_delay(config: &RateLimitConfig, attempt: u32, retry_after: Option<Duration>) -> Duration {
+    let exponential = config.initial_backoff_ms.saturating_mul(1u64 << attempt.min(20));
+    let delay = Duration::from_millis(exponential.min(config.max_backoff_ms));
+    retry_after.map_or(delay, |requested| requested.max(delay))
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl TokenBucket {
+    fn new(config: &RateLimitConfig) -> Self {
+        let capacity = config.burst.max(1) as f64;
+        Self {
+            tokens: capacity,
+            capacity,
+            refill_per_sec: config.requests_per_minute as f64 / 60.0,
+            last_refill: Instant::now(),
+            paused_until: None,
+        }
+    }
+
+    /// Takes a token, or returns how long to wait before trying again.
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        if let Some(paused_until) = self.paused_until {
+            if paused_until > now {
+                return Some(paused_until - now);
+            }
+            self.paused_until = None;
+        }
+        if self.refill_per_sec <= 0.0 {
+            return None;
+        }
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec))
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(connector: &str, config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            bucket: Mutex::new(TokenBucket::new(&config)),
+            status: Mutex::new(ConnectorStatus {
+                connector: connector.to_string(),
+                ..ConnectorStatus::default()
+            }),
+        }
+    }
+
+    /// Waits until the connector may send another request.
+    async fn acquire(&self) {
+        loop {
+            let wait = self.bucket.lock().unwrap().take(Instant::now());
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+
+    /// Holds back every request of the connector for `delay`.
+    fn pause(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.paused_until = Some(bucket.paused_until.map_or(until, |current| current.max(until)));
+        self.status.lock().unwrap().backoff_until = Some(now_secs() + delay.as_secs());
+    }
+
+    /// Sends a request built by `build`, pacing it with the token bucket and retrying
+    /// 429 and 5xx responses with exponential backoff. The final response is returned
+    /// as is, so callers still check its status for other errors.
+    pub async fn send(&self, build: impl Fn() -> reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            self.acquire().await;
+            self.status.lock().unwrap().requests += 1;
+            let response = match build().send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    self.status.lock().unwrap().last_error = Some(e.to_string());
+                    return Err(e.into());
+                }
+            };
+            if !is_retryable(response.status()) {
+                let mut status = self.status.lock().unwrap();
+                status.backoff_until = None;
+                if response.status().is_success() {
+                    status.last_error = None;
+                }
+                return Ok(response);
+            }
+
+            {
+                let mut status = self.status.lock().unwrap();
+                status.throttled += 1;
+                status.last_error = Some(format!("HTTP {}", response.status()));
+            }
+            if attempt >= self.config.max_retries {
+                return Ok(response);
+            }
+            self.pause(backoff_delay(&self.config, attempt, retry_after(&response)));
+            self.status.lock().unwrap().retries += 1;
+            attempt += 1;
+        }
+    }
+
+    pub fn status(&self) -> ConnectorStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+impl RateLimiters {
+    /// Creates the registry with per-connector overrides from settings; other connectors
+    /// use the default configuration.
+    pub fn new(overrides: HashMap<String, RateLimitConfig>) -> Self {
+        Self {
+            overrides,
+            limiters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the limiter shared by every request of a connector.
+    pub fn get(&self, connector: &str) -> Arc<RateLimiter> {
+        let mut limiters = self.limiters.lock().unwrap();
+        limiters
+            .entry(connector.to_string())
+            .or_insert_with(|| {
+                let config = self.overrides.get(connector).copied().unwrap_or_default();
+                Arc::new(RateLimiter::new(connector, config))
+            })
+            .clone()
+    }
+
+    /// Returns the status of every connector that has made requests, sorted by name.
+    pub fn statuses(&self) -> Vec<ConnectorStatus> {
+        let mut statuses: Vec<ConnectorStatus> =
+            self.limiters.lock().unwrap().values().map(|limiter| limiter.status()).collect();
+        statuses.sort_by(|a, b| a.connector.cmp(&b.connector));
+        statuses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_and_backoff() {
+        let config = RateLimitConfig {
+            requests_per_minute: 60,
+            burst: 2,
+            ..RateLimitConfig::default()
+        };
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(&config);
+        assert_eq!(bucket.take(start), None);
+        assert_eq!(bucket.take(start), None);
+        let wait = bucket.take(start).unwrap();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+        assert_eq!(bucket.take(start + Duration::from_secs(1)), None);
+
+        assert_eq!(backoff_delay(&config, 0, None), Duration::from_secs(1));
+        assert_eq!(backoff_delay(&config, 3, None), Duration::from_secs(8));
+        assert_eq!(backoff_delay(&config, 10, None), Duration::from_secs(60));
+        assert_eq!(backoff_delay(&config, 0, Some(Duration::from_secs(30))), Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,100 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::voice_audio::{downmix, resample_to_whisper, MAX_VOICE_SECONDS};
+use anyhow::Result;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SampleFormat, SizedSample};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Microphone audio being recorded on a thread of its own; audio streams can't move
+/// between threads on every platform. Recording stops on its own after the longest
+/// query Whisper can take.
+pub struct Recording {
+    stop_tx: mpsc::Sender<()>,
+    thread: JoinHandle<Result<Vec<f32>>>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Opens an input stream that appends every sample, as f32, to `buffer`.
+fn build_stream<T>(device: &cpal::Device, config: &cpal::StreamConfig, buffer: Arc<Mutex<Vec<f32>>>) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _| buffer.lock().unwrap().extend(data.iter().map(|sample| sample.to_sample::<f32>())),
+        |e| eprintln!("Warning: Microphone stream error: {}", e),
+        None,
+    )?;
+    Ok(stream)
+}
+
+/// Starts the default microphone, returning the stream with its channel count and rate.
+fn open_microphone(buffer: Arc<Mutex<Vec<f32>>>) -> Result<(cpal::Stream, usize, u32)> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or_else(|| anyhow::anyhow!("No microphone found"))?;
+    let supported = device.default_input_config()?;
+    let config: cpal::StreamConfig = supported.clone().into();
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => build_stream::<f32>(&device, &config, buffer)?,
+        SampleFormat::I16 => build_stream::<i16>(&device, &config, buffer)?,
+        SampleFormat::U16 => build_stream::<u16>(&device, &config, buffer)?,
+        other => return Err(anyhow::anyhow!("Unsupported microphone sample format {:?}", other)),
+    };
+    stream.play()?;
+    Ok((stream, config.channels as usize, config.sample_rate.0))
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl Recording {
+    /// Starts recording from the default microphone. Fails right away if there is none
+    /// or the OS denies access to it.
+    pub fn start() -> Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let thread = std::thread::spawn(move || {
+            let buffer = Arc::new(Mutex::new(Vec::new()));
+            let (stream, channels, sample_rate) = match open_microphone(Arc::clone(&buffer)) {
+                Ok(opened) => {
+                    let _ = ready_tx.send(Ok(()));
+                    opened
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e.to_string()));
+                    return Err(e);
+                }
+            };
+            let _ = stop_rx.recv_timeout(Duration::from_secs(MAX_VOICE_SECONDS as u64));
+            drop(stream);
+            let samples = std::mem::take(&mut *buffer.lock().unwrap());
+            Ok(resample_to_whisper(&downmix(&samples, channels), sample_rate))
+        });
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Self { stop_tx, thread }),
+            Ok(Err(e)) => Err(anyhow::anyhow!("Could not start recording: {}", e)),
+            Err(_) => Err(anyhow::anyhow!("The recording thread stopped unexpectedly")),
+        }
+    }
+
+    /// Stops recording and returns the audio as 16 kHz mono, ready for Whisper.
+    pub fn stop(self) -> Result<Vec<f32>> {
+        let _ = self.stop_tx.send(());
+        self.thread.join().map_err(|_| anyhow::anyhow!("The recording thread panicked"))?
+    }
+}
@@ -0,0 +1,171 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A command the user added to results, e.g. "Open in VS Code" running `code {path}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultAction {
+    pub id: String,
+    pub label: String,
+    /// The program and its arguments, with `{path}`, `{dir}` and `{name}` filled in from
+    /// the result. Quoted like a shell command but never run through a shell, so a
+    /// file name can't inject commands or be split into several arguments.
+    pub command: String,
+    /// Sources the action is offered on, e.g. `["file"]`; empty offers it on every result.
+    #[serde(default)]
+    pub source_types: Vec<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Splits a command template into words, honouring single and double quotes and
+/// backslash escapes outside single quotes.
+fn split_command(template: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => word.push(c),
+            (_, '\\') => {
+                word.push(chars.next().ok_or_else(|| anyhow::anyhow!("The command ends with a lone backslash"))?);
+                in_word = true;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(anyhow::anyhow!("The command has an unclosed quote"));
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Fills the placeholders of one word of the template in from the result's path.
+fn fill_placeholders(word: &str, path: &str) -> String {
+    let file = Path::new(path);
+    let dir = file.parent().map(|dir| dir.display().to_string()).unwrap_or_default();
+    let name = file.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    word.replace("{path}", path).replace("{dir}", &dir).replace("{name}", &name)
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns the actions offered on results from the given source, in the order defined.
+pub fn actions_for<'a>(actions: &'a [ResultAction], source_type: &str) -> Vec<&'a ResultAction> {
+    actions.iter()
+        .filter(|action| action.source_types.is_empty() || action.source_types.iter().any(|s| s == source_type))
+        .collect()
+}
+
+/// Checks a set of actions before it is saved: every id unique and every command a
+/// program followed by arguments.
+pub fn validate_actions(actions: &[ResultAction]) -> Result<()> {
+    for (i, action) in actions.iter().enumerate() {
+        if actions[..i].iter().any(|other| other.id == action.id) {
+            return Err(anyhow::anyhow!("Two actions have the id '{}'", action.id));
+        }
+        let words = split_command(&action.command)
+            .map_err(|e| anyhow::anyhow!("Action '{}': {}", action.label, e))?;
+        if words.is_empty() {
+            return Err(anyhow::anyhow!("Action '{}' has no command", action.label));
+        }
+    }
+    Ok(())
+}
+
+/// Turns an action into the program to run and its arguments for one result. Each
+/// placeholder is filled in within its own word, so the path stays one argument.
+pub fn build_command(action: &ResultAction, path: &str) -> Result<(String, Vec<String>)> {
+    let mut words = split_command(&action.command)?.into_iter().map(|word| fill_placeholders(&word, path));
+    let program = words.next().ok_or_else(|| anyhow::anyhow!("Action '{}' has no command", action.label))?;
+    Ok((program, words.collect()))
+}
+
+/// Runs an action on a result without waiting for it to finish, so a long-running
+/// program such as an editor doesn't hold up the launcher.
+pub fn run_action(action: &ResultAction, path: &str) -> Result<()> {
+    let (program, args) = build_command(action, path)?;
+    let mut child = Command::new(&program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Could not run {}: {}", program, e))?;
+    // Reap the process once it exits
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(command: &str, source_types: &[&str]) -> ResultAction {
+        ResultAction {
+            id: command.to_string(),
+            label: command.to_string(),
+            command: command.to_string(),
+            source_types: source_types.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_build_command() {
+        let path = "/Users/me/Q3 report; rm -rf ~.md";
+        let (program, args) = build_command(&action("code --goto {path}", &[]), path).unwrap();
+        assert_eq!(program, "code");
+        assert_eq!(args, vec!["--goto", path]);
+
+        let (program, args) = build_command(&action(r#"open -a "Preview" '{dir}' name=\"{name}\""#, &[]), path).unwrap();
+        assert_eq!(program, "open");
+        assert_eq!(args, vec!["-a", "Preview", "/Users/me", "name=\"Q3 report; rm -rf ~.md\""]);
+
+        assert!(build_command(&action("lp 'unclosed {path}", &[]), path).is_err());
+        assert!(build_command(&action("   ", &[]), path).is_err());
+    }
+
+    #[test]
+    fn test_actions_for_and_validation() {
+        let actions = vec![action("code {path}", &["file"]), action("lp {path}", &[]), action("open {path}", &["web"])];
+        let for_files: Vec<&str> = actions_for(&actions, "file").iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(for_files, vec!["code {path}", "lp {path}"]);
+
+        assert!(validate_actions(&actions).is_ok());
+        let duplicated = vec![action("lp {path}", &[]), action("lp {path}", &["file"])];
+        assert!(validate_actions(&duplicated).is_err());
+        assert!(validate_actions(&[action("\"", &[])]).is_err());
+    }
+}
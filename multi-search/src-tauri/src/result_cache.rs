@@ -0,0 +1,197 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::index_events::{IndexEvent, IndexEventKind};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// What a search asked for apart from the page: every page of it goes stale together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedSearch {
+    pub query: String,
+    /// None unless the search was limited to some sources.
+    pub source_types: Option<Vec<String>>,
+    pub modified_after: Option<u64>,
+    pub modified_before: Option<u64>,
+}
+
+/// Recent search responses, so retyping a query or reopening the launcher on it answers
+/// at once. Entries are dropped when an index change could alter them, and expire after
+/// a while since ranking also drifts with recency and usage.
+pub struct ResultCache<V> {
+    state: Mutex<CacheState<V>>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+// ===================================================================
+//  PRIVATE STRUCTS
+// ===================================================================
+
+struct CacheState<V> {
+    // Least recently used first
+    entries: VecDeque<CacheEntry<V>>,
+    // Bumped by every invalidation, so a search that ran across one isn't stored
+    generation: u64,
+}
+
+struct CacheEntry<V> {
+    search: CachedSearch,
+    // Offset and limit
+    page: (usize, usize),
+    value: V,
+    // The documents in the response, whose changes make it stale
+    paths: HashSet<String>,
+    stored_at: Instant,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns true if an index change could alter a cached search: it touched one of the
+/// results, or it added or updated a document from a source the search covers.
+fn is_affected(search: &CachedSearch, paths: &HashSet<String>, event: &IndexEvent) -> bool {
+    if paths.contains(&event.path) {
+        return true;
+    }
+    match event.kind {
+        IndexEventKind::Deleted => false,
+        IndexEventKind::Added | IndexEventKind::Updated => match (&search.source_types, &event.source_type) {
+            (Some(source_types), Some(source_type)) => source_types.contains(source_type),
+            _ => true,
+        },
+    }
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl<V: Clone> ResultCache<V> {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            state: Mutex::new(CacheState { entries: VecDeque::new(), generation: 0 }),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Returns the cached response for a page of a search, if it is still fresh.
+    pub fn get(&self, search: &CachedSearch, page: (usize, usize)) -> Option<V> {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|entry| entry.stored_at.elapsed() < self.ttl);
+        let position = state.entries.iter().position(|entry| entry.page == page && entry.search == *search)?;
+        let entry = state.entries.remove(position)?;
+        let value = entry.value.clone();
+        state.entries.push_back(entry);
+        Some(value)
+    }
+
+    /// The current generation, taken before running a search and handed to `insert`.
+    pub fn generation(&self) -> u64 {
+        self.state.lock().unwrap().generation
+    }
+
+    /// Stores a response, unless the index changed since `generation` was taken while
+    /// the search ran. Evicts the least recently used entry when full.
+    pub fn insert(&self, search: CachedSearch, page: (usize, usize), value: V, paths: HashSet<String>, generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.generation != generation {
+            return;
+        }
+        state.entries.retain(|entry| !(entry.page == page && entry.search == search));
+        while state.entries.len() >= self.capacity.max(1) {
+            state.entries.pop_front();
+        }
+        state.entries.push_back(CacheEntry { search, page, value, paths, stored_at: Instant::now() });
+    }
+
+    /// Drops every page of each search the index change could alter. Returns how many
+    /// entries were dropped.
+    pub fn invalidate(&self, event: &IndexEvent) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        let affected: Vec<CachedSearch> = state.entries.iter()
+            .filter(|entry| is_affected(&entry.search, &entry.paths, event))
+            .map(|entry| entry.search.clone())
+            .collect();
+        let before = state.entries.len();
+        state.entries.retain(|entry| !affected.contains(&entry.search));
+        before - state.entries.len()
+    }
+
+    /// Drops everything, e.g. after missing index events or a change of aliases or scopes.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(query: &str, source_types: Option<&[&str]>) -> CachedSearch {
+        CachedSearch {
+            query: query.to_string(),
+            source_types: source_types.map(|types| types.iter().map(|t| t.to_string()).collect()),
+            modified_after: None,
+            modified_before: None,
+        }
+    }
+
+    fn event(kind: IndexEventKind, path: &str, source_type: Option<&str>) -> IndexEvent {
+        IndexEvent { kind, path: path.to_string(), source_type: source_type.map(str::to_string) }
+    }
+
+    fn paths(paths: &[&str]) -> HashSet<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let cache = ResultCache::new(8, Duration::from_secs(60));
+        let budget = search("budget", None);
+        let zotero = search("cells", Some(&["zotero"]));
+        let generation = cache.generation();
+        cache.insert(budget.clone(), (0, 20), "budget p1", paths(&["/a.md"]), generation);
+        cache.insert(budget.clone(), (20, 20), "budget p2", paths(&["/b.md"]), generation);
+        cache.insert(zotero.clone(), (0, 20), "cells", paths(&["zotero://1"]), generation);
+        assert_eq!(cache.get(&budget, (20, 20)), Some("budget p2"));
+
+        // Deleting a result drops every page of its search, and nothing else
+        assert_eq!(cache.invalidate(&event(IndexEventKind::Deleted, "/a.md", None)), 2);
+        assert_eq!(cache.get(&budget, (20, 20)), None);
+        assert_eq!(cache.get(&zotero, (0, 20)), Some("cells"));
+
+        // A new file can't appear in a Zotero-only search
+        assert_eq!(cache.invalidate(&event(IndexEventKind::Added, "/c.md", Some("file"))), 0);
+        assert_eq!(cache.invalidate(&event(IndexEventKind::Added, "zotero://2", Some("zotero"))), 1);
+    }
+
+    #[test]
+    fn test_stale_generation_and_eviction() {
+        let cache = ResultCache::new(2, Duration::from_secs(60));
+        // The index changed while this search ran, so its response isn't kept
+        let generation = cache.generation();
+        cache.invalidate(&event(IndexEventKind::Updated, "/a.md", Some("file")));
+        cache.insert(search("a", None), (0, 20), 1, paths(&[]), generation);
+        assert_eq!(cache.get(&search("a", None), (0, 20)), None);
+
+        let generation = cache.generation();
+        cache.insert(search("a", None), (0, 20), 1, paths(&[]), generation);
+        cache.insert(search("b", None), (0, 20), 2, paths(&[]), generation);
+        assert_eq!(cache.get(&search("a", None), (0, 20)), Some(1));
+        cache.insert(search("c", None), (0, 20), 3, paths(&[]), generation);
+        // "b" was the least recently used
+        assert_eq!(cache.get(&search("b", None), (0, 20)), None);
+        assert_eq!(cache.get(&search("a", None), (0, 20)), Some(1));
+    }
+}
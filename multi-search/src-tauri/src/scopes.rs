@@ -0,0 +1,93 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use serde::{Deserialize, Serialize};
+
+// ===================================================================
+//  PUBLIC STRUCT
+// ===================================================================
+
+/// A named collection of documents, e.g. "work-projectx", used to restrict a search.
+/// A document is in scope if it lives under one of the folders or comes from one of
+/// the connectors; an empty list places no restriction on that dimension.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scope {
+    pub folders: Vec<String>,
+    pub source_types: Vec<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Escapes single quotes for use inside a SQL string literal.
+fn escape_sql(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+/// Returns true if `path` is `folder` itself or lies somewhere beneath it.
+fn is_under_folder(path: &str, folder: &str) -> bool {
+    let folder = folder.trim_end_matches(['/', '\\']);
+    path == folder
+        || path.strip_prefix(folder).is_some_and(|rest| rest.starts_with(['/', '\\']))
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl Scope {
+    /// Builds a Tantivy query clause restricting keyword results to the scope's connectors.
+    /// Folders are applied by `matches` because the path field is tokenized.
+    pub fn keyword_filter(&self) -> Option<String> {
+        if self.source_types.is_empty() {
+            return None;
+        }
+        let clauses: Vec<String> = self.source_types
+            .iter()
+            .map(|source_type| format!("source_type:\"{}\"", source_type.replace('"', "")))
+            .collect();
+        Some(format!("({})", clauses.join(" OR ")))
+    }
+
+    /// Builds a LanceDB filter restricting vector results to the scope's folders.
+    /// Connectors are passed to the vector store separately, as `source_types`.
+    pub fn vector_filter(&self) -> Option<String> {
+        if self.folders.is_empty() {
+            return None;
+        }
+        let clauses: Vec<String> = self.folders
+            .iter()
+            .map(|folder| format!("starts_with(document_path, '{}')", escape_sql(folder.trim_end_matches(['/', '\\']))))
+            .collect();
+        Some(format!("({})", clauses.join(" OR ")))
+    }
+
+    /// Returns true if a document belongs to the scope.
+    pub fn matches(&self, path: &str, source_type: &str) -> bool {
+        let in_folder = self.folders.is_empty()
+            || self.folders.iter().any(|folder| is_under_folder(path, folder));
+        let from_source = self.source_types.is_empty()
+            || self.source_types.iter().any(|s| s.eq_ignore_ascii_case(source_type));
+        in_folder && from_source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_matches() {
+        let scope = Scope {
+            folders: vec!["/work/projectx/".to_string()],
+            source_types: vec!["file".to_string()],
+        };
+
+        assert!(scope.matches("/work/projectx/spec.md", "file"));
+        assert!(!scope.matches("/work/projectx-old/spec.md", "file"));
+        assert!(!scope.matches("/work/projectx/spec.md", "slack"));
+        assert_eq!(scope.vector_filter().unwrap(), "(starts_with(document_path, '/work/projectx'))");
+    }
+}
@@ -2,28 +2,216 @@
 //  IMPORTS
 // ===================================================================
 // Import all the modules and structs this orchestrator will manage.
-use crate::index_manager::{IndexManager, IndexableDocument as KeywordDocument};
-use crate::vector_db::VectorDBManager;
-use crate::embedding_generator::EmbeddingGenerator;
+use crate::index_manager::{DateRange, IndexManager, IndexableDocument as KeywordDocument, KeywordSearchResults, MetadataField, SearchResult as KeywordResult};
+use crate::vector_db::{VectorDBManager, DEFAULT_SEARCH_LIMIT};
+use crate::embedding_generator::{chunk_text_with_offsets, EmbeddingGenerator, TextChunk};
+use crate::abstractive_summarizer::AbstractiveSummarizer;
+use crate::chunk_diff::diff_chunks;
+use crate::auth::{self, OAuthClient};
+use crate::connectors::apple_notes::{self, APPLE_NOTES_SOURCE};
+use crate::connectors::gmail::{self, GmailClient, HistoryExpired, GMAIL_SOURCE};
+use crate::connectors::slack::{self, SlackChannel, SlackClient, SlackMessage, SLACK_SOURCE};
+use crate::app_context::CURRENT_PROJECT_SCOPE;
+use crate::backlog::{prioritize, BacklogFile};
+use crate::date_format::{humanize_relative, serialize_iso8601};
+use crate::disk_space::{min_free_bytes, DiskGuard, DiskSpaceStatus};
+use crate::digests::{Digest, DigestFrequency, DigestLog, DigestSection, DIGEST_SECTION_DOCUMENTS};
+use crate::docsets::Docset;
+use crate::experiments::{ExperimentAssignment, ExperimentReport, Experiments, RankingConfig, Variant};
+use crate::file_ingest::{raw_document_from_file, LOCAL_FILE_SOURCE};
+use crate::file_usage::{accessed_after_modified, file_usage};
+use crate::full_text::{should_store, FullTextStore};
+use crate::fs_paths::{canonical_path, long_path, path_key};
+use crate::git_repos;
+use crate::highlights::{fetch_readwise_page, Highlight, READWISE_SOURCE};
+use crate::health::{self, ComponentHealth, HealthReport};
+use crate::identity::{author_aliases, Identity};
+use crate::index_events::{IndexEvent, IndexEventBus, IndexEventKind};
+use crate::keyword_extraction::{extract_keywords, keyword_facets, KeywordFacet, MAX_KEYWORDS};
+use crate::metrics::{directory_size, millis, Metrics, MetricsSnapshot, SearchTimings};
+use crate::parsers::parse_document;
+use crate::password_manager::OnePasswordProvider;
+use crate::providers::{ProviderResult, ResultProvider};
+use crate::rate_limit::{ConnectorStatus, RateLimiter, RateLimiters};
+use crate::query_analytics::{QueryAnalytics, QueryAnalyticsReport};
+use crate::result_cache::{CachedSearch, ResultCache};
+use crate::query_preprocessor::{classify_query, expand_aliases, extract_scope, free_text_words, infer_content_intent, short_query_prefix, QueryKind};
+use crate::scopes::Scope;
+use crate::shell_history;
+use crate::snippets::{Snippet, SnippetStore};
+use crate::settings::Settings;
+use crate::state_store::{now_secs, PendingDeletion, StateStore};
+use crate::sync_cursors::{CursorStore, PendingSync, SyncCursor};
+use crate::summary_budget::{SummaryBudget, SummaryBudgets};
+use crate::team_index::{ResultOrigin, TeamIndex};
+use crate::storage_quota::{plan_eviction, EvictionCandidate, EvictionReport};
+use crate::text_normalization::should_fold_diacritics;
+use crate::thumbnails::{self, ResultIcon};
+use crate::timeline::{build_timeline, Granularity, TimelineBucket, TimelineFilter};
+use crate::topics::cluster_topics;
+use crate::web_capture::{fetch_web_document, web_document, WEB_SOURCE};
+use crate::zotero;
 use anyhow::Result;
-use std::sync::Arc; // For sharing state safely across threads
-use std::collections::HashMap;
-use std::time::{SystemTime, Duration, UNIX_EPOCH};
+use std::sync::{Arc, Mutex, RwLock}; // For sharing state safely across threads
+use tokio::sync::{broadcast, mpsc};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{SystemTime, Duration, Instant, UNIX_EPOCH};
 use sha2::{Sha256, Digest};
 
+/// Results per page unless the caller asks for a different number.
+pub const DEFAULT_RESULT_LIMIT: usize = 20;
+/// Largest page a search returns.
+pub const MAX_RESULT_LIMIT: usize = 100;
+/// Each retrieval leg fetches this many times the results up to the end of the page, so
+/// fusion ranks a wider pool than it returns and pages stay consistent with each other.
+const CANDIDATE_POOL_FACTOR: usize = 2;
+/// Search responses kept for instant repeats, across queries and pages.
+const RESULT_CACHE_CAPACITY: usize = 64;
+/// How long a cached response is served. Index changes drop it sooner; this bounds the
+/// drift of recency and usage signals and of provider results.
+const RESULT_CACHE_TTL: Duration = Duration::from_secs(120);
+
 // ===================================================================
 //  PUBLIC STRUCTS
 // ===================================================================
 
 /// The final, rich search result that will be sent to the UI.
-#[derive(serde::Serialize)] // So Tauri can convert it to JSON
+#[derive(Clone, serde::Serialize)] // So Tauri can convert it to JSON
 pub struct HybridSearchResult {
     pub path: String,
     pub title: String,
     pub source_type: String,
+    #[serde(serialize_with = "serialize_iso8601")] // ISO-8601 string, e.g. "2024-03-05T14:30:00Z"
     pub modified_date: std::time::SystemTime,
+    pub modified_relative: String, // Humanized in the user's language, e.g. "3 days ago"
     pub final_score: f32,
     pub best_matching_chunk: Option<String>, // For displaying snippets
+    pub icon: ResultIcon, // File-type icon and optional thumbnail for rich rendering
+    pub stale: bool, // The local file was deleted or modified after it was indexed
+    /// Why the result ranked where it did, e.g. "matched title; strong semantic match to
+    /// summary; modified yesterday". None for documents that weren't ranked by a search.
+    pub why: Option<String>,
+    /// Salient keywords extracted from the document at indexing time.
+    pub keywords: Vec<String>,
+    /// The start of the document's text, stored at indexing time so previews render
+    /// without re-parsing the file.
+    pub preview: Option<String>,
+    /// Tags the user gave the document.
+    pub tags: Vec<String>,
+    /// Which index the document came from; team results are shown with a badge.
+    pub origin: ResultOrigin,
+    /// Paths of lower-ranked documents with exactly the same text, e.g. copies of a
+    /// file in several folders, collapsed into this result.
+    pub duplicates: Vec<String>,
+    /// SHA-256 of the indexed text, empty if unknown.
+    #[serde(skip)]
+    pub content_hash: String,
+}
+
+/// Which page of results a search returns, for "show more" and infinite scroll.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Most results returned, capped at `MAX_RESULT_LIMIT`.
+    pub limit: usize,
+    /// Ranked results skipped before the page starts.
+    pub offset: usize,
+    /// Only return documents from these sources, e.g. `["file", "zotero"]`.
+    pub source_types: Option<Vec<String>>,
+    /// Unix timestamps (seconds); documents modified before `modified_after` or from
+    /// `modified_before` on are left out.
+    pub modified_after: Option<u64>,
+    pub modified_before: Option<u64>,
+}
+
+/// The ranked results for a query plus hit counts, so the UI can show "231 results".
+#[derive(Clone, serde::Serialize)]
+pub struct HybridSearchResponse {
+    /// The requested page of the ranked results.
+    pub results: Vec<HybridSearchResult>,
+    /// Whether ranked results remain past this page.
+    pub has_more: bool,
+    /// Estimated number of distinct matching documents across both indexes.
+    pub total_estimate: usize,
+    /// Exact number of keyword matches reported by Tantivy.
+    pub keyword_hits: usize,
+    /// Number of distinct documents returned by the vector legs.
+    pub vector_candidates: usize,
+    /// The ranking experiment arm that ordered these results, if an experiment is running.
+    pub experiment: Option<ExperimentAssignment>,
+    /// How the query was classified, which decides the retrieval legs that ran.
+    pub query_kind: QueryKind,
+    /// Live results from registered providers, listed apart from the ranked documents.
+    pub provider_results: Vec<ProviderResult>,
+    /// Keywords shared by several results, for facet chips that refine the query.
+    pub keyword_facets: Vec<KeywordFacet>,
+    /// How long each phase of the search took, for pinpointing what makes one slow.
+    pub timings: SearchTimings,
+    /// True if the response came from the result cache rather than a fresh search.
+    pub cached: bool,
+}
+
+/// One of the hybrid search's retrieval legs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchLeg {
+    Keyword,
+    Titles,
+    Summaries,
+    Chunks,
+}
+
+/// A document a single retrieval leg found, before fusion ranks it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LiveSearchHit {
+    pub path: String,
+    pub title: String,
+    pub source_type: String,
+    /// The matching passage, for hits from the chunk leg.
+    pub snippet: Option<String>,
+    pub origin: ResultOrigin,
+}
+
+/// What one retrieval leg found, sent by `live_search` as soon as the leg finishes so
+/// the launcher can show something while the rest of the search runs. Hits are in the
+/// leg's own order; the final response replaces them with the fused ranking.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LiveSearchBatch {
+    pub leg: SearchLeg,
+    pub hits: Vec<LiveSearchHit>,
+}
+
+/// The outcome of one query in a `batch_search` call. Failures are reported per query
+/// so one bad query doesn't blank out a whole dashboard.
+#[derive(serde::Serialize)]
+pub struct BatchSearchEntry {
+    pub query: String,
+    pub response: Option<HybridSearchResponse>,
+    pub error: Option<String>,
+}
+
+/// A ranked passage inside a single document, with byte offsets into its text. The
+/// offsets are None when the document's text is unavailable and the passage is one of
+/// its stored chunks.
+#[derive(serde::Serialize)]
+pub struct DocumentPassage {
+    pub text: String,
+    pub start_offset: Option<usize>,
+    pub end_offset: Option<usize>,
+    pub score: f32,
+}
+
+/// A topic with its most representative documents, for browsing the corpus without a query.
+#[derive(serde::Serialize)]
+pub struct BrowsedTopic {
+    pub id: usize,
+    pub label: String,
+    pub keywords: Vec<String>,
+    /// Number of documents in the topic.
+    pub size: usize,
+    pub documents: Vec<HybridSearchResult>,
 }
 
 /// A struct to hold the raw data from a connector before processing.
@@ -31,26 +219,138 @@ pub struct RawDocument {
     pub path: String,
     pub title: String,
     pub body: String,
+    /// A summary the source provides, e.g. an HTML page's meta description, embedded in
+    /// place of one generated from the body.
+    pub summary: Option<String>,
     pub source_type: String,
     pub author: Option<String>,
     pub modified_date: std::time::SystemTime,
+    /// Source-specific fields indexed for filtering, e.g. `year` and `journal` for papers.
+    pub metadata: BTreeMap<String, String>,
 }
 
 /// Internal struct to accumulate scores from different search methods.
 #[derive(Debug, Clone)]
 struct CombinedScore {
+    path: String,
     title: String,
     source_type: String,
     modified_date: SystemTime,
     rrf_score: f32,
     best_chunk: Option<String>,
+    // Matching chunks and their summed distance, for the chunk aggregation signal
+    chunk_hits: usize,
+    chunk_distance_sum: f32,
+    // Which legs found the document and how closely, for explaining the ranking
+    keyword_matched: bool,
+    title_distance: Option<f32>,
+    summary_distance: Option<f32>,
+    keywords: Vec<String>,
+    preview: Option<String>,
+    content_hash: String,
+    origin: ResultOrigin,
+    // True if the document is of a kind the query named, such as slides
+    content_type_boosted: bool,
+}
+
+/// A keyword index and vector store searched together: the personal pair or a team's.
+struct IndexPair<'a> {
+    index_manager: &'a Arc<IndexManager>,
+    // None in keyword-only mode
+    vector_db: Option<&'a VectorDBManager>,
+    origin: ResultOrigin,
+}
+
+/// What the retrieval legs look for, shared by every index pair searched.
+struct LegQuery<'a> {
+    keyword_query: &'a str,
+    // Set for queries of a character or two, matched against title prefixes instead,
+    // with `keyword_query` holding only the filters
+    title_prefix: Option<&'a str>,
+    modified: DateRange,
+    embedding: Option<&'a [f32]>,
+    vector_filter: Option<&'a str>,
+    // Sources the vector legs are restricted to, alongside `vector_filter`
+    source_types: Option<&'a [String]>,
+    candidate_limit: usize,
+    chunk_limit: usize,
+}
+
+/// A leg's hits as paths and, for chunks, the matching passage, sent as each leg finishes.
+struct LegHits {
+    leg: SearchLeg,
+    origin: ResultOrigin,
+    hits: Vec<(String, Option<String>)>,
+}
+
+/// What the four retrieval legs found in one index pair.
+struct RetrievedLegs {
+    keyword: KeywordSearchResults,
+    titles: Vec<(String, f32)>,
+    summaries: Vec<(String, f32)>,
+    chunks: Vec<(String, String, f32)>,
+    // How long each leg took; the other phases are left at zero
+    timings: SearchTimings,
+}
+
+/// The embedding model and vector store behind semantic search.
+struct VectorStack {
+    embedding_generator: Arc<EmbeddingGenerator>,
+    vector_db: Arc<VectorDBManager>,
 }
 
 /// The central orchestrator that manages all indexing and search operations.
 pub struct SearchOrchestrator {
     index_manager: Arc<IndexManager>,
-    vector_db: Arc<VectorDBManager>,
-    embedding_generator: Arc<EmbeddingGenerator>,
+    // None if the embedding model or vector store failed to open
+    vector_stack: Option<VectorStack>,
+    // The failed check that turned semantic search off, if any. Only the keyword index
+    // is searched then, and documents are indexed without embeddings.
+    keyword_only: RwLock<Option<ComponentHealth>>,
+    metrics: Arc<Metrics>,
+    aliases: RwLock<HashMap<String, String>>,
+    // Multiplier for documents of a kind the query names; None leaves them unboosted
+    content_type_boost: RwLock<Option<f32>>,
+    identities: RwLock<Vec<Identity>>,
+    scopes: RwLock<HashMap<String, Scope>>,
+    session_scope: RwLock<Option<Scope>>,
+    state_store: Arc<StateStore>,
+    locale: String,
+    experiments: Experiments,
+    auto_reindex_stale: bool,
+    undo_window: Duration,
+    // Directories holding embeddings, for measuring the vector store against its quota
+    embedding_dirs: Vec<PathBuf>,
+    // Pauses indexing while the volumes holding the indexes are nearly full
+    disk_guard: DiskGuard,
+    // Paths waiting to be refreshed by `run_reindex_queue`, deduplicated by `pending_reindex`
+    reindex_tx: mpsc::UnboundedSender<String>,
+    reindex_rx: tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<String>>>,
+    pending_reindex: Mutex<HashSet<String>>,
+    providers: RwLock<Vec<Arc<dyn ResultProvider>>>,
+    snippets: SnippetStore,
+    index_events: IndexEventBus,
+    rate_limiters: RateLimiters,
+    sync_cursors: CursorStore,
+    summary_budgets: SummaryBudgets,
+    query_analytics: QueryAnalytics,
+    digest_log: DigestLog,
+    full_text: FullTextStore,
+    store_full_text: bool,
+    // A team's published indexes, searched read-only next to the personal ones
+    team_index: Option<TeamIndex>,
+    // The last query searched since the launcher was opened, until a result is opened
+    search_session: Mutex<Option<String>>,
+    // The file open in the preview or an editor, re-indexed through the fast lane
+    active_file: Mutex<Option<ActiveFile>>,
+    // Recent responses, dropped as index events show them stale
+    result_cache: ResultCache<HybridSearchResponse>,
+}
+
+/// The file the user is working on, and whether fast-lane refreshes left its summary behind.
+struct ActiveFile {
+    path: String,
+    summary_stale: bool,
 }
 
 // ===================================================================
@@ -64,12 +364,103 @@ fn calculate_hash(content: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+/// Returns true if a local file was deleted or modified since the indexed version.
+/// Paths that aren't absolute file system paths (e.g. URLs) are never reported stale.
+fn is_stale(path: &str, indexed_modified: SystemTime) -> bool {
+    let file_path = Path::new(path);
+    if !file_path.is_absolute() {
+        return false;
+    }
+    match std::fs::metadata(long_path(file_path)).and_then(|m| m.modified()) {
+        // The index keeps whole seconds, so compare at that precision
+        Ok(modified) => {
+            let secs = |t: SystemTime| t.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            secs(modified) > secs(indexed_modified)
+        }
+        Err(_) => true,
+    }
+}
+
+/// Folds results with the same content hash into the first, highest-ranked one, listing
+/// the others' paths as its duplicates. Results with an unknown hash are left alone.
+fn collapse_duplicates(results: Vec<HybridSearchResult>) -> Vec<HybridSearchResult> {
+    let mut collapsed: Vec<HybridSearchResult> = Vec::with_capacity(results.len());
+    let mut representatives: HashMap<String, usize> = HashMap::new();
+    for result in results {
+        if result.content_hash.is_empty() {
+            collapsed.push(result);
+            continue;
+        }
+        match representatives.get(&result.content_hash) {
+            Some(&index) => collapsed[index].duplicates.push(result.path),
+            None => {
+                representatives.insert(result.content_hash.clone(), collapsed.len());
+                collapsed.push(result);
+            }
+        }
+    }
+    collapsed
+}
+
 /// Calculates Reciprocal Rank Fusion (RRF) score for a given rank position.
 /// RRF formula: 1 / (k + rank) where k is typically 60.
 fn calculate_rrf_score(rank: usize) -> f32 {
     1.0 / (60.0 + rank as f32 + 1.0)
 }
 
+/// Scores how much of a document matched the query beyond its best chunk (0.0 to 1.0).
+/// Each extra matching chunk adds coverage up to `FULL_COVERAGE_HITS`, scaled down as
+/// the mean squared L2 distance of the matches grows.
+fn calculate_chunk_aggregation_score(chunk_hits: usize, mean_distance: f32) -> f32 {
+    const FULL_COVERAGE_HITS: usize = 5;
+    if chunk_hits < 2 {
+        return 0.0;
+    }
+    let coverage = (chunk_hits - 1).min(FULL_COVERAGE_HITS - 1) as f32 / (FULL_COVERAGE_HITS - 1) as f32;
+    coverage / (1.0 + mean_distance.max(0.0))
+}
+
+/// Puts into words why a document ranked where it did, from the legs that found it and
+/// how closely, e.g. "matched title; strong semantic match to summary; modified yesterday".
+fn explain_ranking(score_data: &CombinedScore, query_words: &[&str], modified_relative: &str, now: SystemTime) -> Option<String> {
+    // Squared L2 distance between unit vectors; 0.5 corresponds to a cosine similarity of 0.75
+    const STRONG_MATCH_DISTANCE: f32 = 0.5;
+    // Only recent edits are worth mentioning; older ones barely move the recency signal
+    const RECENT_EDIT: Duration = Duration::from_secs(7 * 24 * 3600);
+
+    let mut reasons = Vec::new();
+    if score_data.keyword_matched {
+        let title = score_data.title.to_lowercase();
+        let in_title = !query_words.is_empty()
+            && query_words.iter().all(|word| title.contains(&word.to_lowercase()));
+        reasons.push(if in_title { "matched title".to_string() } else { "matched keywords".to_string() });
+    }
+    for (distance, field) in [(score_data.title_distance, "title"), (score_data.summary_distance, "summary")] {
+        match distance {
+            Some(distance) if distance <= STRONG_MATCH_DISTANCE => reasons.push(format!("strong semantic match to {}", field)),
+            Some(_) => reasons.push(format!("semantic match to {}", field)),
+            None => {}
+        }
+    }
+    match score_data.chunk_hits {
+        0 => {}
+        1 => reasons.push("1 matching passage".to_string()),
+        hits => reasons.push(format!("{} matching passages", hits)),
+    }
+    if score_data.content_type_boosted {
+        reasons.push("the kind of document asked for".to_string());
+    }
+    if now.duration_since(score_data.modified_date).is_ok_and(|age| age <= RECENT_EDIT) {
+        reasons.push(format!("modified {}", modified_relative));
+    }
+
+    if reasons.is_empty() {
+        None
+    } else {
+        Some(reasons.join("; "))
+    }
+}
+
 /// Calculates a recency score based on how recent a document is.
 /// More recent documents get higher scores (0.0 to 1.0).
 fn calculate_recency_score(modified_date: SystemTime) -> f32 {
@@ -90,24 +481,167 @@ fn calculate_recency_score(modified_date: SystemTime) -> f32 {
     recency_factor.max(0.01).min(1.0)
 }
 
+/// Scores how often a document was opened (0.0 to 1.0), growing logarithmically so the
+/// first few opens count most.
+fn calculate_frequency_score(use_count: u64) -> f32 {
+    // Opens at which the score saturates
+    const FREQUENT_USE: f32 = 50.0;
+    ((use_count as f32).ln_1p() / FREQUENT_USE.ln_1p()).min(1.0)
+}
+
+/// Awaits a future and measures how long it took.
+async fn timed<T>(future: impl Future<Output = T>) -> (T, Duration) {
+    let started = Instant::now();
+    let output = future.await;
+    (output, started.elapsed())
+}
+
+/// Runs the keyword, title, summary and chunk searches against one index pair
+/// concurrently, each returning up to `candidate_limit` hits (`chunk_limit` for chunks).
+/// Without a query embedding or a vector store only the keyword leg runs. The date range
+/// narrows the keyword leg only; vector hits carry no dates and are filtered after
+/// fusion. Each leg's hits also go to `leg_tx` as soon as it finishes, if given.
+async fn retrieve_legs(
+    pair: &IndexPair<'_>,
+    query: &LegQuery<'_>,
+    leg_tx: Option<&mpsc::UnboundedSender<LegHits>>,
+) -> Result<RetrievedLegs> {
+    let send = |leg: SearchLeg, hits: Vec<(String, Option<String>)>| {
+        if let Some(leg_tx) = leg_tx {
+            let _ = leg_tx.send(LegHits { leg, origin: pair.origin, hits });
+        }
+    };
+    let ((keyword, keyword_time), (titles, titles_time), (summaries, summaries_time), (chunks, chunks_time)) = tokio::join!(
+        timed(async {
+            let index_manager_clone = Arc::clone(pair.index_manager);
+            let query_clone = query.keyword_query.to_string();
+            let title_prefix = query.title_prefix.map(str::to_string);
+            let (candidate_limit, modified) = (query.candidate_limit, query.modified);
+            let keyword = tokio::task::spawn_blocking(move || {
+                let keyword = match title_prefix {
+                    Some(prefix) => index_manager_clone.search_title_prefix(&prefix, &query_clone, candidate_limit, modified),
+                    None => index_manager_clone.search(&query_clone, candidate_limit, modified),
+                };
+                keyword.map_err(|e| anyhow::anyhow!("Keyword search failed: {}", e))
+            }).await
+                .map_err(|e| anyhow::anyhow!("Keyword search task failed: {}", e))?;
+            if let Ok(keyword) = &keyword {
+                send(SearchLeg::Keyword, keyword.results.iter().map(|r| (r.path.clone(), None)).collect());
+            }
+            keyword
+        }),
+        timed(async {
+            let titles = match (query.embedding, pair.vector_db) {
+                (Some(embedding), Some(vector_db)) => vector_db.search_titles(embedding, query.vector_filter, query.source_types, query.candidate_limit).await,
+                _ => return Ok(Vec::new()),
+            };
+            if let Ok(titles) = &titles {
+                send(SearchLeg::Titles, titles.iter().map(|(path, _)| (path.clone(), None)).collect());
+            }
+            titles
+        }),
+        timed(async {
+            let summaries = match (query.embedding, pair.vector_db) {
+                (Some(embedding), Some(vector_db)) => vector_db.search_summaries(embedding, query.vector_filter, query.source_types, query.candidate_limit).await,
+                _ => return Ok(Vec::new()),
+            };
+            if let Ok(summaries) = &summaries {
+                send(SearchLeg::Summaries, summaries.iter().map(|(path, _)| (path.clone(), None)).collect());
+            }
+            summaries
+        }),
+        timed(async {
+            let chunks = match (query.embedding, pair.vector_db) {
+                (Some(embedding), Some(vector_db)) => vector_db.search_chunks(embedding, query.vector_filter, query.source_types, query.chunk_limit).await,
+                _ => return Ok(Vec::new()),
+            };
+            if let Ok(chunks) = &chunks {
+                send(SearchLeg::Chunks, chunks.iter().map(|(path, chunk, _)| (path.clone(), Some(chunk.clone()))).collect());
+            }
+            chunks
+        })
+    );
+    Ok(RetrievedLegs {
+        keyword: keyword?,
+        titles: titles?,
+        summaries: summaries?,
+        chunks: chunks?,
+        timings: SearchTimings {
+            keyword_ms: millis(keyword_time),
+            titles_ms: millis(titles_time),
+            summaries_ms: millis(summaries_time),
+            chunks_ms: millis(chunks_time),
+            ..SearchTimings::default()
+        },
+    })
+}
+
 // ===================================================================
 //  IMPLEMENTATION
 // ===================================================================
 
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            limit: DEFAULT_RESULT_LIMIT,
+            offset: 0,
+            source_types: None,
+            modified_after: None,
+            modified_before: None,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// How many hits each retrieval leg fetches to rank this page. Never fewer than the
+    /// legs fetched before paging, so a short page doesn't rank a thinner pool.
+    fn candidate_limit(&self) -> usize {
+        ((self.offset + self.limit.min(MAX_RESULT_LIMIT)) * CANDIDATE_POOL_FACTOR).max(DEFAULT_RESULT_LIMIT)
+    }
+
+    fn modified(&self) -> DateRange {
+        DateRange { after: self.modified_after, before: self.modified_before }
+    }
+}
+
+impl VectorStack {
+    /// Loads the embedding model and opens the vector store. If either fails, returns
+    /// the failed check for the startup health report.
+    async fn open(settings: &Settings, disk_guard: &DiskGuard) -> std::result::Result<Self, ComponentHealth> {
+        let locations = &settings.index_locations;
+        let (embedding_generator, elapsed) = timed(EmbeddingGenerator::new(settings.offline_mode)).await;
+        let embedding_generator = embedding_generator
+            .map_err(|e| ComponentHealth::failed(health::EMBEDDING_MODEL, e, elapsed))?;
+        let (vector_db, elapsed) = timed(async {
+            VectorDBManager::new(&locations.vector_store_dir()?, locations.chunk_store.as_deref(), disk_guard).await
+        }).await;
+        let vector_db = vector_db
+            .map_err(|e| ComponentHealth::failed(health::VECTOR_STORE, e, elapsed))?;
+        Ok(Self {
+            embedding_generator: Arc::new(embedding_generator),
+            vector_db: Arc::new(vector_db),
+        })
+    }
+}
+
 impl SearchOrchestrator {
     /// Helper method to ensure document metadata exists in combined_scores.
+    /// Entries are keyed by `path_key` of the stored path, so case variants of one file
+    /// fuse into one result; the key is returned for looking the entry up.
     async fn ensure_metadata_exists(
         &self,
+        pair: &IndexPair<'_>,
         path: &str,
         combined_scores: &mut HashMap<String, CombinedScore>,
-    ) -> Result<()> {
+    ) -> Result<String> {
         // If we already have the document metadata, nothing to do
-        if combined_scores.contains_key(path) {
-            return Ok(());
+        let key = path_key(path);
+        if combined_scores.contains_key(&key) {
+            return Ok(key);
         }
 
         // Fetch metadata from IndexManager (using spawn_blocking for synchronous database access)
-        let index_manager_clone = Arc::clone(&self.index_manager);
+        let index_manager_clone = Arc::clone(pair.index_manager);
         let path_clone = path.to_string();
         let metadata = tokio::task::spawn_blocking(move || {
             index_manager_clone.get_document_metadata(&path_clone)
@@ -118,98 +652,575 @@ impl SearchOrchestrator {
         let score_data = if let Some(metadata) = metadata {
             // Use real metadata from the index
             CombinedScore {
+                path: path.to_string(),
                 title: metadata.title,
                 source_type: metadata.source_type,
                 modified_date: metadata.modified_date,
                 rrf_score: 0.0,
                 best_chunk: None,
+                chunk_hits: 0,
+                chunk_distance_sum: 0.0,
+                keyword_matched: false,
+                title_distance: None,
+                summary_distance: None,
+                keywords: metadata.keywords,
+                preview: metadata.preview,
+                content_hash: metadata.content_hash,
+                origin: pair.origin,
+                content_type_boosted: false,
             }
         } else {
             // Document not found in keyword index - this can happen if it was
             // indexed only in vector DB or there's an inconsistency
             CombinedScore {
+                path: path.to_string(),
                 title: format!("Document: {}", path.split('/').last().unwrap_or("Unknown")),
                 source_type: "Unknown".to_string(),
                 modified_date: SystemTime::UNIX_EPOCH,
                 rrf_score: 0.0,
                 best_chunk: None,
+                chunk_hits: 0,
+                chunk_distance_sum: 0.0,
+                keyword_matched: false,
+                title_distance: None,
+                summary_distance: None,
+                keywords: Vec::new(),
+                preview: None,
+                content_hash: String::new(),
+                origin: pair.origin,
+                content_type_boosted: false,
             }
         };
 
-        combined_scores.insert(path.to_string(), score_data);
-        Ok(())
+        combined_scores.insert(key.clone(), score_data);
+        Ok(key)
+    }
+
+    /// Fuses one index pair's retrieval legs into a combined score per document with
+    /// Reciprocal Rank Fusion, weighted by the active ranking config.
+    async fn fuse_legs(
+        &self,
+        pair: &IndexPair<'_>,
+        legs: &RetrievedLegs,
+        ranking: &RankingConfig,
+    ) -> Result<(HashMap<String, CombinedScore>, Duration)> {
+        // Squared L2 distance between unit vectors; 0.5 corresponds to a cosine similarity of 0.75
+        const STRONG_SUMMARY_DISTANCE: f32 = 0.5;
+
+        // 1. Create a HashMap to store the combined scores for each unique document path.
+        let mut combined_scores: HashMap<String, CombinedScore> = HashMap::new();
+        // Time spent looking up documents only the vector legs found, reported separately
+        let mut metadata_time = Duration::ZERO;
+
+        // 2. Process keyword results and apply Reciprocal Rank Fusion (RRF).
+        //    For each result, add its RRF score to the combined score for that path.
+        //    Also, store the document's metadata (title, date, etc.).
+        for (rank, result) in legs.keyword.results.iter().enumerate() {
+            let rrf_score = calculate_rrf_score(rank);
+            
+            combined_scores.entry(path_key(&result.path))
+                .and_modify(|score| {
+                    score.rrf_score += rrf_score * ranking.keyword_boost; // Boost keyword matches
+                    score.keyword_matched = true;
+                })
+                .or_insert_with(|| CombinedScore {
+                    path: result.path.clone(),
+                    title: result.title.clone(),
+                    source_type: result.source_type.clone(),
+                    modified_date: result.modified_date,
+                    rrf_score: rrf_score * ranking.keyword_boost,
+                    best_chunk: None,
+                    chunk_hits: 0,
+                    chunk_distance_sum: 0.0,
+                    keyword_matched: true,
+                    title_distance: None,
+                    summary_distance: None,
+                    keywords: result.keywords.clone(),
+                    preview: result.preview.clone(),
+                    content_hash: result.content_hash.clone(),
+                    origin: pair.origin,
+                    content_type_boosted: false,
+                });
+        }
+
+        // 3. Process semantic title results.
+        //    For each result, add its RRF score to the combined score for that path.
+        for (rank, (path, distance)) in legs.titles.iter().enumerate() {
+            let rrf_score = calculate_rrf_score(rank);
+            
+            let lookup_started = Instant::now();
+            let key = self.ensure_metadata_exists(pair, path, &mut combined_scores).await?;
+            metadata_time += lookup_started.elapsed();
+            let score_data = combined_scores.get_mut(&key).unwrap();
+            score_data.rrf_score += rrf_score * ranking.title_boost; // Boost title matches
+            score_data.title_distance = Some(score_data.title_distance.map_or(*distance, |best| best.min(*distance)));
+        }
+
+        // 4. Process semantic summary results.
+        //    For each result, add its RRF score to the combined score.
+        //    A strong summary match on a personal document whose chunks were pruned brings them back.
+        for (rank, (path, distance)) in legs.summaries.iter().enumerate() {
+            let rrf_score = calculate_rrf_score(rank);
+            
+            let lookup_started = Instant::now();
+            let key = self.ensure_metadata_exists(pair, path, &mut combined_scores).await?;
+            metadata_time += lookup_started.elapsed();
+            let score_data = combined_scores.get_mut(&key).unwrap();
+            score_data.rrf_score += rrf_score;
+            score_data.summary_distance = Some(score_data.summary_distance.map_or(*distance, |best| best.min(*distance)));
+
+            if pair.origin == ResultOrigin::Personal
+                && *distance <= STRONG_SUMMARY_DISTANCE
+                && self.state_store.document(path).is_some_and(|state| state.chunks_pruned)
+            {
+                self.spawn_chunk_regeneration(path);
+            }
+        }
+
+        // 5. Process semantic chunk results.
+        //    For each result, add its RRF score and store the `best_matching_chunk`.
+        //    Every matching chunk also counts towards the document's chunk aggregation.
+        for (rank, (path, chunk_text, distance)) in legs.chunks.iter().enumerate() {
+            let rrf_score = calculate_rrf_score(rank);
+            
+            let lookup_started = Instant::now();
+            let key = self.ensure_metadata_exists(pair, path, &mut combined_scores).await?;
+            metadata_time += lookup_started.elapsed();
+            let score_data = combined_scores.get_mut(&key).unwrap();
+            score_data.rrf_score += rrf_score;
+            score_data.chunk_hits += 1;
+            score_data.chunk_distance_sum += distance;
+            // Keep the best chunk (first one found, as results are sorted by relevance)
+            if score_data.best_chunk.is_none() {
+                score_data.best_chunk = Some(chunk_text.clone());
+            }
+        }
+
+        Ok((combined_scores, metadata_time))
+    }
+
+    /// The personal keyword index and vector store, the ones indexing writes to.
+    fn personal_index(&self) -> IndexPair<'_> {
+        IndexPair {
+            index_manager: &self.index_manager,
+            vector_db: self.vector_stack().ok().map(|stack| stack.vector_db.as_ref()),
+            origin: ResultOrigin::Personal,
+        }
+    }
+
+    /// The embedding model and vector store, or an error saying why semantic search is off.
+    fn vector_stack(&self) -> Result<&VectorStack> {
+        if let Some(failure) = self.keyword_only.read().unwrap().as_ref() {
+            let reason = failure.error.as_deref().unwrap_or("its self-test failed");
+            return Err(anyhow::anyhow!("Semantic search is unavailable: {}", reason));
+        }
+        self.vector_stack.as_ref().ok_or_else(|| anyhow::anyhow!("Semantic search is unavailable"))
+    }
+
+    /// Returns true if only the keyword index is searched, because the embedding model or
+    /// vector store is broken.
+    pub fn is_keyword_only(&self) -> bool {
+        self.keyword_only.read().unwrap().is_some()
+    }
+
+    /// Checks the search engine end to end on launch: a tiny keyword query, an embedding
+    /// of a short phrase and a vector search with it. If the embedding model or vector
+    /// store fails, search carries on in keyword-only mode for the rest of the session.
+    pub async fn self_test(&self) -> HealthReport {
+        const PROBE: &str = "self test";
+        let mut checks = Vec::new();
+
+        // 1. The keyword index answers a query.
+        let index_manager = Arc::clone(&self.index_manager);
+        let (keyword, elapsed) = timed(tokio::task::spawn_blocking(move || {
+            index_manager.search(PROBE, 1, DateRange::default()).map(|_| ()).map_err(|e| e.to_string())
+        })).await;
+        let keyword = keyword.map_err(|e| e.to_string()).and_then(|result| result);
+        checks.push(ComponentHealth::from_check(health::KEYWORD_INDEX, &keyword, elapsed));
+
+        // 2. The embedding model embeds the phrase and the vector store is searched with
+        //    it. A stack that didn't open reports the check that failed then.
+        match &self.vector_stack {
+            Some(vector_stack) => {
+                let embedding_generator = Arc::clone(&vector_stack.embedding_generator);
+                let (embedding, elapsed) = timed(tokio::task::spawn_blocking(move || {
+                    embedding_generator.generate_single_embedding(PROBE)
+                })).await;
+                let embedding = embedding
+                    .map_err(|e| anyhow::anyhow!("Embedding task failed: {}", e))
+                    .and_then(|embedding| embedding)
+                    .and_then(|embedding| {
+                        if embedding.is_empty() || !embedding.iter().all(|value| value.is_finite()) {
+                            return Err(anyhow::anyhow!("The model returned an invalid embedding"));
+                        }
+                        Ok(embedding)
+                    });
+                checks.push(ComponentHealth::from_check(health::EMBEDDING_MODEL, &embedding, elapsed));
+                if let Ok(embedding) = &embedding {
+                    let (titles, elapsed) = timed(vector_stack.vector_db.search_titles(embedding, None, None, 1)).await;
+                    checks.push(ComponentHealth::from_check(health::VECTOR_STORE, &titles, elapsed));
+                }
+            }
+            None => checks.extend(self.keyword_only.read().unwrap().clone()),
+        }
+
+        // 3. Fall back to keyword-only mode if the vector stack failed.
+        let report = HealthReport::new(checks);
+        let failure = report.components.iter()
+            .find(|check| !check.healthy && check.component != health::KEYWORD_INDEX);
+        if let Some(failure) = failure {
+            let mut keyword_only = self.keyword_only.write().unwrap();
+            if keyword_only.is_none() {
+                *keyword_only = Some(failure.clone());
+            }
+        }
+        report
+    }
+
+    /// Queues the local files indexed in keyword-only mode to be indexed again with
+    /// embeddings. Documents from connectors get theirs on their next sync. Returns how
+    /// many files were queued.
+    pub fn requeue_missing_embeddings(&self) -> usize {
+        if self.is_keyword_only() {
+            return 0;
+        }
+        let files: Vec<String> = self.state_store.documents_missing_embeddings()
+            .into_iter()
+            .filter(|path| long_path(Path::new(path)).is_file())
+            .collect();
+        for path in &files {
+            self.enqueue_reindex(path);
+        }
+        files.len()
     }
 
     /// Asynchronously creates a new SearchOrchestrator.
     /// This is a heavy, one-time operation that initializes all underlying managers.
-    pub async fn new() -> Result<Self> {
+    pub async fn new(settings: &Settings) -> Result<Self> {
         // 1. Initialize each of the core modules. The `await` keyword is used
         //    because the model loading and DB connection are async operations.
-        let index_manager = IndexManager::new().map_err(|e| anyhow::anyhow!("Failed to create IndexManager: {}", e))?;
-        let embedding_generator = EmbeddingGenerator::new().await?;
-        let vector_db = VectorDBManager::new().await?;
+        let fold_diacritics = should_fold_diacritics(&settings.language, settings.fold_diacritics);
+        let locations = &settings.index_locations;
+        let index_manager = IndexManager::new(&locations.keyword_index_dir()?, fold_diacritics, settings.keyword_index_tuning)
+            .map_err(|e| anyhow::anyhow!("Failed to create IndexManager: {}", e))?;
+        // Without the embedding model or vector store, search falls back to keywords only
+        // Guard the data volumes first: opening the vector store may move chunks between them
+        let disk_guard = DiskGuard::new(locations.data_volume_dirs()?, min_free_bytes(settings.min_free_disk_mb));
+        let (vector_stack, keyword_only) = match VectorStack::open(settings, &disk_guard).await {
+            Ok(vector_stack) => (Some(vector_stack), None),
+            Err(failure) => {
+                eprintln!("Error: Semantic search is unavailable, searching keywords only: {}", failure.error.as_deref().unwrap_or_default());
+                (None, Some(failure))
+            }
+        };
+        let state_store = StateStore::open()?;
+        let experiments = Experiments::open()?;
+        let snippets = SnippetStore::open()?;
+        let sync_cursors = CursorStore::open()?;
+        let query_analytics = QueryAnalytics::open()?;
+        let digest_log = DigestLog::open()?;
+        let full_text = FullTextStore::open()?;
+        // A team share that is offline or half-published shouldn't keep the app from starting
+        let team_index = match &settings.team_index {
+            Some(dir) => TeamIndex::open(dir, fold_diacritics).await
+                .map_err(|e| eprintln!("Warning: Could not open the team index: {}", e))
+                .ok(),
+            None => None,
+        };
+        let (reindex_tx, reindex_rx) = mpsc::unbounded_channel();
+        let mut providers: Vec<Arc<dyn ResultProvider>> = Vec::new();
+        if settings.password_manager_provider_enabled {
+            providers.push(Arc::new(OnePasswordProvider::default()));
+        }
 
         // 2. Wrap each manager in an Arc (Atomic Reference Counter) to allow them
         //    to be shared safely and efficiently across multiple threads.
         Ok(Self {
             index_manager: Arc::new(index_manager),
-            vector_db: Arc::new(vector_db),
-            embedding_generator: Arc::new(embedding_generator),
+            vector_stack,
+            keyword_only: RwLock::new(keyword_only),
+            metrics: Arc::new(Metrics::new(locations.keyword_index_dir()?, locations.embedding_dirs()?)),
+            aliases: RwLock::new(settings.aliases.clone()),
+            content_type_boost: RwLock::new(settings.content_type_boost),
+            identities: RwLock::new(settings.identities.clone()),
+            scopes: RwLock::new(settings.scopes.clone()),
+            session_scope: RwLock::new(None),
+            state_store: Arc::new(state_store),
+            locale: settings.language.clone(),
+            experiments,
+            auto_reindex_stale: settings.auto_reindex_stale,
+            undo_window: Duration::from_secs(settings.undo_window_minutes * 60),
+            embedding_dirs: locations.embedding_dirs()?,
+            disk_guard,
+            reindex_tx,
+            reindex_rx: tokio::sync::Mutex::new(Some(reindex_rx)),
+            pending_reindex: Mutex::new(HashSet::new()),
+            providers: RwLock::new(providers),
+            snippets,
+            index_events: IndexEventBus::default(),
+            rate_limiters: RateLimiters::new(settings.rate_limits.clone()),
+            sync_cursors,
+            summary_budgets: SummaryBudgets::new(settings.summary_budgets.clone()),
+            query_analytics,
+            digest_log,
+            full_text,
+            store_full_text: settings.store_full_text,
+            team_index,
+            search_session: Mutex::new(None),
+            active_file: Mutex::new(None),
+            result_cache: ResultCache::new(RESULT_CACHE_CAPACITY, RESULT_CACHE_TTL),
         })
     }
 
+    /// Replaces the query aliases after the user edits them in settings.
+    pub fn set_aliases(&self, aliases: HashMap<String, String>) {
+        *self.aliases.write().unwrap() = aliases;
+        self.result_cache.clear();
+    }
+
+    /// Changes how much documents of a kind the query names are boosted; `None` turns it off.
+    pub fn set_content_type_boost(&self, boost: Option<f32>) {
+        *self.content_type_boost.write().unwrap() = boost;
+        self.result_cache.clear();
+    }
+
+    /// Replaces the author identity table used for documents indexed from now on.
+    pub fn set_identities(&self, identities: Vec<Identity>) {
+        *self.identities.write().unwrap() = identities;
+    }
+
+    /// Adds a live result provider, replacing any registered under the same id.
+    pub fn register_provider(&self, provider: Arc<dyn ResultProvider>) {
+        let mut providers = self.providers.write().unwrap();
+        providers.retain(|existing| existing.id() != provider.id());
+        providers.push(provider);
+    }
+
+    /// Removes the provider with the given id, if one is registered.
+    pub fn unregister_provider(&self, id: &str) {
+        self.providers.write().unwrap().retain(|provider| provider.id() != id);
+    }
+
+    /// Returns the rate limiter every request of an API connector should go through.
+    pub fn rate_limiter(&self, connector: &str) -> Arc<RateLimiter> {
+        self.rate_limiters.get(connector)
+    }
+
+    /// Returns request, throttling and backoff counts per connector for the sync dashboard.
+    pub fn connector_statuses(&self) -> Vec<ConnectorStatus> {
+        self.rate_limiters.statuses()
+    }
+
+    /// Measures free space on the volumes holding the indexes, for the storage settings.
+    pub fn disk_space_status(&self) -> DiskSpaceStatus {
+        self.disk_guard.check()
+    }
+
+    /// Returns a receiver that sees the disk space status whenever indexing pauses or resumes.
+    pub fn subscribe_disk_space(&self) -> tokio::sync::watch::Receiver<DiskSpaceStatus> {
+        self.disk_guard.subscribe()
+    }
+
+    /// Changes how much free space indexing keeps on the data volumes; `None` never pauses.
+    pub fn set_min_free_disk(&self, min_free_mb: Option<u64>) {
+        self.disk_guard.set_min_free_bytes(min_free_bytes(min_free_mb));
+    }
+
+    /// Returns a receiver of every document added, updated or deleted from now on.
+    pub fn subscribe_index_events(&self) -> broadcast::Receiver<IndexEvent> {
+        self.index_events.subscribe()
+    }
+
+    /// Replaces the named search scopes after the user edits them in settings.
+    pub fn set_scopes(&self, scopes: HashMap<String, Scope>) {
+        *self.scopes.write().unwrap() = scopes;
+        self.result_cache.clear();
+    }
+
+    /// Sets the temporary `scope:current-project` scope detected when the launcher opened.
+    pub fn set_session_scope(&self, scope: Option<Scope>) {
+        let mut session_scope = self.session_scope.write().unwrap();
+        // Reopening the launcher in the same project keeps cached searches
+        if *session_scope != scope {
+            *session_scope = scope;
+            self.result_cache.clear();
+        }
+    }
+
+    /// Drops cached search responses an index change could alter. Called for every event
+    /// on the index event bus.
+    pub fn invalidate_cached_results(&self, event: &IndexEvent) {
+        self.result_cache.invalidate(event);
+    }
+
+    /// Drops every cached search response, e.g. after missing index events.
+    pub fn clear_result_cache(&self) {
+        self.result_cache.clear();
+    }
+
+    /// Returns the locally recorded metrics for the stats API.
+    pub fn stats(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Returns click-through stats for the current ranking experiment, if one is defined.
+    pub fn experiment_report(&self) -> Option<ExperimentReport> {
+        self.experiments.report()
+    }
+
+    /// Records that the user clicked a result ranked by the given experiment arm.
+    pub fn record_experiment_click(&self, experiment: &str, variant: Variant) -> Result<()> {
+        self.experiments.record_click(experiment, variant)
+    }
+
+    /// Returns the shared metrics collector.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
     // ===================================================================
     //  DOCUMENT LIFECYCLE METHODS
     // ===================================================================
 
-    /// Processes and indexes a single new document.
-    pub async fn index_document(&self, doc: RawDocument) -> Result<()> {
+    /// Processes and indexes a single new document. Documents the user excluded are skipped.
+    pub async fn index_document(&self, mut doc: RawDocument) -> Result<()> {
+        doc.path = canonical_path(&doc.path);
+        if self.state_store.is_excluded(&doc.path) {
+            return Ok(());
+        }
+        let result = self.index_document_inner(doc, IndexEventKind::Added, None).await;
+        if result.is_err() {
+            self.metrics.record_error("indexing");
+        }
+        result
+    }
+
+    /// Indexes a document. `stored_chunks` holds the chunk texts already embedded for a
+    /// previous version, which is replaced in place: only chunks that changed are
+    /// embedded, and chunks that are gone are deleted. `None` indexes from scratch.
+    /// In keyword-only mode only the keyword entry is written, and the document is
+    /// remembered so it gets its embeddings once semantic search works again.
+    async fn index_document_inner(&self, doc: RawDocument, kind: IndexEventKind, stored_chunks: Option<Vec<String>>) -> Result<()> {
+        // Never start a write that could run the data volume out of space halfway through
+        self.disk_guard.wait_for_space().await;
+
         // 1. Calculate the content hash for deduplication.
         let content_hash = calculate_hash(&doc.body);
 
         // 2. Create the `KeywordDocument` for the Tantivy index.
-        let keyword_doc = KeywordDocument {
-            path: doc.path.clone(),
-            title: doc.title.clone(),
-            body: doc.body.clone(),
-            source_type: doc.source_type.clone(),
-            author: doc.author,
-            modified_date: doc.modified_date,
-            content_hash,
-        };
+        let keyword_doc = self.keyword_document(&doc, content_hash);
 
-        // 3. Generate all the embeddings for the document (using spawn_blocking for CPU-intensive work).
-        let embedding_generator_clone = Arc::clone(&self.embedding_generator);
-        let title_clone = doc.title.clone();
-        let body_clone = doc.body.clone();
-        let path_clone = doc.path.clone();
-        let embedding_records = tokio::task::spawn_blocking(move || {
-            embedding_generator_clone.generate_embeddings_for_document(&title_clone, &body_clone, &path_clone)
-        }).await??;
+        // 3. Generate the embeddings the document needs (using spawn_blocking for CPU-intensive work).
+        let vector_stack = self.vector_stack().ok();
+        let replacing = stored_chunks.is_some();
+        let (embedding_records, removed_chunks) = match vector_stack {
+            Some(vector_stack) => {
+                let embedding_generator_clone = Arc::clone(&vector_stack.embedding_generator);
+                let title_clone = doc.title.clone();
+                let body_clone = doc.body.clone();
+                let summary_clone = doc.summary.clone();
+                let path_clone = doc.path.clone();
+                let summary_budget = self.summary_budgets.for_document(&doc.source_type, &doc.path);
+                tokio::task::spawn_blocking(move || -> Result<_> {
+                    let chunks: Vec<String> = embedding_generator_clone.chunk_text(&body_clone)
+                        .into_iter()
+                        .filter(|chunk| !chunk.trim().is_empty())
+                        .collect();
+                    let diff = diff_chunks(&stored_chunks.unwrap_or_default(), &chunks);
+                    let records = embedding_generator_clone.generate_embeddings_for_document(&title_clone, &body_clone, summary_clone, diff.added, &path_clone, summary_budget)?;
+                    Ok((records, diff.removed))
+                }).await??
+            }
+            None => (Vec::new(), Vec::new()),
+        };
 
         // 4. Use `tokio::join!` to save to both databases concurrently for performance.
+        //    A previous version's keyword entry, title and summary are replaced wholesale.
         let (keyword_result, vector_result) = tokio::join!(
             async {
                 let index_manager_clone = Arc::clone(&self.index_manager);
+                let path_clone = doc.path.clone();
                 tokio::task::spawn_blocking(move || {
+                    if replacing {
+                        index_manager_clone.delete_document(&path_clone)
+                            .map_err(|e| anyhow::anyhow!("Keyword deletion failed: {}", e))?;
+                    }
                     index_manager_clone.add_document_batch(vec![keyword_doc])
                         .map_err(|e| anyhow::anyhow!("Keyword indexing failed: {}", e))
                 }).await
                     .map_err(|e| anyhow::anyhow!("Keyword indexing task failed: {}", e))?
             },
             async {
-                self.vector_db.add_embeddings(embedding_records).await
+                let Some(vector_stack) = vector_stack else { return Ok(()) };
+                if replacing {
+                    vector_stack.vector_db.delete_document_overview(&doc.path).await?;
+                    vector_stack.vector_db.delete_chunks_with_text(&doc.path, &removed_chunks).await?;
+                }
+                vector_stack.vector_db.add_embeddings(embedding_records, &doc.source_type).await
             }
         );
 
-        // 5. Check for errors and return the result.
+        // 5. Check for errors. Fresh chunks were just written, so the document is no longer
+        //    pruned. Without a vector store it is left waiting for its embeddings instead.
         keyword_result?;
         vector_result?;
+        let state = self.state_store.document(&doc.path).unwrap_or_default();
+        if vector_stack.is_none() {
+            if !state.embeddings_missing {
+                self.state_store.set_embeddings_missing(&doc.path, true)?;
+            }
+        } else {
+            if state.chunks_pruned {
+                self.state_store.set_chunks_pruned(&doc.path, false)?;
+            }
+            if state.embeddings_missing {
+                self.state_store.set_embeddings_missing(&doc.path, false)?;
+            }
+        }
+        // 6. Keep the text of remote documents for the reader. The document is searchable
+        //    without it, so a failure only costs the reader a refetch.
+        if self.store_full_text && should_store(&doc.source_type) {
+            if let Err(e) = self.full_text.put(&doc.path, &doc.body) {
+                eprintln!("Warning: Could not store the text of {}: {}", doc.path, e);
+            }
+        }
+        self.index_events.publish(kind, &doc.path, Some(&doc.source_type));
         Ok(())
     }
 
+    /// Builds a document's entry in the Tantivy index, resolving the author against the
+    /// identity table so every known name for them is searchable, and extracting the
+    /// keywords shown as facets and boosted at query time.
+    fn keyword_document(&self, doc: &RawDocument, content_hash: String) -> KeywordDocument {
+        let author_aliases = doc.author.as_deref()
+            .map(|author| author_aliases(author, &self.identities.read().unwrap()))
+            .unwrap_or_default();
+        KeywordDocument {
+            path: doc.path.clone(),
+            title: doc.title.clone(),
+            body: doc.body.clone(),
+            source_type: doc.source_type.clone(),
+            author: doc.author.clone(),
+            author_aliases,
+            modified_date: doc.modified_date,
+            content_hash,
+            metadata: doc.metadata.clone(),
+            keywords: extract_keywords(&doc.title, &doc.body, MAX_KEYWORDS),
+        }
+    }
+
     /// Deletes a document from both databases using its unique path.
     pub async fn delete_document(&self, path: &str) -> Result<()> {
+        let path = canonical_path(path);
+        self.delete_from_stores(&path).await?;
+        // Forget any usage state for the document.
+        self.state_store.remove_document(&path)?;
+        self.index_events.publish(IndexEventKind::Deleted, &path, None);
+        Ok(())
+    }
+
+    /// Removes a document from both databases while keeping its usage state.
+    async fn delete_from_stores(&self, path: &str) -> Result<()> {
         // 1. Use `tokio::join!` to delete from both databases concurrently.
         let (keyword_result, vector_result) = tokio::join!(
             async {
@@ -222,157 +1233,1882 @@ impl SearchOrchestrator {
                     .map_err(|e| anyhow::anyhow!("Keyword deletion task failed: {}", e))?
             },
             async {
-                self.vector_db.delete_document_embeddings(path).await
+                // In keyword-only mode the embeddings stay behind, found by nothing
+                match self.vector_stack() {
+                    Ok(vector_stack) => vector_stack.vector_db.delete_document_embeddings(path).await,
+                    Err(_) => Ok(()),
+                }
             }
         );
 
-        // 2. Check for errors.
+        // 2. Check for errors, then drop any stored text.
         keyword_result?;
         vector_result?;
-        Ok(())
+        self.full_text.remove(path)
     }
 
-    /// Updates a document by deleting the old versions and indexing the new version.
-    pub async fn update_document(&self, doc: RawDocument) -> Result<()> {
-        // 1. First, delete the old document from both stores to ensure a clean state.
-        self.delete_document(&doc.path).await?;
-        // 2. Then, index the new version of the document.
-        self.index_document(doc).await?;
+    /// Parses a local file and indexes it, replacing any version already in the index.
+    /// The OS's record of how often the file was opened is refreshed along the way.
+    pub async fn index_file(&self, path: &Path) -> Result<()> {
+        let path_clone = path.to_path_buf();
+        let (doc, usage) = tokio::task::spawn_blocking(move || {
+            raw_document_from_file(&path_clone).map(|doc| (doc, file_usage(&path_clone)))
+        })
+            .await
+            .map_err(|e| anyhow::anyhow!("File parsing task failed: {}", e))??;
+        self.update_document(doc).await?;
+        let path = canonical_path(&path.display().to_string());
+        if !self.state_store.is_excluded(&path) {
+            self.state_store.set_os_usage(&path, usage)?;
+        }
         Ok(())
     }
 
-    // ===================================================================
-    //  HYBRID SEARCH METHOD
-    // ===================================================================
+    /// Orders files waiting to be indexed so recently modified and frequently opened ones
+    /// come first, making the most useful documents searchable early in a large backlog.
+    pub fn prioritize_backlog(&self, files: Vec<PathBuf>) -> Vec<PathBuf> {
+        let backlog = files.into_iter()
+            .map(|path| {
+                let metadata = std::fs::metadata(&path).ok();
+                let modified = metadata.as_ref()
+                    .and_then(|metadata| metadata.modified().ok())
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |d| d.as_secs());
+                // The access time is cheap to read for every file; Spotlight is only asked
+                // once a file is indexed.
+                let accessed = metadata.as_ref().and_then(accessed_after_modified);
+                let state = self.state_store.document(&canonical_path(&path.display().to_string())).unwrap_or_default();
+                BacklogFile { path, modified, last_opened: state.last_used().max(accessed), open_count: state.use_count() }
+            })
+            .collect();
+        prioritize(backlog, now_secs())
+    }
 
-    /// Performs a hybrid search and returns an intelligently ranked list of results.
-    pub async fn hybrid_search(&self, query: &str) -> Result<Vec<HybridSearchResult>> {
-        // Ranking weight constants for easy tuning
-        const KEYWORD_BOOST: f32 = 1.2;
-        const TITLE_BOOST: f32 = 1.1;
-        const RECENCY_WEIGHT: f32 = 0.3;
-        const RRF_WEIGHT: f32 = 0.7;
-        // --- STAGE 1: PARALLEL RETRIEVAL ---
-        // 1. Generate the query embedding once (using spawn_blocking for CPU-intensive work).
-        let embedding_generator_clone = Arc::clone(&self.embedding_generator);
-        let query_clone = query.to_string();
-        let query_embedding = tokio::task::spawn_blocking(move || {
-            embedding_generator_clone.generate_single_embedding(&query_clone)
-        }).await??;
+    /// Fetches a web page and indexes its main text, replacing any earlier capture of the URL.
+    pub async fn index_url(&self, url: &str) -> Result<()> {
+        let doc = fetch_web_document(url, &self.rate_limiter(WEB_SOURCE)).await?;
+        self.update_document(doc).await
+    }
 
-        // 2. Use `tokio::join!` to run all four searches concurrently.
-        let (
-            keyword_results,
-            title_results,
-            summary_results,
-            chunk_results
-        ) = tokio::join!(
-            async {
-                let index_manager_clone = Arc::clone(&self.index_manager);
-                let query_clone = query.to_string();
-                tokio::task::spawn_blocking(move || {
-                    index_manager_clone.search(&query_clone)
-                        .map_err(|e| anyhow::anyhow!("Keyword search failed: {}", e))
-                }).await
-                    .map_err(|e| anyhow::anyhow!("Keyword search task failed: {}", e))?
-            },
-            async {
-                self.vector_db.search_titles(&query_embedding).await
+    /// Indexes a page whose HTML was captured by the browser, e.g. via the bookmarklet.
+    /// This also covers pages behind a login, which can't be fetched directly.
+    pub async fn index_web_page(&self, url: &str, html: &str, title: Option<String>) -> Result<()> {
+        self.update_document(web_document(url, html, title)).await
+    }
+
+    /// Indexes book highlights one document per highlight, replacing earlier imports of the
+    /// same highlight. Returns how many were indexed; failures are logged and skipped.
+    pub async fn import_highlights(&self, highlights: Vec<Highlight>) -> usize {
+        let imported_at = SystemTime::now();
+        let mut indexed = 0;
+        for highlight in highlights {
+            let path = highlight.path.clone();
+            match self.update_document(highlight.into_raw_document(imported_at)).await {
+                Ok(()) => indexed += 1,
+                Err(e) => eprintln!("Warning: Could not index highlight {}: {}", path, e),
+            }
+        }
+        indexed
+    }
+
+    /// Syncs the Readwise highlights changed since the last complete sync, saving progress
+    /// after every page so an interrupted sync resumes where it stopped. Returns how many
+    /// highlights were indexed.
+    pub async fn sync_readwise(&self, token: &str) -> Result<usize> {
+        let limiter = self.rate_limiter(READWISE_SOURCE);
+        let cursor = self.sync_cursors.get(READWISE_SOURCE);
+        let updated_after = cursor.committed.as_deref()
+            .and_then(|secs| secs.parse().ok())
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+        // A resumed sync commits the start time of the run it continues, so highlights
+        // changed while it was interrupted are picked up by the next one
+        let (mut page, commit_to) = match cursor.pending {
+            Some(pending) => (Some(pending.page), pending.commit_to),
+            None => (None, now_secs().to_string()),
+        };
+
+        let mut indexed = 0;
+        loop {
+            let export = fetch_readwise_page(token, updated_after, page.as_deref(), &limiter).await?;
+            indexed += self.import_highlights(export.highlights).await;
+            match export.next_page {
+                Some(next) => {
+                    self.sync_cursors.checkpoint(READWISE_SOURCE, &next, &commit_to)?;
+                    page = Some(next);
+                }
+                None => break,
+            }
+        }
+        self.sync_cursors.complete(READWISE_SOURCE, &commit_to)?;
+        Ok(indexed)
+    }
+
+    /// Indexes the notes added or edited in Apple Notes since they were last indexed and
+    /// removes the ones deleted or locked there. Returns how many were indexed.
+    pub async fn sync_apple_notes(&self) -> Result<usize> {
+        // 1. Read every note from a snapshot of the Notes database, and the notes indexed so far.
+        let notes = tokio::task::spawn_blocking(|| apple_notes::read_notes(None))
+            .await
+            .map_err(|e| anyhow::anyhow!("Apple Notes reading task failed: {}", e))??;
+        let secs = |t: SystemTime| t.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let indexed_notes: HashMap<String, u64> = self.source_documents(APPLE_NOTES_SOURCE).await?
+            .into_iter()
+            .map(|doc| (doc.path, secs(doc.modified_date)))
+            .collect();
+
+        // 2. Index new notes and notes whose modification date changed. Edits synced from
+        //    other devices keep the date they were made, so dates are compared rather than
+        //    checked against the last sync. Notes indexed in keyword-only mode are embedded.
+        let current: HashSet<String> = notes.iter().map(|note| note.link()).collect();
+        let mut indexed = 0;
+        for note in notes {
+            let link = note.link();
+            let unchanged = note.modified.is_some_and(|modified| indexed_notes.get(&link) == Some(&secs(modified)));
+            let embeddings_due = self.state_store.document(&link).is_some_and(|state| state.embeddings_missing);
+            if unchanged && !(embeddings_due && !self.is_keyword_only()) {
+                continue;
+            }
+            match self.update_document(note.into_raw_document()).await {
+                Ok(()) => indexed += 1,
+                Err(e) => eprintln!("Warning: Could not index note {}: {}", link, e),
+            }
+        }
+
+        // 3. Remove the notes that are gone.
+        for path in indexed_notes.keys().filter(|path| !current.contains(*path)) {
+            if let Err(e) = self.delete_document(path).await {
+                eprintln!("Warning: Could not remove deleted note {}: {}", path, e);
+            }
+        }
+
+        self.sync_cursors.complete(APPLE_NOTES_SOURCE, &now_secs().to_string())?;
+        Ok(indexed)
+    }
+
+    /// Indexes the Gmail messages that arrived since the last sync and removes the ones
+    /// deleted, trashed or marked as spam. The first sync, and any after Gmail dropped the
+    /// history since the last one, lists the past year of mail instead. Resumes an
+    /// interrupted sync. Returns how many messages were indexed.
+    pub async fn sync_gmail(&self, client: &OAuthClient) -> Result<usize> {
+        let token = auth::access_token(GMAIL_SOURCE, client).await?;
+        let limiter = self.rate_limiter(GMAIL_SOURCE);
+        let gmail = GmailClient::new(&token, &limiter)?;
+        let cursor = self.sync_cursors.get(GMAIL_SOURCE);
+        let Some(start_history_id) = cursor.committed else {
+            return self.sync_gmail_mailbox(&gmail, cursor.pending).await;
+        };
+        match self.sync_gmail_history(&gmail, &start_history_id, cursor.pending).await {
+            Err(e) if e.is::<HistoryExpired>() => {
+                self.sync_cursors.reset(GMAIL_SOURCE)?;
+                self.sync_gmail_mailbox(&gmail, None).await
+            }
+            result => result,
+        }
+    }
+
+    /// Lists and indexes the mailbox page by page, then commits the history id read before
+    /// the first page, so mail arriving meanwhile is picked up by the next sync.
+    async fn sync_gmail_mailbox(&self, gmail: &GmailClient<'_>, pending: Option<PendingSync>) -> Result<usize> {
+        let (mut page, commit_to) = match pending {
+            Some(pending) => (Some(pending.page), pending.commit_to),
+            None => (None, gmail.history_id().await?),
+        };
+        let mut indexed = 0;
+        loop {
+            let listed = gmail.list_messages(page.as_deref()).await?;
+            indexed += self.import_gmail_messages(gmail, listed.ids).await;
+            match listed.next_page {
+                Some(next) => {
+                    self.sync_cursors.checkpoint(GMAIL_SOURCE, &next, &commit_to)?;
+                    page = Some(next);
+                }
+                None => break,
+            }
+        }
+        self.sync_cursors.complete(GMAIL_SOURCE, &commit_to)?;
+        Ok(indexed)
+    }
+
+    /// Applies the mailbox history since `start_history_id` page by page.
+    async fn sync_gmail_history(&self, gmail: &GmailClient<'_>, start_history_id: &str, pending: Option<PendingSync>) -> Result<usize> {
+        let (mut page, mut commit_to) = match pending {
+            Some(pending) => (Some(pending.page), Some(pending.commit_to)),
+            None => (None, None),
+        };
+        let mut indexed = 0;
+        loop {
+            let history = gmail.history(start_history_id, page.as_deref()).await?;
+            // Later pages report a newer history id; the one from the first page is committed
+            let commit_to = commit_to.get_or_insert(history.history_id).clone();
+            for id in history.removed {
+                self.delete_indexed(&gmail::message_key(&id)).await;
+            }
+            indexed += self.import_gmail_messages(gmail, history.added).await;
+            match history.next_page {
+                Some(next) => {
+                    self.sync_cursors.checkpoint(GMAIL_SOURCE, &next, &commit_to)?;
+                    page = Some(next);
+                }
+                None => {
+                    self.sync_cursors.complete(GMAIL_SOURCE, &commit_to)?;
+                    return Ok(indexed);
+                }
+            }
+        }
+    }
+
+    /// Fetches and indexes messages, removing any that turned out to be spam, trash or
+    /// drafts. Returns how many were indexed; failures are logged and skipped.
+    async fn import_gmail_messages(&self, gmail: &GmailClient<'_>, ids: Vec<String>) -> usize {
+        let mut indexed = 0;
+        for id in ids {
+            let key = gmail::message_key(&id);
+            match gmail.message(&id).await {
+                Ok(Some(message)) if message.is_indexable() => match self.update_document(message.into_raw_document()).await {
+                    Ok(()) => indexed += 1,
+                    Err(e) => eprintln!("Warning: Could not index Gmail message {}: {}", key, e),
+                },
+                Ok(_) => self.delete_indexed(&key).await,
+                Err(e) => eprintln!("Warning: Could not fetch Gmail message {}: {}", key, e),
+            }
+        }
+        indexed
+    }
+
+    /// Lists the Slack channels and DMs the signed-in user is in, for picking which to index.
+    pub async fn slack_channels(&self, client: &OAuthClient) -> Result<Vec<SlackChannel>> {
+        let token = auth::access_token(SLACK_SOURCE, client).await?;
+        let limiter = self.rate_limiter(SLACK_SOURCE);
+        let slack = SlackClient::new(&token, &limiter)?;
+        let users = slack.users().await?;
+        slack.channels(&users).await
+    }
+
+    /// Indexes the messages posted in the selected Slack channels and DMs since the last
+    /// sync, a year back for channels not synced before, and removes the messages of
+    /// channels no longer selected. Progress is saved after each channel, so an interrupted
+    /// sync only fetches what is new in the channels it finished. Threads started since
+    /// the last sync are indexed with their replies, but replies to older threads, and
+    /// edits and deletions of messages already indexed, aren't picked up. Returns how
+    /// many messages were indexed.
+    pub async fn sync_slack(&self, client: &OAuthClient, selected: &[String]) -> Result<usize> {
+        let token = auth::access_token(SLACK_SOURCE, client).await?;
+        let limiter = self.rate_limiter(SLACK_SOURCE);
+        let slack = SlackClient::new(&token, &limiter)?;
+
+        // 1. The cursor holds the newest message indexed per channel, as JSON; a resumed
+        //    sync continues from the channels the interrupted one finished.
+        let cursor = self.sync_cursors.get(SLACK_SOURCE);
+        let mut newest: BTreeMap<String, String> = cursor.pending.map(|pending| pending.commit_to)
+            .or(cursor.committed)
+            .map(|json| serde_json::from_str(&json))
+            .transpose()?
+            .unwrap_or_default();
+        newest.retain(|channel, _| selected.contains(channel));
+
+        // 2. Remove the messages of channels no longer selected.
+        for doc in self.source_documents(SLACK_SOURCE).await? {
+            if !slack::permalink_channel(&doc.path).is_some_and(|channel| selected.iter().any(|id| id == channel)) {
+                self.delete_indexed(&doc.path).await;
+            }
+        }
+
+        // 3. Read the workspace address, members and channel names messages are indexed with.
+        let workspace_url = slack.workspace_url().await?;
+        let users = slack.users().await?;
+        let channels: HashMap<String, SlackChannel> = slack.channels(&users).await?
+            .into_iter()
+            .map(|channel| (channel.id.clone(), channel))
+            .collect();
+
+        // 4. Index each channel's new messages page by page, newest first.
+        let first_sync_start = slack::ts_at(now_secs().saturating_sub(slack::FULL_SYNC_SECS));
+        let mut indexed = 0;
+        for id in selected {
+            let Some(channel) = channels.get(id) else {
+                eprintln!("Warning: Slack channel {} is archived or was left, skipping it", id);
+                continue;
+            };
+            let oldest = newest.get(id).cloned().unwrap_or_else(|| first_sync_start.clone());
+            // One channel failing, e.g. after the user left it, shouldn't hold up the others
+            match self.sync_slack_channel(&slack, channel, &oldest, &workspace_url, &users).await {
+                Ok((channel_indexed, latest)) => {
+                    indexed += channel_indexed;
+                    newest.insert(id.clone(), latest);
+                    self.sync_cursors.checkpoint(SLACK_SOURCE, id, &serde_json::to_string(&newest)?)?;
+                }
+                Err(e) => eprintln!("Warning: Could not sync Slack channel {}: {}", channel.name, e),
+            }
+        }
+
+        self.sync_cursors.complete(SLACK_SOURCE, &serde_json::to_string(&newest)?)?;
+        Ok(indexed)
+    }
+
+    /// Indexes one channel's messages posted after `oldest`, with the replies of threads
+    /// they start. Returns how many were indexed and where the channel's next sync starts:
+    /// the newest message, or just before the first one that failed so it is retried.
+    async fn sync_slack_channel(
+        &self,
+        slack: &SlackClient<'_>,
+        channel: &SlackChannel,
+        oldest: &str,
+        workspace_url: &str,
+        users: &HashMap<String, String>,
+    ) -> Result<(usize, String)> {
+        let mut indexed = 0;
+        let mut synced: Vec<(String, bool)> = Vec::new();
+        let mut page: Option<String> = None;
+        loop {
+            let history = slack.history(&channel.id, oldest, page.as_deref()).await?;
+            for message in history.messages {
+                let (ts, has_replies) = (message.ts.clone(), message.reply_count > 0);
+                let mut ok = self.index_slack_message(message, workspace_url, channel, users).await;
+                indexed += usize::from(ok);
+                // A thread counts as synced only once all its replies are
+                if has_replies {
+                    match self.sync_slack_thread(slack, channel, &ts, workspace_url, users).await {
+                        Ok((thread_indexed, thread_ok)) => {
+                            indexed += thread_indexed;
+                            ok &= thread_ok;
+                        }
+                        Err(e) => {
+                            eprintln!("Warning: Could not read the replies to Slack message {}: {}", ts, e);
+                            ok = false;
+                        }
+                    }
+                }
+                synced.push((ts, ok));
+            }
+            match history.next_page {
+                Some(next) => page = Some(next),
+                None => return Ok((indexed, slack::resume_point(oldest, &synced))),
+            }
+        }
+    }
+
+    /// Indexes the replies in a thread. Returns how many were indexed and whether all were.
+    async fn sync_slack_thread(
+        &self,
+        slack: &SlackClient<'_>,
+        channel: &SlackChannel,
+        thread_ts: &str,
+        workspace_url: &str,
+        users: &HashMap<String, String>,
+    ) -> Result<(usize, bool)> {
+        let (mut indexed, mut all_indexed) = (0, true);
+        let mut page: Option<String> = None;
+        loop {
+            let replies = slack.replies(&channel.id, thread_ts, page.as_deref()).await?;
+            for reply in replies.messages {
+                let ok = self.index_slack_message(reply, workspace_url, channel, users).await;
+                indexed += usize::from(ok);
+                all_indexed &= ok;
+            }
+            match replies.next_page {
+                Some(next) => page = Some(next),
+                None => return Ok((indexed, all_indexed)),
+            }
+        }
+    }
+
+    /// Indexes one Slack message, returning whether it was indexed.
+    async fn index_slack_message(
+        &self,
+        message: SlackMessage,
+        workspace_url: &str,
+        channel: &SlackChannel,
+        users: &HashMap<String, String>,
+    ) -> bool {
+        let doc = message.into_raw_document(workspace_url, channel, users);
+        let path = doc.path.clone();
+        match self.update_document(doc).await {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Warning: Could not index Slack message {}: {}", path, e);
+                false
+            }
+        }
+    }
+
+    /// Looks up a document's stored fields in the keyword index, on a blocking thread.
+    async fn document_metadata(&self, path: &str) -> Result<Option<KeywordResult>> {
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        let path_clone = path.to_string();
+        tokio::task::spawn_blocking(move || {
+            index_manager_clone.get_document_metadata(&path_clone)
+                .map_err(|e| anyhow::anyhow!("Failed to fetch document metadata: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Metadata fetch task failed: {}", e))?
+    }
+
+    /// Deletes a document if it is indexed, so removals a connector reports for documents
+    /// it never indexed don't show up as deletions.
+    async fn delete_indexed(&self, path: &str) {
+        if self.document_metadata(path).await.ok().flatten().is_none() {
+            return;
+        }
+        if let Err(e) = self.delete_document(path).await {
+            eprintln!("Warning: Could not remove {}: {}", path, e);
+        }
+    }
+
+    /// Returns the stored fields of every indexed document from one source.
+    async fn source_documents(&self, source_type: &str) -> Result<Vec<KeywordResult>> {
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        let documents = tokio::task::spawn_blocking(move || {
+            index_manager_clone.all_documents()
+                .map_err(|e| anyhow::anyhow!("Failed to read indexed documents: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Document scan task failed: {}", e))??;
+        Ok(documents.into_iter().filter(|doc| doc.source_type == source_type).collect())
+    }
+
+    /// Returns how many documents from one source are indexed.
+    pub async fn source_document_count(&self, source_type: &str) -> Result<usize> {
+        Ok(self.source_documents(source_type).await?.len())
+    }
+
+    /// Removes every document a connector indexed and forgets its sync cursor, so turning
+    /// it back on syncs from scratch. Returns how many documents were removed.
+    pub async fn remove_connector_documents(&self, connector: &str) -> Result<usize> {
+        let documents = self.source_documents(connector).await?;
+        for doc in &documents {
+            self.delete_document(&doc.path).await?;
+        }
+        self.sync_cursors.reset(connector)?;
+        Ok(documents.len())
+    }
+
+    /// Returns every connector's sync cursor, for the sync dashboard.
+    pub fn sync_cursors(&self) -> HashMap<String, SyncCursor> {
+        self.sync_cursors.all()
+    }
+
+    /// Forgets a connector's sync cursor, so its next sync fetches everything again.
+    pub fn reset_sync_cursor(&self, connector: &str) -> Result<()> {
+        self.sync_cursors.reset(connector)
+    }
+
+    /// Indexes a Zotero library, one document per reference with its PDFs' text and citation
+    /// metadata. Returns how many were indexed; failures are logged and skipped.
+    pub async fn import_zotero_library(&self, data_dir: Option<PathBuf>) -> Result<usize> {
+        let items = tokio::task::spawn_blocking(move || zotero::read_library(data_dir.as_deref()))
+            .await
+            .map_err(|e| anyhow::anyhow!("Zotero reading task failed: {}", e))??;
+
+        let mut indexed = 0;
+        for item in items {
+            let link = item.link();
+            let doc = tokio::task::spawn_blocking(move || zotero::raw_document_from_item(&item))
+                .await
+                .map_err(|e| anyhow::anyhow!("PDF parsing task failed: {}", e))?;
+            match self.update_document(doc).await {
+                Ok(()) => indexed += 1,
+                Err(e) => eprintln!("Warning: Could not index Zotero item {}: {}", link, e),
+            }
+        }
+        Ok(indexed)
+    }
+
+    /// Indexes a git repository's new commits, its branch names and its README.
+    /// Returns how many commits were indexed.
+    pub async fn index_git_repository(&self, path: &Path, commit_limit: usize) -> Result<usize> {
+        let path_clone = path.to_path_buf();
+        let index_manager = Arc::clone(&self.index_manager);
+        let scan = tokio::task::spawn_blocking(move || {
+            git_repos::scan_repository(&path_clone, commit_limit, |key| {
+                index_manager.get_document_metadata(key).ok().flatten().is_some()
+            })
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Git scanning task failed: {}", e))??;
+
+        let mut indexed = 0;
+        for commit in scan.commits {
+            let path = commit.path.clone();
+            match self.index_document(commit).await {
+                Ok(()) => indexed += 1,
+                Err(e) => eprintln!("Warning: Could not index commit {}: {}", path, e),
+            }
+        }
+        if let Some(branches) = scan.branches {
+            self.update_document(branches).await?;
+        }
+        if let Some(readme) = scan.readme {
+            self.index_file(&readme).await?;
+        }
+        Ok(indexed)
+    }
+
+    /// Indexes the most recent distinct shell commands that aren't indexed yet.
+    /// Returns how many were added.
+    pub async fn index_shell_history(&self, limit: usize) -> Result<usize> {
+        let index_manager = Arc::clone(&self.index_manager);
+        let commands = tokio::task::spawn_blocking(move || {
+            shell_history::read_shell_history(limit)
+                .into_iter()
+                .filter(|command| index_manager.get_document_metadata(&command.key()).ok().flatten().is_none())
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Shell history reading task failed: {}", e))?;
+
+        let mut indexed = 0;
+        for command in commands {
+            match self.index_document(command.into_raw_document()).await {
+                Ok(()) => indexed += 1,
+                Err(e) => eprintln!("Warning: Could not index shell command: {}", e),
+            }
+        }
+        Ok(indexed)
+    }
+
+    /// Indexes every page of a Dash/Zeal docset as `docs`, tagged with its language.
+    /// Returns how many pages were indexed; unreadable pages are logged and skipped.
+    pub async fn index_docset(&self, path: &Path) -> Result<usize> {
+        let path_clone = path.to_path_buf();
+        let (docset, pages) = tokio::task::spawn_blocking(move || -> Result<_> {
+            let docset = Docset::open(&path_clone)?;
+            let pages = docset.pages()?;
+            Ok((docset, pages))
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Docset reading task failed: {}", e))??;
+
+        let docset = Arc::new(docset);
+        let mut indexed = 0;
+        for page in pages {
+            let docset = Arc::clone(&docset);
+            let file = page.file.clone();
+            let doc = tokio::task::spawn_blocking(move || docset.raw_document(&page))
+                .await
+                .map_err(|e| anyhow::anyhow!("Docset page task failed: {}", e))?;
+            match doc {
+                Ok(doc) => match self.update_document(doc).await {
+                    Ok(()) => indexed += 1,
+                    Err(e) => eprintln!("Warning: Could not index docset page {}: {}", file.display(), e),
+                },
+                Err(e) => eprintln!("Warning: Could not read docset page {}: {}", file.display(), e),
+            }
+        }
+        Ok(indexed)
+    }
+
+    /// Returns the saved snippets, most recently updated first.
+    pub fn list_snippets(&self) -> Vec<Snippet> {
+        self.snippets.list()
+    }
+
+    /// Returns one snippet by id.
+    pub fn snippet(&self, id: u64) -> Option<Snippet> {
+        self.snippets.get(id)
+    }
+
+    /// Saves a new snippet and indexes it.
+    pub async fn create_snippet(&self, title: Option<String>, body: String) -> Result<Snippet> {
+        let snippet = self.snippets.create(title, body)?;
+        self.index_document(snippet.to_raw_document()).await?;
+        Ok(snippet)
+    }
+
+    /// Edits a snippet and re-indexes it.
+    pub async fn update_snippet(&self, id: u64, title: Option<String>, body: String) -> Result<Snippet> {
+        let snippet = self.snippets.update(id, title, body)?;
+        self.update_document(snippet.to_raw_document()).await?;
+        Ok(snippet)
+    }
+
+    /// Deletes a snippet and removes it from the indexes.
+    pub async fn delete_snippet(&self, id: u64) -> Result<()> {
+        if let Some(snippet) = self.snippets.delete(id)? {
+            self.delete_document(&snippet.path()).await?;
+        }
+        Ok(())
+    }
+
+    /// Updates a document by deleting the old versions and indexing the new version.
+    pub async fn update_document(&self, mut doc: RawDocument) -> Result<()> {
+        doc.path = canonical_path(&doc.path);
+        if self.state_store.is_excluded(&doc.path) {
+            return Ok(());
+        }
+        // 1. Re-crawls mostly find documents as they were. With the same text and title,
+        //    the embeddings are still good, so at most the keyword entry is rewritten to
+        //    pick up a new modification date. Pruned chunks stay pruned, but a document
+        //    indexed in keyword-only mode is embedded once that is possible.
+        let state = self.state_store.document(&doc.path).unwrap_or_default();
+        let existing = self.document_metadata(&doc.path).await.ok().flatten();
+        if let Some(existing) = &existing {
+            let embeddings_due = state.embeddings_missing && !self.is_keyword_only();
+            if existing.content_hash == calculate_hash(&doc.body) && existing.title == doc.title && !embeddings_due {
+                return self.refresh_keyword_entry(&doc, existing).await;
+            }
+        }
+
+        // 2. An indexed version keeps the embeddings of chunks that didn't change. Without
+        //    one, with its chunks pruned or without a vector store, clear any leftovers
+        //    and start from scratch. Usage state such as open history is kept across
+        //    versions either way.
+        let indexed = existing.is_some();
+        let (kind, stored_chunks) = match self.vector_stack() {
+            Ok(vector_stack) if indexed && !state.chunks_pruned && !state.embeddings_missing => {
+                (IndexEventKind::Updated, Some(vector_stack.vector_db.document_chunk_texts(&doc.path).await?))
+            }
+            _ => {
+                self.delete_from_stores(&doc.path).await?;
+                (if indexed { IndexEventKind::Updated } else { IndexEventKind::Added }, None)
+            }
+        };
+        // 3. Then, index the new version of the document.
+        let result = self.index_document_inner(doc, kind, stored_chunks).await;
+        if result.is_err() {
+            self.metrics.record_error("indexing");
+        }
+        result
+    }
+
+    /// Rewrites the keyword entry of a document whose text didn't change, if its
+    /// modification date did; otherwise the stale-result check would keep flagging it.
+    async fn refresh_keyword_entry(&self, doc: &RawDocument, existing: &KeywordResult) -> Result<()> {
+        let secs = |time: SystemTime| time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        if secs(existing.modified_date) == secs(doc.modified_date) {
+            return Ok(());
+        }
+        let keyword_doc = self.keyword_document(doc, existing.content_hash.clone());
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        tokio::task::spawn_blocking(move || {
+            index_manager_clone.update_document(keyword_doc)
+                .map_err(|e| anyhow::anyhow!("Keyword indexing failed: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Keyword indexing task failed: {}", e))??;
+        self.index_events.publish(IndexEventKind::Updated, &doc.path, Some(&doc.source_type));
+        Ok(())
+    }
+
+    /// Marks the file open in the preview or an editor, or none. Changes to it take the
+    /// fast lane in `refresh_active_file`. Returns the previously active file if it was
+    /// refreshed that way, so it can get a full re-index now that it is no longer active.
+    pub fn set_active_file(&self, path: Option<&str>) -> Option<String> {
+        let next = path.map(|path| ActiveFile { path: canonical_path(path), summary_stale: false });
+        let mut active = self.active_file.lock().unwrap();
+        if active.as_ref().map(|file| &file.path) == next.as_ref().map(|file| &file.path) {
+            return None;
+        }
+        std::mem::replace(&mut *active, next)
+            .filter(|file| file.summary_stale)
+            .map(|file| file.path)
+    }
+
+    /// Returns true if `path` is the file marked with `set_active_file`.
+    pub fn is_active_file(&self, path: &Path) -> bool {
+        self.active_file.lock().unwrap().as_ref()
+            .is_some_and(|file| file.path == canonical_path(&path.display().to_string()))
+    }
+
+    /// Re-indexes the active file within moments of a save: the keyword index is
+    /// rewritten, but only chunks whose text changed are embedded again. The title and
+    /// summary embeddings are left until the file stops being active. Files that aren't
+    /// indexed yet, have no chunks stored, or are indexed in keyword-only mode get a full
+    /// index instead.
+    pub async fn refresh_active_file(&self, path: &Path) -> Result<()> {
+        // 1. Parse the file and check there are chunks worth diffing against.
+        let path_clone = path.to_path_buf();
+        let mut doc = tokio::task::spawn_blocking(move || raw_document_from_file(&path_clone))
+            .await
+            .map_err(|e| anyhow::anyhow!("File parsing task failed: {}", e))??;
+        doc.path = canonical_path(&doc.path);
+        if self.state_store.is_excluded(&doc.path) {
+            return Ok(());
+        }
+        let indexed = matches!(self.document_metadata(&doc.path).await, Ok(Some(_)));
+        let state = self.state_store.document(&doc.path).unwrap_or_default();
+        let vector_stack = match self.vector_stack() {
+            Ok(vector_stack) if indexed && !state.chunks_pruned && !state.embeddings_missing => vector_stack,
+            _ => return self.index_file(path).await,
+        };
+
+        // 2. Embed only the chunks that aren't stored yet.
+        let stored = vector_stack.vector_db.document_chunk_texts(&doc.path).await?;
+        let embedding_generator = Arc::clone(&vector_stack.embedding_generator);
+        let body = doc.body.clone();
+        let path_clone = doc.path.clone();
+        let (removed, records) = tokio::task::spawn_blocking(move || -> Result<_> {
+            let chunks: Vec<String> = embedding_generator.chunk_text(&body)
+                .into_iter()
+                .filter(|chunk| !chunk.trim().is_empty())
+                .collect();
+            let diff = diff_chunks(&stored, &chunks);
+            Ok((diff.removed, embedding_generator.embed_chunks(diff.added, &path_clone)?))
+        }).await??;
+
+        // 3. Replace the keyword entry and swap the changed chunks.
+        let keyword_doc = self.keyword_document(&doc, calculate_hash(&doc.body));
+        let index_manager = Arc::clone(&self.index_manager);
+        let path_clone = doc.path.clone();
+        tokio::task::spawn_blocking(move || {
+            index_manager.delete_document(&path_clone)
+                .and_then(|_| index_manager.add_document_batch(vec![keyword_doc]))
+                .map_err(|e| anyhow::anyhow!("Keyword indexing failed: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Keyword indexing task failed: {}", e))??;
+        vector_stack.vector_db.delete_chunks_with_text(&doc.path, &removed).await?;
+        vector_stack.vector_db.add_embeddings(records, &doc.source_type).await?;
+
+        // 4. Remember the summary is behind, for a full re-index once the file is put away.
+        if let Some(file) = self.active_file.lock().unwrap().as_mut().filter(|file| file.path == doc.path) {
+            file.summary_stale = true;
+        }
+        self.index_events.publish(IndexEventKind::Updated, &doc.path, Some(&doc.source_type));
+        Ok(())
+    }
+
+    /// Re-parses and re-indexes a local file on request, e.g. to fix a stale result.
+    /// This also lifts a previous exclusion of the document.
+    pub async fn reindex_document(&self, path: &str) -> Result<()> {
+        let path = canonical_path(path);
+        let file_path = Path::new(&path);
+        if !long_path(file_path).is_file() {
+            return Err(anyhow::anyhow!("{} is not a local file that can be re-indexed", path));
+        }
+        self.state_store.set_excluded(&path, false)?;
+        self.index_file(file_path).await
+    }
+
+    /// Hides a document and keeps it out of future indexing runs. It stays in both
+    /// indexes until the undo window passes, so `restore_document` can bring it back.
+    pub async fn exclude_document(&self, path: &str) -> Result<()> {
+        let path = canonical_path(path);
+        self.state_store.soft_delete(&path)?;
+        self.index_events.publish(IndexEventKind::Deleted, &path, None);
+        Ok(())
+    }
+
+    /// Tags several documents at once.
+    pub fn tag_documents(&self, paths: &[String], tag: &str) -> Result<()> {
+        let paths: Vec<String> = paths.iter().map(|path| canonical_path(path)).collect();
+        self.state_store.add_tag(&paths, tag)?;
+        // Results carry their tags
+        self.result_cache.clear();
+        Ok(())
+    }
+
+    /// Follows a local file that was moved: the old path leaves the index and the new one
+    /// is indexed, keeping the document's tags and usage history.
+    pub async fn move_document(&self, from: &str, to: &Path) -> Result<()> {
+        let from = canonical_path(from);
+        self.state_store.rename_document(&from, &canonical_path(&to.display().to_string()))?;
+        self.delete_from_stores(&from).await?;
+        self.index_events.publish(IndexEventKind::Deleted, &from, None);
+        self.index_file(to).await
+    }
+
+    /// Undoes an exclusion that is still within its undo window.
+    pub fn restore_document(&self, path: &str) -> Result<()> {
+        let path = canonical_path(path);
+        if !self.state_store.is_deleted(&path) {
+            return Err(anyhow::anyhow!("{} has no pending deletion to undo", path));
+        }
+        self.state_store.set_excluded(&path, false)?;
+        let source_type = self.index_manager.get_document_metadata(&path).ok().flatten().map(|doc| doc.source_type);
+        self.index_events.publish(IndexEventKind::Added, &path, source_type.as_deref());
+        Ok(())
+    }
+
+    /// Returns the excluded documents that can still be restored.
+    pub fn pending_deletions(&self) -> Vec<PendingDeletion> {
+        self.state_store.pending_deletions(self.undo_window.as_secs())
+    }
+
+    /// Removes documents whose undo window has passed from both indexes. They stay
+    /// excluded. Returns how many were removed.
+    pub async fn purge_expired_deletions(&self) -> Result<usize> {
+        let now = now_secs();
+        let mut purged = 0;
+        for deletion in self.pending_deletions().into_iter().filter(|deletion| deletion.purge_at <= now) {
+            self.delete_from_stores(&deletion.path).await?;
+            self.state_store.clear_deleted(&deletion.path)?;
+            purged += 1;
+        }
+        Ok(purged)
+    }
+
+    /// Queues a document to be refreshed in the background, unless it is already queued.
+    pub fn enqueue_reindex(&self, path: &str) {
+        if self.pending_reindex.lock().unwrap().insert(path.to_string()) {
+            let _ = self.reindex_tx.send(path.to_string());
+        }
+    }
+
+    /// Processes queued refreshes until the orchestrator is dropped: files that still exist
+    /// are re-indexed and deleted ones are removed. Meant to be spawned once at startup.
+    pub async fn run_reindex_queue(&self) {
+        let Some(mut reindex_rx) = self.reindex_rx.lock().await.take() else {
+            return;
+        };
+        while let Some(path) = reindex_rx.recv().await {
+            let file_path = Path::new(&path);
+            let result = if long_path(file_path).is_file() {
+                self.index_file(file_path).await
+            } else {
+                self.delete_document(&path).await
+            };
+            if let Err(e) = result {
+                eprintln!("Warning: Could not refresh stale document {}: {}", path, e);
+            }
+            self.pending_reindex.lock().unwrap().remove(&path);
+        }
+    }
+
+    /// Records that the user opened a document, feeding storage eviction priorities.
+    pub fn record_document_opened(&self, path: &str) -> Result<()> {
+        self.end_search_session(true);
+        self.state_store.record_open(&canonical_path(path))
+    }
+
+    // ===================================================================
+    //  STORAGE MANAGEMENT
+    // ===================================================================
+
+    /// Lists every document whose chunk embeddings can be dropped, with its usage and
+    /// modification time. Only local files qualify: their chunks are regenerated by
+    /// re-reading them from disk, while connector documents would need a full resync.
+    async fn collect_eviction_candidates(&self) -> Result<Vec<EvictionCandidate>> {
+        let chunk_counts = self.vector_stack()?.vector_db.chunk_counts_by_document().await?;
+        let mut candidates = Vec::with_capacity(chunk_counts.len());
+        for (path, chunk_count) in chunk_counts {
+            let index_manager_clone = Arc::clone(&self.index_manager);
+            let path_clone = path.clone();
+            let metadata = tokio::task::spawn_blocking(move || {
+                if !long_path(Path::new(&path_clone)).is_file() {
+                    return Ok(None);
+                }
+                index_manager_clone.get_document_metadata(&path_clone)
+                    .map(Some)
+                    .map_err(|e| anyhow::anyhow!("Failed to fetch document metadata: {}", e))
+            }).await
+                .map_err(|e| anyhow::anyhow!("Metadata fetch task failed: {}", e))??;
+            let Some(metadata) = metadata else { continue };
+
+            let modified = metadata
+                .and_then(|m| m.modified_date.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let last_opened = self.state_store.document(&path).and_then(|state| state.last_opened);
+            candidates.push(EvictionCandidate { path, chunk_count, last_opened, modified });
+        }
+        Ok(candidates)
+    }
+
+    /// Measures the vector store on disk, walking its datasets on a blocking thread.
+    async fn vector_store_size(&self) -> Result<u64> {
+        let embedding_dirs = self.embedding_dirs.clone();
+        tokio::task::spawn_blocking(move || embedding_dirs.iter().map(|dir| directory_size(dir)).sum::<u64>())
+            .await
+            .map_err(|e| anyhow::anyhow!("Vector store size task failed: {}", e))
+    }
+
+    /// Keeps the vector store under `quota_bytes` by dropping chunk embeddings of the
+    /// least-used local files. Titles and summaries are always kept so pruned documents
+    /// stay findable. Connector documents are never pruned, so the quota may stay exceeded.
+    pub async fn enforce_storage_quota(&self, quota_bytes: u64) -> Result<EvictionReport> {
+        let bytes_before = self.vector_store_size().await?;
+
+        let mut report = EvictionReport {
+            quota_bytes,
+            bytes_before,
+            bytes_after: bytes_before,
+            documents_pruned: 0,
+            chunks_removed: 0,
+        };
+        if bytes_before <= quota_bytes {
+            return Ok(report);
+        }
+
+        // 1. Gather every local file that still has chunks, with its usage and modification time.
+        let vector_db = &self.vector_stack()?.vector_db;
+        let candidates = self.collect_eviction_candidates().await?;
+
+        // 2. Drop chunks in eviction order until the estimate fits the quota.
+        for candidate in plan_eviction(candidates, bytes_before - quota_bytes) {
+            vector_db.delete_document_chunks(&candidate.path).await?;
+            self.state_store.set_chunks_pruned(&candidate.path, true)?;
+            report.documents_pruned += 1;
+            report.chunks_removed += candidate.chunk_count;
+        }
+
+        // 3. Compact so the deleted rows are released from disk. Compaction writes the
+        //    merged files before dropping the old ones, so it needs room too.
+        if report.documents_pruned > 0 {
+            self.disk_guard.wait_for_space().await;
+            vector_db.compact().await?;
+        }
+        report.bytes_after = self.vector_store_size().await?;
+        Ok(report)
+    }
+
+    /// Applies the tiered retention policy: local files neither modified nor opened within
+    /// `retention_days` keep only their title and summary embeddings. Their chunks are
+    /// regenerated lazily when a search strongly matches the summary. Connector documents
+    /// can't be re-parsed to regenerate them, and documents without a real modification
+    /// date can't be aged, so both are left alone. Returns how many documents were pruned.
+    pub async fn apply_chunk_retention(&self, retention_days: u64) -> Result<usize> {
+        let cutoff = now_secs().saturating_sub(retention_days.saturating_mul(24 * 3600));
+        let vector_db = &self.vector_stack()?.vector_db;
+        let stale: Vec<EvictionCandidate> = self.collect_eviction_candidates().await?
+            .into_iter()
+            .filter(|candidate| candidate.modified > 0)
+            .filter(|candidate| candidate.last_opened.unwrap_or(0).max(candidate.modified) < cutoff)
+            .collect();
+
+        for candidate in &stale {
+            vector_db.delete_document_chunks(&candidate.path).await?;
+            self.state_store.set_chunks_pruned(&candidate.path, true)?;
+        }
+        if !stale.is_empty() {
+            self.disk_guard.wait_for_space().await;
+            vector_db.compact().await?;
+        }
+        Ok(stale.len())
+    }
+
+    /// Regenerates chunk embeddings for a pruned document in the background.
+    /// The document is re-parsed from disk, so this only applies to local files.
+    fn spawn_chunk_regeneration(&self, path: &str) {
+        let Ok(vector_stack) = self.vector_stack() else { return };
+        if !long_path(Path::new(path)).is_file() {
+            return;
+        }
+        // Clear the flag up front so concurrent searches don't queue the same work twice
+        if self.state_store.set_chunks_pruned(path, false).is_err() {
+            return;
+        }
+
+        let index_manager = Arc::clone(&self.index_manager);
+        let embedding_generator = Arc::clone(&vector_stack.embedding_generator);
+        let vector_db = Arc::clone(&vector_stack.vector_db);
+        let state_store = Arc::clone(&self.state_store);
+        let path = path.to_string();
+        tokio::spawn(async move {
+            let path_clone = path.clone();
+            let records = tokio::task::spawn_blocking(move || -> Result<_> {
+                let source_type = index_manager.get_document_metadata(&path_clone).ok().flatten()
+                    .map_or_else(|| LOCAL_FILE_SOURCE.to_string(), |doc| doc.source_type);
+                let body = parse_document(Path::new(&path_clone))?;
+                Ok((embedding_generator.generate_chunk_embeddings(&body, &path_clone)?, source_type))
+            }).await;
+
+            let result = match records {
+                Ok(Ok((records, source_type))) => vector_db.add_embeddings(records, &source_type).await,
+                Ok(Err(e)) => Err(e),
+                Err(e) => Err(anyhow::anyhow!("Chunk regeneration task failed: {}", e)),
+            };
+            if let Err(e) = result {
+                eprintln!("Warning: Could not regenerate chunks for {}: {}", path, e);
+                let _ = state_store.set_chunks_pruned(&path, true);
+            }
+        });
+    }
+
+    // ===================================================================
+    //  HYBRID SEARCH METHOD
+    // ===================================================================
+
+    /// Performs a hybrid search and returns one page of an intelligently ranked list of
+    /// results. How long each phase took goes to the metrics and back with the results.
+    pub async fn hybrid_search(&self, query: &str, options: SearchOptions) -> Result<HybridSearchResponse> {
+        self.timed_hybrid_search(query, options, None).await
+    }
+
+    /// Runs a hybrid search like `hybrid_search`, also passing `on_batch` what each
+    /// retrieval leg found as soon as it finishes, well before fusion is done. Hits are
+    /// checked against the requested source types and dates like the final results.
+    pub async fn live_search(
+        &self,
+        query: &str,
+        options: SearchOptions,
+        on_batch: impl Fn(LiveSearchBatch),
+    ) -> Result<HybridSearchResponse> {
+        let (leg_tx, mut leg_rx) = mpsc::unbounded_channel();
+        let limit = options.limit.min(MAX_RESULT_LIMIT);
+        let filters = options.clone();
+        let forward = async {
+            while let Some(leg_hits) = leg_rx.recv().await {
+                match self.live_batch(leg_hits, &filters, limit).await {
+                    Ok(batch) if !batch.hits.is_empty() => on_batch(batch),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Warning: Could not send live search results: {}", e),
+                }
+            }
+        };
+        let (response, ()) = tokio::join!(self.timed_hybrid_search(query, options, Some(leg_tx)), forward);
+        response
+    }
+
+    /// Looks up the title and source of a leg's first `limit` distinct hits, dropping
+    /// documents the final results would leave out.
+    async fn live_batch(&self, leg_hits: LegHits, options: &SearchOptions, limit: usize) -> Result<LiveSearchBatch> {
+        let index_manager = match leg_hits.origin {
+            ResultOrigin::Personal => Arc::clone(&self.index_manager),
+            ResultOrigin::Team => match &self.team_index {
+                Some(team) => Arc::clone(&team.index_manager),
+                None => return Ok(LiveSearchBatch { leg: leg_hits.leg, hits: Vec::new() }),
             },
-            async {
-                self.vector_db.search_summaries(&query_embedding).await
+        };
+        let source_filter = options.source_types.as_ref()
+            .filter(|source_types| !source_types.is_empty())
+            .map(|source_types| Scope { folders: Vec::new(), source_types: source_types.clone() });
+        let mut seen = HashSet::new();
+        let hits: Vec<(String, Option<String>)> = leg_hits.hits.into_iter()
+            .filter(|(path, _)| seen.insert(path_key(path)) && !self.state_store.is_deleted(path))
+            .collect();
+        let modified = options.modified();
+
+        let origin = leg_hits.origin;
+        let hits = tokio::task::spawn_blocking(move || {
+            let mut live_hits = Vec::new();
+            for (path, snippet) in hits {
+                if live_hits.len() >= limit {
+                    break;
+                }
+                let Some(metadata) = index_manager.get_document_metadata(&path)
+                    .map_err(|e| anyhow::anyhow!("Failed to fetch document metadata: {}", e))? else { continue };
+                let modified_secs = metadata.modified_date.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+                if source_filter.as_ref().is_some_and(|filter| !filter.matches(&path, &metadata.source_type))
+                    || modified.after.is_some_and(|after| modified_secs < after)
+                    || modified.before.is_some_and(|before| modified_secs >= before)
+                {
+                    continue;
+                }
+                live_hits.push(LiveSearchHit {
+                    path,
+                    title: metadata.title,
+                    source_type: metadata.source_type,
+                    snippet,
+                    origin,
+                });
+            }
+            Ok::<_, anyhow::Error>(live_hits)
+        }).await
+            .map_err(|e| anyhow::anyhow!("Metadata fetch task failed: {}", e))??;
+        Ok(LiveSearchBatch { leg: leg_hits.leg, hits })
+    }
+
+    /// Runs a hybrid search and records its latency, then adds provider results and
+    /// notes the outcome for the first page.
+    async fn timed_hybrid_search(
+        &self,
+        query: &str,
+        options: SearchOptions,
+        leg_tx: Option<mpsc::UnboundedSender<LegHits>>,
+    ) -> Result<HybridSearchResponse> {
+        let started = Instant::now();
+        let search = CachedSearch {
+            query: query.trim().to_string(),
+            source_types: options.source_types.clone().filter(|source_types| !source_types.is_empty()),
+            modified_after: options.modified_after,
+            modified_before: options.modified_before,
+        };
+        let page = (options.offset, options.limit.min(MAX_RESULT_LIMIT));
+        if let Some(mut response) = self.result_cache.get(&search, page) {
+            response.cached = true;
+            response.timings = SearchTimings { total_ms: millis(started.elapsed()), ..SearchTimings::default() };
+            self.metrics.record_query_latency(started.elapsed());
+            if options.offset == 0 {
+                self.record_search_outcome(query, &response);
+            }
+            return Ok(response);
+        }
+
+        let generation = self.result_cache.generation();
+        let mut result = self.run_hybrid_search(query, &options, leg_tx).await;
+        match &mut result {
+            Ok(response) => {
+                response.timings.total_ms = millis(started.elapsed());
+                self.metrics.record_query_latency(started.elapsed());
+                self.metrics.record_search_timings(&response.timings);
+            }
+            Err(_) => self.metrics.record_error("search"),
+        }
+        // Providers and the query analytics only concern the first page; later pages are
+        // the same search scrolled further
+        if let (Ok(response), 0) = (&mut result, options.offset) {
+            response.provider_results = self.query_providers(query).await;
+            self.record_search_outcome(query, response);
+        }
+        if let Ok(response) = &result {
+            let paths = response.results.iter()
+                .flat_map(|result| std::iter::once(&result.path).chain(&result.duplicates))
+                .cloned()
+                .collect();
+            self.result_cache.insert(search, page, response.clone(), paths, generation);
+        }
+        result
+    }
+
+    /// Notes a search for the failed-query analytics: the query becomes the session's
+    /// latest, and searches that found nothing at all are recorded right away.
+    fn record_search_outcome(&self, query: &str, response: &HybridSearchResponse) {
+        if query.trim().is_empty() {
+            return;
+        }
+        *self.search_session.lock().unwrap() = Some(query.to_string());
+        if response.results.is_empty() && response.provider_results.is_empty() {
+            self.metrics.record_zero_result_query();
+            if let Err(e) = self.query_analytics.record_zero_result(query) {
+                eprintln!("Warning: Could not record zero-result query: {}", e);
+            }
+        }
+    }
+
+    /// Ends the launcher's search session. Closing it without having opened a result
+    /// counts its last query as abandoned.
+    pub fn end_search_session(&self, selected: bool) {
+        let Some(query) = self.search_session.lock().unwrap().take() else { return };
+        if selected {
+            return;
+        }
+        self.metrics.record_abandoned_query();
+        if let Err(e) = self.query_analytics.record_abandoned(&query) {
+            eprintln!("Warning: Could not record abandoned query: {}", e);
+        }
+    }
+
+    /// Clusters every document's summary embedding into labeled topics and stores them in
+    /// the state file, replacing the previous run. Returns how many topics were found.
+    pub async fn cluster_topics(&self) -> Result<usize> {
+        let summaries: Vec<_> = self.vector_stack()?.vector_db.summary_embeddings().await?
+            .into_iter()
+            .filter(|summary| !self.state_store.is_excluded(&summary.document_path))
+            .collect();
+        let topics = tokio::task::spawn_blocking(move || cluster_topics(&summaries)).await
+            .map_err(|e| anyhow::anyhow!("Topic clustering task failed: {}", e))?;
+        let count = topics.len();
+        self.state_store.set_topics(topics)?;
+        Ok(count)
+    }
+
+    /// Re-clusters topics if the last run is older than `max_age` or there was none.
+    pub async fn refresh_topics(&self, max_age: Duration) -> Result<()> {
+        let fresh = self.state_store.topics_clustered_at()
+            .is_some_and(|clustered_at| now_secs().saturating_sub(clustered_at) < max_age.as_secs());
+        if !fresh {
+            self.cluster_topics().await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the stored topics, largest first, each with up to `documents_per_topic` of
+    /// its most representative documents. Documents deleted since clustering are skipped.
+    pub async fn browse_topics(&self, documents_per_topic: usize) -> Result<Vec<BrowsedTopic>> {
+        let mut browsed = Vec::new();
+        for topic in self.state_store.topics() {
+            let mut documents = Vec::new();
+            for path in &topic.document_paths {
+                if documents.len() >= documents_per_topic {
+                    break;
+                }
+                if let Some(result) = self.document_result(path).await? {
+                    documents.push(result);
+                }
+            }
+            browsed.push(BrowsedTopic {
+                id: topic.id,
+                label: topic.label,
+                keywords: topic.keywords,
+                size: topic.document_paths.len(),
+                documents,
+            });
+        }
+        Ok(browsed)
+    }
+
+    /// Lists the metadata keys documents carry, such as frontmatter fields, with their
+    /// common values, across the personal index and the team index if one is mounted.
+    pub async fn metadata_fields(&self) -> Result<Vec<MetadataField>> {
+        let mut index_managers = vec![Arc::clone(&self.index_manager)];
+        index_managers.extend(self.team_index.as_ref().map(|team| Arc::clone(&team.index_manager)));
+        let per_index = tokio::task::spawn_blocking(move || {
+            index_managers.iter()
+                .map(|index_manager| index_manager.metadata_fields())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| anyhow::anyhow!("Failed to read metadata fields: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Metadata field scan task failed: {}", e))??;
+
+        // Fields both indexes have keep the personal index's values first
+        let mut fields: Vec<MetadataField> = Vec::new();
+        for field in per_index.into_iter().flatten() {
+            match fields.iter_mut().find(|existing| existing.name == field.name) {
+                Some(existing) => {
+                    for value in field.values {
+                        if !existing.values.contains(&value) {
+                            existing.values.push(value);
+                        }
+                    }
+                }
+                None => fields.push(field),
+            }
+        }
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(fields)
+    }
+
+    /// Verifies the keyword index's checksums and returns the files that fail, which
+    /// means the index is corrupted. Reads every index file, so it runs off the async runtime.
+    pub async fn damaged_index_files(&self) -> Result<Vec<String>> {
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        tokio::task::spawn_blocking(move || {
+            index_manager_clone.damaged_files()
+                .map_err(|e| anyhow::anyhow!("Failed to verify the keyword index: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Index verification task failed: {}", e))?
+    }
+
+    /// Merges small segments of the keyword index, which pile up with incremental updates
+    /// and slow searches down over time. Returns how many segments were merged away.
+    pub async fn merge_keyword_segments(&self) -> Result<usize> {
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        tokio::task::spawn_blocking(move || {
+            index_manager_clone.merge_segments()
+                .map_err(|e| anyhow::anyhow!("Failed to merge keyword index segments: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Segment merge task failed: {}", e))?
+    }
+
+    /// Buckets the documents passing the filter by when they were last modified, in local
+    /// time, with the latest titles of each bucket.
+    pub async fn timeline(
+        &self,
+        filter: TimelineFilter,
+        granularity: Granularity,
+        titles_per_bucket: usize,
+    ) -> Result<Vec<TimelineBucket>> {
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        let documents = tokio::task::spawn_blocking(move || {
+            index_manager_clone.all_documents()
+                .map_err(|e| anyhow::anyhow!("Failed to read indexed documents: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Document scan task failed: {}", e))??;
+
+        let documents = documents.into_iter()
+            .filter(|doc| !self.state_store.is_excluded(&doc.path))
+            .filter(|doc| filter.matches(&doc.path, &doc.source_type, doc.modified_date))
+            .map(|doc| (doc.title, doc.modified_date));
+        Ok(build_timeline(documents, granularity, &chrono::Local, titles_per_bucket))
+    }
+
+    /// Notes an index change for the next digest.
+    pub fn record_digest_change(&self, event: &IndexEvent) {
+        if let Err(e) = self.digest_log.record(event) {
+            eprintln!("Warning: Could not record change for the digest: {}", e);
+        }
+    }
+
+    /// Returns true when the changes since the last digest are due to be summarized.
+    pub fn digest_due(&self, frequency: DigestFrequency) -> bool {
+        self.digest_log.is_due(frequency, now_secs())
+    }
+
+    /// Summarizes what was indexed since the last digest, one section per source (per
+    /// folder for files) with its latest titles and a short summary written from their
+    /// document summaries. Returns None when nothing worth reporting changed. In
+    /// keyword-only mode there are no summaries, so sections list titles only.
+    pub async fn generate_digest(&self) -> Result<Option<Digest>> {
+        let created_at = now_secs();
+        let (groups, since) = self.digest_log.pending_groups();
+        let vector_stack = self.vector_stack().ok();
+
+        // 1. Look up each group's latest documents that are still indexed, all in one
+        //    blocking task since the keyword index is read synchronously.
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        let state_store_clone = Arc::clone(&self.state_store);
+        let (groups, group_documents) = tokio::task::spawn_blocking(move || {
+            let group_documents: Vec<Vec<(String, String)>> = groups.iter()
+                .map(|group| group.changes.iter()
+                    .filter(|change| !state_store_clone.is_excluded(&change.path))
+                    .filter_map(|change| {
+                        let metadata = index_manager_clone.get_document_metadata(&change.path).ok().flatten()?;
+                        Some((change.path.clone(), metadata.title))
+                    })
+                    .take(DIGEST_SECTION_DOCUMENTS)
+                    .collect())
+                .collect();
+            (groups, group_documents)
+        }).await
+            .map_err(|e| anyhow::anyhow!("Digest lookup task failed: {}", e))?;
+
+        let mut sections = Vec::new();
+        for (group, documents) in groups.into_iter().zip(group_documents) {
+            if documents.is_empty() {
+                continue;
+            }
+            let mut summaries = Vec::new();
+            if let Some(vector_stack) = vector_stack {
+                for (path, _) in &documents {
+                    if let Some(summary) = vector_stack.vector_db.document_summary(path).await? {
+                        summaries.push(summary);
+                    }
+                }
+            }
+            let titles: Vec<String> = documents.into_iter().map(|(_, title)| title).collect();
+
+            // 2. Condense their summaries into a sentence or two.
+            let summary = match vector_stack {
+                Some(vector_stack) => {
+                    let embedding_generator_clone = Arc::clone(&vector_stack.embedding_generator);
+                    let text = summaries.join(" ");
+                    tokio::task::spawn_blocking(move || {
+                        embedding_generator_clone.summarize(&text, SummaryBudget { min_sentences: 1, max_sentences: 2 })
+                    }).await
+                        .map_err(|e| anyhow::anyhow!("Digest summary task failed: {}", e))?
+                }
+                None => String::new(),
+            };
+
+            let added = group.changes.iter().filter(|change| change.added).count();
+            sections.push(DigestSection {
+                source_type: group.source_type,
+                name: group.name,
+                added,
+                updated: group.changes.len() - added,
+                titles,
+                summary,
+            });
+        }
+
+        // 3. Keep the digest and start collecting changes for the next one.
+        let digest = Digest { created_at, since, sections };
+        self.digest_log.complete(digest.clone())?;
+        Ok((!digest.sections.is_empty()).then_some(digest))
+    }
+
+    /// Returns the recent digests, newest first.
+    pub fn digests(&self) -> Vec<Digest> {
+        self.digest_log.digests()
+    }
+
+    /// Returns the queries that most often found nothing or were abandoned.
+    pub fn query_analytics(&self, limit: usize) -> QueryAnalyticsReport {
+        self.query_analytics.report(limit)
+    }
+
+    pub fn clear_query_analytics(&self) -> Result<()> {
+        self.query_analytics.clear()
+    }
+
+    /// Asks every registered provider for live results. A failing provider is skipped;
+    /// for providers over sensitive data, neither the query nor the error is logged.
+    async fn query_providers(&self, query: &str) -> Vec<ProviderResult> {
+        let providers = self.providers.read().unwrap().clone();
+        let searches = providers.into_iter().map(|provider| {
+            let query = query.to_string();
+            tokio::task::spawn_blocking(move || {
+                provider.search(&query).unwrap_or_else(|e| {
+                    if provider.handles_sensitive_data() {
+                        eprintln!("Warning: Provider {} failed", provider.id());
+                    } else {
+                        eprintln!("Warning: Provider {} failed: {}", provider.id(), e);
+                    }
+                    Vec::new()
+                })
+            })
+        });
+        futures::future::join_all(searches)
+            .await
+            .into_iter()
+            .flat_map(|results| results.unwrap_or_default())
+            .collect()
+    }
+
+    /// Runs several queries concurrently against the same shared index reader, returning
+    /// one entry per query in the original order.
+    pub async fn batch_search(&self, queries: Vec<String>) -> Vec<BatchSearchEntry> {
+        let searches = queries.iter().map(|query| self.hybrid_search(query, SearchOptions::default()));
+        let responses = futures::future::join_all(searches).await;
+
+        queries.into_iter()
+            .zip(responses)
+            .map(|(query, response)| match response {
+                Ok(response) => BatchSearchEntry { query, response: Some(response), error: None },
+                Err(e) => BatchSearchEntry { query, response: None, error: Some(e.to_string()) },
+            })
+            .collect()
+    }
+
+    /// Returns a document's summary for its preview: abstractive when the model was loaded
+    /// as it was indexed, extractive otherwise.
+    pub async fn document_summary(&self, path: &str) -> Result<Option<String>> {
+        match self.vector_stack() {
+            Ok(vector_stack) => vector_stack.vector_db.document_summary(&canonical_path(path)).await,
+            // Summaries live in the vector store
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Returns a document's full text for the reader: the stored copy for remote sources,
+    /// or a fresh parse for local files. None if neither is available.
+    pub async fn reader_text(&self, path: &str) -> Result<Option<String>> {
+        let path = canonical_path(path);
+        if let Some(text) = self.full_text.get(&path)? {
+            return Ok(Some(text));
+        }
+        if !long_path(Path::new(&path)).is_file() {
+            return Ok(None);
+        }
+        let text = tokio::task::spawn_blocking(move || parse_document(Path::new(&path)))
+            .await
+            .map_err(|e| anyhow::anyhow!("File parsing task failed: {}", e))??;
+        Ok(Some(text))
+    }
+
+    /// Switches new summaries to the abstractive model once it has loaded.
+    pub fn set_abstractive_summarizer(&self, summarizer: AbstractiveSummarizer) {
+        if let Some(vector_stack) = &self.vector_stack {
+            vector_stack.embedding_generator.set_abstractive_summarizer(summarizer);
+        }
+    }
+
+    /// Looks up one indexed document as a result, for opening its preview without a search.
+    /// Returns None if it isn't indexed or is waiting to be deleted.
+    pub async fn document_result(&self, path: &str) -> Result<Option<HybridSearchResult>> {
+        let path = canonical_path(path);
+        if self.state_store.is_deleted(&path) {
+            return Ok(None);
+        }
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        let metadata = tokio::task::spawn_blocking(move || {
+            index_manager_clone.get_document_metadata(&path)
+                .map_err(|e| anyhow::anyhow!("Failed to fetch document metadata: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Metadata fetch task failed: {}", e))??;
+
+        Ok(metadata.map(|metadata| self.unranked_result(metadata)))
+    }
+
+    /// Returns the documents the user most recently opened or changed, for showing before
+    /// anything is typed. Previews come from the index, so no file is parsed.
+    pub async fn recent_documents(&self, limit: usize) -> Result<Vec<HybridSearchResult>> {
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        let documents = tokio::task::spawn_blocking(move || {
+            index_manager_clone.all_documents()
+                .map_err(|e| anyhow::anyhow!("Failed to read indexed documents: {}", e))
+        }).await
+            .map_err(|e| anyhow::anyhow!("Document listing task failed: {}", e))??;
+
+        let mut recent: Vec<(SystemTime, KeywordResult)> = documents.into_iter()
+            .filter(|document| !self.state_store.is_deleted(&document.path))
+            .map(|document| {
+                let last_used = self.state_store.document(&document.path)
+                    .and_then(|state| state.last_used())
+                    .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+                (last_used.map_or(document.modified_date, |used| used.max(document.modified_date)), document)
+            })
+            .collect();
+        recent.sort_by_key(|(touched, _)| std::cmp::Reverse(*touched));
+        Ok(recent.into_iter().take(limit).map(|(_, document)| self.unranked_result(document)).collect())
+    }
+
+    /// Turns a document's stored fields into a result that wasn't ranked by a search.
+    fn unranked_result(&self, metadata: KeywordResult) -> HybridSearchResult {
+        let tags = self.state_store.document(&metadata.path).map(|state| state.tags).unwrap_or_default();
+        HybridSearchResult {
+            icon: ResultIcon {
+                icon_id: thumbnails::icon_id_for(&metadata.path, &metadata.source_type),
+                thumbnail_path: thumbnails::cached_thumbnail(&metadata.path).map(|p| p.display().to_string()),
             },
-            async {
-                self.vector_db.search_chunks(&query_embedding).await
+            stale: is_stale(&metadata.path, metadata.modified_date),
+            modified_relative: humanize_relative(metadata.modified_date, SystemTime::now(), &self.locale),
+            path: metadata.path,
+            title: metadata.title,
+            source_type: metadata.source_type,
+            modified_date: metadata.modified_date,
+            final_score: 0.0,
+            best_matching_chunk: None,
+            why: None,
+            keywords: metadata.keywords,
+            preview: metadata.preview,
+            tags,
+            origin: ResultOrigin::Personal,
+            duplicates: Vec::new(),
+            content_hash: metadata.content_hash,
+        }
+    }
+
+    /// Searches for passages inside a single document, powering find-in-preview.
+    /// Both retrieval legs are restricted to the given path and fused with RRF; in
+    /// keyword-only mode the keyword leg ranks the passages alone.
+    pub async fn search_in_document(&self, path: &str, query: &str) -> Result<Vec<DocumentPassage>> {
+        const KEYWORD_BOOST: f32 = 1.2;
+        let vector_stack = self.vector_stack().ok();
+        let path = canonical_path(path);
+
+        // 1. Split the document's text, as the reader shows it, so offsets match it.
+        //    Without a stored copy of a remote document, its stored chunks are the passages.
+        let (passages, has_offsets): (Vec<TextChunk>, bool) = match (self.reader_text(&path).await?, vector_stack) {
+            (Some(text), _) => (chunk_text_with_offsets(&text), true),
+            (None, Some(vector_stack)) => {
+                let chunks = vector_stack.vector_db.document_chunk_texts(&path).await?;
+                (chunks.into_iter().map(|text| TextChunk { end: text.len(), text, start: 0 }).collect(), false)
             }
-        );
+            (None, None) => return Ok(Vec::new()),
+        };
+        let passages = Arc::new(passages);
 
-        // Handle any errors from the parallel searches
-        let keyword_results = keyword_results?;
-        let title_results = title_results?;
-        let summary_results = summary_results?;
-        let chunk_results = chunk_results?;
+        // 2. Keyword leg: rank passages by how many query-term occurrences they contain,
+        //    using the same analyzer as the keyword index so normalization matches.
+        let index_manager_clone = Arc::clone(&self.index_manager);
+        let passages_clone = Arc::clone(&passages);
+        let query_clone = query.to_string();
+        let keyword_matches = tokio::task::spawn_blocking(move || -> Result<Vec<(usize, usize)>> {
+            let query_terms: HashSet<String> = index_manager_clone.analyze_text(&query_clone)
+                .map_err(|e| anyhow::anyhow!("Failed to analyze query: {}", e))?
+                .into_iter()
+                .collect();
+            let mut keyword_matches = Vec::new();
+            for (index, passage) in passages_clone.iter().enumerate() {
+                let terms = index_manager_clone.analyze_text(&passage.text)
+                    .map_err(|e| anyhow::anyhow!("Failed to analyze passage: {}", e))?;
+                let matches = terms.iter().filter(|term| query_terms.contains(*term)).count();
+                if matches > 0 {
+                    keyword_matches.push((index, matches));
+                }
+            }
+            keyword_matches.sort_by(|a, b| b.1.cmp(&a.1));
+            Ok(keyword_matches)
+        }).await
+            .map_err(|e| anyhow::anyhow!("Passage analysis task failed: {}", e))??;
 
-        // --- STAGE 2: INTELLIGENT RE-RANKING ---
-        // 3. Create a HashMap to store the combined scores for each unique document path.
-        let mut combined_scores: HashMap<String, CombinedScore> = HashMap::new();
+        // 3. Vector leg: nearest stored chunks restricted to this document, when semantic
+        //    search is available.
+        let chunk_results = match vector_stack {
+            Some(vector_stack) => {
+                let embedding_generator_clone = Arc::clone(&vector_stack.embedding_generator);
+                let query_clone = query.to_string();
+                let query_embedding = tokio::task::spawn_blocking(move || {
+                    embedding_generator_clone.generate_single_embedding(&query_clone)
+                }).await??;
+                vector_stack.vector_db.search_chunks_in_document(&query_embedding, &path).await?
+            }
+            None => Vec::new(),
+        };
 
-        // 4. Process keyword results and apply Reciprocal Rank Fusion (RRF).
-        //    For each result, add its RRF score to the combined score for that path.
-        //    Also, store the document's metadata (title, date, etc.).
-        for (rank, result) in keyword_results.iter().enumerate() {
-            let rrf_score = calculate_rrf_score(rank);
-            
-            combined_scores.entry(result.path.clone())
-                .and_modify(|score| score.rrf_score += rrf_score * KEYWORD_BOOST) // Boost keyword matches
-                .or_insert_with(|| CombinedScore {
-                    title: result.title.clone(),
-                    source_type: result.source_type.clone(),
-                    modified_date: result.modified_date,
-                    rrf_score: rrf_score * KEYWORD_BOOST,
-                    best_chunk: None,
-                });
+        // 4. Fuse both legs with Reciprocal Rank Fusion, keyed by passage index.
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+        for (rank, (index, _)) in keyword_matches.iter().enumerate() {
+            *scores.entry(*index).or_insert(0.0) += calculate_rrf_score(rank) * KEYWORD_BOOST;
+        }
+        for (rank, (chunk_text, _distance)) in chunk_results.iter().enumerate() {
+            // Stored chunks only line up with passages the document still contains unchanged
+            if let Some(index) = passages.iter().position(|passage| &passage.text == chunk_text) {
+                *scores.entry(index).or_insert(0.0) += calculate_rrf_score(rank);
+            }
         }
 
-        // 5. Process semantic title results.
-        //    For each result, add its RRF score to the combined score for that path.
-        for (rank, (path, _distance)) in title_results.iter().enumerate() {
-            let rrf_score = calculate_rrf_score(rank);
-            
-            self.ensure_metadata_exists(path, &mut combined_scores).await?;
-            let score_data = combined_scores.get_mut(path).unwrap();
-            score_data.rrf_score += rrf_score * TITLE_BOOST; // Boost title matches
+        // 5. Sort by fused score and return the passages.
+        let mut ranked: Vec<(usize, f32)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        Ok(ranked.into_iter()
+            .map(|(index, score)| {
+                let passage = &passages[index];
+                DocumentPassage {
+                    text: passage.text.clone(),
+                    start_offset: has_offsets.then_some(passage.start),
+                    end_offset: has_offsets.then_some(passage.end),
+                    score,
+                }
+            })
+            .collect())
+    }
+
+    async fn run_hybrid_search(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+        leg_tx: Option<mpsc::UnboundedSender<LegHits>>,
+    ) -> Result<HybridSearchResponse> {
+        // Expand user-defined aliases (e.g. `gd` -> a Drive filter) before anything parses the query
+        let expanded_query = expand_aliases(query, &self.aliases.read().unwrap());
+
+        // Pull out `scope:name` and translate the scope into a filter for each store
+        let (scoped_query, scope_name) = extract_scope(&expanded_query);
+        let query = scoped_query.as_str();
+        let scope = match scope_name {
+            Some(name) if name == CURRENT_PROJECT_SCOPE => Some(self.session_scope.read().unwrap().clone()
+                .ok_or_else(|| anyhow::anyhow!("No current project was detected"))?),
+            Some(name) => Some(self.scopes.read().unwrap().get(&name).cloned()
+                .ok_or_else(|| anyhow::anyhow!("Unknown scope '{}'", name))?),
+            None => None,
+        };
+        // Requested source types restrict the search like a scope's connectors do
+        let source_filter = options.source_types.as_ref()
+            .filter(|source_types| !source_types.is_empty())
+            .map(|source_types| Scope { folders: Vec::new(), source_types: source_types.clone() });
+        let keyword_filters: Vec<String> = [&scope, &source_filter].into_iter()
+            .flatten()
+            .filter_map(|s| s.keyword_filter())
+            .collect();
+        // A character or two is only matched against title prefixes, leaving its filters
+        let short_query = short_query_prefix(query);
+        let keyword_base = short_query.as_ref().map_or(query, |(_, filters)| filters.as_str());
+        let keyword_query = match keyword_filters.join(" AND ") {
+            filter if filter.is_empty() => keyword_base.to_string(),
+            filter if keyword_base.trim().is_empty() => filter,
+            filter => format!("({}) AND {}", keyword_base, filter),
+        };
+        let vector_filter = scope.as_ref().and_then(|s| s.vector_filter());
+        // The vector legs take one list of sources: those both the scope and the request allow
+        let vector_source_types: Option<Vec<String>> = match (scope.as_ref().map(|s| &s.source_types), &source_filter) {
+            (Some(scoped), Some(requested)) if !scoped.is_empty() => Some(requested.source_types.iter()
+                .filter(|source_type| scoped.iter().any(|s| s.eq_ignore_ascii_case(source_type)))
+                .cloned()
+                .collect()),
+            (Some(scoped), None) if !scoped.is_empty() => Some(scoped.clone()),
+            (_, requested) => requested.as_ref().map(|requested| requested.source_types.clone()),
+        };
+
+        // Ranking weights come from the active experiment arm, or the defaults when none is running
+        let (ranking, experiment) = self.experiments.assign();
+        // Questions are usually answered by a passage, so look at more chunks for them
+        const QUESTION_CHUNK_LIMIT: usize = 25;
+
+        // Adapt the pipeline to the query: file-name lookups and short queries skip the
+        // semantic legs entirely, as does every query in keyword-only mode
+        let query_kind = classify_query(query);
+        let candidate_limit = options.candidate_limit();
+        let chunk_limit = match query_kind {
+            QueryKind::Question => QUESTION_CHUNK_LIMIT,
+            _ => DEFAULT_SEARCH_LIMIT,
+        }.max(candidate_limit);
+
+        // --- STAGE 1: PARALLEL RETRIEVAL ---
+        // 1. Generate the query embedding once (using spawn_blocking for CPU-intensive work).
+        let embedding_started = Instant::now();
+        let query_embedding = match self.vector_stack() {
+            Ok(vector_stack) if query_kind != QueryKind::Navigational && short_query.is_none() => {
+                let embedding_generator_clone = Arc::clone(&vector_stack.embedding_generator);
+                let query_clone = query.to_string();
+                Some(tokio::task::spawn_blocking(move || {
+                    embedding_generator_clone.generate_single_embedding(&query_clone)
+                }).await??)
+            }
+            _ => None,
+        };
+        let embedding_time = embedding_started.elapsed();
+
+        // 2. Run the retrieval legs against the personal index and, if one is mounted,
+        //    the team index, all concurrently.
+        let personal = self.personal_index();
+        let team = self.team_index.as_ref().map(|team| IndexPair {
+            index_manager: &team.index_manager,
+            vector_db: Some(team.vector_db.as_ref()),
+            origin: ResultOrigin::Team,
+        });
+        let leg_query = LegQuery {
+            keyword_query: &keyword_query,
+            title_prefix: short_query.as_ref().map(|(prefix, _)| prefix.as_str()),
+            modified: options.modified(),
+            embedding: query_embedding.as_deref(),
+            vector_filter: vector_filter.as_deref(),
+            source_types: vector_source_types.as_deref(),
+            candidate_limit,
+            chunk_limit,
+        };
+        let (personal_legs, team_legs) = tokio::join!(
+            retrieve_legs(&personal, &leg_query, leg_tx.as_ref()),
+            async {
+                match &team {
+                    Some(team) => Some(retrieve_legs(team, &leg_query, leg_tx.as_ref()).await),
+                    None => None,
+                }
+            }
+        );
+        // Every leg has reported; dropping the sender lets `live_search` stop listening
+        drop(leg_tx);
+        let personal_legs = personal_legs?;
+        // An unreachable team share shouldn't take personal search down with it
+        let team_legs = team_legs.transpose()
+            .map_err(|e| eprintln!("Warning: Team index search failed: {}", e))
+            .ok()
+            .flatten();
+        // The legs against both indexes run side by side, so the slower of each is what counts
+        let mut timings = personal_legs.timings.clone();
+        timings.embedding_ms = millis(embedding_time);
+        if let Some(legs) = &team_legs {
+            timings.keyword_ms = timings.keyword_ms.max(legs.timings.keyword_ms);
+            timings.titles_ms = timings.titles_ms.max(legs.timings.titles_ms);
+            timings.summaries_ms = timings.summaries_ms.max(legs.timings.summaries_ms);
+            timings.chunks_ms = timings.chunks_ms.max(legs.timings.chunks_ms);
         }
 
-        // 6. Process semantic summary results.
-        //    For each result, add its RRF score to the combined score.
-        for (rank, (path, _distance)) in summary_results.iter().enumerate() {
-            let rrf_score = calculate_rrf_score(rank);
-            
-            self.ensure_metadata_exists(path, &mut combined_scores).await?;
-            let score_data = combined_scores.get_mut(path).unwrap();
-            score_data.rrf_score += rrf_score;
+        // Count documents found only by the vector legs; keyword hits are already counted exactly.
+        let mut keyword_hits = 0;
+        let mut vector_candidates = 0;
+        let mut total_estimate = 0;
+        for legs in std::iter::once(&personal_legs).chain(team_legs.as_ref()) {
+            let keyword_paths: HashSet<&str> = legs.keyword.results.iter().map(|r| r.path.as_str()).collect();
+            let vector_paths: HashSet<&str> = legs.titles.iter().map(|(path, _)| path.as_str())
+                .chain(legs.summaries.iter().map(|(path, _)| path.as_str()))
+                .chain(legs.chunks.iter().map(|(path, _, _)| path.as_str()))
+                .collect();
+            keyword_hits += legs.keyword.total_hits;
+            vector_candidates += vector_paths.len();
+            total_estimate += legs.keyword.total_hits + vector_paths.difference(&keyword_paths).count();
         }
 
-        // 7. Process semantic chunk results.
-        //    For each result, add its RRF score and store the `best_matching_chunk`.
-        for (rank, (path, chunk_text, _distance)) in chunk_results.iter().enumerate() {
-            let rrf_score = calculate_rrf_score(rank);
-            
-            self.ensure_metadata_exists(path, &mut combined_scores).await?;
-            let score_data = combined_scores.get_mut(path).unwrap();
-            score_data.rrf_score += rrf_score;
-            // Keep the best chunk (first one found, as results are sorted by relevance)
-            if score_data.best_chunk.is_none() {
-                score_data.best_chunk = Some(chunk_text.clone());
+        // --- STAGE 2: INTELLIGENT RE-RANKING ---
+        // 3. Fuse each index's legs into one score per document. A document in both
+        //    indexes keeps its personal entry, which is kept current locally.
+        let fusion_started = Instant::now();
+        let (mut combined_scores, mut metadata_time) = self.fuse_legs(&personal, &personal_legs, &ranking).await?;
+        if let (Some(team), Some(legs)) = (&team, &team_legs) {
+            match self.fuse_legs(team, legs, &ranking).await {
+                Ok((team_scores, team_metadata_time)) => {
+                    metadata_time += team_metadata_time;
+                    for (key, score_data) in team_scores {
+                        combined_scores.entry(key).or_insert(score_data);
+                    }
+                }
+                Err(e) => eprintln!("Warning: Team index search failed: {}", e),
             }
         }
 
-        // 8. Calculate the final score for every candidate document.
+        // Drop anything the store-level filters couldn't exclude (folders in Tantivy, connectors
+        // in LanceDB rows written before they recorded their source).
+        for scope in [&scope, &source_filter].into_iter().flatten() {
+            combined_scores.retain(|_, score_data| scope.matches(&score_data.path, &score_data.source_type));
+        }
+        // Vector hits, and keyword hits from indexes without indexed dates, may fall outside the date range
+        if let Some(after) = options.modified_after {
+            combined_scores.retain(|_, score_data| score_data.modified_date >= UNIX_EPOCH + Duration::from_secs(after));
+        }
+        if let Some(before) = options.modified_before {
+            combined_scores.retain(|_, score_data| score_data.modified_date < UNIX_EPOCH + Duration::from_secs(before));
+        }
+        // Soft-deleted documents are still in the stores during their undo window
+        combined_scores.retain(|_, score_data| !self.state_store.is_deleted(&score_data.path));
+        // A query naming a kind of document ("slides", `.pdf`) lifts documents of that kind
+        // without dropping the rest, since the word may just be part of the topic
+        let content_type_boost = *self.content_type_boost.read().unwrap();
+        if let (Some(boost), Some(intent)) = (content_type_boost, infer_content_intent(query)) {
+            for score_data in combined_scores.values_mut() {
+                if intent.matches(&score_data.path, &score_data.source_type) {
+                    score_data.rrf_score *= boost;
+                    score_data.content_type_boosted = true;
+                }
+            }
+        }
+
+        // 4. Calculate the final score for every candidate document.
         let mut final_results = Vec::new();
-        for (path, score_data) in combined_scores {
-            // Calculate a recency score (e.g., from 0.0 to 1.0) based on `modified_date`.
-            let recency_score = calculate_recency_score(score_data.modified_date);
+        let now = SystemTime::now();
+        let query_words = free_text_words(query);
+        for score_data in combined_scores.into_values() {
+            let path = score_data.path;
+            // Calculate a recency score (e.g., from 0.0 to 1.0) from the later of when the
+            // document was modified and when the user last opened it, in the app or elsewhere.
+            let usage = self.state_store.document(&path).unwrap_or_default();
+            let last_used = usage.last_used().map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+            let recency_score = calculate_recency_score(last_used.map_or(score_data.modified_date, |used| used.max(score_data.modified_date)));
+            // Frequently opened files rank above untouched archives.
+            let frequency_score = calculate_frequency_score(usage.use_count());
+
+            // Documents matching the query in many places rank above those with one lucky chunk.
+            let chunk_aggregation_score = match score_data.chunk_hits {
+                0 => 0.0,
+                hits => calculate_chunk_aggregation_score(hits, score_data.chunk_distance_sum / hits as f32),
+            };
 
             // Apply our final weighted formula.
-            let final_score = (RECENCY_WEIGHT * recency_score) + (RRF_WEIGHT * score_data.rrf_score);
+            let final_score = (ranking.recency_weight * recency_score)
+                + (ranking.rrf_weight * score_data.rrf_score)
+                + (ranking.chunk_aggregation_weight * chunk_aggregation_score)
+                + (ranking.frequency_weight * frequency_score);
+
+            // Thumbnails are filled in below, only for the results actually returned.
+            let icon = ResultIcon {
+                icon_id: thumbnails::icon_id_for(&path, &score_data.source_type),
+                thumbnail_path: None,
+            };
+
+            let modified_relative = humanize_relative(score_data.modified_date, now, &self.locale);
+            let why = explain_ranking(&score_data, &query_words, &modified_relative, now);
 
             final_results.push(HybridSearchResult {
                 path,
                 title: score_data.title,
                 source_type: score_data.source_type,
                 modified_date: score_data.modified_date,
+                modified_relative,
                 final_score,
                 best_matching_chunk: score_data.best_chunk,
+                icon,
+                stale: false,
+                why,
+                keywords: score_data.keywords,
+                preview: score_data.preview,
+                tags: usage.tags,
+                origin: score_data.origin,
+                duplicates: Vec::new(),
+                content_hash: score_data.content_hash,
             });
         }
 
-        // 9. Sort the final list by the `final_score` in descending order.
+        // 5. Sort the final list by the `final_score` in descending order.
         final_results.sort_by(|a, b| b.final_score.partial_cmp(&a.final_score).unwrap());
 
-        // 10. (Future Step) Apply result collapsing for similar documents here.
+        // 6. Collapse copies of the same document into the best-ranked one, before paging
+        //    so a page never repeats a result from an earlier one.
+        let final_results = collapse_duplicates(final_results);
 
-        // 11. Return the top N results.
-        Ok(final_results.into_iter().take(20).collect())
+        // 7. Return the requested page along with the hit counts.
+        let limit = options.limit.min(MAX_RESULT_LIMIT);
+        let has_more = final_results.len() > options.offset + limit;
+        let mut results: Vec<HybridSearchResult> = final_results.into_iter().skip(options.offset).take(limit).collect();
+        timings.metadata_ms = millis(metadata_time);
+        timings.fusion_ms = millis(fusion_started.elapsed().saturating_sub(metadata_time));
+
+        // 8. Attach cached thumbnails and render missing ones in the background,
+        //    so they show up the next time these results are displayed. Team results
+        //    point at files on teammates' machines, so there is nothing local to render
+        //    or check for them.
+        for result in results.iter_mut().filter(|result| result.origin == ResultOrigin::Personal) {
+            result.icon.thumbnail_path = thumbnails::cached_thumbnail(&result.path)
+                .map(|p| p.display().to_string());
+            if result.icon.thumbnail_path.is_none() && thumbnails::needs_thumbnail(&result.path) {
+                let path = result.path.clone();
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = thumbnails::generate_thumbnail(&path) {
+                        eprintln!("Warning: Could not generate thumbnail for {}: {}", path, e);
+                    }
+                });
+            }
+        }
+
+        // 9. Flag results whose local file changed or vanished since indexing,
+        //    and queue them to be refreshed if the user allows it.
+        for result in results.iter_mut().filter(|result| result.origin == ResultOrigin::Personal) {
+            result.stale = is_stale(&result.path, result.modified_date);
+            if result.stale && self.auto_reindex_stale {
+                self.enqueue_reindex(&result.path);
+            }
+        }
+
+        let keyword_facets = keyword_facets(results.iter().map(|result| result.keywords.as_slice()), MAX_KEYWORDS);
+
+        Ok(HybridSearchResponse {
+            total_estimate: total_estimate.max(options.offset + results.len()),
+            results,
+            has_more,
+            keyword_hits,
+            vector_candidates,
+            experiment,
+            query_kind,
+            provider_results: Vec::new(),
+            keyword_facets,
+            timings,
+            cached: false,
+        })
     }
 }
\ No newline at end of file
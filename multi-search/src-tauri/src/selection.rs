@@ -0,0 +1,173 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Longest selection used as a query; anything past it is cut at a word boundary.
+const MAX_SELECTION_CHARS: usize = 200;
+/// How long the frontmost app gets to put its selection on the clipboard after Copy.
+const COPY_DELAY: Duration = Duration::from_millis(150);
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Reads the selection of the focused element in the frontmost app through the
+/// accessibility API, which leaves the clipboard alone. Apps that don't expose
+/// `AXSelectedText`, such as most browsers' page content, return nothing.
+#[cfg(target_os = "macos")]
+fn accessibility_selected_text() -> Option<String> {
+    let output = std::process::Command::new("osascript")
+        .args([
+            "-e", "tell application \"System Events\"",
+            "-e", "set frontApp to first application process whose frontmost is true",
+            "-e", "set focusedElement to value of attribute \"AXFocusedUIElement\" of frontApp",
+            "-e", "return value of attribute \"AXSelectedText\" of focusedElement",
+            "-e", "end tell",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn accessibility_selected_text() -> Option<String> {
+    None
+}
+
+/// Returns true if the clipboard is empty or holds only plain text, which is all that
+/// can be put back after copying the selection through it. Copied images, files and
+/// formatted text would be lost, so the clipboard fallback is skipped for them.
+#[cfg(target_os = "macos")]
+fn clipboard_holds_only_text() -> bool {
+    const TEXT_CLASSES: &[&str] = &["«class utf8»", "«class ut16»", "string", "Unicode text"];
+    let Ok(output) = std::process::Command::new("osascript").args(["-e", "clipboard info"]).output() else {
+        return false;
+    };
+    if !output.status.success() {
+        return false;
+    }
+    // Pairs of a class and its size, e.g. `«class utf8», 5, string, 5`
+    let info = String::from_utf8_lossy(&output.stdout);
+    info.trim().split(", ").step_by(2).filter(|class| !class.is_empty()).all(|class| TEXT_CLASSES.contains(&class))
+}
+
+#[cfg(target_os = "windows")]
+fn clipboard_holds_only_text() -> bool {
+    use windows_sys::Win32::System::DataExchange::{CloseClipboard, EnumClipboardFormats, OpenClipboard};
+    // CF_TEXT, CF_OEMTEXT, CF_UNICODETEXT and CF_LOCALE, which Windows adds to any text
+    const TEXT_FORMATS: &[u32] = &[1, 7, 13, 16];
+    unsafe {
+        if OpenClipboard(std::ptr::null_mut()) == 0 {
+            return false;
+        }
+        let mut only_text = true;
+        let mut format = EnumClipboardFormats(0);
+        while format != 0 {
+            only_text &= TEXT_FORMATS.contains(&format);
+            format = EnumClipboardFormats(format);
+        }
+        CloseClipboard();
+        only_text
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn clipboard_holds_only_text() -> bool {
+    false
+}
+
+/// Sends Copy to the frontmost app. Returns false where that isn't supported.
+#[cfg(target_os = "macos")]
+fn simulate_copy() -> bool {
+    std::process::Command::new("osascript")
+        .args(["-e", "tell application \"System Events\" to keystroke \"c\" using command down"])
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(target_os = "windows")]
+fn simulate_copy() -> bool {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP, VIRTUAL_KEY, VK_C, VK_CONTROL,
+    };
+    let key = |vk: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT { wVk: vk, wScan: 0, dwFlags: flags, time: 0, dwExtraInfo: 0 },
+        },
+    };
+    let inputs = [key(VK_CONTROL, 0), key(VK_C, 0), key(VK_C, KEYEVENTF_KEYUP), key(VK_CONTROL, KEYEVENTF_KEYUP)];
+    let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32) };
+    sent == inputs.len() as u32
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn simulate_copy() -> bool {
+    false
+}
+
+/// Copies the selection via the clipboard and puts the user's clipboard text back after.
+/// Skipped when the clipboard holds anything but plain text, which couldn't be put back.
+/// The clipboard is emptied first, so an app with nothing selected isn't mistaken for
+/// one whose selection matches what was already there.
+async fn copied_selected_text(app: &AppHandle) -> Option<String> {
+    if !tokio::task::spawn_blocking(clipboard_holds_only_text).await.unwrap_or(false) {
+        return None;
+    }
+    let clipboard = app.clipboard();
+    let previous = clipboard.read_text().ok();
+    clipboard.write_text(String::new()).ok()?;
+
+    let copied = if tokio::task::spawn_blocking(simulate_copy).await.unwrap_or(false) {
+        tokio::time::sleep(COPY_DELAY).await;
+        clipboard.read_text().ok()
+    } else {
+        None
+    };
+    if let Err(e) = clipboard.write_text(previous.unwrap_or_default()) {
+        eprintln!("Warning: Could not restore the clipboard: {}", e);
+    }
+    copied
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Turns selected text into a query: whitespace, including line breaks, is collapsed,
+/// and long selections are cut at a word boundary. None if nothing is selected.
+pub fn selection_query(text: &str) -> Option<String> {
+    let mut query = String::new();
+    for word in text.split_whitespace() {
+        let separator = usize::from(!query.is_empty());
+        if query.chars().count() + separator + word.chars().count() > MAX_SELECTION_CHARS {
+            if query.is_empty() {
+                query = word.chars().take(MAX_SELECTION_CHARS).collect();
+            }
+            break;
+        }
+        if separator == 1 {
+            query.push(' ');
+        }
+        query.push_str(word);
+    }
+    (!query.is_empty()).then_some(query)
+}
+
+/// Grabs the text selected in the frontmost app, as a query. Must run before the
+/// launcher takes focus. Tries the accessibility API first, then falls back to copying
+/// the selection through the clipboard.
+pub async fn selected_text_query(app: &AppHandle) -> Option<String> {
+    let selected = tokio::task::spawn_blocking(accessibility_selected_text).await.ok().flatten();
+    if let Some(query) = selected.as_deref().and_then(selection_query) {
+        return Some(query);
+    }
+    copied_selected_text(app).await.as_deref().and_then(selection_query)
+}
+
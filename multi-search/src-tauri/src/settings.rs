@@ -0,0 +1,307 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::auth::OAuthClient;
+use crate::backdrop::WindowBackdrop;
+use crate::data_dir;
+use crate::digests::DigestFrequency;
+use crate::identity::Identity;
+use crate::index_manager::IndexTuning;
+use crate::notifications::NotificationSettings;
+use crate::rate_limit::RateLimitConfig;
+use crate::result_actions::ResultAction;
+use crate::scopes::Scope;
+use crate::shortcuts::{DEFAULT_LAUNCHER_SHORTCUT, DEFAULT_SELECTION_SHORTCUT};
+use crate::summary_budget::SummaryBudget;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+// ===================================================================
+//  PUBLIC STRUCT
+// ===================================================================
+
+/// User-facing settings persisted as JSON in the app data directory.
+/// Every field has a default so older settings files keep loading as new options are added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// When true, aggregated metrics may be written out for debugging reports.
+    pub metrics_export_enabled: bool,
+    /// ISO 639-1 code of the user's primary language, used to pick text normalization
+    /// defaults and to localize relative dates in search results.
+    pub language: String,
+    /// Forces accent folding on or off regardless of language. `None` uses the language default.
+    pub fold_diacritics: Option<bool>,
+    /// Launcher shortcuts expanded before query parsing, e.g. "gd" -> "source_type:gdrive".
+    pub aliases: HashMap<String, String>,
+    /// Maximum size of the vector store in megabytes. `None` means unlimited.
+    pub vector_store_quota_mb: Option<u64>,
+    /// Indexing pauses while the volume holding the indexes has less than this many
+    /// megabytes free. `None` never pauses.
+    pub min_free_disk_mb: Option<u64>,
+    /// Local files untouched for this many days keep only title and summary embeddings.
+    /// `None` disables the retention policy.
+    pub chunk_retention_days: Option<u64>,
+    /// Downloaded models neither loaded for this many days nor needed by the current
+    /// settings are deleted at startup. `None` keeps every model.
+    pub model_retention_days: Option<u64>,
+    /// People known under several names, so `author:` queries match every variant.
+    /// Applied at indexing time; documents indexed before an edit keep their old names.
+    pub identities: Vec<Identity>,
+    /// Named document collections that queries can target with `scope:name`.
+    pub scopes: HashMap<String, Scope>,
+    /// When true, the launcher notes the frontmost app and project as it opens, enabling
+    /// `scope:current-project`. Off by default since macOS asks for automation permission.
+    pub context_provider_enabled: bool,
+    /// When true, results whose file changed or was deleted since indexing are refreshed
+    /// in the background as soon as a search notices them.
+    pub auto_reindex_stale: bool,
+    /// Multiplier on the fused score of documents of a kind the query names, such as
+    /// "slides" or `.pdf`. `None` turns these boosts off.
+    pub content_type_boost: Option<f32>,
+    /// When true, a localhost endpoint accepts pages sent by the browser bookmarklet.
+    /// Read at startup, so turning it on takes effect on the next launch.
+    pub capture_endpoint_enabled: bool,
+    /// Port of the capture endpoint on 127.0.0.1.
+    pub capture_endpoint_port: u16,
+    /// Secret the bookmarklet sends with every capture. Generated when first needed.
+    pub capture_token: String,
+    /// Access token for the Readwise export API. `None` disables Readwise sync.
+    pub readwise_token: Option<String>,
+    /// When true, 1Password item titles (never their contents) are offered as results,
+    /// looked up live through the `op` CLI and never indexed.
+    pub password_manager_provider_enabled: bool,
+    /// Local git repositories whose commits, branches and README are indexed.
+    pub git_repositories: Vec<PathBuf>,
+    /// Most recent commits indexed per repository.
+    pub git_commit_limit: usize,
+    /// When true, commands from zsh, bash, fish and Atuin history are indexed.
+    /// Opt-in, and commands that look like they carry credentials are always skipped.
+    pub shell_history_enabled: bool,
+    /// Most recent distinct commands kept from shell history.
+    pub shell_history_limit: usize,
+    /// Dash/Zeal docsets indexed for offline API documentation search.
+    pub docsets: Vec<PathBuf>,
+    /// Minutes an excluded document can be restored before it is removed from the indexes.
+    pub undo_window_minutes: u64,
+    /// Request pacing and retry settings per API connector, keyed by source type.
+    /// Connectors not listed use the defaults.
+    pub rate_limits: HashMap<String, RateLimitConfig>,
+    /// OAuth apps connectors sign in with, keyed by connector. Tokens themselves are
+    /// kept in the OS keychain, never in this file.
+    pub oauth_clients: HashMap<String, OAuthClient>,
+    /// Minutes between background syncs per connector ("apple_notes", "readwise").
+    /// Connectors not listed only sync when asked to.
+    pub connector_sync_minutes: HashMap<String, u64>,
+    /// IDs of the Slack channels and DMs the Slack connector indexes.
+    pub slack_channels: Vec<String>,
+    /// When true, the local endpoint accepts push notifications (Gmail Pub/Sub, Microsoft
+    /// Graph) forwarded by a relay, syncing the connector right away instead of on the next poll.
+    pub webhooks_enabled: bool,
+    /// Secret every push notification URL carries. Generated when first needed.
+    pub webhook_token: String,
+    /// Summary length overrides by connector ("gmail") or file type ("file:pdf"), applied
+    /// when documents are next indexed. Unlisted ones use built-in budgets.
+    pub summary_budgets: HashMap<String, SummaryBudget>,
+    /// When true, a small local model (Flan-T5, downloaded on first use) writes document
+    /// summaries instead of picking sentences out of them. Read at startup.
+    pub abstractive_summaries_enabled: bool,
+    /// How often a digest of newly indexed content is written: "daily", "weekly" or "off".
+    pub digest_frequency: DigestFrequency,
+    /// Which background events raise a native notification.
+    pub notifications: NotificationSettings,
+    /// When true, the full text of emails, web pages and other remote documents is kept,
+    /// compressed, so the reader can show them offline. Read at startup.
+    pub store_full_text: bool,
+    /// Folders to keep indexed, e.g. imported from Alfred or Recoll. They are watched for
+    /// changes from startup on.
+    pub indexed_folders: Vec<PathBuf>,
+    /// Folders whose files are never indexed, even inside an indexed folder.
+    pub excluded_folders: Vec<PathBuf>,
+    /// Volumes the indexes are kept on, e.g. chunks on a large HDD. Read at startup.
+    pub index_locations: IndexLocations,
+    /// A folder where a team publishes its indexes, e.g. on a network share, searched
+    /// read-only alongside the personal ones. Read at startup.
+    pub team_index: Option<PathBuf>,
+    /// Global shortcut that shows and hides the launcher, as an accelerator string.
+    pub launcher_shortcut: String,
+    /// Global shortcut that opens the launcher searching for the selected text; None turns it off.
+    pub selection_shortcut: Option<String>,
+    /// Global shortcut held down to speak a query; None, the default, turns voice queries off.
+    pub voice_shortcut: Option<String>,
+    /// Keyword index writer memory and segment merging. Read at startup.
+    pub keyword_index_tuning: IndexTuning,
+    /// Material behind the launcher on Windows; falls back to what the Windows build supports.
+    pub window_backdrop: WindowBackdrop,
+    /// Never download models. A missing embedding model holds up the search engine until
+    /// it is downloaded on request or its files are put in the app data directory.
+    pub offline_mode: bool,
+    /// Commands offered on results, e.g. "Open in VS Code" for files, in menu order.
+    pub result_actions: Vec<ResultAction>,
+}
+
+/// Where each index lives. Unset locations stay in the app data directory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexLocations {
+    pub keyword_index: Option<PathBuf>,
+    /// Title and summary embeddings, searched on every query; best on a fast drive.
+    pub vector_store: Option<PathBuf>,
+    /// Chunk embeddings, the bulk of the vector data. Unset keeps them with the titles
+    /// and summaries. Existing chunks move over when this is first set.
+    pub chunk_store: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            metrics_export_enabled: false,
+            language: "en".to_string(),
+            fold_diacritics: None,
+            aliases: HashMap::new(),
+            vector_store_quota_mb: None,
+            min_free_disk_mb: Some(1024),
+            chunk_retention_days: None,
+            model_retention_days: None,
+            identities: Vec::new(),
+            scopes: HashMap::new(),
+            context_provider_enabled: false,
+            auto_reindex_stale: true,
+            content_type_boost: None,
+            capture_endpoint_enabled: false,
+            capture_endpoint_port: 47615,
+            capture_token: String::new(),
+            readwise_token: None,
+            password_manager_provider_enabled: false,
+            git_repositories: Vec::new(),
+            git_commit_limit: 1000,
+            shell_history_enabled: false,
+            shell_history_limit: 5000,
+            docsets: Vec::new(),
+            undo_window_minutes: 10,
+            rate_limits: HashMap::from([
+                // Readwise allows 20 requests a minute to its export endpoint
+                (
+                    "readwise".to_string(),
+                    RateLimitConfig { requests_per_minute: 20, burst: 1, ..RateLimitConfig::default() },
+                ),
+                // Gmail allows 250 quota units a second per user, and reading a message costs 5
+                (
+                    "gmail".to_string(),
+                    RateLimitConfig { requests_per_minute: 1200, burst: 20, ..RateLimitConfig::default() },
+                ),
+                // Slack allows about 50 history requests a minute, and 20 for listing members and channels
+                (
+                    "slack".to_string(),
+                    RateLimitConfig { requests_per_minute: 40, burst: 5, ..RateLimitConfig::default() },
+                ),
+            ]),
+            oauth_clients: HashMap::new(),
+            connector_sync_minutes: HashMap::new(),
+            slack_channels: Vec::new(),
+            webhooks_enabled: false,
+            webhook_token: String::new(),
+            summary_budgets: HashMap::new(),
+            abstractive_summaries_enabled: false,
+            digest_frequency: DigestFrequency::Daily,
+            notifications: NotificationSettings::default(),
+            store_full_text: true,
+            indexed_folders: Vec::new(),
+            excluded_folders: Vec::new(),
+            index_locations: IndexLocations::default(),
+            team_index: None,
+            launcher_shortcut: DEFAULT_LAUNCHER_SHORTCUT.to_string(),
+            selection_shortcut: Some(DEFAULT_SELECTION_SHORTCUT.to_string()),
+            voice_shortcut: None,
+            keyword_index_tuning: IndexTuning::default(),
+            window_backdrop: WindowBackdrop::default(),
+            offline_mode: false,
+            result_actions: Vec::new(),
+        }
+    }
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the root directory where the app stores its indexes and state: the platform
+/// data directory unless another was chosen at startup.
+pub fn app_data_dir() -> Result<PathBuf> {
+    data_dir::data_root()
+}
+
+/// Returns the path of the settings file.
+fn settings_path() -> Result<PathBuf> {
+    Ok(app_data_dir()?.join("settings.json"))
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl IndexLocations {
+    /// Returns the directory of the keyword index.
+    pub fn keyword_index_dir(&self) -> Result<PathBuf> {
+        match &self.keyword_index {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(app_data_dir()?.join("keyword_index")),
+        }
+    }
+
+    /// Returns the directory of the title and summary embeddings.
+    pub fn vector_store_dir(&self) -> Result<PathBuf> {
+        match &self.vector_store {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(app_data_dir()?.join("vector_store")),
+        }
+    }
+
+    /// Returns every directory holding embeddings: the vector store and, if separate,
+    /// the chunk store.
+    pub fn embedding_dirs(&self) -> Result<Vec<PathBuf>> {
+        let mut dirs = vec![self.vector_store_dir()?];
+        dirs.extend(self.chunk_store.clone());
+        Ok(dirs)
+    }
+
+    /// Returns a directory on every volume the app writes to: the app data directory and
+    /// each index, for guarding their free space.
+    pub fn data_volume_dirs(&self) -> Result<Vec<PathBuf>> {
+        let mut dirs = vec![app_data_dir()?, self.keyword_index_dir()?];
+        dirs.extend(self.embedding_dirs()?);
+        Ok(dirs)
+    }
+}
+
+impl Settings {
+    /// Loads the settings file, falling back to defaults if it doesn't exist yet.
+    pub fn load() -> Result<Self> {
+        let path = settings_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read settings file {}: {}", path.display(), e))?;
+        let settings = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Failed to parse settings file {}: {}", path.display(), e))?;
+        Ok(settings)
+    }
+
+    /// Writes the settings to disk, replacing the previous file atomically.
+    pub fn save(&self) -> Result<()> {
+        let path = settings_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Write to a temporary file first so a crash never leaves a half-written settings file
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::fs_paths::long_path;
+use crate::search_orchestrator::RawDocument;
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source type of commands read from shell history.
+pub const SHELL_SOURCE: &str = "shell";
+
+/// Commands containing any of these are skipped, since history often holds inline credentials.
+const SENSITIVE_MARKERS: &[&str] = &["password", "passwd", "secret", "token", "api_key", "apikey", "authorization:"];
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// One command from a shell history file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellCommand {
+    pub shell: &'static str,
+    pub command: String,
+    pub executed_at: Option<SystemTime>,
+    /// Only Atuin records where a command ran; plain history files don't.
+    pub working_directory: Option<String>,
+}
+
+impl ShellCommand {
+    /// Returns the document key, derived from the command text so repeats share one document.
+    pub fn key(&self) -> String {
+        let digest = Sha256::digest(self.command.as_bytes());
+        let hash: String = digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect();
+        format!("shell://{}", hash)
+    }
+
+    /// Converts the command into a document titled by the command itself, ready to copy.
+    pub fn into_raw_document(self) -> RawDocument {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("shell".to_string(), self.shell.to_string());
+        if let Some(cwd) = &self.working_directory {
+            metadata.insert("cwd".to_string(), cwd.clone());
+        }
+        RawDocument {
+            path: self.key(),
+            title: self.command.clone(),
+            body: self.command,
+            summary: None,
+            source_type: SHELL_SOURCE.to_string(),
+            author: None,
+            modified_date: self.executed_at.unwrap_or(UNIX_EPOCH),
+            metadata,
+        }
+    }
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+fn from_unix_secs(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// Returns true if the command probably contains a credential.
+fn looks_sensitive(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    SENSITIVE_MARKERS.iter().any(|marker| lower.contains(marker))
+}
+
+/// Parses zsh history, plain or in the extended `: <time>:<duration>;<command>` form.
+/// Multi-line commands continue on lines ending with a backslash.
+fn parse_zsh_history(contents: &str) -> Vec<ShellCommand> {
+    let mut commands: Vec<ShellCommand> = Vec::new();
+    let mut continues = false;
+    for line in contents.lines() {
+        if continues {
+            if let Some(last) = commands.last_mut() {
+                last.command.push('\n');
+                last.command.push_str(line);
+            }
+        } else {
+            let (executed_at, command) = match line.strip_prefix(": ").and_then(|rest| rest.split_once(';')) {
+                Some((meta, command)) => {
+                    let secs = meta.split(':').next().and_then(|secs| secs.trim().parse().ok());
+                    (secs.map(from_unix_secs), command)
+                }
+                None => (None, line),
+            };
+            commands.push(ShellCommand {
+                shell: "zsh",
+                command: command.to_string(),
+                executed_at,
+                working_directory: None,
+            });
+        }
+        continues = line.ends_with('\\');
+    }
+    commands
+}
+
+/// Parses bash history, where `HISTTIMEFORMAT` adds `#<time>` lines before commands.
+fn parse_bash_history(contents: &str) -> Vec<ShellCommand> {
+    let mut commands = Vec::new();
+    let mut executed_at = None;
+    for line in contents.lines() {
+        if let Some(secs) = line.strip_prefix('#').and_then(|secs| secs.trim().parse().ok()) {
+            executed_at = Some(from_unix_secs(secs));
+            continue;
+        }
+        commands.push(ShellCommand {
+            shell: "bash",
+            command: line.to_string(),
+            executed_at: executed_at.take(),
+            working_directory: None,
+        });
+    }
+    commands
+}
+
+/// Parses fish history, a YAML-like list of `- cmd:` entries with `when:` timestamps.
+fn parse_fish_history(contents: &str) -> Vec<ShellCommand> {
+    let mut commands: Vec<ShellCommand> = Vec::new();
+    for line in contents.lines() {
+        if let Some(command) = line.strip_prefix("- cmd: ") {
+            // fish escapes backslashes and newlines inside the command
+            let command = command.replace("\\\\", "\u{0}").replace("\\n", "\n").replace('\u{0}', "\\");
+            commands.push(ShellCommand {
+                shell: "fish",
+                command,
+                executed_at: None,
+                working_directory: None,
+            });
+        } else if let Some(secs) = line.trim().strip_prefix("when: ").and_then(|secs| secs.trim().parse().ok()) {
+            if let Some(last) = commands.last_mut() {
+                last.executed_at = Some(from_unix_secs(secs));
+            }
+        }
+    }
+    commands
+}
+
+/// Reads Atuin's history database, the one source that records the working directory.
+fn read_atuin_history(database: &Path) -> Result<Vec<ShellCommand>> {
+    let connection = rusqlite::Connection::open_with_flags(database, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut statement = connection.prepare("SELECT command, timestamp, cwd FROM history WHERE deleted_at IS NULL")?;
+    let rows = statement.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<String>>(2)?))
+    })?;
+    let mut commands = Vec::new();
+    for row in rows {
+        let (command, timestamp_nanos, cwd) = row?;
+        commands.push(ShellCommand {
+            shell: "atuin",
+            command,
+            executed_at: Some(UNIX_EPOCH + Duration::from_nanos(timestamp_nanos.max(0) as u64)),
+            working_directory: cwd,
+        });
+    }
+    Ok(commands)
+}
+
+/// Reads a text history file, tolerating the non-UTF-8 bytes zsh uses for metacharacters.
+fn read_history_file(path: &Path) -> Option<String> {
+    std::fs::read(long_path(path))
+        .ok()
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Keeps the latest run of each command, dropping blanks and likely credentials,
+/// and returns at most `limit` commands, most recent first.
+fn deduplicate(commands: Vec<ShellCommand>, limit: usize) -> Vec<ShellCommand> {
+    let mut latest: HashMap<String, ShellCommand> = HashMap::new();
+    // Histories are oldest first, so later entries win and carry the last working directory
+    for (order, mut command) in commands.into_iter().enumerate() {
+        command.command = command.command.trim().to_string();
+        if command.command.is_empty() || looks_sensitive(&command.command) {
+            continue;
+        }
+        // Untimed entries are ordered by their position in the file
+        command.executed_at = command.executed_at.or(Some(from_unix_secs(order as u64)));
+        latest.insert(command.command.clone(), command);
+    }
+    let mut commands: Vec<ShellCommand> = latest.into_values().collect();
+    commands.sort_by_key(|command| std::cmp::Reverse(command.executed_at));
+    commands.truncate(limit);
+    commands
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns the history files and databases found in the home directory.
+pub fn history_sources() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    [
+        home.join(".zsh_history"),
+        home.join(".bash_history"),
+        home.join(".local/share/fish/fish_history"),
+        home.join(".local/share/atuin/history.db"),
+    ]
+    .into_iter()
+    .filter(|path| long_path(path).is_file())
+    .collect()
+}
+
+/// Reads every available shell history and returns the `limit` most recent distinct
+/// commands. Unreadable sources are skipped with a warning.
+pub fn read_shell_history(limit: usize) -> Vec<ShellCommand> {
+    let mut commands = Vec::new();
+    for source in history_sources() {
+        let file_name = source.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let parsed = match file_name.as_str() {
+            "history.db" => read_atuin_history(&source).unwrap_or_else(|e| {
+                eprintln!("Warning: Could not read Atuin history {}: {}", source.display(), e);
+                Vec::new()
+            }),
+            ".zsh_history" => read_history_file(&source).map(|c| parse_zsh_history(&c)).unwrap_or_default(),
+            ".bash_history" => read_history_file(&source).map(|c| parse_bash_history(&c)).unwrap_or_default(),
+            _ => read_history_file(&source).map(|c| parse_fish_history(&c)).unwrap_or_default(),
+        };
+        commands.extend(parsed);
+    }
+    deduplicate(commands, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_histories() {
+        let zsh = parse_zsh_history(": 1700000000:0;ffmpeg -ss 10 -i in.mp4 out.mp4\n: 1700000001:0;echo a \\\nb\nls");
+        assert_eq!(zsh.len(), 3);
+        assert_eq!(zsh[0].command, "ffmpeg -ss 10 -i in.mp4 out.mp4");
+        assert_eq!(zsh[0].executed_at, Some(from_unix_secs(1_700_000_000)));
+        assert_eq!(zsh[1].command, "echo a \\\nb");
+
+        let bash = parse_bash_history("#1700000000\ngit status\nls");
+        assert_eq!(bash[0].executed_at, Some(from_unix_secs(1_700_000_000)));
+        assert_eq!(bash[1].executed_at, None);
+
+        let fish = parse_fish_history("- cmd: echo one\\ntwo\n  when: 1700000000\n  paths:\n    - two\n");
+        assert_eq!(fish[0].command, "echo one\ntwo");
+        assert_eq!(fish[0].executed_at, Some(from_unix_secs(1_700_000_000)));
+    }
+
+    #[test]
+    fn test_deduplicate_keeps_latest_and_drops_secrets() {
+        let commands = parse_bash_history("#100\nls\n#200\nexport API_TOKEN=abc\n#300\nls\n#50\ngit log");
+        let commands = deduplicate(commands, 10);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].command, "ls");
+        assert_eq!(commands[0].executed_at, Some(from_unix_secs(300)));
+        assert_eq!(commands[1].command, "git log");
+    }
+}
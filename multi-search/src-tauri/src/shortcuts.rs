@@ -0,0 +1,219 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Shortcut that shows and hides the launcher until the user picks another.
+pub const DEFAULT_LAUNCHER_SHORTCUT: &str = "CmdOrCtrl+Shift+Space";
+/// Shortcut that searches for the text selected in the frontmost app, until the user picks another.
+pub const DEFAULT_SELECTION_SHORTCUT: &str = "CmdOrCtrl+Alt+KeyF";
+
+/// Shortcuts the OS keeps for itself, with what they do, so the UI can say why one is refused.
+const MACOS_RESERVED: &[(&str, &str)] = &[
+    ("CmdOrCtrl+Space", "Spotlight"),
+    ("Ctrl+Space", "switching input sources"),
+    ("CmdOrCtrl+Tab", "switching apps"),
+    ("CmdOrCtrl+KeyQ", "quitting the frontmost app"),
+    ("CmdOrCtrl+KeyH", "hiding the frontmost app"),
+    ("CmdOrCtrl+Alt+Escape", "Force Quit"),
+    ("CmdOrCtrl+Shift+Digit3", "screenshots"),
+    ("CmdOrCtrl+Shift+Digit4", "screenshots"),
+    ("CmdOrCtrl+Shift+Digit5", "screenshots"),
+];
+const DESKTOP_RESERVED: &[(&str, &str)] = &[
+    ("Alt+Tab", "switching windows"),
+    ("Alt+F4", "closing windows"),
+    ("CmdOrCtrl+Alt+Delete", "the security screen"),
+    ("CmdOrCtrl+Shift+Escape", "the task manager"),
+    ("Super+KeyL", "locking the screen"),
+    ("Super+KeyD", "showing the desktop"),
+    ("Super+Tab", "switching windows"),
+];
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A key chord as the launcher's keydown handler saw it. `code` is the physical key
+/// (`KeyboardEvent.code`, e.g. "KeyQ"), `key` what the user's layout types on it
+/// (`KeyboardEvent.key`, e.g. "a" on AZERTY).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RawKeyChord {
+    pub code: String,
+    #[serde(default)]
+    pub key: String,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub meta: bool,
+}
+
+/// A recorded shortcut, ready to be saved as the new hotkey unless it has conflicts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapturedShortcut {
+    /// Accelerator to register, built from physical keys so it fires on any layout.
+    pub accelerator: String,
+    /// The chord as the user's keyboard labels it, e.g. "⇧⌘A".
+    pub label: String,
+    /// Why the shortcut can't or shouldn't be used; empty when it is free.
+    pub conflicts: Vec<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns true for the key codes a global shortcut can be bound to.
+fn is_bindable_code(code: &str) -> bool {
+    const NAMED: &[&str] = &[
+        "Space", "Enter", "Tab", "Backspace", "Escape", "Delete", "Insert", "Home", "End",
+        "PageUp", "PageDown", "ArrowUp", "ArrowDown", "ArrowLeft", "ArrowRight",
+        "Minus", "Equal", "BracketLeft", "BracketRight", "Backslash", "Semicolon",
+        "Quote", "Backquote", "Comma", "Period", "Slash",
+    ];
+    let suffix_in = |prefix: &str, valid: fn(&str) -> bool| code.strip_prefix(prefix).is_some_and(valid);
+    NAMED.contains(&code)
+        || suffix_in("Key", |rest| rest.len() == 1 && rest.chars().all(|c| c.is_ascii_uppercase()))
+        || suffix_in("Digit", |rest| rest.len() == 1 && rest.chars().all(|c| c.is_ascii_digit()))
+        || suffix_in("Numpad", |rest| rest.len() == 1 && rest.chars().all(|c| c.is_ascii_digit()))
+        || is_function_key(code)
+}
+
+/// Returns true for F1 to F24, which may be bound without a modifier.
+fn is_function_key(code: &str) -> bool {
+    code.strip_prefix('F')
+        .and_then(|number| number.parse::<u8>().ok())
+        .is_some_and(|number| (1..=24).contains(&number))
+}
+
+/// Names a key the way its keycap does: the character the layout types on it when it
+/// types one, otherwise a name derived from the physical code.
+fn key_label(chord: &RawKeyChord, mac: bool) -> String {
+    // Option changes what a key types on macOS ("å" for A), so fall back to the code there
+    let mut chars = chord.key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        let altered = mac && chord.alt && !c.is_ascii();
+        if !(c.is_whitespace() || c.is_control() || altered) {
+            return c.to_uppercase().collect();
+        }
+    }
+    match chord.code.as_str() {
+        "ArrowUp" => "↑".to_string(),
+        "ArrowDown" => "↓".to_string(),
+        "ArrowLeft" => "←".to_string(),
+        "ArrowRight" => "→".to_string(),
+        code => code.strip_prefix("Key")
+            .or_else(|| code.strip_prefix("Digit"))
+            .unwrap_or(code)
+            .to_string(),
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Turns a recorded chord into an accelerator string and a label for the user's layout,
+/// and lists conflicts with shortcuts the OS reserves or the app already uses, including
+/// the current `launcher_shortcut`. On macOS Command maps to `CmdOrCtrl`, elsewhere
+/// Control does, so saved shortcuts stay portable.
+pub fn capture_shortcut(chord: &RawKeyChord, mac: bool, launcher_shortcut: &str) -> Result<CapturedShortcut> {
+    if !is_bindable_code(&chord.code) {
+        return Err(anyhow::anyhow!("'{}' can't be used in a shortcut; press a letter, digit or F-key", chord.code));
+    }
+    let primary = if mac { chord.meta } else { chord.ctrl };
+    let secondary_ctrl = mac && chord.ctrl;
+    let secondary_super = !mac && chord.meta;
+    if !(primary || secondary_ctrl || secondary_super || chord.alt || is_function_key(&chord.code)) {
+        return Err(anyhow::anyhow!("Add a modifier such as {} so the shortcut doesn't clash with typing", if mac { "⌘" } else { "Ctrl" }));
+    }
+
+    // 1. Build the accelerator in a fixed modifier order so equal chords compare equal.
+    let mut parts = Vec::new();
+    if primary {
+        parts.push("CmdOrCtrl");
+    }
+    if secondary_ctrl {
+        parts.push("Ctrl");
+    }
+    if secondary_super {
+        parts.push("Super");
+    }
+    if chord.alt {
+        parts.push("Alt");
+    }
+    if chord.shift {
+        parts.push("Shift");
+    }
+    parts.push(&chord.code);
+    let accelerator = parts.join("+");
+
+    // 2. Label it the way the platform writes shortcuts.
+    let mut label = String::new();
+    if mac {
+        for (held, symbol) in [(chord.ctrl, "⌃"), (chord.alt, "⌥"), (chord.shift, "⇧"), (chord.meta, "⌘")] {
+            if held {
+                label.push_str(symbol);
+            }
+        }
+    } else {
+        for (held, name) in [(chord.ctrl, "Ctrl+"), (chord.meta, "Win+"), (chord.alt, "Alt+"), (chord.shift, "Shift+")] {
+            if held {
+                label.push_str(name);
+            }
+        }
+    }
+    label.push_str(&key_label(chord, mac));
+
+    // 3. Check it against what the OS and the app already use.
+    let reserved = if mac { MACOS_RESERVED } else { DESKTOP_RESERVED };
+    let mut conflicts: Vec<String> = reserved.iter()
+        .filter(|(taken, _)| *taken == accelerator)
+        .map(|(_, purpose)| format!("Reserved by the system for {}", purpose))
+        .collect();
+    if accelerator == launcher_shortcut {
+        conflicts.push("Already opens the launcher".to_string());
+    }
+
+    Ok(CapturedShortcut { accelerator, label, conflicts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(code: &str, key: &str) -> RawKeyChord {
+        RawKeyChord { code: code.to_string(), key: key.to_string(), ..RawKeyChord::default() }
+    }
+
+    #[test]
+    fn test_capture_shortcut() {
+        // AZERTY: the key labelled A sits where QWERTY has Q
+        let azerty = RawKeyChord { meta: true, shift: true, ..chord("KeyQ", "A") };
+        let captured = capture_shortcut(&azerty, true, DEFAULT_LAUNCHER_SHORTCUT).unwrap();
+        assert_eq!(captured.accelerator, "CmdOrCtrl+Shift+KeyQ");
+        assert_eq!(captured.label, "⇧⌘A");
+        assert!(captured.conflicts.is_empty());
+
+        let windows = RawKeyChord { ctrl: true, alt: true, ..chord("Digit1", "&") };
+        let captured = capture_shortcut(&windows, false, DEFAULT_LAUNCHER_SHORTCUT).unwrap();
+        assert_eq!(captured.accelerator, "CmdOrCtrl+Alt+Digit1");
+        assert_eq!(captured.label, "Ctrl+Alt+&");
+
+        let spotlight = RawKeyChord { meta: true, ..chord("Space", " ") };
+        let captured = capture_shortcut(&spotlight, true, DEFAULT_LAUNCHER_SHORTCUT).unwrap();
+        assert_eq!(captured.label, "⌘Space");
+        assert_eq!(captured.conflicts, vec!["Reserved by the system for Spotlight".to_string()]);
+        let captured = capture_shortcut(&azerty, true, "CmdOrCtrl+Shift+KeyQ").unwrap();
+        assert_eq!(captured.conflicts, vec!["Already opens the launcher".to_string()]);
+
+        assert!(capture_shortcut(&chord("KeyK", "k"), false, DEFAULT_LAUNCHER_SHORTCUT).is_err());
+        assert!(capture_shortcut(&RawKeyChord { ctrl: true, ..chord("ControlLeft", "Control") }, false, DEFAULT_LAUNCHER_SHORTCUT).is_err());
+        assert_eq!(capture_shortcut(&chord("F5", "F5"), false, DEFAULT_LAUNCHER_SHORTCUT).unwrap().accelerator, "F5");
+    }
+}
@@ -0,0 +1,310 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::disk_space::{min_free_bytes, DiskGuard};
+use crate::fs_paths::long_path;
+use crate::settings::{app_data_dir, IndexLocations};
+use crate::state_store::now_secs;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Snapshots kept; the oldest is deleted when a new one is taken.
+const MAX_SNAPSHOTS: usize = 3;
+const MANIFEST_FILE: &str = "snapshot.json";
+/// Holds the id of the snapshot to restore at the next launch.
+const PENDING_ROLLBACK_FILE: &str = "pending_rollback";
+/// Holds the app version that last opened the indexes.
+const APP_VERSION_FILE: &str = "app_version";
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A copy of the indexes taken before something risky happened to them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    /// Unix seconds.
+    pub created_at: u64,
+    /// What was about to happen, e.g. "upgrade from 0.1.0 to 0.2.0".
+    pub reason: String,
+    pub app_version: String,
+    /// Where each index lived, by name, so a rollback puts it back there. Indexes that
+    /// didn't exist yet are listed too, and removed by a rollback.
+    pub directories: BTreeMap<String, PathBuf>,
+}
+
+/// Snapshots of the keyword index and vector stores, one folder each.
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Recreates a directory tree at `to` out of hard links to the files in `from`. Tantivy
+/// and Lance write new files rather than changing existing ones in place, so a linked
+/// file can't change under a snapshot. Fails across volumes, where links can't be made.
+fn link_tree(from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir_all(long_path(to))?;
+    for entry in std::fs::read_dir(long_path(from))? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            link_tree(&entry.path(), &target)?;
+        } else {
+            std::fs::hard_link(entry.path(), long_path(&target))
+                .map_err(|e| anyhow::anyhow!("Could not link {}: {}", entry.path().display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the path next to `path` with `suffix` added to its name, e.g. `vector_store.restore`.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", suffix));
+    path.with_file_name(name)
+}
+
+/// Removes a directory if it exists.
+fn remove_dir_if_exists(path: &Path) -> Result<()> {
+    match std::fs::remove_dir_all(long_path(path)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Decides whether startup is about to do something risky to existing indexes: open
+/// them with a different app version, or move chunks to a newly configured chunk store.
+/// No recorded version means the indexes predate snapshots, so that counts as an upgrade.
+fn startup_snapshot_reason(previous_version: Option<&str>, current_version: &str, chunk_store_pending: bool) -> Option<String> {
+    match previous_version {
+        Some(previous) if previous == current_version => {}
+        Some(previous) => return Some(format!("upgrade from {} to {}", previous, current_version)),
+        None => return Some(format!("upgrade to {}", current_version)),
+    }
+    chunk_store_pending.then(|| "moving chunks to the chunk store".to_string())
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl SnapshotStore {
+    /// Opens the store in the app data directory.
+    pub fn open() -> Result<Self> {
+        Ok(Self::open_at(app_data_dir()?.join("snapshots")))
+    }
+
+    fn open_at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Snapshots the given index directories. Built under a temporary name, so a crash
+    /// midway never leaves a partial snapshot to roll back to. An index on another volume
+    /// than the snapshots is left out with a warning rather than copied in full.
+    pub fn create(&self, directories: &BTreeMap<String, PathBuf>, reason: &str) -> Result<SnapshotInfo> {
+        let created_at = now_secs();
+        let id = (1..)
+            .map(|n| if n == 1 { created_at.to_string() } else { format!("{}-{}", created_at, n) })
+            .find(|id| !self.dir.join(id).exists())
+            .unwrap();
+
+        let tmp_dir = self.dir.join(format!("{}.tmp", id));
+        remove_dir_if_exists(&tmp_dir)?;
+        std::fs::create_dir_all(&tmp_dir)?;
+        let mut covered = BTreeMap::new();
+        for (name, path) in directories {
+            if long_path(path).is_dir() {
+                if let Err(e) = link_tree(path, &tmp_dir.join(name)) {
+                    eprintln!(
+                        "Warning: Not snapshotting {}, which must be on the same volume as {}: {}",
+                        path.display(), self.dir.display(), e
+                    );
+                    remove_dir_if_exists(&tmp_dir.join(name))?;
+                    continue;
+                }
+            }
+            covered.insert(name.clone(), path.clone());
+        }
+        let snapshot = SnapshotInfo {
+            id: id.clone(),
+            created_at,
+            reason: reason.to_string(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            directories: covered,
+        };
+        std::fs::write(tmp_dir.join(MANIFEST_FILE), serde_json::to_string_pretty(&snapshot)?)?;
+        std::fs::rename(&tmp_dir, self.dir.join(&id))?;
+
+        for old in self.list()?.into_iter().skip(MAX_SNAPSHOTS) {
+            std::fs::remove_dir_all(self.dir.join(&old.id))?;
+        }
+        Ok(snapshot)
+    }
+
+    /// Returns the snapshots, newest first.
+    pub fn list(&self) -> Result<Vec<SnapshotInfo>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut snapshots: Vec<SnapshotInfo> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| std::fs::read_to_string(entry.path().join(MANIFEST_FILE)).ok())
+            .filter_map(|contents| serde_json::from_str(&contents).ok())
+            .collect();
+        snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_at));
+        Ok(snapshots)
+    }
+
+    /// Marks a snapshot to be restored at the next launch, before the indexes are opened;
+    /// they can't be swapped out while in use.
+    pub fn request_rollback(&self, id: &str) -> Result<()> {
+        if !self.list()?.iter().any(|snapshot| snapshot.id == id) {
+            return Err(anyhow::anyhow!("No snapshot '{}'", id));
+        }
+        std::fs::write(self.dir.join(PENDING_ROLLBACK_FILE), id)?;
+        Ok(())
+    }
+
+    /// Restores the snapshot marked by `request_rollback`, if any, replacing the current
+    /// indexes. Each index is rebuilt beside the live one and swapped in by renames, and
+    /// the mark is cleared last, so a crash midway is finished at the next launch rather
+    /// than leaving no index. The snapshot is kept, so the same rollback can be repeated.
+    pub fn apply_pending_rollback(&self) -> Result<Option<SnapshotInfo>> {
+        let pending = self.dir.join(PENDING_ROLLBACK_FILE);
+        let id = match std::fs::read_to_string(&pending) {
+            Ok(id) => id.trim().to_string(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let snapshot_dir = self.dir.join(&id);
+        let snapshot: SnapshotInfo = serde_json::from_str(&std::fs::read_to_string(snapshot_dir.join(MANIFEST_FILE))?)?;
+        for (name, path) in &snapshot.directories {
+            let (staging, replaced) = (sibling(path, "restore"), sibling(path, "replaced"));
+            remove_dir_if_exists(&staging)?;
+            let restore = snapshot_dir.join(name).is_dir();
+            if restore {
+                link_tree(&snapshot_dir.join(name), &staging)?;
+            }
+            // The live index moves aside, rather than being deleted, until its replacement is in place
+            if long_path(path).exists() {
+                remove_dir_if_exists(&replaced)?;
+                std::fs::rename(long_path(path), long_path(&replaced))?;
+            }
+            if restore {
+                std::fs::rename(long_path(&staging), long_path(path))?;
+            }
+            remove_dir_if_exists(&replaced)?;
+        }
+        std::fs::remove_file(&pending)?;
+        Ok(Some(snapshot))
+    }
+
+    /// Returns the app version that last opened the indexes, if recorded.
+    fn previous_app_version(&self) -> Option<String> {
+        std::fs::read_to_string(self.dir.join(APP_VERSION_FILE))
+            .ok()
+            .map(|version| version.trim().to_string())
+    }
+
+    fn record_app_version(&self, version: &str) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join(APP_VERSION_FILE), version)?;
+        Ok(())
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns the directories a snapshot covers, by name.
+pub fn index_directories(locations: &IndexLocations) -> Result<BTreeMap<String, PathBuf>> {
+    let mut directories = BTreeMap::new();
+    directories.insert("keyword_index".to_string(), locations.keyword_index_dir()?);
+    directories.insert("vector_store".to_string(), locations.vector_store_dir()?);
+    if let Some(chunk_store) = &locations.chunk_store {
+        directories.insert("chunk_store".to_string(), chunk_store.clone());
+    }
+    Ok(directories)
+}
+
+/// Gets the indexes ready to be opened at startup: restores a snapshot the user rolled
+/// back to, then snapshots the indexes if this launch may change them. No snapshot is
+/// taken while the data volumes have less than `min_free_mb` free. Returns the snapshot
+/// that was restored, if any.
+pub fn prepare_indexes(locations: &IndexLocations, min_free_mb: Option<u64>) -> Result<Option<SnapshotInfo>> {
+    let store = SnapshotStore::open()?;
+    // 1. Put back a snapshot the user rolled back to, while nothing has the indexes open.
+    let restored = store.apply_pending_rollback()?;
+
+    // 2. Snapshot existing indexes before an upgrade or migration touches them.
+    let directories = index_directories(locations)?;
+    let current_version = env!("CARGO_PKG_VERSION");
+    let chunk_store_pending = locations.chunk_store.as_ref().is_some_and(|dir| !long_path(dir).exists());
+    let reason = startup_snapshot_reason(store.previous_app_version().as_deref(), current_version, chunk_store_pending);
+    if let Some(reason) = reason {
+        let disk_guard = DiskGuard::new(locations.data_volume_dirs()?, min_free_bytes(min_free_mb));
+        if disk_guard.check().paused {
+            eprintln!("Warning: Not snapshotting the indexes before {}; the data volume is nearly full", reason);
+        } else if directories.values().any(|dir| long_path(dir).is_dir()) {
+            store.create(&directories, &reason)?;
+        }
+    }
+    store.record_app_version(current_version)?;
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_startup_snapshot_reason() {
+        assert_eq!(startup_snapshot_reason(Some("0.2.0"), "0.2.0", false), None);
+        assert_eq!(startup_snapshot_reason(Some("0.1.0"), "0.2.0", false).as_deref(), Some("upgrade from 0.1.0 to 0.2.0"));
+        assert_eq!(startup_snapshot_reason(None, "0.2.0", false).as_deref(), Some("upgrade to 0.2.0"));
+        assert_eq!(startup_snapshot_reason(Some("0.2.0"), "0.2.0", true).as_deref(), Some("moving chunks to the chunk store"));
+    }
+
+    #[test]
+    fn test_snapshot_and_rollback() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let store = SnapshotStore::open_at(dir.join("snapshots"));
+        let index = dir.join("keyword_index");
+        let chunks = dir.join("chunk_store");
+        std::fs::create_dir_all(index.join("segments")).unwrap();
+        std::fs::write(index.join("meta.json"), "v1").unwrap();
+        std::fs::write(index.join("segments/a.idx"), "segment a").unwrap();
+        let directories = BTreeMap::from([
+            ("keyword_index".to_string(), index.clone()),
+            ("chunk_store".to_string(), chunks.clone()),
+        ]);
+
+        let snapshot = store.create(&directories, "upgrade to 0.2.0").unwrap();
+        assert_eq!(store.list().unwrap(), vec![snapshot.clone()]);
+
+        // The upgrade rewrites the index and creates the chunk store
+        std::fs::write(index.join("meta.json.tmp"), "v2").unwrap();
+        std::fs::rename(index.join("meta.json.tmp"), index.join("meta.json")).unwrap();
+        std::fs::remove_file(index.join("segments/a.idx")).unwrap();
+        std::fs::create_dir_all(&chunks).unwrap();
+
+        assert_eq!(store.apply_pending_rollback().unwrap(), None);
+        assert!(store.request_rollback("missing").is_err());
+        store.request_rollback(&snapshot.id).unwrap();
+        assert_eq!(store.apply_pending_rollback().unwrap(), Some(snapshot));
+        assert_eq!(std::fs::read_to_string(index.join("meta.json")).unwrap(), "v1");
+        assert_eq!(std::fs::read_to_string(index.join("segments/a.idx")).unwrap(), "segment a");
+        assert!(!chunks.exists());
+        assert_eq!(store.apply_pending_rollback().unwrap(), None);
+    }
+}
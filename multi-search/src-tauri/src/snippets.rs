@@ -0,0 +1,208 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::search_orchestrator::RawDocument;
+use crate::settings::app_data_dir;
+use crate::state_store::now_secs;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Source type of the user's saved snippets.
+pub const SNIPPET_SOURCE: &str = "snippet";
+/// Prefix of snippet document paths; the snippet id follows it.
+const SNIPPET_PATH_PREFIX: &str = "snippet://";
+/// Longest title derived from a snippet's first line.
+const MAX_DERIVED_TITLE_CHARS: usize = 60;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A piece of text the user saved to paste again later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    /// Unix timestamps (seconds).
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl Snippet {
+    /// Returns the path the snippet is indexed under.
+    pub fn path(&self) -> String {
+        format!("{}{}", SNIPPET_PATH_PREFIX, self.id)
+    }
+
+    /// Converts the snippet into a document so it is searched like everything else.
+    pub fn to_raw_document(&self) -> RawDocument {
+        RawDocument {
+            path: self.path(),
+            title: self.title.clone(),
+            body: self.body.clone(),
+            summary: None,
+            source_type: SNIPPET_SOURCE.to_string(),
+            author: None,
+            modified_date: UNIX_EPOCH + Duration::from_secs(self.updated_at),
+            metadata: BTreeMap::new(),
+        }
+    }
+}
+
+/// Everything persisted in the snippets file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SnippetData {
+    next_id: u64,
+    snippets: HashMap<u64, Snippet>,
+}
+
+/// The user's snippet library, stored as JSON next to the indexes. The file is the
+/// source of truth; the indexes only hold a searchable copy.
+pub struct SnippetStore {
+    path: PathBuf,
+    data: Mutex<SnippetData>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Derives a title from the first non-empty line when the user didn't give one.
+fn derive_title(body: &str) -> String {
+    let first_line = body.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("Untitled snippet");
+    match first_line.char_indices().nth(MAX_DERIVED_TITLE_CHARS) {
+        Some((end, _)) => format!("{}…", &first_line[..end]),
+        None => first_line.to_string(),
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Returns the snippet id encoded in a document path, or `None` for other documents.
+pub fn snippet_id(path: &str) -> Option<u64> {
+    path.strip_prefix(SNIPPET_PATH_PREFIX)?.parse().ok()
+}
+
+/// Pastes the clipboard into the frontmost app by sending Cmd+V. Returns false where
+/// pasting isn't supported, leaving the snippet on the clipboard for the user to paste.
+#[cfg(target_os = "macos")]
+pub fn simulate_paste() -> bool {
+    std::process::Command::new("osascript")
+        .args(["-e", "tell application \"System Events\" to keystroke \"v\" using command down"])
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn simulate_paste() -> bool {
+    false
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl SnippetStore {
+    /// Opens the snippets file in the app data directory, starting empty if it doesn't exist.
+    pub fn open() -> Result<Self> {
+        let path = app_data_dir()?.join("snippets.json");
+        let data = if path.exists() {
+            let contents = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Failed to parse snippets file {}: {}", path.display(), e))?
+        } else {
+            SnippetData::default()
+        };
+
+        Ok(Self {
+            path,
+            data: Mutex::new(data),
+        })
+    }
+
+    /// Writes the snippets via a temporary file so a crash never truncates them.
+    fn persist(&self, data: &SnippetData) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(data)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Returns all snippets, most recently updated first.
+    pub fn list(&self) -> Vec<Snippet> {
+        let mut snippets: Vec<Snippet> = self.data.lock().unwrap().snippets.values().cloned().collect();
+        snippets.sort_by_key(|snippet| std::cmp::Reverse(snippet.updated_at));
+        snippets
+    }
+
+    /// Returns one snippet by id.
+    pub fn get(&self, id: u64) -> Option<Snippet> {
+        self.data.lock().unwrap().snippets.get(&id).cloned()
+    }
+
+    /// Saves a new snippet. Without a title, the first line of the body is used.
+    pub fn create(&self, title: Option<String>, body: String) -> Result<Snippet> {
+        if body.trim().is_empty() {
+            return Err(anyhow::anyhow!("A snippet needs some text"));
+        }
+        let mut data = self.data.lock().unwrap();
+        data.next_id += 1;
+        let now = now_secs();
+        let snippet = Snippet {
+            id: data.next_id,
+            title: title.filter(|title| !title.trim().is_empty()).unwrap_or_else(|| derive_title(&body)),
+            body,
+            created_at: now,
+            updated_at: now,
+        };
+        data.snippets.insert(snippet.id, snippet.clone());
+        self.persist(&data)?;
+        Ok(snippet)
+    }
+
+    /// Replaces a snippet's title and body.
+    pub fn update(&self, id: u64, title: Option<String>, body: String) -> Result<Snippet> {
+        let mut data = self.data.lock().unwrap();
+        let snippet = data.snippets.get_mut(&id).ok_or_else(|| anyhow::anyhow!("Snippet {} not found", id))?;
+        snippet.title = title.filter(|title| !title.trim().is_empty()).unwrap_or_else(|| derive_title(&body));
+        snippet.body = body;
+        snippet.updated_at = now_secs();
+        let snippet = snippet.clone();
+        self.persist(&data)?;
+        Ok(snippet)
+    }
+
+    /// Deletes a snippet, returning it if it existed.
+    pub fn delete(&self, id: u64) -> Result<Option<Snippet>> {
+        let mut data = self.data.lock().unwrap();
+        let removed = data.snippets.remove(&id);
+        if removed.is_some() {
+            self.persist(&data)?;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_titles_and_paths() {
+        assert_eq!(derive_title("\n  git rebase -i HEAD~3\nmore"), "git rebase -i HEAD~3");
+        assert_eq!(derive_title(&"x".repeat(100)), format!("{}…", "x".repeat(60)));
+        assert_eq!(snippet_id("snippet://42"), Some(42));
+        assert_eq!(snippet_id("/home/me/snippet.txt"), None);
+    }
+}
@@ -0,0 +1,267 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::file_usage::FileUsage;
+use crate::settings::app_data_dir;
+use crate::topics::Topic;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Per-document state that isn't part of either index, such as usage history.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocumentState {
+    /// Unix timestamp (seconds) of the last time the user opened this document from a result.
+    pub last_opened: Option<u64>,
+    pub open_count: u64,
+    /// True when the document's chunk embeddings were dropped to save space.
+    pub chunks_pruned: bool,
+    /// True when the document was indexed in keyword-only mode and has no embeddings yet.
+    pub embeddings_missing: bool,
+    /// True when the user hid the document; it stays out of the index until re-indexed by hand.
+    pub excluded: bool,
+    /// Unix timestamp (seconds) of a soft delete. The document stays in both indexes,
+    /// hidden from results, until the undo window has passed.
+    pub deleted_at: Option<u64>,
+    /// Opens recorded by the OS (Spotlight or the access time) as of the last indexing,
+    /// which include opens from other apps.
+    pub os_last_used: Option<u64>,
+    pub os_use_count: u64,
+    /// Labels the user gave the document, e.g. with a bulk action.
+    pub tags: Vec<String>,
+}
+
+/// A soft-deleted document that can still be restored.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingDeletion {
+    pub path: String,
+    /// Unix timestamps (seconds).
+    pub deleted_at: u64,
+    pub purge_at: u64,
+}
+
+/// Everything persisted in the state file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StateData {
+    documents: HashMap<String, DocumentState>,
+    /// Topics from the last clustering run, largest first.
+    topics: Vec<Topic>,
+    /// Unix timestamp (seconds) of the last clustering run.
+    topics_clustered_at: Option<u64>,
+}
+
+/// A small JSON-backed store for app state that lives alongside the indexes.
+/// Every mutation is written through to disk so state survives crashes.
+pub struct StateStore {
+    path: PathBuf,
+    data: Mutex<StateData>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+impl DocumentState {
+    /// Unix timestamp (seconds) of the latest open seen by the app or the OS.
+    pub fn last_used(&self) -> Option<u64> {
+        self.last_opened.max(self.os_last_used)
+    }
+
+    /// How often the document was opened. The OS count usually includes opens from the
+    /// app's results, so the larger count is used rather than the sum.
+    pub fn use_count(&self) -> u64 {
+        self.open_count.max(self.os_use_count)
+    }
+}
+
+/// Returns the current time as Unix seconds.
+pub fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl StateStore {
+    /// Opens the state file in the app data directory, starting empty if it doesn't exist.
+    pub fn open() -> Result<Self> {
+        let path = app_data_dir()?.join("state.json");
+        let data = if path.exists() {
+            let contents = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Failed to parse state file {}: {}", path.display(), e))?
+        } else {
+            StateData::default()
+        };
+
+        Ok(Self {
+            path,
+            data: Mutex::new(data),
+        })
+    }
+
+    /// Writes the state to disk via a temporary file so a crash never truncates it.
+    fn persist(&self, data: &StateData) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(data)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Applies a change to one document's state and persists the result.
+    fn update_document<F: FnOnce(&mut DocumentState)>(&self, path: &str, update: F) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        update(data.documents.entry(path.to_string()).or_default());
+        self.persist(&data)
+    }
+
+    /// Returns the stored state for a document, if any.
+    pub fn document(&self, path: &str) -> Option<DocumentState> {
+        self.data.lock().unwrap().documents.get(path).cloned()
+    }
+
+    /// Records that the user opened a document from the results list.
+    pub fn record_open(&self, path: &str) -> Result<()> {
+        self.update_document(path, |state| {
+            state.last_opened = Some(now_secs());
+            state.open_count += 1;
+        })
+    }
+
+    /// Stores the OS's record of opens, read when the document was last indexed.
+    pub fn set_os_usage(&self, path: &str, usage: FileUsage) -> Result<()> {
+        self.update_document(path, |state| {
+            state.os_last_used = usage.last_used;
+            state.os_use_count = usage.use_count;
+        })
+    }
+
+    /// Adds a tag to several documents with a single write.
+    pub fn add_tag(&self, paths: &[String], tag: &str) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        for path in paths {
+            let state = data.documents.entry(path.clone()).or_default();
+            if !state.tags.iter().any(|existing| existing == tag) {
+                state.tags.push(tag.to_string());
+            }
+        }
+        self.persist(&data)
+    }
+
+    /// Carries a document's state over to its new path after it was moved.
+    pub fn rename_document(&self, from: &str, to: &str) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        if let Some(state) = data.documents.remove(from) {
+            data.documents.insert(to.to_string(), state);
+            self.persist(&data)?;
+        }
+        Ok(())
+    }
+
+    /// Marks whether a document's chunk embeddings have been pruned.
+    pub fn set_chunks_pruned(&self, path: &str, pruned: bool) -> Result<()> {
+        self.update_document(path, |state| state.chunks_pruned = pruned)
+    }
+
+    /// Marks whether a document was indexed without embeddings, in keyword-only mode.
+    pub fn set_embeddings_missing(&self, path: &str, missing: bool) -> Result<()> {
+        self.update_document(path, |state| state.embeddings_missing = missing)
+    }
+
+    /// Lists the documents indexed in keyword-only mode that still lack embeddings.
+    pub fn documents_missing_embeddings(&self) -> Vec<String> {
+        let data = self.data.lock().unwrap();
+        data.documents.iter()
+            .filter(|(_, state)| state.embeddings_missing)
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Marks whether the user has excluded a document from the index. Lifting an
+    /// exclusion also cancels its pending deletion.
+    pub fn set_excluded(&self, path: &str, excluded: bool) -> Result<()> {
+        self.update_document(path, |state| {
+            state.excluded = excluded;
+            if !excluded {
+                state.deleted_at = None;
+            }
+        })
+    }
+
+    /// Excludes a document and starts its undo window instead of deleting it right away.
+    pub fn soft_delete(&self, path: &str) -> Result<()> {
+        self.update_document(path, |state| {
+            state.excluded = true;
+            state.deleted_at = Some(now_secs());
+        })
+    }
+
+    /// Ends a document's undo window once it is gone from the indexes; it stays excluded.
+    pub fn clear_deleted(&self, path: &str) -> Result<()> {
+        self.update_document(path, |state| state.deleted_at = None)
+    }
+
+    /// Returns true if the document is soft-deleted and should be hidden from results.
+    pub fn is_deleted(&self, path: &str) -> bool {
+        self.document(path).is_some_and(|state| state.deleted_at.is_some())
+    }
+
+    /// Returns the soft-deleted documents, oldest first, given the undo window in seconds.
+    pub fn pending_deletions(&self, undo_window_secs: u64) -> Vec<PendingDeletion> {
+        let data = self.data.lock().unwrap();
+        let mut pending: Vec<PendingDeletion> = data.documents.iter()
+            .filter_map(|(path, state)| state.deleted_at.map(|deleted_at| PendingDeletion {
+                path: path.clone(),
+                deleted_at,
+                purge_at: deleted_at + undo_window_secs,
+            }))
+            .collect();
+        pending.sort_by_key(|deletion| deletion.deleted_at);
+        pending
+    }
+
+    /// Returns true if the user excluded this document.
+    pub fn is_excluded(&self, path: &str) -> bool {
+        self.document(path).is_some_and(|state| state.excluded)
+    }
+
+    /// Replaces the stored topics with a new clustering run.
+    pub fn set_topics(&self, topics: Vec<Topic>) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        data.topics = topics;
+        data.topics_clustered_at = Some(now_secs());
+        self.persist(&data)
+    }
+
+    /// Returns the topics from the last clustering run.
+    pub fn topics(&self) -> Vec<Topic> {
+        self.data.lock().unwrap().topics.clone()
+    }
+
+    /// Returns when topics were last clustered, if ever.
+    pub fn topics_clustered_at(&self) -> Option<u64> {
+        self.data.lock().unwrap().topics_clustered_at
+    }
+
+    /// Removes all state for a document, e.g. after it is deleted from the index.
+    pub fn remove_document(&self, path: &str) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        if data.documents.remove(path).is_some() {
+            self.persist(&data)?;
+        }
+        Ok(())
+    }
+}
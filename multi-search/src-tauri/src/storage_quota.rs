@@ -0,0 +1,91 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use std::cmp::Ordering;
+
+/// Rough on-disk cost of one chunk row: a 384-dim f32 vector plus ~1KB of text and metadata.
+pub const ESTIMATED_CHUNK_ROW_BYTES: u64 = 384 * 4 + 1024;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A document whose chunk embeddings could be dropped to save space.
+#[derive(Debug, Clone)]
+pub struct EvictionCandidate {
+    pub path: String,
+    pub chunk_count: usize,
+    /// Unix seconds of the last time the user opened it, `None` if never opened.
+    pub last_opened: Option<u64>,
+    /// Unix seconds of the document's last modification.
+    pub modified: u64,
+}
+
+/// Summary of a quota enforcement run, returned to the UI.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EvictionReport {
+    pub quota_bytes: u64,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub documents_pruned: usize,
+    pub chunks_removed: usize,
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Orders candidates by eviction priority: never-opened documents first, then by
+/// oldest activity (last open or modification, whichever is newer). Returns the
+/// documents whose chunks should be dropped to free roughly `bytes_to_free`.
+pub fn plan_eviction(mut candidates: Vec<EvictionCandidate>, bytes_to_free: u64) -> Vec<EvictionCandidate> {
+    candidates.sort_by(|a, b| match (a.last_opened, b.last_opened) {
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        _ => {
+            let a_activity = a.last_opened.unwrap_or(0).max(a.modified);
+            let b_activity = b.last_opened.unwrap_or(0).max(b.modified);
+            a_activity.cmp(&b_activity)
+        }
+    });
+
+    let mut freed = 0u64;
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.chunk_count > 0)
+        .take_while(|candidate| {
+            let keep_going = freed < bytes_to_free;
+            freed += candidate.chunk_count as u64 * ESTIMATED_CHUNK_ROW_BYTES;
+            keep_going
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(path: &str, last_opened: Option<u64>, modified: u64) -> EvictionCandidate {
+        EvictionCandidate { path: path.to_string(), chunk_count: 10, last_opened, modified }
+    }
+
+    #[test]
+    fn test_plan_eviction_prefers_never_opened_and_oldest() {
+        let candidates = vec![
+            candidate("opened-recently", Some(900), 100),
+            candidate("never-opened-new", None, 800),
+            candidate("never-opened-old", None, 100),
+            candidate("opened-long-ago", Some(200), 100),
+        ];
+
+        let plan = plan_eviction(candidates, 10 * ESTIMATED_CHUNK_ROW_BYTES * 3);
+        let paths: Vec<&str> = plan.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["never-opened-old", "never-opened-new", "opened-long-ago"]);
+    }
+
+    #[test]
+    fn test_plan_eviction_nothing_to_free() {
+        let plan = plan_eviction(vec![candidate("a", None, 0)], 0);
+        assert!(plan.is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Sentences of a document per summary sentence, before the budget's bounds apply.
+const SENTENCES_PER_SUMMARY_SENTENCE: usize = 3;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// How many sentences the extractive summary of a document may keep. Longer documents
+/// get more, within these bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SummaryBudget {
+    pub min_sentences: usize,
+    pub max_sentences: usize,
+}
+
+impl Default for SummaryBudget {
+    fn default() -> Self {
+        Self {
+            min_sentences: 3,
+            max_sentences: 5,
+        }
+    }
+}
+
+/// Summary budgets by connector, with user overrides from settings. Keys are a source
+/// type ("gmail") or, for files, a source type and extension ("file:pdf").
+pub struct SummaryBudgets {
+    overrides: HashMap<String, SummaryBudget>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Built-in budgets: a line or two for messages, a paragraph for papers.
+fn default_budget(key: &str) -> Option<SummaryBudget> {
+    let (min_sentences, max_sentences) = match key {
+        "gmail" | "email" | "slack" | "file:eml" | "file:msg" | "shell" | "snippet" => (1, 2),
+        "kindle" | "readwise" => (1, 3),
+        "file:pdf" | "zotero" => (5, 8),
+        _ => return None,
+    };
+    Some(SummaryBudget { min_sentences, max_sentences })
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl SummaryBudget {
+    /// Returns how many of a document's sentences to keep in its summary.
+    pub fn sentence_count(&self, document_sentences: usize) -> usize {
+        let max = self.max_sentences.max(1);
+        let min = self.min_sentences.clamp(1, max);
+        (document_sentences / SENTENCES_PER_SUMMARY_SENTENCE).clamp(min, max)
+    }
+}
+
+impl SummaryBudgets {
+    pub fn new(overrides: HashMap<String, SummaryBudget>) -> Self {
+        Self { overrides }
+    }
+
+    /// Returns the budget for a document: the most specific override or built-in entry,
+    /// trying "source:extension" before "source", else the default.
+    pub fn for_document(&self, source_type: &str, path: &str) -> SummaryBudget {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| format!("{}:{}", source_type, extension.to_lowercase()));
+        let keys = extension.iter().map(String::as_str).chain(std::iter::once(source_type));
+
+        let mut defaults = None;
+        for key in keys {
+            if let Some(budget) = self.overrides.get(key) {
+                return *budget;
+            }
+            defaults = defaults.or_else(|| default_budget(key));
+        }
+        defaults.unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_by_source_and_length() {
+        let budgets = SummaryBudgets::new(HashMap::from([(
+            "slack".to_string(),
+            SummaryBudget { min_sentences: 1, max_sentences: 1 },
+        )]));
+        let paper = budgets.for_document("file", "/papers/Attention.PDF");
+        assert_eq!(paper, SummaryBudget { min_sentences: 5, max_sentences: 8 });
+        assert_eq!(paper.sentence_count(9), 5);
+        assert_eq!(paper.sentence_count(21), 7);
+        assert_eq!(paper.sentence_count(400), 8);
+
+        assert_eq!(budgets.for_document("gmail", "gmail://msg/1").sentence_count(30), 2);
+        assert_eq!(budgets.for_document("slack", "slack://C1/1").sentence_count(30), 1);
+        assert_eq!(budgets.for_document("file", "/notes/todo.md"), SummaryBudget::default());
+    }
+}
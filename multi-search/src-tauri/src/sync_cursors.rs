@@ -0,0 +1,159 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::settings::app_data_dir;
+use crate::state_store::now_secs;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Where a connector's syncs start from. Values are opaque to the store: a history id,
+/// a delta token or a timestamp, whatever the connector's API pages by.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncCursor {
+    /// Resume point of the last complete sync; the next sync fetches only what changed since.
+    pub committed: Option<String>,
+    /// Progress of a sync that hasn't finished, so it resumes instead of starting over.
+    pub pending: Option<PendingSync>,
+    /// Unix timestamp (seconds) of the last complete sync.
+    pub last_synced_at: Option<u64>,
+}
+
+/// A sync in progress: the page to fetch next, and the cursor to commit once it finishes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingSync {
+    pub page: String,
+    pub commit_to: String,
+}
+
+/// Sync cursors of every connector, stored as JSON next to the indexes. Each update is
+/// written through atomically before it is applied in memory, so a crash leaves either
+/// the old cursor or the new one, never a cursor ahead of what was indexed.
+pub struct CursorStore {
+    path: PathBuf,
+    cursors: Mutex<HashMap<String, SyncCursor>>,
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl CursorStore {
+    /// Opens the cursor file in the app data directory, starting empty if it doesn't exist.
+    pub fn open() -> Result<Self> {
+        Self::open_at(app_data_dir()?.join("sync_cursors.json"))
+    }
+
+    fn open_at(path: PathBuf) -> Result<Self> {
+        let cursors = if path.exists() {
+            let contents = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Failed to parse sync cursor file {}: {}", path.display(), e))?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            path,
+            cursors: Mutex::new(cursors),
+        })
+    }
+
+    /// Writes the cursors via a temporary file so a crash never truncates them.
+    fn persist(&self, cursors: &HashMap<String, SyncCursor>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(cursors)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Applies a change to one connector's cursor, keeping the old one if writing fails.
+    fn update<F: FnOnce(&mut SyncCursor)>(&self, connector: &str, update: F) -> Result<()> {
+        let mut cursors = self.cursors.lock().unwrap();
+        let mut updated = cursors.clone();
+        update(updated.entry(connector.to_string()).or_default());
+        self.persist(&updated)?;
+        *cursors = updated;
+        Ok(())
+    }
+
+    /// Returns a connector's cursor; empty if it has never synced.
+    pub fn get(&self, connector: &str) -> SyncCursor {
+        self.cursors.lock().unwrap().get(connector).cloned().unwrap_or_default()
+    }
+
+    /// Returns every connector's cursor, for the sync dashboard.
+    pub fn all(&self) -> HashMap<String, SyncCursor> {
+        self.cursors.lock().unwrap().clone()
+    }
+
+    /// Records that everything before `next_page` is indexed. Call only after the page's
+    /// documents are written, so a resumed sync never skips any.
+    pub fn checkpoint(&self, connector: &str, next_page: &str, commit_to: &str) -> Result<()> {
+        self.update(connector, |cursor| {
+            cursor.pending = Some(PendingSync {
+                page: next_page.to_string(),
+                commit_to: commit_to.to_string(),
+            });
+        })
+    }
+
+    /// Finishes a sync: the next one starts from `committed`.
+    pub fn complete(&self, connector: &str, committed: &str) -> Result<()> {
+        self.update(connector, |cursor| {
+            cursor.committed = Some(committed.to_string());
+            cursor.pending = None;
+            cursor.last_synced_at = Some(now_secs());
+        })
+    }
+
+    /// Forgets a connector's cursor, so its next sync fetches everything again.
+    pub fn reset(&self, connector: &str) -> Result<()> {
+        let mut cursors = self.cursors.lock().unwrap();
+        if cursors.contains_key(connector) {
+            let mut updated = cursors.clone();
+            updated.remove(connector);
+            self.persist(&updated)?;
+            *cursors = updated;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_survives_reopen_and_completes() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("sync_cursors.json");
+        let store = CursorStore::open_at(path.clone()).unwrap();
+        store.checkpoint("readwise", "page-2", "1700000000").unwrap();
+
+        // An interrupted sync picks up from the checkpoint after a restart
+        let reopened = CursorStore::open_at(path.clone()).unwrap();
+        let cursor = reopened.get("readwise");
+        assert_eq!(cursor.committed, None);
+        assert_eq!(cursor.pending.as_ref().map(|pending| pending.page.as_str()), Some("page-2"));
+
+        reopened.complete("readwise", "1700000000").unwrap();
+        let cursor = reopened.get("readwise");
+        assert_eq!(cursor.committed.as_deref(), Some("1700000000"));
+        assert_eq!(cursor.pending, None);
+        assert!(cursor.last_synced_at.is_some());
+
+        reopened.reset("readwise").unwrap();
+        assert_eq!(CursorStore::open_at(path).unwrap().get("readwise"), SyncCursor::default());
+    }
+}
@@ -0,0 +1,47 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::index_manager::IndexManager;
+use crate::vector_db::VectorDBManager;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Which index a result came from, so the UI can badge the team's documents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultOrigin {
+    #[default]
+    Personal,
+    Team,
+}
+
+/// A keyword index and vector store published by a team, opened read-only. The folder
+/// is laid out like the app data directory, with `keyword_index` and `vector_store`
+/// inside, so publishing one is a matter of copying those two folders.
+pub struct TeamIndex {
+    pub index_manager: Arc<IndexManager>,
+    pub vector_db: Arc<VectorDBManager>,
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl TeamIndex {
+    /// Opens the team's indexes in `dir`. Nothing is created or written there.
+    pub async fn open(dir: &Path, fold_diacritics: bool) -> Result<Self> {
+        let index_manager = IndexManager::open_read_only(&dir.join("keyword_index"), fold_diacritics)
+            .map_err(|e| anyhow::anyhow!("Failed to open the team keyword index in {}: {}", dir.display(), e))?;
+        let vector_db = VectorDBManager::open_read_only(&dir.join("vector_store")).await?;
+        Ok(Self {
+            index_manager: Arc::new(index_manager),
+            vector_db: Arc::new(vector_db),
+        })
+    }
+}
@@ -0,0 +1,200 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use tantivy::tokenizer::{LowerCaser, RemoveLongFilter, SimpleTokenizer, TextAnalyzer, Tokenizer};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Name under which the normalizing analyzer is registered with Tantivy.
+pub const NORMALIZED_TOKENIZER: &str = "multi_search_normalized";
+
+/// Languages where accented letters are distinct letters, so folding "å" to "a"
+/// would merge unrelated words. Folding is off by default for these.
+const DIACRITIC_SIGNIFICANT_LANGUAGES: &[&str] = &[
+    "cs", "da", "et", "fi", "hu", "is", "lt", "lv", "nb", "nn", "no", "pl", "ro", "sk", "sv", "tr", "vi",
+];
+
+/// Invisible characters that would otherwise split a word in two during tokenization.
+const ZERO_WIDTH_CHARS: &[char] = &['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Appends an emoji's searchable words, e.g. "rocket" for 🚀 or "party popper tada" for 🎉.
+fn push_emoji_words(output: &mut String, emoji: &emojis::Emoji) {
+    output.push(' ');
+    output.push_str(emoji.name());
+    if let Some(shortcode) = emoji.shortcode() {
+        if shortcode != emoji.name() {
+            output.push(' ');
+            output.push_str(shortcode);
+        }
+    }
+    output.push(' ');
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Replaces `:shortcode:` sequences (as used by Slack and GitHub) with the emoji's searchable
+/// words. Unknown shortcodes and ordinary colons are left untouched.
+pub fn expand_emoji_shortcodes(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(':') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        if let Some(end) = after.find(':') {
+            let code = &after[..end];
+            let is_shortcode = !code.is_empty()
+                && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+' || c == '-');
+            if let Some(emoji) = is_shortcode.then(|| emojis::get_by_shortcode(code)).flatten() {
+                push_emoji_words(&mut output, emoji);
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+
+        output.push(':');
+        rest = after;
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Maps emoji to their names and strips zero-width characters so symbols become
+/// searchable words instead of noise. Zero-width joiners inside emoji sequences
+/// (e.g. family emoji) are resolved as part of the emoji before stripping.
+pub fn replace_emoji(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+
+    for grapheme in text.graphemes(true) {
+        if !grapheme.is_ascii() {
+            if let Some(emoji) = emojis::get(grapheme) {
+                push_emoji_words(&mut output, emoji);
+                continue;
+            }
+        }
+        output.extend(grapheme.chars().filter(|c| !ZERO_WIDTH_CHARS.contains(c)));
+    }
+
+    expand_emoji_shortcodes(&output)
+}
+
+/// Decides whether accents should be folded for the given ISO 639-1 language code.
+/// An explicit user override always wins over the per-language default.
+pub fn should_fold_diacritics(language: &str, user_override: Option<bool>) -> bool {
+    if let Some(fold) = user_override {
+        return fold;
+    }
+    let language = language.trim().to_lowercase();
+    let primary = language.split(['-', '_']).next().unwrap_or("");
+    !DIACRITIC_SIGNIFICANT_LANGUAGES.contains(&primary)
+}
+
+/// Applies Unicode normalization, and optionally accent folding, to a piece of text.
+/// NFKC keeps accents but unifies composed/decomposed forms; folding decomposes and
+/// drops combining marks so "résumé" becomes "resume".
+pub fn normalize_text(text: &str, fold_diacritics: bool) -> String {
+    if fold_diacritics {
+        text.nfkd().filter(|c| !is_combining_mark(*c)).collect()
+    } else {
+        text.nfkc().collect()
+    }
+}
+
+/// Builds the analyzer used for all full-text fields, at both index and query time.
+pub fn build_analyzer(fold_diacritics: bool) -> TextAnalyzer {
+    TextAnalyzer::builder(NormalizingTokenizer::new(fold_diacritics))
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser)
+        .build()
+}
+
+// ===================================================================
+//  TOKENIZER
+// ===================================================================
+
+/// Wraps `SimpleTokenizer`, replacing emoji and normalizing the text before it is split into tokens.
+/// Normalizing first matters because decomposed accents are not alphanumeric and
+/// would otherwise split a word in two. Token offsets refer to the normalized text.
+#[derive(Clone)]
+pub struct NormalizingTokenizer {
+    inner: SimpleTokenizer,
+    buffer: String,
+    fold_diacritics: bool,
+}
+
+impl NormalizingTokenizer {
+    pub fn new(fold_diacritics: bool) -> Self {
+        Self {
+            inner: SimpleTokenizer::default(),
+            buffer: String::new(),
+            fold_diacritics,
+        }
+    }
+}
+
+impl Tokenizer for NormalizingTokenizer {
+    type TokenStream<'a> = <SimpleTokenizer as Tokenizer>::TokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.buffer = normalize_text(&replace_emoji(text), self.fold_diacritics);
+        self.inner.token_stream(&self.buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::tokenizer::TokenStream;
+
+    fn tokens(analyzer: &mut TextAnalyzer, text: &str) -> Vec<String> {
+        let mut stream = analyzer.token_stream(text);
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_folds_accents_and_case() {
+        let mut analyzer = build_analyzer(true);
+        assert_eq!(tokens(&mut analyzer, "Résumé"), vec!["resume"]);
+        // Decomposed form (e + combining acute accent) must not split the word
+        assert_eq!(tokens(&mut analyzer, "Re\u{301}sume\u{301}"), vec!["resume"]);
+    }
+
+    #[test]
+    fn test_preserves_accents_when_not_folding() {
+        let mut analyzer = build_analyzer(false);
+        assert_eq!(tokens(&mut analyzer, "Re\u{301}sume\u{301}"), vec!["résumé"]);
+    }
+
+    #[test]
+    fn test_emoji_and_zero_width_characters() {
+        let mut analyzer = build_analyzer(true);
+        assert_eq!(tokens(&mut analyzer, "launch 🚀"), vec!["launch", "rocket"]);
+        assert_eq!(tokens(&mut analyzer, "ship it :rocket:"), vec!["ship", "it", "rocket"]);
+        assert_eq!(tokens(&mut analyzer, "road\u{200B}map"), vec!["roadmap"]);
+        // Ordinary colons and unknown shortcodes are preserved
+        assert_eq!(expand_emoji_shortcodes("status: done :notanemoji:"), "status: done :notanemoji:");
+    }
+
+    #[test]
+    fn test_should_fold_diacritics() {
+        assert!(should_fold_diacritics("en", None));
+        assert!(should_fold_diacritics("fr-CA", None));
+        assert!(!should_fold_diacritics("sv", None));
+        assert!(!should_fold_diacritics("vi_VN", None));
+        assert!(should_fold_diacritics("sv", Some(true)));
+        assert!(!should_fold_diacritics("en", Some(false)));
+    }
+}
@@ -0,0 +1,149 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::fs_paths::long_path;
+use crate::settings::app_data_dir;
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Longest edge, in pixels, of generated thumbnails.
+const THUMBNAIL_SIZE: u32 = 256;
+
+// ===================================================================
+//  PUBLIC STRUCT
+// ===================================================================
+
+/// Describes how the UI should render a result's icon: a built-in file-type icon
+/// and, when one has been generated, a thumbnail image on disk.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ResultIcon {
+    pub icon_id: String,
+    pub thumbnail_path: Option<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the lowercase extension of a path, if any.
+fn extension_of(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Returns true for file types we can render a thumbnail for.
+fn supports_thumbnail(extension: &str) -> bool {
+    matches!(extension, "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "tiff" | "pdf")
+}
+
+/// Returns where the thumbnail for a file is cached. The key includes the modification
+/// time so edited files get a fresh thumbnail.
+fn thumbnail_cache_path(path: &Path) -> Result<PathBuf> {
+    let modified = std::fs::metadata(long_path(path))?
+        .modified()?
+        .duration_since(UNIX_EPOCH)?
+        .as_secs();
+
+    let mut hasher = Sha256::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    hasher.update(modified.to_le_bytes());
+    let key = format!("{:x}", hasher.finalize());
+
+    Ok(app_data_dir()?.join("thumbnails").join(format!("{}.png", key)))
+}
+
+/// Renders the first page of a PDF using Quick Look, which ships with macOS.
+#[cfg(target_os = "macos")]
+fn render_pdf_thumbnail(source: &Path, destination: &Path) -> Result<()> {
+    let output_dir = destination.parent()
+        .ok_or_else(|| anyhow::anyhow!("Thumbnail path has no parent directory"))?;
+    let status = std::process::Command::new("qlmanage")
+        .args(["-t", "-s", &THUMBNAIL_SIZE.to_string(), "-o"])
+        .arg(output_dir)
+        .arg(source)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("qlmanage failed for {}", source.display()));
+    }
+
+    // Quick Look names its output after the source file, so move it to our cache key
+    let file_name = source.file_name()
+        .ok_or_else(|| anyhow::anyhow!("PDF path has no file name"))?;
+    let generated = output_dir.join(format!("{}.png", file_name.to_string_lossy()));
+    std::fs::rename(generated, destination)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn render_pdf_thumbnail(_source: &Path, _destination: &Path) -> Result<()> {
+    Err(anyhow::anyhow!("PDF thumbnails are only supported on macOS"))
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Maps a document to one of the UI's built-in icon ids based on its source and extension.
+pub fn icon_id_for(path: &str, source_type: &str) -> String {
+    let icon = match extension_of(path).as_str() {
+        "pdf" => "pdf",
+        "doc" | "docx" | "odt" | "rtf" => "document",
+        "xls" | "xlsx" | "csv" | "ods" => "spreadsheet",
+        "ppt" | "pptx" | "key" | "odp" => "presentation",
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "tiff" | "svg" => "image",
+        "md" | "txt" | "log" => "text",
+        "html" | "htm" => "web",
+        "json" | "xml" | "yml" | "yaml" | "toml" | "ini" | "cfg" | "conf" => "config",
+        "js" | "ts" | "py" | "rs" | "c" | "cpp" | "h" | "hpp" | "java" | "go" | "php" | "rb"
+        | "swift" | "kt" | "scala" | "sh" | "bat" | "css" => "code",
+        // No recognizable extension: fall back to the source, e.g. "email" or "slack"
+        _ if !source_type.is_empty() => source_type,
+        _ => "file",
+    };
+    icon.to_string()
+}
+
+/// Returns the cached thumbnail for a file if one has already been generated.
+pub fn cached_thumbnail(path: &str) -> Option<PathBuf> {
+    if !supports_thumbnail(&extension_of(path)) {
+        return None;
+    }
+    thumbnail_cache_path(Path::new(path)).ok().filter(|cached| cached.exists())
+}
+
+/// Returns true if a thumbnail could be generated for this path but hasn't been yet.
+pub fn needs_thumbnail(path: &str) -> bool {
+    supports_thumbnail(&extension_of(path))
+        && long_path(Path::new(path)).is_file()
+        && cached_thumbnail(path).is_none()
+}
+
+/// Renders and caches a thumbnail for an image or PDF. This is slow, so callers
+/// should run it off the async runtime.
+pub fn generate_thumbnail(path: &str) -> Result<PathBuf> {
+    let source = Path::new(path);
+    let destination = thumbnail_cache_path(source)?;
+    if destination.exists() {
+        return Ok(destination);
+    }
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    match extension_of(path).as_str() {
+        "pdf" => render_pdf_thumbnail(source, &destination)?,
+        _ => {
+            let image = image::open(long_path(source))
+                .map_err(|e| anyhow::anyhow!("Failed to decode image {}: {}", path, e))?;
+            image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).save(&destination)?;
+        }
+    }
+    Ok(destination)
+}
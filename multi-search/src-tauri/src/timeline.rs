@@ -0,0 +1,151 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::scopes::Scope;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Titles listed per bucket when the caller doesn't ask for a number.
+pub const DEFAULT_TITLES_PER_BUCKET: usize = 3;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// How wide each timeline bucket is. Weeks start on Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Day,
+    Week,
+    Month,
+}
+
+/// Restricts the timeline to a scope's folders and connectors and to a time range.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TimelineFilter {
+    #[serde(flatten)]
+    pub scope: Scope,
+    /// Unix timestamps (seconds); documents modified before `since` or from `until` on are left out.
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+/// Documents modified in one day, week or month.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineBucket {
+    /// First day of the bucket in local time, e.g. "2024-03-01".
+    pub start: String,
+    pub count: usize,
+    /// Titles of the most recently modified documents in the bucket.
+    pub titles: Vec<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the first day of the bucket a date falls in.
+fn bucket_start(date: NaiveDate, granularity: Granularity) -> NaiveDate {
+    match granularity {
+        Granularity::Day => date,
+        Granularity::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        Granularity::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl TimelineFilter {
+    /// Returns true if a document passes the filter.
+    pub fn matches(&self, path: &str, source_type: &str, modified_date: SystemTime) -> bool {
+        let modified = modified_date.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        self.scope.matches(path, source_type)
+            && self.since.is_none_or(|since| modified >= since)
+            && self.until.is_none_or(|until| modified < until)
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Buckets documents by the day, week or month they were last modified in, in the given
+/// time zone, and lists up to `titles_per_bucket` of the latest titles in each. Buckets are
+/// in chronological order; empty ones are left out.
+pub fn build_timeline<Tz: TimeZone>(
+    documents: impl IntoIterator<Item = (String, SystemTime)>,
+    granularity: Granularity,
+    time_zone: &Tz,
+    titles_per_bucket: usize,
+) -> Vec<TimelineBucket> {
+    let mut buckets: BTreeMap<NaiveDate, Vec<(SystemTime, String)>> = BTreeMap::new();
+    for (title, modified_date) in documents {
+        let local_date = DateTime::<Utc>::from(modified_date).with_timezone(time_zone).date_naive();
+        buckets.entry(bucket_start(local_date, granularity)).or_default().push((modified_date, title));
+    }
+
+    buckets.into_iter()
+        .map(|(start, mut documents)| {
+            documents.sort_by_key(|(modified_date, _)| std::cmp::Reverse(*modified_date));
+            TimelineBucket {
+                start: start.format("%Y-%m-%d").to_string(),
+                count: documents.len(),
+                titles: documents.into_iter().take(titles_per_bucket).map(|(_, title)| title).collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_timeline() {
+        // 2024-03-04 is a Monday
+        let day = |d: u64, hour: u64| UNIX_EPOCH + std::time::Duration::from_secs(1_709_510_400 + (d * 24 + hour) * 3600);
+        let documents = vec![
+            ("Kickoff".to_string(), day(0, 9)),
+            ("Budget".to_string(), day(2, 10)),
+            ("Retro".to_string(), day(6, 23)),
+            ("Roadmap".to_string(), day(8, 12)),
+            ("Old notes".to_string(), day(0, 0) - std::time::Duration::from_secs(40 * 24 * 3600)),
+        ];
+
+        let weeks = build_timeline(documents.clone(), Granularity::Week, &Utc, 2);
+        assert_eq!(weeks, vec![
+            TimelineBucket { start: "2024-01-22".to_string(), count: 1, titles: vec!["Old notes".to_string()] },
+            TimelineBucket { start: "2024-03-04".to_string(), count: 3, titles: vec!["Retro".to_string(), "Budget".to_string()] },
+            TimelineBucket { start: "2024-03-11".to_string(), count: 1, titles: vec!["Roadmap".to_string()] },
+        ]);
+
+        let months = build_timeline(documents.clone(), Granularity::Month, &Utc, 3);
+        assert_eq!(months.iter().map(|b| (b.start.as_str(), b.count)).collect::<Vec<_>>(), vec![("2024-01-01", 1), ("2024-03-01", 4)]);
+
+        // Late Sunday evening in UTC is already Monday further east
+        let tokyo = chrono::FixedOffset::east_opt(9 * 3600).unwrap();
+        let days = build_timeline(documents, Granularity::Day, &tokyo, 1);
+        assert!(days.iter().any(|b| b.start == "2024-03-11" && b.titles == vec!["Retro".to_string()]));
+        assert!(!days.iter().any(|b| b.start == "2024-03-10"));
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let filter = TimelineFilter {
+            scope: Scope { folders: vec!["/work".to_string()], source_types: Vec::new() },
+            since: Some(100),
+            until: Some(200),
+        };
+        let at = |secs: u64| UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        assert!(filter.matches("/work/a.md", "file", at(150)));
+        assert!(!filter.matches("/home/a.md", "file", at(150)));
+        assert!(!filter.matches("/work/a.md", "file", at(200)));
+        assert!(TimelineFilter::default().matches("/anything", "gmail", at(0)));
+    }
+}
@@ -0,0 +1,247 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::keyword_extraction::{extract_keywords, MAX_KEYWORDS};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Fewer documents than this aren't worth grouping.
+const MIN_DOCUMENTS: usize = 6;
+/// Upper bound on the number of topics, so the browse view stays scannable.
+const MAX_TOPICS: usize = 30;
+/// Lloyd iterations; assignments settle well before this on summary embeddings.
+const MAX_ITERATIONS: usize = 50;
+/// Keywords kept per topic; the first two make up its label.
+const TOPIC_KEYWORDS: usize = 5;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// A document's summary and its embedding, the input to clustering.
+pub struct SummaryEmbedding {
+    pub document_path: String,
+    pub summary: String,
+    pub embedding: Vec<f32>,
+}
+
+/// A group of documents about the same thing, labeled with the phrases their summaries share.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Topic {
+    pub id: usize,
+    /// Short name for the topic, e.g. "vector database, query latency".
+    pub label: String,
+    pub keywords: Vec<String>,
+    /// Member documents, most representative first.
+    pub document_paths: Vec<String>,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Scales a vector to unit length, so dot products are cosine similarities.
+fn normalized(vector: &[f32]) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|v| v / norm).collect()
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Picks the number of topics: the square root of half the documents, a common rule of thumb.
+fn topic_count(documents: usize) -> usize {
+    ((documents as f32 / 2.0).sqrt().round() as usize).clamp(2, MAX_TOPICS)
+}
+
+/// Chooses starting centroids by farthest-point seeding: the first vector, then repeatedly
+/// the vector least similar to every centroid so far. Deterministic, so topics don't
+/// reshuffle between runs over the same corpus.
+fn seed_centroids(vectors: &[Vec<f32>], k: usize) -> Vec<Vec<f32>> {
+    let mut centroids = vec![vectors[0].clone()];
+    let mut best_similarity: Vec<f32> = vectors.iter().map(|v| dot(v, &vectors[0])).collect();
+    while centroids.len() < k {
+        let (farthest, _) = best_similarity.iter().enumerate()
+            .min_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .unwrap();
+        let centroid = vectors[farthest].clone();
+        for (similarity, vector) in best_similarity.iter_mut().zip(vectors) {
+            *similarity = similarity.max(dot(vector, &centroid));
+        }
+        centroids.push(centroid);
+    }
+    centroids
+}
+
+/// Spherical k-means over unit vectors. Returns each vector's cluster and the centroids.
+fn kmeans(vectors: &[Vec<f32>], k: usize) -> (Vec<usize>, Vec<Vec<f32>>) {
+    let mut centroids = seed_centroids(vectors, k);
+    let mut assignments = vec![usize::MAX; vectors.len()];
+
+    for _ in 0..MAX_ITERATIONS {
+        // 1. Assign every vector to its most similar centroid.
+        let mut changed = false;
+        for (assignment, vector) in assignments.iter_mut().zip(vectors) {
+            let (closest, _) = centroids.iter().enumerate()
+                .map(|(cluster, centroid)| (cluster, dot(vector, centroid)))
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap();
+            if *assignment != closest {
+                *assignment = closest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+
+        // 2. Move each centroid to the normalized mean of its members. A cluster that
+        //    lost all its members keeps its old centroid.
+        for (cluster, centroid) in centroids.iter_mut().enumerate() {
+            let mut sum = vec![0.0; centroid.len()];
+            for (vector, _) in vectors.iter().zip(&assignments).filter(|(_, a)| **a == cluster) {
+                for (total, v) in sum.iter_mut().zip(vector) {
+                    *total += v;
+                }
+            }
+            if sum.iter().any(|v| *v != 0.0) {
+                *centroid = normalized(&sum);
+            }
+        }
+    }
+    (assignments, centroids)
+}
+
+/// Picks the phrases most of a topic's summaries share. Each summary's keywords and their
+/// shorter sub-phrases are counted once per summary; ties go to the longer phrase, and
+/// phrases overlapping a kept one are skipped. Only phrases shared by two or more
+/// summaries qualify, unless the topic has a single document.
+fn topic_keywords(summaries: &[&str], limit: usize) -> Vec<String> {
+    // 1. Count how many summaries contain each phrase.
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for summary in summaries {
+        let mut phrases = HashSet::new();
+        for keyword in extract_keywords("", summary, MAX_KEYWORDS) {
+            let words: Vec<&str> = keyword.split(' ').collect();
+            for length in 1..=words.len() {
+                phrases.extend(words.windows(length).map(|window| window.join(" ")));
+            }
+        }
+        for phrase in phrases {
+            *counts.entry(phrase).or_insert(0) += 1;
+        }
+    }
+
+    // 2. Keep the most shared, skipping phrases that overlap ones already kept.
+    let min_count = if summaries.len() > 1 { 2 } else { 1 };
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().filter(|(_, count)| *count >= min_count).collect();
+    ranked.sort_by(|a, b| {
+        b.1.cmp(&a.1)
+            .then_with(|| b.0.split(' ').count().cmp(&a.0.split(' ').count()))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    let mut keywords: Vec<String> = Vec::new();
+    for (phrase, _) in ranked {
+        if keywords.len() >= limit {
+            break;
+        }
+        let overlaps = keywords.iter().any(|kept| {
+            let kept_words: HashSet<&str> = kept.split(' ').collect();
+            phrase.split(' ').any(|word| kept_words.contains(word))
+        });
+        if !overlaps {
+            keywords.push(phrase);
+        }
+    }
+    keywords
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Groups documents into topics by clustering their summary embeddings, and labels each
+/// topic with the phrases its summaries share. Topics are ordered largest first.
+/// Returns no topics when there are too few documents to group.
+pub fn cluster_topics(documents: &[SummaryEmbedding]) -> Vec<Topic> {
+    if documents.len() < MIN_DOCUMENTS {
+        return Vec::new();
+    }
+    let vectors: Vec<Vec<f32>> = documents.iter().map(|doc| normalized(&doc.embedding)).collect();
+    let (assignments, centroids) = kmeans(&vectors, topic_count(documents.len()));
+
+    let mut topics: Vec<Topic> = centroids.iter().enumerate()
+        .filter_map(|(cluster, centroid)| {
+            // Members closest to the centroid first; they best represent the topic
+            let mut members: Vec<(usize, f32)> = assignments.iter().enumerate()
+                .filter(|(_, a)| **a == cluster)
+                .map(|(i, _)| (i, dot(&vectors[i], centroid)))
+                .collect();
+            if members.is_empty() {
+                return None;
+            }
+            members.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+            let summaries: Vec<&str> = members.iter().map(|(i, _)| documents[*i].summary.as_str()).collect();
+            let keywords = topic_keywords(&summaries, TOPIC_KEYWORDS);
+            let label = if keywords.is_empty() {
+                format!("Topic {}", cluster + 1)
+            } else {
+                keywords.iter().take(2).cloned().collect::<Vec<_>>().join(", ")
+            };
+            Some(Topic {
+                id: 0,
+                label,
+                keywords,
+                document_paths: members.iter().map(|(i, _)| documents[*i].document_path.clone()).collect(),
+            })
+        })
+        .collect();
+
+    topics.sort_by(|a, b| b.document_paths.len().cmp(&a.document_paths.len()).then_with(|| a.label.cmp(&b.label)));
+    for (id, topic) in topics.iter_mut().enumerate() {
+        topic.id = id;
+    }
+    topics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(path: &str, summary: &str, embedding: [f32; 3]) -> SummaryEmbedding {
+        SummaryEmbedding {
+            document_path: path.to_string(),
+            summary: summary.to_string(),
+            embedding: embedding.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_cluster_topics() {
+        let documents = [
+            document("/db/a.md", "Vector database benchmarks.", [1.0, 0.1, 0.0]),
+            document("/db/b.md", "Tuning the vector database.", [0.9, 0.2, 0.0]),
+            document("/db/c.md", "Vector database migration.", [1.0, 0.0, 0.1]),
+            document("/garden/a.md", "Tomato seedlings need light.", [0.0, 0.1, 1.0]),
+            document("/garden/b.md", "Watering tomato seedlings.", [0.1, 0.0, 0.9]),
+            document("/garden/c.md", "Pruning tomato seedlings in June.", [0.0, 0.2, 1.0]),
+            document("/db/d.md", "Vector database sharding.", [0.8, 0.1, 0.1]),
+        ];
+        let topics = cluster_topics(&documents);
+        assert_eq!(topics.len(), 2);
+        assert_eq!(topics[0].id, 0);
+        assert_eq!(topics[0].document_paths.len(), 4);
+        assert!(topics[0].document_paths.iter().all(|path| path.starts_with("/db/")));
+        assert_eq!(topics[0].keywords[0], "vector database");
+        assert!(topics[1].document_paths.iter().all(|path| path.starts_with("/garden/")));
+        assert_eq!(topics[0].label, "vector database");
+        assert_eq!(topics[1].label, "tomato seedlings");
+
+        assert!(cluster_topics(&documents[..3]).is_empty());
+    }
+}
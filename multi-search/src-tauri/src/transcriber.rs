@@ -0,0 +1,117 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::model_store::{model_files, WHISPER_MODEL};
+use crate::voice_audio::{mel_filters, pad_to_window, WHISPER_SAMPLE_RATE};
+use anyhow::{Error as E, Result};
+use candle_core::{Device, IndexOp, Tensor, D};
+use candle_nn::VarBuilder;
+use candle_transformers::models::whisper::{self, audio, model::Whisper, Config};
+use std::sync::Mutex;
+use tokenizers::Tokenizer;
+
+/// Longest transcription; a spoken query is a sentence or two.
+const MAX_QUERY_TOKENS: usize = 96;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Turns speech into text with a local Whisper model, for voice queries. Nothing
+/// recorded leaves the machine.
+pub struct Transcriber {
+    model: Mutex<Whisper>,
+    tokenizer: Tokenizer,
+    config: Config,
+    mel_filters: Vec<f32>,
+    // Logit offsets that rule out timestamps and the tokens Whisper is told never to emit
+    suppress: Tensor,
+    device: Device,
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+fn token_id(tokenizer: &Tokenizer, token: &str) -> Result<u32> {
+    tokenizer.token_to_id(token)
+        .ok_or_else(|| anyhow::anyhow!("The Whisper tokenizer has no {} token", token))
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl Transcriber {
+    /// Downloads the model on first use, unless `offline` is set, and loads it.
+    pub async fn load(offline: bool) -> Result<Self> {
+        let device = Device::Cpu;
+
+        let files = model_files(&WHISPER_MODEL, offline).await?;
+        let [config_filename, tokenizer_filename, weights_filename] = <[_; 3]>::try_from(files)
+            .map_err(|_| E::msg("Unexpected Whisper model files"))?;
+        let config: Config = serde_json::from_str(&std::fs::read_to_string(config_filename)?)?;
+        let tokenizer = Tokenizer::from_file(tokenizer_filename).map_err(E::msg)?;
+        let vb = unsafe {
+            VarBuilder::from_mmaped_safetensors(&[weights_filename], whisper::DTYPE, &device)?
+        };
+        let model = Whisper::load(&vb, config.clone())?;
+
+        let no_timestamps = token_id(&tokenizer, whisper::NO_TIMESTAMPS_TOKEN)?;
+        let suppress: Vec<f32> = (0..config.vocab_size as u32)
+            .map(|token| {
+                if config.suppress_tokens.contains(&token) || token == no_timestamps {
+                    f32::NEG_INFINITY
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let suppress = Tensor::new(suppress.as_slice(), &device)?;
+
+        println!("Transcriber model loaded successfully");
+        Ok(Self {
+            model: Mutex::new(model),
+            mel_filters: mel_filters(WHISPER_SAMPLE_RATE, whisper::N_FFT, config.num_mel_bins),
+            tokenizer,
+            config,
+            suppress,
+            device,
+        })
+    }
+
+    /// Transcribes up to 30 seconds of 16 kHz mono audio. Decoding is greedy, which is
+    /// as good as beam search for a short, clearly spoken query.
+    pub fn transcribe(&self, samples: Vec<f32>) -> Result<String> {
+        // 1. Turn the audio into the log-mel spectrogram of one 30-second window.
+        let mel = audio::pcm_to_mel(&self.config, &pad_to_window(samples), &self.mel_filters);
+        let frames = mel.len() / self.config.num_mel_bins;
+        let mel = Tensor::from_vec(mel, (1, self.config.num_mel_bins, frames), &self.device)?
+            .narrow(2, 0, frames.min(whisper::N_FRAMES))?;
+
+        // 2. Encode once, then decode one token at a time from the transcription prompt.
+        let mut model = self.model.lock().unwrap();
+        let audio_features = model.encoder.forward(&mel, true)?;
+        let end_of_text = token_id(&self.tokenizer, whisper::EOT_TOKEN)?;
+        let mut tokens = vec![
+            token_id(&self.tokenizer, whisper::SOT_TOKEN)?,
+            token_id(&self.tokenizer, whisper::NO_TIMESTAMPS_TOKEN)?,
+        ];
+        let prompt_len = tokens.len();
+        for step in 0..MAX_QUERY_TOKENS {
+            let input = Tensor::new(tokens.as_slice(), &self.device)?.unsqueeze(0)?;
+            let hidden = model.decoder.forward(&input, &audio_features, step == 0)?;
+            let (_, seq_len, _) = hidden.dims3()?;
+            let logits = model.decoder.final_linear(&hidden.i((..1, seq_len - 1..))?)?.i(0)?.i(0)?;
+            let next_token = logits.broadcast_add(&self.suppress)?.argmax(D::Minus1)?.to_scalar::<u32>()?;
+            if next_token == end_of_text {
+                break;
+            }
+            tokens.push(next_token);
+        }
+
+        // 3. Turn the generated tokens back into text.
+        let text = self.tokenizer.decode(&tokens[prompt_len..], true).map_err(E::msg)?;
+        Ok(text.trim().to_string())
+    }
+}
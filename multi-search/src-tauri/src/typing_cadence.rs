@@ -0,0 +1,75 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use std::time::{Duration, Instant};
+
+/// Wait before the search for the first keystroke after a pause, which is usually the
+/// start of a query worth showing results for right away.
+pub const MIN_SEARCH_DEBOUNCE: Duration = Duration::from_millis(20);
+/// Longest wait, for a slow but steady typist.
+pub const MAX_SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+/// A gap this long between keystrokes ends a burst of typing.
+const BURST_GAP: Duration = Duration::from_secs(1);
+/// Weight of the latest gap in the running average, so the wait follows changes in speed.
+const GAP_SMOOTHING: f64 = 0.3;
+/// The wait is this multiple of the average gap, so most follow-up keystrokes land in it.
+const GAP_MARGIN: f64 = 1.2;
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// Tracks how fast the user types in the launcher, to decide how long a search waits for
+/// the next keystroke before it starts. Fast typing is coalesced into fewer searches,
+/// and thus fewer embedding passes, without slowing down a single keystroke after a pause.
+#[derive(Debug, Default)]
+pub struct TypingCadence {
+    last_keystroke: Option<Instant>,
+    // Kept across pauses, since a user's typing speed doesn't change between queries
+    average_gap: Option<Duration>,
+}
+
+// ===================================================================
+//  IMPLEMENTATION
+// ===================================================================
+
+impl TypingCadence {
+    /// Notes a keystroke at `now` and returns how long its search should wait.
+    pub fn debounce(&mut self, now: Instant) -> Duration {
+        let gap = self.last_keystroke.map(|last| now.saturating_duration_since(last));
+        self.last_keystroke = Some(now);
+        let Some(gap) = gap.filter(|gap| *gap < BURST_GAP) else { return MIN_SEARCH_DEBOUNCE };
+
+        let average = match self.average_gap {
+            Some(average) => average.mul_f64(1.0 - GAP_SMOOTHING) + gap.mul_f64(GAP_SMOOTHING),
+            None => gap,
+        };
+        self.average_gap = Some(average);
+        average.mul_f64(GAP_MARGIN).clamp(MIN_SEARCH_DEBOUNCE, MAX_SEARCH_DEBOUNCE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounce_follows_typing_speed() {
+        let mut cadence = TypingCadence::default();
+        let start = Instant::now();
+        let mut debounce_ms = |ms: u64| {
+            let debounce = cadence.debounce(start + Duration::from_millis(ms));
+            (debounce.as_secs_f64() * 1000.0).round() as u64
+        };
+
+        assert_eq!(debounce_ms(0), 20);
+        // Keystrokes 100 ms apart wait a little longer than that
+        assert_eq!(debounce_ms(100), 120);
+        assert_eq!(debounce_ms(200), 120);
+        // One quick keystroke shortens the wait only gradually
+        assert_eq!(debounce_ms(205), 86);
+        // The first keystroke after a pause searches at once, and slow typing is capped
+        assert_eq!(debounce_ms(5000), 20);
+        assert_eq!(debounce_ms(5900), 150);
+    }
+}
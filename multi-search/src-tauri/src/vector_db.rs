@@ -1,52 +1,141 @@
 // ===================================================================
 //  IMPORTS
 // ===================================================================
+use crate::disk_space::DiskGuard;
 use crate::embedding_generator::EmbeddingRecord;
+use crate::topics::SummaryEmbedding;
 use anyhow::Result;
-use arrow::array::{Array, Float32Array, StringArray, FixedSizeListArray};
-use arrow::datatypes::{DataType, Field, Schema, Float32Type};
+use arrow::array::{Array, Float16Array, Float32Array, StringArray, FixedSizeListArray};
+use arrow::datatypes::{DataType, Field, Schema, Float16Type, Float32Type};
+use half::f16;
 use arrow::record_batch::{RecordBatch, RecordBatchIterator};
-use lancedb::{connection::Connection, table::Table, query::{QueryBase, ExecutableQuery}};
+use lancedb::{connection::Connection, table::{NewColumnTransform, OptimizeAction, Table}, query::{QueryBase, ExecutableQuery, Select}};
 use futures::TryStreamExt;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// How many times a write is attempted before giving up on a commit conflict.
+const MAX_WRITE_ATTEMPTS: u32 = 5;
+/// Delay before the first retry; doubled on every further attempt.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(50);
+/// Number of nearest neighbours returned by a vector search unless the caller asks for more.
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+/// Chunk rows whose documents are moved together when the chunk store is first separated.
+const MOVE_BATCH_ROWS: usize = 2_000;
 
 // ===================================================================
 //  PUBLIC STRUCT
 // ===================================================================
 
+/// Element type of the stored embedding vectors.
+/// New tables use f16, halving storage; tables created before that keep f32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorPrecision {
+    F32,
+    F16,
+}
+
 /// Manages the LanceDB vector database connection and all related operations.
 pub struct VectorDBManager {
     _conn: Connection,
     table: Table,
+    // Chunk embeddings when they are kept on another volume; otherwise they are in `table`
+    _chunk_conn: Option<Connection>,
+    chunk_table: Option<Table>,
+    precision: VectorPrecision,
+    // False for a read-only store written before rows recorded their source type
+    has_source_types: bool,
+    // Serializes writes from this process so parallel indexing tasks don't race each other's commits
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+/// One row of a vector search result.
+struct VectorHit {
+    document_path: String,
+    text_chunk: Option<String>,
+    distance: f32,
 }
 
 // ===================================================================
 //  PRIVATE HELPERS
 // ===================================================================
 
+impl VectorHit {
+    /// Reads the hits in a result batch, skipping rows without a path or distance.
+    fn read_batch(batch: &RecordBatch, include_text_chunk: bool) -> Result<Vec<Self>> {
+        let paths = VectorDBManager::column::<StringArray>(batch, "document_path")?;
+        let distances = VectorDBManager::column::<Float32Array>(batch, "_distance")?;
+        let chunks = if include_text_chunk {
+            Some(VectorDBManager::column::<StringArray>(batch, "text_chunk")?)
+        } else {
+            None
+        };
+        Ok((0..batch.num_rows())
+            .filter(|&row| !paths.is_null(row) && !distances.is_null(row))
+            .map(|row| VectorHit {
+                document_path: paths.value(row).to_string(),
+                text_chunk: chunks.filter(|chunks| !chunks.is_null(row)).map(|chunks| chunks.value(row).to_string()),
+                distance: distances.value(row),
+            })
+            .collect())
+    }
+}
+
 impl VectorDBManager {
     /// Creates the Arrow schema for our embeddings table.
-    fn create_schema() -> Arc<Schema> {
+    fn create_schema(precision: VectorPrecision) -> Arc<Schema> {
+        let item_type = match precision {
+            VectorPrecision::F32 => DataType::Float32,
+            VectorPrecision::F16 => DataType::Float16,
+        };
+
         Arc::new(Schema::new(vec![
             Field::new("embedding", DataType::FixedSizeList(
-                Arc::new(Field::new("item", DataType::Float32, false)),
+                Arc::new(Field::new("item", item_type, false)),
                 384 // BERT all-MiniLM-L6-v2 produces 384-dimensional embeddings
             ), false),
             Field::new("text_chunk", DataType::Utf8, false),
             Field::new("document_path", DataType::Utf8, false),
             Field::new("embedding_type", DataType::Utf8, false),
+            // Empty for rows written before the source type was recorded
+            Field::new("source_type", DataType::Utf8, true),
         ]))
     }
 
-    /// Converts EmbeddingRecord structs into an Arrow RecordBatch.
-    fn records_to_batch(records: &[EmbeddingRecord]) -> Result<RecordBatch> {
+    /// Reads the vector precision from an existing table's schema.
+    fn precision_from_schema(schema: &Schema) -> VectorPrecision {
+        match schema.field_with_name("embedding").map(|field| field.data_type()) {
+            Ok(DataType::FixedSizeList(item, _)) if item.data_type() == &DataType::Float16 => VectorPrecision::F16,
+            _ => VectorPrecision::F32,
+        }
+    }
+
+    /// Builds the embedding column, converting f32 model output to the table's precision.
+    fn embeddings_to_array(embeddings: &[&[f32]], precision: VectorPrecision) -> FixedSizeListArray {
+        match precision {
+            VectorPrecision::F32 => FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+                embeddings.iter().map(|embedding| Some(embedding.iter().map(|&v| Some(v)).collect::<Vec<_>>())),
+                384
+            ),
+            VectorPrecision::F16 => FixedSizeListArray::from_iter_primitive::<Float16Type, _, _>(
+                embeddings.iter().map(|embedding| Some(embedding.iter().map(|&v| Some(f16::from_f32(v))).collect::<Vec<_>>())),
+                384
+            ),
+        }
+    }
+
+    /// Converts EmbeddingRecord structs of one document source into an Arrow RecordBatch.
+    fn records_to_batch(records: &[EmbeddingRecord], source_type: &str, precision: VectorPrecision) -> Result<RecordBatch> {
         if records.is_empty() {
             return Err(anyhow::anyhow!("Cannot create batch from empty records"));
         }
 
         // Convert records to Arrow format
-        let embeddings: Vec<Option<Vec<Option<f32>>>> = records.iter()
-            .map(|record| Some(record.embedding.iter().map(|&v| Some(v)).collect()))
+        let embeddings: Vec<&[f32]> = records.iter()
+            .map(|record| record.embedding.as_slice())
             .collect();
         
         let text_chunks: Vec<&str> = records.iter()
@@ -62,114 +151,243 @@ impl VectorDBManager {
             .collect();
 
         // Create Arrow arrays
-        let embedding_array = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
-            embeddings,
-            384
-        );
+        let embedding_array = Self::embeddings_to_array(&embeddings, precision);
         let text_chunk_array = StringArray::from(text_chunks);
         let doc_path_array = StringArray::from(doc_paths);
         let embedding_type_array = StringArray::from(embedding_types);
+        let source_type_array = StringArray::from(vec![source_type; records.len()]);
 
         // Create record batch
         let record_batch = RecordBatch::try_new(
-            Self::create_schema(),
+            Self::create_schema(precision),
             vec![
                 Arc::new(embedding_array),
                 Arc::new(text_chunk_array),
                 Arc::new(doc_path_array),
                 Arc::new(embedding_type_array),
+                Arc::new(source_type_array),
             ],
         )?;
 
         Ok(record_batch)
     }
 
+    /// Reads one stored embedding back as f32, whatever the table's precision.
+    fn embedding_at(embeddings: &FixedSizeListArray, row: usize) -> Option<Vec<f32>> {
+        if embeddings.is_null(row) {
+            return None;
+        }
+        let values = embeddings.value(row);
+        if let Some(values) = values.as_any().downcast_ref::<Float32Array>() {
+            Some(values.values().to_vec())
+        } else {
+            values.as_any().downcast_ref::<Float16Array>()
+                .map(|values| values.values().iter().map(|v| v.to_f32()).collect())
+        }
+    }
+
     /// Creates an empty RecordBatch for table initialization.
-    fn create_empty_batch() -> Result<RecordBatch> {
-        let empty_embedding = vec![Some(vec![Some(0.0f32); 384])];
+    fn create_empty_batch(precision: VectorPrecision) -> Result<RecordBatch> {
+        let empty_embedding = [0.0f32; 384];
         let empty_text = vec![""];
         let empty_path = vec![""];
         let empty_type = vec![""];
+        let empty_source = vec![""];
 
-        let embedding_array = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
-            empty_embedding,
-            384
-        );
+        let embedding_array = Self::embeddings_to_array(&[&empty_embedding], precision);
         let text_chunk_array = StringArray::from(empty_text);
         let doc_path_array = StringArray::from(empty_path);
         let embedding_type_array = StringArray::from(empty_type);
+        let source_type_array = StringArray::from(empty_source);
 
         let record_batch = RecordBatch::try_new(
-            Self::create_schema(),
+            Self::create_schema(precision),
             vec![
                 Arc::new(embedding_array),
                 Arc::new(text_chunk_array),
                 Arc::new(doc_path_array),
                 Arc::new(embedding_type_array),
+                Arc::new(source_type_array),
             ],
         )?;
 
         Ok(record_batch)
     }
 
-    /// Executes a vector search with the given filter and returns parsed results.
+    /// Returns the table holding chunk embeddings.
+    fn chunks(&self) -> &Table {
+        self.chunk_table.as_ref().unwrap_or(&self.table)
+    }
+
+    /// Returns every table: the main one and, if separate, the chunk table.
+    fn tables(&self) -> impl Iterator<Item = &Table> {
+        std::iter::once(&self.table).chain(self.chunk_table.as_ref())
+    }
+
+    /// Opens the "embeddings" table of a database, creating it with `precision` if it
+    /// doesn't exist. An existing table keeps whatever precision it was created with.
+    async fn open_or_create_table(db: &Connection, precision: VectorPrecision) -> Result<(Table, VectorPrecision)> {
+        if db.table_names().execute().await?.contains(&"embeddings".to_string()) {
+            // If YES, open existing table and keep whatever precision it was created with
+            let table = db.open_table("embeddings").execute().await?;
+            let schema = table.schema().await?;
+            let precision = Self::precision_from_schema(&schema);
+            // Tables from before rows recorded their source type get the column, empty
+            if schema.field_with_name("source_type").is_err() {
+                table.add_columns(
+                    NewColumnTransform::SqlExpressions(vec![("source_type".to_string(), "''".to_string())]),
+                    None,
+                ).await?;
+            }
+            Ok((table, precision))
+        } else {
+            // If NO, create it with empty schema
+            let empty_batch = Self::create_empty_batch(precision)?;
+            let batch_iterator = RecordBatchIterator::new(
+                vec![Ok(empty_batch)].into_iter(),
+                Self::create_schema(precision)
+            );
+
+            let table = db.create_table("embeddings", Box::new(batch_iterator)).execute().await?;
+
+            // Clean up the initialization record
+            table.delete("text_chunk = ''").await?;
+
+            Ok((table, precision))
+        }
+    }
+
+    /// Moves chunk embeddings left in the main table, from before a separate chunk store
+    /// was configured, over to the chunk table, a few documents at a time so memory stays
+    /// bounded. Each batch first clears its documents from the chunk table, so a batch
+    /// added before a crash is replaced rather than duplicated on the next launch, and
+    /// leaves the main table only once its add has succeeded. Batches wait while the data
+    /// volumes are nearly full.
+    async fn move_chunks(table: &Table, chunk_table: &Table, disk_guard: &DiskGuard) -> Result<()> {
+        loop {
+            disk_guard.wait_for_space().await;
+            let paths = Self::chunk_paths(table, MOVE_BATCH_ROWS).await?;
+            if paths.is_empty() {
+                return Ok(());
+            }
+            let quoted: Vec<String> = paths.iter()
+                .map(|path| format!("'{}'", Self::escape_sql_string(path)))
+                .collect();
+            let filter = format!("embedding_type = 'chunk' AND document_path IN ({})", quoted.join(", "));
+
+            let batches: Vec<RecordBatch> = table
+                .query()
+                .only_if(filter.as_str())
+                .execute()
+                .await?
+                .try_collect()
+                .await?;
+            chunk_table.delete(&filter).await?;
+            if let Some(schema) = batches.first().map(|batch| batch.schema()) {
+                let batch_iterator = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+                chunk_table.add(Box::new(batch_iterator)).execute().await?;
+            }
+            table.delete(&filter).await?;
+        }
+    }
+
+    /// Returns the distinct documents among the first `limit` chunk rows of a table.
+    async fn chunk_paths(table: &Table, limit: usize) -> Result<Vec<String>> {
+        let mut stream = table
+            .query()
+            .only_if("embedding_type = 'chunk'")
+            .select(Select::columns(&["document_path"]))
+            .limit(limit)
+            .execute()
+            .await?;
+
+        let mut paths = Vec::new();
+        while let Some(batch) = stream.try_next().await? {
+            let doc_array = Self::column::<StringArray>(&batch, "document_path")?;
+            for i in 0..batch.num_rows() {
+                if !doc_array.is_null(i) && !paths.iter().any(|path: &String| path == doc_array.value(i)) {
+                    paths.push(doc_array.value(i).to_string());
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Executes a vector search with the given filter and reads up to `limit` hits,
+    /// stopping as soon as that many have streamed in.
     async fn execute_search(
         &self,
+        table: &Table,
         query_vector: &[f32],
         filter: &str,
         include_text_chunk: bool,
-    ) -> Result<Vec<(String, Option<String>, f32)>> {
+        limit: usize,
+    ) -> Result<Vec<VectorHit>> {
+        // Queries stay f32; LanceDB casts the query vector to the column's precision
         let query_vec: Vec<f32> = query_vector.to_vec();
-        
-        let mut search_results = self.table
+        // Fetch only what the hits are built from; `_distance` is always added. Leaving out
+        // the embedding column avoids reading 384 floats per row.
+        let columns: &[&str] = if include_text_chunk {
+            &["document_path", "text_chunk"]
+        } else {
+            &["document_path"]
+        };
+
+        let mut search_results = table
             .query()
             .nearest_to(query_vec)?
+            .select(Select::columns(columns))
             .only_if(filter)
-            .limit(10)
+            .limit(limit)
             .execute()
             .await?;
 
-        let mut parsed_results = Vec::new();
-        
-        while let Some(batch) = search_results.try_next().await? {
-            for i in 0..batch.num_rows() {
-                let doc_path_col = batch.column_by_name("document_path")
-                    .ok_or_else(|| anyhow::anyhow!("Missing document_path column"))?;
-                let distance_col = batch.column_by_name("_distance")
-                    .ok_or_else(|| anyhow::anyhow!("Missing _distance column"))?;
-
-                if let (Some(doc_array), Some(dist_array)) = (
-                    doc_path_col.as_any().downcast_ref::<StringArray>(),
-                    distance_col.as_any().downcast_ref::<Float32Array>()
-                ) {
-                    let path = doc_array.value(i);
-                    let distance = dist_array.value(i);
-                    
-                    if !doc_array.is_null(i) && !dist_array.is_null(i) {
-                        let text_chunk = if include_text_chunk {
-                            let text_chunk_col = batch.column_by_name("text_chunk")
-                                .ok_or_else(|| anyhow::anyhow!("Missing text_chunk column"))?;
-                            
-                            if let Some(chunk_array) = text_chunk_col.as_any().downcast_ref::<StringArray>() {
-                                if !chunk_array.is_null(i) {
-                                    Some(chunk_array.value(i).to_string())
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        };
-                        
-                        parsed_results.push((path.to_string(), text_chunk, distance));
-                    }
+        let mut hits = Vec::new();
+        while hits.len() < limit {
+            let Some(batch) = search_results.try_next().await? else { break };
+            hits.extend(VectorHit::read_batch(&batch, include_text_chunk)?);
+        }
+        hits.truncate(limit);
+        Ok(hits)
+    }
+
+    /// Looks a column up by name and downcasts it to its Arrow type, once per batch
+    /// rather than once per row.
+    fn column<'a, T: Array + 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a T> {
+        batch.column_by_name(name)
+            .ok_or_else(|| anyhow::anyhow!("Missing {} column", name))?
+            .as_any()
+            .downcast_ref::<T>()
+            .ok_or_else(|| anyhow::anyhow!("Unexpected type for the {} column", name))
+    }
+
+    /// Returns true for errors caused by a concurrent commit, which are safe to retry.
+    fn is_commit_conflict(error: &lancedb::Error) -> bool {
+        let message = error.to_string().to_lowercase();
+        message.contains("conflict") || message.contains("retryable")
+    }
+
+    /// Runs a write while holding the write lock, retrying with exponential backoff when
+    /// the commit conflicts with another writer (e.g. a compaction or another process).
+    async fn write_with_retry<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = lancedb::Result<T>>,
+    {
+        let _guard = self.write_lock.lock().await;
+        let mut delay = INITIAL_RETRY_DELAY;
+
+        for attempt in 1..=MAX_WRITE_ATTEMPTS {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < MAX_WRITE_ATTEMPTS && Self::is_commit_conflict(&e) => {
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
                 }
+                Err(e) => return Err(e.into()),
             }
         }
-        
-        Ok(parsed_results)
+        unreachable!("the last attempt always returns")
     }
 
     /// Safely escapes a string for SQL queries.
@@ -179,6 +397,33 @@ impl VectorDBManager {
         // This is a temporary solution until parameterized queries are available
         input.replace("'", "''")
     }
+
+    /// ANDs an optional extra filter (e.g. from a search scope) onto a base filter.
+    fn with_scope_filter(base: &str, scope_filter: Option<&str>) -> String {
+        match scope_filter {
+            Some(extra) => format!("{} AND {}", base, extra),
+            None => base.to_string(),
+        }
+    }
+
+    /// Combines a base filter with a scope filter and, where rows record it, a restriction
+    /// to `source_types`. Stores without the column leave sources to the caller.
+    fn search_filter(&self, base: &str, scope_filter: Option<&str>, source_types: Option<&[String]>) -> String {
+        let filter = Self::with_scope_filter(base, scope_filter);
+        match source_types.filter(|source_types| self.has_source_types && !source_types.is_empty()) {
+            Some(source_types) => format!("{} AND {}", filter, Self::source_filter(source_types)),
+            None => filter,
+        }
+    }
+
+    /// Builds a filter restricting rows to the given sources, matched case-insensitively.
+    /// Rows written before the source type was recorded are kept, for the caller to check.
+    fn source_filter(source_types: &[String]) -> String {
+        let quoted: Vec<String> = source_types.iter()
+            .map(|source_type| format!("'{}'", Self::escape_sql_string(&source_type.to_lowercase())))
+            .collect();
+        format!("(lower(source_type) IN ({}) OR source_type = '')", quoted.join(", "))
+    }
 }
 
 // ===================================================================
@@ -186,57 +431,95 @@ impl VectorDBManager {
 // ===================================================================
 
 impl VectorDBManager {
-    /// Creates or opens the LanceDB database and the "embeddings" table.
-    /// This is a one-time setup operation.
-    pub async fn new() -> Result<Self> {
-        // 1. Get the path to the app's data directory
-        let data_dir = dirs::data_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not find application data directory"))?;
-        let db_path = data_dir.join("multi-search").join("vector_store");
-        std::fs::create_dir_all(&db_path)?;
-
-        // 2. Connect to the LanceDB database at that path
-        let db = lancedb::connect(db_path.to_str().unwrap()).execute().await?;
-
-        // 3. Check if table exists, if not create it
-        let table = if db.table_names().execute().await?.contains(&"embeddings".to_string()) {
-            // If YES, open existing table
-            db.open_table("embeddings").execute().await?
-        } else {
-            // If NO, create it with empty schema
-            let empty_batch = Self::create_empty_batch()?;
-            let batch_iterator = RecordBatchIterator::new(
-                vec![Ok(empty_batch)].into_iter(),
-                Self::create_schema()
-            );
+    /// Creates or opens the LanceDB database in `db_path` and its "embeddings" table.
+    /// With a `chunk_db_path`, chunk embeddings go to a second database there, e.g. on a
+    /// larger, slower drive. This is a one-time setup operation; chunks moving to a new
+    /// chunk store wait for `disk_guard` to see room for them.
+    pub async fn new(db_path: &Path, chunk_db_path: Option<&Path>, disk_guard: &DiskGuard) -> Result<Self> {
+        // 1. Connect to the LanceDB database, creating the table (as f16) if needed.
+        std::fs::create_dir_all(db_path)?;
+        let db = lancedb::connect(&db_path.to_string_lossy()).execute().await?;
+        let (table, precision) = Self::open_or_create_table(&db, VectorPrecision::F16).await?;
 
-            let table = db.create_table("embeddings", Box::new(batch_iterator)).execute().await?;
-            
-            // Clean up the initialization record
-            table.delete("text_chunk = ''").await?;
-            
-            table
+        // 2. Open the chunk store, if it is separate, with the same precision so rows can
+        //    move between the two.
+        let (chunk_conn, chunk_table) = match chunk_db_path {
+            Some(chunk_db_path) => {
+                std::fs::create_dir_all(chunk_db_path)?;
+                let chunk_db = lancedb::connect(&chunk_db_path.to_string_lossy()).execute().await?;
+                let (chunk_table, chunk_precision) = Self::open_or_create_table(&chunk_db, precision).await?;
+                if chunk_precision != precision {
+                    return Err(anyhow::anyhow!(
+                        "The chunk store in {} was created with different vector precision; move it aside to rebuild it",
+                        chunk_db_path.display()
+                    ));
+                }
+                Self::move_chunks(&table, &chunk_table, disk_guard).await?;
+                (Some(chunk_db), Some(chunk_table))
+            }
+            None => (None, None),
         };
 
         Ok(VectorDBManager {
             _conn: db,
             table,
+            _chunk_conn: chunk_conn,
+            chunk_table,
+            precision,
+            has_source_types: true,
+            write_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Opens an existing database without ever writing to it, e.g. one a team publishes
+    /// on a network share. Chunks are searched in its own table. Fails if there is no
+    /// "embeddings" table in `db_path`.
+    pub async fn open_read_only(db_path: &Path) -> Result<Self> {
+        let db = lancedb::connect(&db_path.to_string_lossy()).execute().await?;
+        if !db.table_names().execute().await?.contains(&"embeddings".to_string()) {
+            return Err(anyhow::anyhow!("No embeddings table in {}", db_path.display()));
+        }
+        let table = db.open_table("embeddings").execute().await?;
+        let schema = table.schema().await?;
+        let precision = Self::precision_from_schema(&schema);
+
+        Ok(VectorDBManager {
+            _conn: db,
+            table,
+            _chunk_conn: None,
+            chunk_table: None,
+            precision,
+            has_source_types: schema.field_with_name("source_type").is_ok(),
+            write_lock: tokio::sync::Mutex::new(()),
         })
     }
 
-    /// Adds a batch of new embedding records to the database.
-    pub async fn add_embeddings(&self, records: Vec<EmbeddingRecord>) -> Result<()> {
+    /// Adds a batch of new embedding records of a document from `source_type`, e.g. `file`.
+    pub async fn add_embeddings(&self, records: Vec<EmbeddingRecord>, source_type: &str) -> Result<()> {
         if records.is_empty() {
             return Ok(());
         }
 
-        let record_batch = Self::records_to_batch(&records)?;
-        let batch_iterator = RecordBatchIterator::new(
-            vec![Ok(record_batch)].into_iter(),
-            Self::create_schema()
-        );
-        
-        self.table.add(Box::new(batch_iterator)).execute().await?;
+        // Chunks go to their own table when it is separate
+        let (chunks, others): (Vec<EmbeddingRecord>, Vec<EmbeddingRecord>) = match &self.chunk_table {
+            Some(_) => records.into_iter().partition(|record| record.embedding_type == "chunk"),
+            None => (Vec::new(), records),
+        };
+        for (table, records) in [(&self.table, others), (self.chunks(), chunks)] {
+            if records.is_empty() {
+                continue;
+            }
+            let record_batch = Self::records_to_batch(&records, source_type, self.precision)?;
+
+            // The batch iterator is consumed by each attempt, so rebuild it from the (cheaply cloned) batch
+            self.write_with_retry(|| {
+                let batch_iterator = RecordBatchIterator::new(
+                    vec![Ok(record_batch.clone())].into_iter(),
+                    Self::create_schema(self.precision)
+                );
+                table.add(Box::new(batch_iterator)).execute()
+            }).await?;
+        }
         Ok(())
     }
 
@@ -244,7 +527,9 @@ impl VectorDBManager {
     pub async fn delete_document_embeddings(&self, document_path: &str) -> Result<()> {
         let escaped_path = Self::escape_sql_string(document_path);
         let filter_string = format!("document_path = '{}'", escaped_path);
-        self.table.delete(&filter_string).await?;
+        for table in self.tables() {
+            self.write_with_retry(|| table.delete(&filter_string)).await?;
+        }
         Ok(())
     }
 
@@ -252,10 +537,158 @@ impl VectorDBManager {
     pub async fn update_document_embeddings(
         &self,
         document_path: &str,
+        source_type: &str,
         new_records: Vec<EmbeddingRecord>,
     ) -> Result<()> {
         self.delete_document_embeddings(document_path).await?;
-        self.add_embeddings(new_records).await?;
+        self.add_embeddings(new_records, source_type).await?;
+        Ok(())
+    }
+
+    /// Deletes only the chunk embeddings of a document, keeping its title and summary.
+    pub async fn delete_document_chunks(&self, document_path: &str) -> Result<()> {
+        let filter_string = format!(
+            "embedding_type = 'chunk' AND document_path = '{}'",
+            Self::escape_sql_string(document_path)
+        );
+        self.write_with_retry(|| self.chunks().delete(&filter_string)).await?;
+        Ok(())
+    }
+
+    /// Deletes a document's title and summary embeddings, keeping its chunks.
+    pub async fn delete_document_overview(&self, document_path: &str) -> Result<()> {
+        let filter_string = format!(
+            "embedding_type != 'chunk' AND document_path = '{}'",
+            Self::escape_sql_string(document_path)
+        );
+        self.write_with_retry(|| self.table.delete(&filter_string)).await?;
+        Ok(())
+    }
+
+    /// Returns the text of every chunk stored for a document, in no particular order.
+    pub async fn document_chunk_texts(&self, document_path: &str) -> Result<Vec<String>> {
+        let filter = format!(
+            "embedding_type = 'chunk' AND document_path = '{}'",
+            Self::escape_sql_string(document_path)
+        );
+        let mut stream = self.chunks()
+            .query()
+            .only_if(filter)
+            .select(Select::columns(&["text_chunk"]))
+            .execute()
+            .await?;
+
+        let mut texts = Vec::new();
+        while let Some(batch) = stream.try_next().await? {
+            let chunk_array = Self::column::<StringArray>(&batch, "text_chunk")?;
+            texts.extend((0..batch.num_rows())
+                .filter(|&i| !chunk_array.is_null(i))
+                .map(|i| chunk_array.value(i).to_string()));
+        }
+        Ok(texts)
+    }
+
+    /// Deletes the chunk embeddings of a document whose text is one of `texts`.
+    pub async fn delete_chunks_with_text(&self, document_path: &str, texts: &[String]) -> Result<()> {
+        if texts.is_empty() {
+            return Ok(());
+        }
+        let quoted: Vec<String> = texts.iter()
+            .map(|text| format!("'{}'", Self::escape_sql_string(text)))
+            .collect();
+        let filter_string = format!(
+            "embedding_type = 'chunk' AND document_path = '{}' AND text_chunk IN ({})",
+            Self::escape_sql_string(document_path),
+            quoted.join(", ")
+        );
+        self.write_with_retry(|| self.chunks().delete(&filter_string)).await?;
+        Ok(())
+    }
+
+    /// Returns the summary stored for a document, for showing in its preview.
+    pub async fn document_summary(&self, document_path: &str) -> Result<Option<String>> {
+        let filter = format!(
+            "embedding_type = 'summary' AND document_path = '{}'",
+            Self::escape_sql_string(document_path)
+        );
+        let mut stream = self.table
+            .query()
+            .only_if(filter)
+            .select(Select::columns(&["text_chunk"]))
+            .limit(1)
+            .execute()
+            .await?;
+
+        while let Some(batch) = stream.try_next().await? {
+            let chunk_array = Self::column::<StringArray>(&batch, "text_chunk")?;
+            if batch.num_rows() > 0 && !chunk_array.is_null(0) {
+                return Ok(Some(chunk_array.value(0).to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Counts chunk embeddings per document with a full scan of the path column.
+    pub async fn chunk_counts_by_document(&self) -> Result<HashMap<String, usize>> {
+        let mut stream = self.chunks()
+            .query()
+            .only_if("embedding_type = 'chunk'")
+            .select(Select::columns(&["document_path"]))
+            .execute()
+            .await?;
+
+        let mut counts = HashMap::new();
+        while let Some(batch) = stream.try_next().await? {
+            let doc_array = Self::column::<StringArray>(&batch, "document_path")?;
+            for i in 0..batch.num_rows() {
+                if !doc_array.is_null(i) {
+                    *counts.entry(doc_array.value(i).to_string()).or_insert(0) += 1;
+                }
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Reads every document's summary and summary embedding, for clustering into topics.
+    pub async fn summary_embeddings(&self) -> Result<Vec<SummaryEmbedding>> {
+        let mut stream = self.table
+            .query()
+            .only_if("embedding_type = 'summary'")
+            .select(Select::columns(&["document_path", "text_chunk", "embedding"]))
+            .execute()
+            .await?;
+
+        let mut summaries = Vec::new();
+        while let Some(batch) = stream.try_next().await? {
+            let doc_array = Self::column::<StringArray>(&batch, "document_path")?;
+            let chunk_array = Self::column::<StringArray>(&batch, "text_chunk")?;
+            let embedding_array = Self::column::<FixedSizeListArray>(&batch, "embedding")?;
+            for i in 0..batch.num_rows() {
+                if doc_array.is_null(i) || chunk_array.is_null(i) {
+                    continue;
+                }
+                if let Some(embedding) = Self::embedding_at(embedding_array, i) {
+                    summaries.push(SummaryEmbedding {
+                        document_path: doc_array.value(i).to_string(),
+                        summary: chunk_array.value(i).to_string(),
+                        embedding,
+                    });
+                }
+            }
+        }
+        Ok(summaries)
+    }
+
+    /// Compacts the tables and prunes old versions so deleted rows actually free disk space.
+    pub async fn compact(&self) -> Result<()> {
+        for table in self.tables() {
+            self.write_with_retry(|| table.optimize(OptimizeAction::All)).await?;
+            self.write_with_retry(|| table.optimize(OptimizeAction::Prune {
+                older_than: Some(chrono::Duration::zero()),
+                delete_unverified: Some(false),
+                error_if_tagged_old_versions: None,
+            })).await?;
+        }
         Ok(())
     }
 
@@ -263,40 +696,81 @@ impl VectorDBManager {
     //  SEARCH METHODS
     // ===================================================================
 
-    /// Searches for the most similar document titles.
-    pub async fn search_titles(&self, query_vector: &[f32]) -> Result<Vec<(String, f32)>> {
+    /// Searches for the `limit` most similar document titles.
+    pub async fn search_titles(
+        &self,
+        query_vector: &[f32],
+        scope_filter: Option<&str>,
+        source_types: Option<&[String]>,
+        limit: usize,
+    ) -> Result<Vec<(String, f32)>> {
         let results = self.execute_search(
+            &self.table,
             query_vector,
-            "embedding_type = 'title'",
-            false
+            &self.search_filter("embedding_type = 'title'", scope_filter, source_types),
+            false,
+            limit
         ).await?;
         
-        Ok(results.into_iter().map(|(path, _, distance)| (path, distance)).collect())
+        Ok(results.into_iter().map(|hit| (hit.document_path, hit.distance)).collect())
     }
 
-    /// Searches for the most similar document summaries.
-    pub async fn search_summaries(&self, query_vector: &[f32]) -> Result<Vec<(String, f32)>> {
+    /// Searches for the `limit` most similar document summaries.
+    pub async fn search_summaries(
+        &self,
+        query_vector: &[f32],
+        scope_filter: Option<&str>,
+        source_types: Option<&[String]>,
+        limit: usize,
+    ) -> Result<Vec<(String, f32)>> {
         let results = self.execute_search(
+            &self.table,
             query_vector,
-            "embedding_type = 'summary'",
-            false
+            &self.search_filter("embedding_type = 'summary'", scope_filter, source_types),
+            false,
+            limit
         ).await?;
         
-        Ok(results.into_iter().map(|(path, _, distance)| (path, distance)).collect())
+        Ok(results.into_iter().map(|hit| (hit.document_path, hit.distance)).collect())
     }
 
-    /// Searches for the most similar text chunks (for finding answers).
-    pub async fn search_chunks(&self, query_vector: &[f32]) -> Result<Vec<(String, String, f32)>> {
+    /// Searches for the `limit` most similar text chunks (for finding answers).
+    pub async fn search_chunks(
+        &self,
+        query_vector: &[f32],
+        scope_filter: Option<&str>,
+        source_types: Option<&[String]>,
+        limit: usize,
+    ) -> Result<Vec<(String, String, f32)>> {
         let results = self.execute_search(
+            self.chunks(),
             query_vector,
-            "embedding_type = 'chunk'",
-            true
+            &self.search_filter("embedding_type = 'chunk'", scope_filter, source_types),
+            true,
+            limit
         ).await?;
         
         Ok(results.into_iter()
-            .filter_map(|(path, text_chunk, distance)| {
-                text_chunk.map(|chunk| (path, chunk, distance))
+            .filter_map(|hit| {
+                hit.text_chunk.map(|chunk| (hit.document_path, chunk, hit.distance))
             })
             .collect())
     }
+
+    /// Searches for the most similar text chunks within a single document.
+    pub async fn search_chunks_in_document(
+        &self,
+        query_vector: &[f32],
+        document_path: &str,
+    ) -> Result<Vec<(String, f32)>> {
+        let filter = format!(
+            "embedding_type = 'chunk' AND document_path = '{}'",
+            Self::escape_sql_string(document_path)
+        );
+        let results = self.execute_search(self.chunks(), query_vector, &filter, true, DEFAULT_SEARCH_LIMIT).await?;
+
+        Ok(results.into_iter()
+            .filter_map(|hit| hit.text_chunk.map(|chunk| (chunk, hit.distance)))
+            .collect())
+    }
 }
\ No newline at end of file
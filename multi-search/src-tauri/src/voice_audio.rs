@@ -0,0 +1,129 @@
+/// Whisper listens at 16 kHz, in 30-second windows.
+pub const WHISPER_SAMPLE_RATE: u32 = 16_000;
+pub const MAX_VOICE_SECONDS: usize = 30;
+/// Recordings shorter than this are a tap of the hotkey rather than a question.
+pub const MIN_VOICE_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize / 4;
+/// The Slaney mel scale is linear up to 1 kHz, in steps of 200/3 Hz per mel.
+const LINEAR_MEL_HZ: f64 = 200.0 / 3.0;
+const MIN_LOG_HZ: f64 = 1000.0;
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Converts to the Slaney mel scale used by librosa and Whisper, logarithmic above 1 kHz.
+fn hz_to_mel(hz: f64) -> f64 {
+    let log_step = 6.4f64.ln() / 27.0;
+    if hz >= MIN_LOG_HZ {
+        MIN_LOG_HZ / LINEAR_MEL_HZ + (hz / MIN_LOG_HZ).ln() / log_step
+    } else {
+        hz / LINEAR_MEL_HZ
+    }
+}
+
+fn mel_to_hz(mel: f64) -> f64 {
+    let min_log_mel = MIN_LOG_HZ / LINEAR_MEL_HZ;
+    let log_step = 6.4f64.ln() / 27.0;
+    if mel >= min_log_mel {
+        MIN_LOG_HZ * (log_step * (mel - min_log_mel)).exp()
+    } else {
+        mel * LINEAR_MEL_HZ
+    }
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Builds the triangular mel filterbank Whisper was trained with, as `n_mels` rows of
+/// `n_fft / 2 + 1` weights, row after row. Matches `librosa.filters.mel` with Slaney
+/// normalization, so no filter file needs to ship with the model.
+pub fn mel_filters(sample_rate: u32, n_fft: usize, n_mels: usize) -> Vec<f32> {
+    let bins = n_fft / 2 + 1;
+    let nyquist = sample_rate as f64 / 2.0;
+    let fft_freqs: Vec<f64> = (0..bins).map(|bin| bin as f64 * nyquist / (bins - 1) as f64).collect();
+    let max_mel = hz_to_mel(nyquist);
+    let mel_freqs: Vec<f64> = (0..n_mels + 2)
+        .map(|i| mel_to_hz(max_mel * i as f64 / (n_mels + 1) as f64))
+        .collect();
+
+    let mut filters = Vec::with_capacity(n_mels * bins);
+    for i in 0..n_mels {
+        let (lower, center, upper) = (mel_freqs[i], mel_freqs[i + 1], mel_freqs[i + 2]);
+        // Slaney normalization keeps each filter's area constant as they widen
+        let norm = 2.0 / (upper - lower);
+        for &freq in &fft_freqs {
+            let rising = (freq - lower) / (center - lower);
+            let falling = (upper - freq) / (upper - center);
+            filters.push((rising.min(falling).max(0.0) * norm) as f32);
+        }
+    }
+    filters
+}
+
+/// Averages interleaved frames of `channels` samples into one mono channel.
+pub fn downmix(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples.chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// Resamples mono audio to Whisper's 16 kHz by linear interpolation, which is plenty for
+/// speech recorded at the usual 44.1 or 48 kHz.
+pub fn resample_to_whisper(samples: &[f32], sample_rate: u32) -> Vec<f32> {
+    if sample_rate == WHISPER_SAMPLE_RATE || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = sample_rate as f64 / WHISPER_SAMPLE_RATE as f64;
+    let len = (samples.len() as f64 / ratio).floor() as usize;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = position as usize;
+            let next = samples[(index + 1).min(samples.len() - 1)];
+            let fraction = (position - index as f64) as f32;
+            samples[index] + (next - samples[index]) * fraction
+        })
+        .collect()
+}
+
+/// Fits a recording into one Whisper window: cut at `MAX_VOICE_SECONDS` and padded
+/// with silence to the full length, as the model was trained on.
+pub fn pad_to_window(mut samples: Vec<f32>) -> Vec<f32> {
+    samples.resize(WHISPER_SAMPLE_RATE as usize * MAX_VOICE_SECONDS, 0.0);
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mel_filters() {
+        let filters = mel_filters(16_000, 400, 80);
+        assert_eq!(filters.len(), 80 * 201);
+        assert!(filters.iter().all(|weight| *weight >= 0.0));
+        // Each filter peaks at a higher frequency than the one before it
+        let peaks: Vec<usize> = filters.chunks(201)
+            .map(|row| row.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).unwrap().0)
+            .collect();
+        assert!(peaks.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(peaks[79] > 190);
+        // The conversions invert each other on both sides of 1 kHz
+        assert!((mel_to_hz(hz_to_mel(440.0)) - 440.0).abs() < 1e-9);
+        assert!((mel_to_hz(hz_to_mel(6000.0)) - 6000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_downmix_and_resample() {
+        assert_eq!(downmix(&[0.2, 0.4, -1.0, 1.0], 2), vec![0.3f32, 0.0]);
+        let one_second: Vec<f32> = (0..48_000).map(|i| (i % 3) as f32).collect();
+        let resampled = resample_to_whisper(&one_second, 48_000);
+        assert_eq!(resampled.len(), 16_000);
+        assert!(resampled.iter().all(|sample| *sample == 0.0));
+        assert_eq!(pad_to_window(vec![1.0; 10]).len(), 480_000);
+    }
+}
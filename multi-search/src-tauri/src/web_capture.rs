@@ -0,0 +1,92 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::html_text::parse_html;
+use crate::rate_limit::RateLimiter;
+use crate::search_orchestrator::RawDocument;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+/// Source type recorded for documents captured from the web.
+pub const WEB_SOURCE: &str = "web";
+
+/// How long to wait for a page before giving up.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+/// Pages larger than this are rejected rather than parsed.
+const MAX_PAGE_BYTES: usize = 10 * 1024 * 1024;
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Extracts the title and main text of an HTML page, dropping navigation, scripts and
+/// other boilerplate. Falls back to the URL when the page has no `<title>`.
+pub fn extract_page_text(html: &str, url: &str) -> (String, String) {
+    let page = parse_html(html);
+    (page.title.unwrap_or_else(|| url.to_string()), page.body)
+}
+
+/// Builds an indexable document from a page's HTML, keyed by its URL, with the page's
+/// meta description as its summary.
+pub fn web_document(url: &str, html: &str, title_override: Option<String>) -> RawDocument {
+    let page = parse_html(html);
+    RawDocument {
+        path: url.to_string(),
+        title: title_override.filter(|title| !title.trim().is_empty())
+            .or(page.title)
+            .unwrap_or_else(|| url.to_string()),
+        body: page.body,
+        summary: page.description,
+        source_type: WEB_SOURCE.to_string(),
+        author: None,
+        modified_date: SystemTime::now(),
+        metadata: BTreeMap::new(),
+    }
+}
+
+/// Downloads a page and turns it into an indexable document.
+pub async fn fetch_web_document(url: &str, limiter: &RateLimiter) -> Result<RawDocument> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(anyhow::anyhow!("Only http and https URLs can be indexed: {}", url));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("multi-search/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let response = limiter.send(|| client.get(url)).await?.error_for_status()?;
+
+    let content_type = response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/html")
+        .to_lowercase();
+    if !content_type.contains("html") && !content_type.starts_with("text/") {
+        return Err(anyhow::anyhow!("Unsupported content type {} for {}", content_type, url));
+    }
+
+    let bytes = response.bytes().await?;
+    if bytes.len() > MAX_PAGE_BYTES {
+        return Err(anyhow::anyhow!("Page is too large to index: {}", url));
+    }
+    let html = String::from_utf8_lossy(&bytes);
+    Ok(web_document(url, &html, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_page_text_strips_boilerplate() {
+        let html = r#"<html><head><title> Release notes </title><script>var x = 1;</script></head>
+            <body><nav>Home | Docs</nav>
+            <article><h1>Version 2.0</h1><p>Faster   search.</p><aside>Share this</aside><p>New UI.</p></article>
+            <footer>Copyright</footer></body></html>"#;
+
+        let (title, body) = extract_page_text(html, "https://example.com");
+        assert_eq!(title, "Release notes");
+        assert_eq!(body, "Version 2.0\nFaster search.\nNew UI.");
+    }
+}
@@ -0,0 +1,96 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::capture_server::tokens_match;
+use reqwest::Url;
+use serde::Deserialize;
+
+/// Path prefix of push notification endpoints on the local server; the connector follows.
+pub const WEBHOOK_PATH_PREFIX: &str = "/webhook/";
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// What to do with an incoming push notification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebhookAction {
+    /// Answer a subscription handshake by echoing this value as plain text.
+    Validate(String),
+    /// Sync the connector now instead of waiting for the next poll.
+    Sync(String),
+    Reject(&'static str),
+}
+
+/// A Microsoft Graph change notification batch. Only `clientState` is read: it carries
+/// the secret the subscription was created with.
+#[derive(Debug, Deserialize)]
+struct GraphNotifications {
+    #[serde(default)]
+    value: Vec<GraphNotification>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphNotification {
+    client_state: Option<String>,
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Builds the URL a relay or tunnel should forward a connector's notifications to.
+pub fn webhook_url(port: u16, connector: &str, token: &str) -> String {
+    format!("http://127.0.0.1:{}{}{}?token={}", port, WEBHOOK_PATH_PREFIX, connector, token)
+}
+
+/// Decides how to answer a push notification sent to `target` (path and query). Every
+/// request must carry the webhook token; Graph batches must also carry it as their
+/// `clientState`. Gmail Pub/Sub pushes only say that the mailbox changed, so any
+/// authenticated push triggers a sync, which picks up from the connector's cursor.
+pub fn route_webhook(target: &str, body: &[u8], token: &str) -> WebhookAction {
+    let Ok(url) = Url::parse(&format!("http://127.0.0.1{}", target)) else {
+        return WebhookAction::Reject("Malformed webhook URL");
+    };
+    let Some(connector) = url.path().strip_prefix(WEBHOOK_PATH_PREFIX).filter(|c| !c.is_empty() && !c.contains('/')) else {
+        return WebhookAction::Reject("Unknown webhook");
+    };
+    let param = |name: &str| url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned());
+    if token.is_empty() || !param("token").is_some_and(|given| tokens_match(&given, token)) {
+        return WebhookAction::Reject("Invalid webhook token");
+    }
+
+    // Graph confirms a new subscription by asking for its validation token back
+    if let Some(validation_token) = param("validationToken") {
+        return WebhookAction::Validate(validation_token);
+    }
+    if let Ok(notifications) = serde_json::from_slice::<GraphNotifications>(body) {
+        let forged = notifications.value.iter()
+            .any(|notification| notification.client_state.as_deref().is_some_and(|state| !tokens_match(state, token)));
+        if forged {
+            return WebhookAction::Reject("Invalid client state");
+        }
+    }
+    WebhookAction::Sync(connector.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_webhook() {
+        let gmail = br#"{"message":{"data":"eyJoaXN0b3J5SWQiOjF9"},"subscription":"s"}"#;
+        assert_eq!(route_webhook("/webhook/gmail?token=abc", gmail, "abc"), WebhookAction::Sync("gmail".to_string()));
+        assert_eq!(route_webhook("/webhook/gmail?token=nope", gmail, "abc"), WebhookAction::Reject("Invalid webhook token"));
+        assert_eq!(route_webhook("/webhook/gmail", gmail, ""), WebhookAction::Reject("Invalid webhook token"));
+        assert_eq!(
+            route_webhook("/webhook/outlook?token=abc&validationToken=Validation%3A+ok", b"", "abc"),
+            WebhookAction::Validate("Validation: ok".to_string())
+        );
+        let graph = br#"{"value":[{"clientState":"other","resource":"me/messages/1"}]}"#;
+        assert_eq!(route_webhook("/webhook/outlook?token=abc", graph, "abc"), WebhookAction::Reject("Invalid client state"));
+        assert_eq!(route_webhook("/webhook/?token=abc", b"", "abc"), WebhookAction::Reject("Unknown webhook"));
+    }
+}
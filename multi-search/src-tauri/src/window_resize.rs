@@ -0,0 +1,71 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use std::time::Duration;
+
+/// How long the launcher takes to grow or shrink to a new height.
+pub const RESIZE_DURATION: Duration = Duration::from_millis(150);
+/// Time between animation frames, about 60 per second.
+pub const RESIZE_FRAME: Duration = Duration::from_millis(16);
+/// The launcher's height with only the search field showing, in logical pixels.
+pub const MIN_LAUNCHER_HEIGHT: f64 = 80.0;
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Starts fast and settles gently, so the window seems to follow the content.
+fn ease_out_cubic(t: f64) -> f64 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Clamps a requested launcher height to the search field's height and the space left
+/// below the window on its display. Heights are logical pixels, so the same request gives
+/// the same size on displays with different scale factors.
+pub fn fit_height(requested: f64, available: f64) -> f64 {
+    requested.min(available).max(MIN_LAUNCHER_HEIGHT)
+}
+
+/// Returns the height of each animation frame from `from` to `to`, ending exactly on
+/// `to`. Empty if the window is already that tall.
+pub fn resize_frames(from: f64, to: f64, duration: Duration, frame: Duration) -> Vec<f64> {
+    if (to - from).abs() < 0.5 {
+        return Vec::new();
+    }
+    let count = (duration.as_millis() / frame.as_millis().max(1)).max(1) as usize;
+    (1..=count)
+        .map(|step| from + (to - from) * ease_out_cubic(step as f64 / count as f64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_height() {
+        assert_eq!(fit_height(400.0, 900.0), 400.0);
+        assert_eq!(fit_height(1200.0, 900.0), 900.0);
+        assert_eq!(fit_height(20.0, 900.0), MIN_LAUNCHER_HEIGHT);
+    }
+
+    #[test]
+    fn test_resize_frames() {
+        assert!(resize_frames(80.0, 80.2, RESIZE_DURATION, RESIZE_FRAME).is_empty());
+
+        let frames = resize_frames(80.0, 480.0, RESIZE_DURATION, RESIZE_FRAME);
+        assert_eq!(frames.len(), 9);
+        assert_eq!(*frames.last().unwrap(), 480.0);
+        assert!(frames.windows(2).all(|pair| pair[0] < pair[1]));
+        // Most of the distance is covered early on
+        assert!(frames[2] > 280.0);
+
+        let frames = resize_frames(480.0, 80.0, RESIZE_DURATION, RESIZE_FRAME);
+        assert_eq!(*frames.last().unwrap(), 80.0);
+        assert!(frames.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+}
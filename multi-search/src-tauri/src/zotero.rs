@@ -0,0 +1,242 @@
+// ===================================================================
+//  IMPORTS
+// ===================================================================
+use crate::fs_paths::long_path;
+use crate::parsers::parse_document;
+use crate::search_orchestrator::RawDocument;
+use crate::settings::app_data_dir;
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Source type of items read from a Zotero library.
+pub const ZOTERO_SOURCE: &str = "zotero";
+
+/// Item types that hang off a parent item rather than being references themselves.
+const CHILD_ITEM_TYPES: &[&str] = &["attachment", "note", "annotation"];
+
+// ===================================================================
+//  PUBLIC STRUCTS
+// ===================================================================
+
+/// One reference from the library with the citation metadata researchers filter on.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZoteroItem {
+    pub key: String,
+    pub item_type: String,
+    pub title: String,
+    /// Creators in citation order, as "First Last".
+    pub authors: Vec<String>,
+    pub year: Option<String>,
+    /// The journal, proceedings or book the item was published in.
+    pub publication: Option<String>,
+    pub doi: Option<String>,
+    pub abstract_note: Option<String>,
+    pub date_modified: Option<SystemTime>,
+    /// Attached PDFs that exist on disk.
+    pub pdf_attachments: Vec<PathBuf>,
+}
+
+impl ZoteroItem {
+    /// Returns the `zotero://` link that selects the item in the Zotero app.
+    pub fn link(&self) -> String {
+        format!("zotero://select/library/items/{}", self.key)
+    }
+
+    /// Builds the citation fields stored as filterable metadata, e.g. `year:2020`.
+    pub fn metadata(&self) -> BTreeMap<String, String> {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("item_type".to_string(), self.item_type.clone());
+        if !self.authors.is_empty() {
+            metadata.insert("authors".to_string(), self.authors.join("; "));
+        }
+        let optional = [("year", &self.year), ("journal", &self.publication), ("doi", &self.doi)];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                metadata.insert(key.to_string(), value.clone());
+            }
+        }
+        if let Some(pdf) = self.pdf_attachments.first() {
+            metadata.insert("attachment".to_string(), pdf.display().to_string());
+        }
+        metadata
+    }
+}
+
+// ===================================================================
+//  HELPER FUNCTIONS
+// ===================================================================
+
+/// Returns the default Zotero data directory, `~/Zotero`.
+fn default_data_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join("Zotero"))
+}
+
+/// Zotero keeps its database locked while running, so a snapshot is read instead.
+fn open_snapshot(database: &Path) -> Result<Connection> {
+    let snapshot = app_data_dir()?.join("zotero_snapshot.sqlite");
+    if let Some(parent) = snapshot.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(long_path(database), &snapshot)
+        .map_err(|e| anyhow::anyhow!("Failed to read Zotero database {}: {}", database.display(), e))?;
+    Ok(Connection::open_with_flags(&snapshot, OpenFlags::SQLITE_OPEN_READ_ONLY)?)
+}
+
+/// Extracts the year from a Zotero date such as "2020-05-00 May 2020".
+fn parse_year(date: &str) -> Option<String> {
+    let year = date.get(..4)?;
+    (year.chars().all(|c| c.is_ascii_digit()) && year != "0000").then(|| year.to_string())
+}
+
+/// Resolves an attachment's stored path. Imported files are stored as `storage:name.pdf`
+/// under the attachment's own key; linked files keep their absolute path.
+fn attachment_path(data_dir: &Path, attachment_key: &str, stored_path: &str) -> PathBuf {
+    match stored_path.strip_prefix("storage:") {
+        Some(file_name) => data_dir.join("storage").join(attachment_key).join(file_name),
+        None => PathBuf::from(stored_path),
+    }
+}
+
+/// Reads every top-level, non-deleted item with its fields, creators and PDF attachments.
+fn read_items(connection: &Connection, data_dir: &Path) -> Result<Vec<ZoteroItem>> {
+    // 1. The items themselves.
+    let mut items: BTreeMap<i64, ZoteroItem> = BTreeMap::new();
+    let mut statement = connection.prepare(
+        "SELECT items.itemID, items.key, itemTypes.typeName, items.dateModified
+         FROM items JOIN itemTypes ON items.itemTypeID = itemTypes.itemTypeID
+         WHERE items.itemID NOT IN (SELECT itemID FROM deletedItems)",
+    )?;
+    let rows = statement.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, Option<String>>(3)?))
+    })?;
+    for row in rows {
+        let (item_id, key, item_type, date_modified) = row?;
+        if CHILD_ITEM_TYPES.contains(&item_type.as_str()) {
+            continue;
+        }
+        let date_modified = date_modified
+            .and_then(|date| NaiveDateTime::parse_from_str(&date, "%Y-%m-%d %H:%M:%S").ok())
+            .map(|date| SystemTime::from(date.and_utc()));
+        items.insert(item_id, ZoteroItem { key, item_type, date_modified, ..Default::default() });
+    }
+
+    // 2. Their fields, stored as an entity-attribute-value table.
+    let mut statement = connection.prepare(
+        "SELECT itemData.itemID, fields.fieldName, itemDataValues.value
+         FROM itemData
+         JOIN fields ON itemData.fieldID = fields.fieldID
+         JOIN itemDataValues ON itemData.valueID = itemDataValues.valueID",
+    )?;
+    let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+    for row in rows {
+        let (item_id, field, value) = row?;
+        let Some(item) = items.get_mut(&item_id) else { continue };
+        match field.as_str() {
+            "title" => item.title = value,
+            "date" => item.year = parse_year(&value),
+            "publicationTitle" | "proceedingsTitle" | "bookTitle" => item.publication = Some(value),
+            "DOI" => item.doi = Some(value),
+            "abstractNote" => item.abstract_note = Some(value),
+            _ => {}
+        }
+    }
+
+    // 3. Creators, in citation order.
+    let mut statement = connection.prepare(
+        "SELECT itemCreators.itemID, creators.firstName, creators.lastName
+         FROM itemCreators JOIN creators ON itemCreators.creatorID = creators.creatorID
+         ORDER BY itemCreators.itemID, itemCreators.orderIndex",
+    )?;
+    let rows = statement.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, Option<String>>(2)?))
+    })?;
+    for row in rows {
+        let (item_id, first_name, last_name) = row?;
+        let Some(item) = items.get_mut(&item_id) else { continue };
+        let name = [first_name.unwrap_or_default(), last_name.unwrap_or_default()].join(" ");
+        if !name.trim().is_empty() {
+            item.authors.push(name.trim().to_string());
+        }
+    }
+
+    // 4. PDF attachments of each item.
+    let mut statement = connection.prepare(
+        "SELECT itemAttachments.parentItemID, items.key, itemAttachments.path
+         FROM itemAttachments JOIN items ON itemAttachments.itemID = items.itemID
+         WHERE itemAttachments.contentType = 'application/pdf'
+           AND itemAttachments.parentItemID IS NOT NULL AND itemAttachments.path IS NOT NULL",
+    )?;
+    let rows = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+    for row in rows {
+        let (parent_id, attachment_key, stored_path) = row?;
+        let Some(item) = items.get_mut(&parent_id) else { continue };
+        let path = attachment_path(data_dir, &attachment_key, &stored_path);
+        if long_path(&path).is_file() {
+            item.pdf_attachments.push(path);
+        }
+    }
+
+    Ok(items.into_values().filter(|item| !item.title.is_empty()).collect())
+}
+
+// ===================================================================
+//  PUBLIC FUNCTIONS
+// ===================================================================
+
+/// Reads a Zotero library. `data_dir` is the folder holding `zotero.sqlite` and defaults
+/// to `~/Zotero`.
+pub fn read_library(data_dir: Option<&Path>) -> Result<Vec<ZoteroItem>> {
+    let data_dir = match data_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => default_data_dir().ok_or_else(|| anyhow::anyhow!("Could not find the home directory"))?,
+    };
+    let database = data_dir.join("zotero.sqlite");
+    if !long_path(&database).is_file() {
+        return Err(anyhow::anyhow!("No Zotero library found in {}", data_dir.display()));
+    }
+    let connection = open_snapshot(&database)?;
+    read_items(&connection, &data_dir)
+}
+
+/// Turns a reference into a document: its abstract followed by the text of its PDFs,
+/// parsed with the same parser as local files. Unreadable PDFs are skipped with a warning.
+pub fn raw_document_from_item(item: &ZoteroItem) -> RawDocument {
+    let mut sections: Vec<String> = item.abstract_note.iter().cloned().collect();
+    for pdf in &item.pdf_attachments {
+        match parse_document(pdf) {
+            Ok(text) => sections.push(text),
+            Err(e) => eprintln!("Warning: Could not parse Zotero attachment {}: {}", pdf.display(), e),
+        }
+    }
+
+    RawDocument {
+        path: item.link(),
+        title: item.title.clone(),
+        body: sections.join("\n\n"),
+        summary: None,
+        source_type: ZOTERO_SOURCE.to_string(),
+        author: item.authors.first().cloned(),
+        modified_date: item.date_modified.unwrap_or_else(SystemTime::now),
+        metadata: item.metadata(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zotero_field_helpers() {
+        assert_eq!(parse_year("2020-05-00 May 2020"), Some("2020".to_string()));
+        assert_eq!(parse_year("0000-00-00 n.d."), None);
+        assert_eq!(
+            attachment_path(Path::new("/z"), "ABCD1234", "storage:paper.pdf"),
+            PathBuf::from("/z/storage/ABCD1234/paper.pdf")
+        );
+        assert_eq!(attachment_path(Path::new("/z"), "ABCD1234", "/papers/x.pdf"), PathBuf::from("/papers/x.pdf"));
+    }
+}